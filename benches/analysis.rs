@@ -0,0 +1,87 @@
+use std::{fmt::Write, fs, hint::black_box, path::Path};
+
+use bashtyped::FileInfo;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn many_assignments(count: usize) -> String {
+    let mut source = String::new();
+    for i in 0..count {
+        match i % 4 {
+            0 => writeln!(source, "var{i}={i}"),
+            1 => writeln!(source, "var{i}=\"text\" #/ string"),
+            2 => writeln!(source, "#/ int\nvar{i}={i}"),
+            _ => writeln!(source, "var{i}=\"$var{}\"", i - 1),
+        }
+        .unwrap();
+    }
+    source
+}
+
+fn deep_nesting(depth: usize) -> String {
+    let mut source = String::new();
+    for i in 0..depth {
+        writeln!(source, "if true; then\nvar{i}={i} #/ int").unwrap();
+    }
+    for _ in 0..depth {
+        source.push_str("fi\n");
+    }
+    source
+}
+
+fn many_unions(count: usize) -> String {
+    let mut source = String::from("base=1 #/ int | string | bool\n");
+    for i in 0..count {
+        writeln!(source, "var{i}=\"$base\" #/ bool | string | int | any").unwrap();
+    }
+    source
+}
+
+fn analyze(source: &str) {
+    let mut info = FileInfo::new(source);
+    info.parse_code();
+    black_box(info.errors);
+}
+
+fn generated(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generated");
+    for count in [1_000, 10_000] {
+        let source = many_assignments(count);
+        group.bench_with_input(BenchmarkId::new("assignments", count), &source, |b, s| {
+            b.iter(|| analyze(s))
+        });
+    }
+    let source = deep_nesting(200);
+    group.bench_with_input(BenchmarkId::new("nesting", 200), &source, |b, s| {
+        b.iter(|| analyze(s))
+    });
+    let source = many_unions(5_000);
+    group.bench_with_input(BenchmarkId::new("unions", 5_000), &source, |b, s| {
+        b.iter(|| analyze(s))
+    });
+    group.finish();
+}
+
+/// Benchmarks every script in `benches/corpus`, so real-world files can be dropped in to
+/// validate performance-motivated changes
+fn corpus(c: &mut Criterion) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/corpus");
+    let mut entries = fs::read_dir(dir)
+        .expect("corpus directory to exist")
+        .map(|entry| entry.expect("corpus entry to be readable").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sh"))
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    let mut group = c.benchmark_group("corpus");
+    for path in entries {
+        let source = fs::read_to_string(&path).expect("corpus file to be valid utf-8");
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        group.bench_with_input(BenchmarkId::from_parameter(name), &source, |b, s| {
+            b.iter(|| analyze(s))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, generated, corpus);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+#!/usr/bin/env bash
+# A representative deployment script used as a benchmark fixture
+
+#/ string
+environment="staging"
+replicas=3 #/ int
+verbose="$replicas" #/ int | bool
+region="eu-west-1" #/ string | any
+
+echo "Deploying to $environment"
+for i in 1 2 3; do
+    attempt="$i" #/ int | string
+    if [ "$attempt" = "3" ]; then
+        last_attempt="$attempt"
+    fi
+done
+
+#[set_var(image, string)]
+tag="latest"
+full_image="registry/$tag" #/ string
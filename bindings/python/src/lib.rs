@@ -0,0 +1,121 @@
+//! Python bindings for bashtyped, built with `maturin build` from this directory. Offsets are
+//! counted in code points, the way Python indexes a `str`, rather than in UTF-8 bytes.
+
+use std::collections::HashMap;
+
+use bashtyped::{FileInfo, SourceIndex};
+use pyo3::prelude::*;
+
+#[pyclass(name = "Label", frozen, get_all, skip_from_py_object)]
+#[derive(Clone)]
+struct PyLabel {
+    start: usize,
+    end: usize,
+    message: String,
+}
+
+#[pymethods]
+impl PyLabel {
+    fn __repr__(&self) -> String {
+        format!("Label({}..{}, {:?})", self.start, self.end, self.message)
+    }
+}
+
+#[pyclass(name = "Diagnostic", frozen, get_all)]
+struct PyDiagnostic {
+    severity: String,
+    code: Option<String>,
+    message: String,
+    offset: usize,
+    labels: Vec<PyLabel>,
+    note: Option<String>,
+    help: Option<String>,
+}
+
+#[pymethods]
+impl PyDiagnostic {
+    fn __repr__(&self) -> String {
+        format!("Diagnostic({:?}, offset={})", self.message, self.offset)
+    }
+}
+
+#[pyclass(name = "Variable", frozen, get_all)]
+struct PyVariable {
+    bash_type: String,
+    method: String,
+    start: usize,
+    end: usize,
+}
+
+#[pymethods]
+impl PyVariable {
+    fn __repr__(&self) -> String {
+        format!("Variable({}, {})", self.bash_type, self.method)
+    }
+}
+
+fn analyzed(source: &str) -> FileInfo<'_> {
+    let mut info = FileInfo::new(source);
+    info.parse_code();
+    info
+}
+
+/// Returns every diagnostic found in `source`
+#[pyfunction]
+fn analyze(source: &str) -> Vec<PyDiagnostic> {
+    let index = SourceIndex::new(source);
+    analyzed(source)
+        .errors
+        .into_iter()
+        .map(|error| PyDiagnostic {
+            severity: format!("{:?}", error.severity).to_lowercase(),
+            code: error.code,
+            message: error.message,
+            offset: index.char_offset(error.offset),
+            labels: error
+                .labels
+                .into_iter()
+                .map(|label| PyLabel {
+                    start: index.char_offset(label.range.start),
+                    end: index.char_offset(label.range.end),
+                    message: match label.bash_type {
+                        Some(bash_type) => format!("{} {bash_type}", label.text),
+                        None => label.text,
+                    },
+                })
+                .collect(),
+            note: error.note,
+            help: error.help,
+        })
+        .collect()
+}
+
+/// Returns the type of every variable defined in `source`
+#[pyfunction]
+fn variables(source: &str) -> HashMap<String, PyVariable> {
+    let index = SourceIndex::new(source);
+    analyzed(source)
+        .variables
+        .into_iter()
+        .map(|(name, declaration)| {
+            let variable = PyVariable {
+                bash_type: declaration.bash_type.to_string(),
+                method: format!("{:?}", declaration.method).to_lowercase(),
+                start: index.char_offset(declaration.range.start),
+                end: index.char_offset(declaration.range.end),
+            };
+            (name, variable)
+        })
+        .collect()
+}
+
+#[pymodule]
+#[pyo3(name = "bashtyped")]
+fn bashtyped_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDiagnostic>()?;
+    m.add_class::<PyLabel>()?;
+    m.add_class::<PyVariable>()?;
+    m.add_function(wrap_pyfunction!(analyze, m)?)?;
+    m.add_function(wrap_pyfunction!(variables, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,24 @@
+"""Tests for the Python bindings, run with `python -m unittest` once the module is installed with
+`maturin develop`"""
+
+import unittest
+
+import bashtyped
+
+
+class OffsetTests(unittest.TestCase):
+    def test_diagnostic_offsets_index_the_source(self):
+        source = 'name="日本語"\ncount=1\ncount="héllo" #/ int'
+        [diagnostic] = bashtyped.analyze(source)
+        self.assertEqual(diagnostic.offset, source.index('count="'))
+        spans = [source[label.start : label.end] for label in diagnostic.labels]
+        self.assertEqual(spans, ["int", 'count="héllo"'])
+
+    def test_variable_offsets_index_the_source(self):
+        source = 'greeting="こんにちは"\nafter=1'
+        after = bashtyped.variables(source)["after"]
+        self.assertEqual(source[after.start : after.end], "after=1")
+
+
+if __name__ == "__main__":
+    unittest.main()
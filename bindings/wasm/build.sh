@@ -0,0 +1,19 @@
+#!/bin/sh
+# Builds the bindings for wasm32-unknown-unknown with the clang and wasi-libc of a wasi-sdk, found
+# through WASI_SDK_PATH. Pass --check to only check that the crate compiles for the target, which
+# doesn't need wasm-pack
+set -eu
+
+: "${WASI_SDK_PATH:?set WASI_SDK_PATH to a wasi-sdk, from https://github.com/WebAssembly/wasi-sdk}"
+cd "$(dirname "$0")"
+
+export CC_wasm32_unknown_unknown="$WASI_SDK_PATH/bin/clang"
+export AR_wasm32_unknown_unknown="$WASI_SDK_PATH/bin/llvm-ar"
+export CFLAGS_wasm32_unknown_unknown="--sysroot=$WASI_SDK_PATH/share/wasi-sysroot"
+
+rustup target add wasm32-unknown-unknown
+if [ "${1:-}" = --check ]; then
+    cargo build --target wasm32-unknown-unknown --release
+else
+    wasm-pack build --release "$@"
+fi
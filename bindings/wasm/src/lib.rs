@@ -0,0 +1,102 @@
+//! JavaScript bindings for bashtyped, for use in browser playgrounds and web extension hosts.
+//!
+//! Build with `build.sh`, which runs `wasm-pack build`. The tree-sitter parsers are written in C,
+//! so the C compiler used for `wasm32-unknown-unknown` (`CC_wasm32_unknown_unknown`) has to be a
+//! clang with the wasm backend, pointed at a libc sysroot such as wasi-libc through
+//! `CFLAGS_wasm32_unknown_unknown="--sysroot=..."`. The script sets both from a wasi-sdk.
+//!
+//! Offsets, both the ones passed in and the ones returned, are counted in UTF-16 code units, the
+//! way JavaScript indexes a string, rather than in UTF-8 bytes.
+
+use std::ops::Range;
+
+use bashtyped::{FileInfo, SourceIndex};
+use wasm_bindgen::prelude::*;
+
+fn utf16_range(index: &SourceIndex, range: &mut Range<usize>) {
+    *range = index.utf16_offset(range.start)..index.utf16_offset(range.end);
+}
+
+/// Analyzes `source` and returns a JSON object with the `errors` found and the `variables` table
+#[wasm_bindgen]
+pub fn analyze(source: &str) -> String {
+    let mut info = FileInfo::new(source);
+    info.parse_code();
+    let index = SourceIndex::new(source);
+    for error in &mut info.errors {
+        error.offset = index.utf16_offset(error.offset);
+        for label in &mut error.labels {
+            utf16_range(&index, &mut label.range);
+        }
+        for edit in error.fixes.iter_mut().flat_map(|fix| &mut fix.edits) {
+            utf16_range(&index, &mut edit.range);
+        }
+    }
+    for variable in info.variables.values_mut() {
+        utf16_range(&index, &mut variable.range);
+        if let Some(annotation) = &mut variable.annotation {
+            utf16_range(&index, &mut annotation.range);
+            utf16_range(&index, &mut annotation.type_range);
+        }
+    }
+    serde_json::json!({
+        "errors": info.errors,
+        "variables": info.variables,
+    })
+    .to_string()
+}
+
+/// Returns a JSON array of the completions at `offset` in `source`, for annotations being written
+/// in an editor
+#[wasm_bindgen]
+pub fn complete(source: &str, offset: usize) -> String {
+    let index = SourceIndex::new(source);
+    let mut completions = bashtyped::completion::complete(source, index.from_utf16(offset));
+    for completion in &mut completions {
+        utf16_range(&index, &mut completion.range);
+    }
+    serde_json::to_string(&completions).expect("completions to serialize")
+}
+
+/// Returns a JSON array of the semantic tokens of the annotations in `source`, for highlighting them
+/// differently from ordinary comments
+#[wasm_bindgen]
+pub fn semantic_tokens(source: &str) -> String {
+    let index = SourceIndex::new(source);
+    let mut tokens = FileInfo::new(source).semantic_tokens();
+    for token in &mut tokens {
+        utf16_range(&index, &mut token.range);
+    }
+    serde_json::to_string(&tokens).expect("tokens to serialize")
+}
+
+/// Returns a JSON array of the variables and functions in `source` with their types, in the order
+/// they appear
+#[wasm_bindgen]
+pub fn document_symbols(source: &str) -> String {
+    let mut info = FileInfo::new(source);
+    info.parse_code();
+    let index = SourceIndex::new(source);
+    let mut symbols = bashtyped::symbols::Symbols::new(&info).document_symbols();
+    for symbol in &mut symbols {
+        symbol.span.start = index.utf16_offset(symbol.span.start);
+        symbol.span.end = index.utf16_offset(symbol.span.end);
+    }
+    serde_json::to_string(&symbols).expect("symbols to serialize")
+}
+
+/// Returns the signature of the annotated function called at `offset` in `source` as JSON, or
+/// `null` outside of a call. The ranges of the parameters are in its label
+#[wasm_bindgen]
+pub fn signature_help(source: &str, offset: usize) -> String {
+    let mut info = FileInfo::new(source);
+    info.parse_code();
+    let mut signature = info.signature_help(SourceIndex::new(source).from_utf16(offset));
+    if let Some(signature) = &mut signature {
+        let index = SourceIndex::new(&signature.label);
+        for parameter in &mut signature.parameters {
+            utf16_range(&index, parameter);
+        }
+    }
+    serde_json::to_string(&signature).expect("signature to serialize")
+}
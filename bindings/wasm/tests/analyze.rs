@@ -0,0 +1,87 @@
+use serde_json::Value;
+
+#[test]
+fn test_analyze_returns_json() {
+    let output: Value = serde_json::from_str(&bashtyped_wasm::analyze(
+        r#"a=1
+b="text" #/ int"#,
+    ))
+    .unwrap();
+    assert_eq!(output["variables"]["a"]["bash_type"], "Integer");
+    assert_eq!(output["errors"][0]["message"], "Types do not match");
+}
+
+#[test]
+fn test_complete_returns_json() {
+    let output: Value = serde_json::from_str(&bashtyped_wasm::complete("a=1 #/ in", 9)).unwrap();
+    assert_eq!(output[0]["label"], "int");
+    assert_eq!(output[0]["kind"], "Type");
+    assert_eq!(output[0]["range"]["start"], 7);
+}
+
+#[test]
+fn test_semantic_tokens_returns_json() {
+    let output: Value =
+        serde_json::from_str(&bashtyped_wasm::semantic_tokens("a=1 #/ int")).unwrap();
+    assert_eq!(output[1]["kind"], "Type");
+    assert_eq!(output[1]["range"]["start"], 7);
+}
+
+#[test]
+fn test_document_symbols_returns_json() {
+    let output: Value =
+        serde_json::from_str(&bashtyped_wasm::document_symbols("a=1\nf() { :; }")).unwrap();
+    assert_eq!(output[0]["name"], "a");
+    assert_eq!(output[0]["detail"], "int");
+    assert_eq!(output[1]["kind"], "Function");
+}
+
+#[test]
+fn test_signature_help_returns_json() {
+    let source = "#[params name: string]\ngreet() { :; }\ngreet ";
+    let output: Value =
+        serde_json::from_str(&bashtyped_wasm::signature_help(source, source.len())).unwrap();
+    assert_eq!(output["label"], "greet(name: string)");
+    assert_eq!(output["active_parameter"], 0);
+}
+
+/// Offsets of a string as JavaScript indexes it
+fn utf16(source: &str, offset: usize) -> usize {
+    source[..offset].encode_utf16().count()
+}
+
+#[test]
+fn test_offsets_are_utf16() {
+    let source = "name=\"😀 日本\"\ncount=1\ncount=\"héllo\" #/ int";
+    let output: Value = serde_json::from_str(&bashtyped_wasm::analyze(source)).unwrap();
+    let error = &output["errors"][0];
+    let second = source.rfind("count").unwrap();
+    assert_eq!(error["offset"], utf16(source, second));
+    assert_eq!(
+        error["labels"][0]["range"]["end"],
+        utf16(source, source.len())
+    );
+    let inferred = &error["labels"][1]["range"];
+    assert_eq!(inferred["start"], utf16(source, second));
+    assert_eq!(inferred["end"], utf16(source, source.find(" #/").unwrap()));
+    assert_eq!(
+        error["fixes"][0]["edits"][0]["range"]["start"],
+        utf16(source, source.find("#/").unwrap())
+    );
+    let first = &output["variables"]["count"]["range"];
+    assert_eq!(first["start"], utf16(source, source.find("count").unwrap()));
+    assert_eq!(
+        first["end"],
+        utf16(source, source.find("\ncount=\"").unwrap())
+    );
+    let source = "greeting=😀 #/ in";
+    let end = utf16(source, source.len());
+    let output: Value = serde_json::from_str(&bashtyped_wasm::complete(source, end)).unwrap();
+    assert_eq!(output[0]["label"], "int");
+    assert_eq!(output[0]["range"]["start"], end - 2);
+
+    let source = "#[params name: string]\ngreet() { :; }\ngreet \"é😀\" ";
+    let end = utf16(source, source.len());
+    let output: Value = serde_json::from_str(&bashtyped_wasm::signature_help(source, end)).unwrap();
+    assert_eq!(output["label"], "greet(name: string)");
+}
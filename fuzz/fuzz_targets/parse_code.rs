@@ -0,0 +1,14 @@
+//! Analyzes arbitrary text, which mostly exercises the error recovery of the parser and the
+//! rules on the partial trees it makes
+#![no_main]
+
+use bashtyped::testing::{analyze, check_ranges};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let source = String::from_utf8_lossy(data);
+    let file = analyze(&source);
+    if let Err(problems) = check_ranges(&file) {
+        panic!("{problems}");
+    }
+});
@@ -0,0 +1,15 @@
+//! Analyzes scripts made of the constructs the rules look at, with random annotations
+#![no_main]
+
+use bashtyped::{
+    fuzzing::Script,
+    testing::{analyze, check_ranges},
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|script: Script| {
+    let file = analyze(&script.0);
+    if let Err(problems) = check_ranges(&file) {
+        panic!("{problems}in\n{}", script.0);
+    }
+});
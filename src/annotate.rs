@@ -0,0 +1,183 @@
+//! Rewriting the annotations of a file into one consistent form, like a formatter for the types
+
+use tree_sitter::Node;
+
+use crate::{fix::TextEdit, AnnotationStyle, BashType, FileInfo, Method};
+
+impl FileInfo<'_> {
+    /// The edits that write every `#/` annotation in its canonical form and move it where the
+    /// `annotation_style` setting says. With `insert_inferred`, the first assignment of each
+    /// variable without an annotation is also annotated with the type inferred for it by
+    /// [`FileInfo::parse_code`]
+    pub fn organize_annotations(&mut self, insert_inferred: bool) -> Vec<TextEdit> {
+        let tree = self.parse_tree();
+        let mut edits = Vec::new();
+        let mut worklist = vec![tree.root_node()];
+        while let Some(node) = worklist.pop() {
+            let mut previous = None;
+            // Whether the last statement is preceded by an annotation on its own line
+            let mut annotated = false;
+            for child in node.named_children(&mut node.walk()) {
+                worklist.push(child);
+                if child.kind() != "comment" {
+                    if insert_inferred && !annotated {
+                        edits.extend(self.inferred_annotation(child));
+                    }
+                    previous = Some(child);
+                    annotated = false;
+                    continue;
+                }
+                let Some(bash_type) = self.annotation_type(child) else {
+                    continue;
+                };
+                let annotation = format!("#/ {}", bash_type.normalized());
+                let inline_after = previous
+                    .filter(|previous| previous.end_position().row == child.start_position().row);
+                annotated = inline_after.is_none();
+                let moved = match (inline_after, self.config.annotation_style) {
+                    (Some(statement), AnnotationStyle::Above) => {
+                        self.move_above(statement, child, &annotation)
+                    }
+                    (None, AnnotationStyle::Inline) => self.move_inline(child, &annotation),
+                    _ => None,
+                };
+                match moved {
+                    Some(moved) => edits.extend(moved),
+                    None if self.source_code[self.comment_range(child)] != annotation => {
+                        edits.push(TextEdit {
+                            range: self.comment_range(child),
+                            new_text: annotation,
+                        });
+                    }
+                    None => (),
+                }
+            }
+        }
+        edits
+    }
+
+    /// The type of a valid `#/` annotation
+    fn annotation_type(&self, comment: Node) -> Option<BashType> {
+        self.node_text(comment)
+            .ok()?
+            .strip_prefix("#/")?
+            .parse()
+            .ok()
+    }
+
+    /// Moves an annotation at the end of the line of `statement` to its own line before it
+    fn move_above(
+        &self,
+        statement: Node,
+        comment: Node,
+        annotation: &str,
+    ) -> Option<[TextEdit; 2]> {
+        let line_start = self.line_start(statement.start_byte());
+        let indent = &self.source_code[line_start..statement.start_byte()];
+        // `a=1; b=2 #/ int` can't be moved without splitting the line
+        if !indent.chars().all(|c| c == ' ' || c == '\t') {
+            return None;
+        }
+        Some([
+            TextEdit {
+                range: line_start..line_start,
+                new_text: format!("{indent}{annotation}{}", self.line_ending()),
+            },
+            TextEdit {
+                range: statement.end_byte()..self.comment_range(comment).end,
+                new_text: String::new(),
+            },
+        ])
+    }
+
+    /// Moves an annotation on its own line to the end of the statement after it
+    fn move_inline(&self, comment: Node, annotation: &str) -> Option<[TextEdit; 2]> {
+        let statement = comment
+            .next_named_sibling()
+            .filter(|statement| statement.kind() != "comment")?;
+        let line_start = self.line_start(comment.start_byte());
+        let alone = self.source_code[line_start..comment.start_byte()]
+            .chars()
+            .all(|c| c == ' ' || c == '\t');
+        let has_comment = statement.next_named_sibling().is_some_and(|next| {
+            next.kind() == "comment" && next.start_position().row == statement.end_position().row
+        });
+        if !alone || has_comment || statement.start_position().row != comment.end_position().row + 1
+        {
+            return None;
+        }
+        Some([
+            TextEdit {
+                range: line_start..self.line_start(statement.start_byte()),
+                new_text: String::new(),
+            },
+            TextEdit {
+                range: statement.end_byte()..statement.end_byte(),
+                new_text: format!(" {annotation}"),
+            },
+        ])
+    }
+
+    /// An annotation with the inferred type for the first assignment of a variable
+    fn inferred_annotation(&self, statement: Node) -> Option<TextEdit> {
+        let assignment = match statement.kind() {
+            // Assignments in `local a=1` are annotated as the whole declaration
+            "variable_assignment"
+                if statement
+                    .parent()
+                    .is_none_or(|parent| parent.kind() != "declaration_command") =>
+            {
+                statement
+            }
+            "declaration_command" => {
+                let mut assignments = statement
+                    .named_children(&mut statement.walk())
+                    .filter(|child| child.kind() == "variable_assignment")
+                    .collect::<Vec<_>>();
+                if assignments.len() != 1 {
+                    return None;
+                }
+                assignments.pop()?
+            }
+            _ => return None,
+        };
+        let has_comment = statement.next_named_sibling().is_some_and(|next| {
+            next.kind() == "comment" && next.start_position().row == statement.end_position().row
+        });
+        if has_comment {
+            return None;
+        }
+        let name = self
+            .node_text(assignment.child_by_field_name("name")?)
+            .ok()?;
+        let first = self.history.get(name)?.first()?;
+        let bash_type = first.bash_type.normalized().to_string();
+        // Types with unknown parts can't be written in an annotation
+        let is_known = bash_type.parse::<BashType>().is_ok();
+        if first.method != Method::Inferred
+            || first.range != (assignment.start_byte()..assignment.end_byte())
+            || !is_known
+        {
+            return None;
+        }
+        let annotation = format!("#/ {bash_type}");
+        if self.config.annotation_style == AnnotationStyle::Above {
+            let line_start = self.line_start(statement.start_byte());
+            let indent = &self.source_code[line_start..statement.start_byte()];
+            if indent.chars().all(|c| c == ' ' || c == '\t') {
+                return Some(TextEdit {
+                    range: line_start..line_start,
+                    new_text: format!("{indent}{annotation}{}", self.line_ending()),
+                });
+            }
+        }
+        Some(TextEdit {
+            range: statement.end_byte()..statement.end_byte(),
+            new_text: format!(" {annotation}"),
+        })
+    }
+
+    fn line_start(&self, offset: usize) -> usize {
+        self.source_code[..offset].rfind('\n').map_or(0, |i| i + 1)
+    }
+}
@@ -0,0 +1,121 @@
+//! Every annotation written in a file, whether or not it ended up describing a variable
+
+use std::ops::Range;
+
+use crate::{
+    directive_version, functions::parse_params, BashType, BashVersion, CommentKind, FileInfo,
+    Parameter,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationKind {
+    /// `#/ type`
+    Type,
+    /// `#[command]`
+    Command,
+    /// `#![directive]`
+    Directive,
+}
+
+/// What an annotation says, parsed the same way as when the file is checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotationPayload {
+    Type(BashType),
+    Force,
+    Returns(BashType),
+    Params(Vec<Parameter>),
+    SetVar {
+        name: String,
+        bash_type: BashType,
+    },
+    BashVersion(BashVersion),
+    /// An annotation that can't be parsed, with the reason
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    /// The whole comment
+    pub range: Range<usize>,
+    /// The text between the markers, like `int | string` in `#/ int | string`
+    pub text_range: Range<usize>,
+    pub text: String,
+    pub payload: AnnotationPayload,
+}
+
+impl FileInfo<'_> {
+    /// The annotations in the file in source order, including the ones that are misplaced or
+    /// invalid. Doesn't need [`FileInfo::parse_code`] to have run
+    pub fn annotations(&mut self) -> Vec<Annotation> {
+        let tree = self.parse_tree();
+        let mut annotations = Vec::new();
+        let mut worklist = vec![tree.root_node()];
+        while let Some(node) = worklist.pop() {
+            if node.kind() == "comment" {
+                if let Ok(Some(comment)) = self.handle_comment(node) {
+                    let (kind, payload) = match comment.kind {
+                        CommentKind::Type => (AnnotationKind::Type, type_payload(&comment.text)),
+                        CommentKind::Command => {
+                            (AnnotationKind::Command, command_payload(&comment.text))
+                        }
+                        CommentKind::Directive => (
+                            AnnotationKind::Directive,
+                            directive_version(&comment.text).map_or_else(
+                                || AnnotationPayload::Invalid(String::from("Unknown directive")),
+                                AnnotationPayload::BashVersion,
+                            ),
+                        ),
+                    };
+                    annotations.push(Annotation {
+                        kind,
+                        range: comment.range,
+                        text_range: comment.text_range,
+                        text: comment.text,
+                        payload,
+                    });
+                }
+            }
+            let mut children = node.named_children(&mut node.walk()).collect::<Vec<_>>();
+            children.reverse();
+            worklist.extend(children);
+        }
+        annotations
+    }
+}
+
+fn type_payload(text: &str) -> AnnotationPayload {
+    text.parse()
+        .map_or_else(AnnotationPayload::Invalid, AnnotationPayload::Type)
+}
+
+fn command_payload(command: &str) -> AnnotationPayload {
+    if command == "force" {
+        return AnnotationPayload::Force;
+    }
+    if let Some(return_type) = command.strip_prefix("returns ") {
+        return return_type
+            .parse()
+            .map_or_else(AnnotationPayload::Invalid, AnnotationPayload::Returns);
+    }
+    if let Some(params) = command.strip_prefix("params ") {
+        return parse_params(params)
+            .map_or_else(AnnotationPayload::Invalid, AnnotationPayload::Params);
+    }
+    let Some(args) = command
+        .strip_prefix("set_var(")
+        .and_then(|args| args.strip_suffix(')'))
+    else {
+        return AnnotationPayload::Invalid(format!("Unknown command {command}"));
+    };
+    match args.split_once(',') {
+        Some((name, bash_type)) => match bash_type.parse() {
+            Ok(bash_type) => AnnotationPayload::SetVar {
+                name: name.trim().to_owned(),
+                bash_type,
+            },
+            Err(reason) => AnnotationPayload::Invalid(reason),
+        },
+        None => AnnotationPayload::Invalid(String::from("set_var takes a name and a type")),
+    }
+}
@@ -0,0 +1,181 @@
+//! Variables named by the arguments of builtins, like `line` in `read -r line` or `out` in
+//! `printf -v out`, which are assigned without an assignment in the syntax tree
+
+use std::ops::Range;
+
+use tree_sitter::Node;
+
+use crate::{arithmetic::arithmetic_assignments, arrays::is_identifier, FileInfo, ParseResult};
+
+/// Options of `mapfile` and `readarray` that take an argument
+const MAPFILE_OPTIONS_WITH_ARGUMENT: &[char] = &['d', 'n', 'O', 's', 'u', 'C', 'c'];
+/// Options of `read` that take an argument, which can be joined to them as in `-d,`
+pub(crate) const READ_OPTIONS_WITH_ARGUMENT: &[char] = &['a', 'd', 'i', 'n', 'N', 'p', 't', 'u'];
+
+/// Options, each with the argument it takes if it takes one, and the operands after them
+type SplitArguments<'tree> = (Vec<(char, Option<Node<'tree>>)>, Vec<Node<'tree>>);
+
+/// A variable named by an argument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NamedArgument {
+    pub(crate) name: String,
+    /// Where the name is written, or `None` when it can't be pointed at, like in `read l\ine`
+    pub(crate) range: Option<Range<usize>>,
+}
+
+impl FileInfo<'_> {
+    /// The variables a command assigns through its arguments: the names given to `read`,
+    /// `printf -v`, `wait -p`, `mapfile` and `readarray`, and the ones `let` assigns. Names only
+    /// known at runtime are left out
+    pub(crate) fn assigned_arguments(&self, command: Node) -> ParseResult<Vec<NamedArgument>> {
+        let Some(name) = command.child_by_field_name("name") else {
+            return Ok(Vec::new());
+        };
+        let arguments = command
+            .children_by_field_name("argument", &mut command.walk())
+            .collect::<Vec<_>>();
+        let mut named = Vec::new();
+        match self.node_text(name)? {
+            "read" => {
+                let (options, operands) =
+                    self.split_options(&arguments, READ_OPTIONS_WITH_ARGUMENT)?;
+                named.extend(self.option_names(&options, 'a')?);
+                for operand in operands {
+                    named.extend(self.argument_name(operand)?);
+                }
+            }
+            "printf" => {
+                let (options, _) = self.split_options(&arguments, &['v'])?;
+                named.extend(self.option_names(&options, 'v')?);
+            }
+            "wait" => {
+                let (options, _) = self.split_options(&arguments, &['p'])?;
+                named.extend(self.option_names(&options, 'p')?);
+            }
+            "mapfile" | "readarray" => {
+                let (_, operands) =
+                    self.split_options(&arguments, MAPFILE_OPTIONS_WITH_ARGUMENT)?;
+                if let Some(operand) = operands.first() {
+                    named.extend(self.argument_name(*operand)?);
+                }
+            }
+            "let" => {
+                for argument in arguments {
+                    named.extend(self.let_assignments(argument)?);
+                }
+            }
+            _ => (),
+        }
+        Ok(named)
+    }
+
+    /// Splits arguments into options, each with the argument it takes if it takes one, and the
+    /// operands after them. Option clusters like `-rp prompt` give their last letter
+    pub(crate) fn split_options<'tree>(
+        &self,
+        arguments: &[Node<'tree>],
+        with_argument: &[char],
+    ) -> ParseResult<SplitArguments<'tree>> {
+        let mut options = Vec::new();
+        let mut arguments = arguments.iter().copied();
+        while let Some(argument) = arguments.next() {
+            let text = self.node_text(argument)?;
+            if text == "--" {
+                return Ok((options, arguments.collect()));
+            }
+            let Some(flags) = text.strip_prefix('-').filter(|flags| !flags.is_empty()) else {
+                let mut operands = vec![argument];
+                operands.extend(arguments);
+                return Ok((options, operands));
+            };
+            match flags.find(with_argument) {
+                // An argument joined to the option, like `-d,`, isn't a separate word
+                Some(position) if position + 1 == flags.len() => {
+                    options.push((flags.as_bytes()[position] as char, arguments.next()));
+                }
+                Some(position) => options.push((flags.as_bytes()[position] as char, None)),
+                None => options.extend(flags.chars().map(|flag| (flag, None))),
+            }
+        }
+        Ok((options, Vec::new()))
+    }
+
+    /// The variables given to the options `flag` of `options`
+    fn option_names(
+        &self,
+        options: &[(char, Option<Node>)],
+        flag: char,
+    ) -> ParseResult<Vec<NamedArgument>> {
+        let mut named = Vec::new();
+        for (option, argument) in options {
+            if let (true, Some(argument)) = (*option == flag, argument) {
+                named.extend(self.argument_name(*argument)?);
+            }
+        }
+        Ok(named)
+    }
+
+    /// The variable an argument names, leaving out a subscript as in `read 'arr[0]'`
+    pub(crate) fn argument_name(&self, argument: Node) -> ParseResult<Option<NamedArgument>> {
+        let text = if argument.kind() == "concatenation" {
+            let mut text = Some(String::new());
+            for part in argument.named_children(&mut argument.walk()) {
+                text = text
+                    .zip(self.static_text(part)?)
+                    .map(|(text, part)| text + &part);
+            }
+            text
+        } else {
+            self.static_text(argument)?
+        };
+        let Some(text) = text else {
+            return Ok(None);
+        };
+        let name = text.split('[').next().unwrap_or_default();
+        if !is_identifier(name) {
+            return Ok(None);
+        }
+        let start = self.name_start(argument, name)?;
+        Ok(Some(NamedArgument {
+            name: name.to_owned(),
+            range: start.map(|start| start..start + name.len()),
+        }))
+    }
+
+    /// Where the text of `argument` starts in the source, if `text` is written there as it is
+    fn name_start(&self, argument: Node, text: &str) -> ParseResult<Option<usize>> {
+        let (start, written) = match argument.kind() {
+            "word" => (argument.start_byte(), self.node_text(argument)?),
+            "raw_string" => (
+                argument.start_byte() + 1,
+                self.node_text(argument)?.trim_start_matches('\''),
+            ),
+            "string" => match argument.named_child(0) {
+                Some(content) => (content.start_byte(), self.node_text(content)?),
+                None => return Ok(None),
+            },
+            // Only a name written whole in the first part, as in `arr[0]`, can be pointed at
+            "concatenation" => match argument.named_child(0) {
+                Some(first) => return self.name_start(first, text),
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        Ok(written.starts_with(text).then_some(start))
+    }
+
+    /// The variables an argument of `let`, like `i+=1`, assigns
+    fn let_assignments(&self, argument: Node) -> ParseResult<Vec<NamedArgument>> {
+        let Some(text) = self.static_text(argument)? else {
+            return Ok(Vec::new());
+        };
+        let start = self.name_start(argument, &text)?;
+        Ok(arithmetic_assignments(&text)
+            .into_iter()
+            .map(|range| NamedArgument {
+                name: text[range.clone()].to_owned(),
+                range: start.map(|start| start + range.start..start + range.end),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,240 @@
+use std::ops::Range;
+
+use tree_sitter::Node;
+
+use crate::{
+    bash_integer, codes, BashType, Diagnostic, FileInfo, Label, LabelKind, ParseResult, Severity,
+};
+
+/// What is known about the value of a divisor
+enum Divisor {
+    Zero,
+    /// A variable that might be unset or empty, both of which arithmetic reads as 0
+    MaybeZero(String),
+    Other,
+}
+
+/// Whether `node` is part of an arithmetic expression like `$((...))` or `((...))`
+pub(crate) fn in_arithmetic(node: Node) -> bool {
+    let mut ancestor = node.parent();
+    while let Some(parent) = ancestor {
+        if parent.kind() == "arithmetic_expansion" {
+            return true;
+        }
+        ancestor = parent.parent();
+    }
+    false
+}
+
+/// Whether `node` is an arithmetic command like `(( x > 5 ))`, whose status is whether the
+/// result is nonzero
+pub(crate) fn is_arithmetic_command(node: Node) -> bool {
+    node.kind() == "command"
+        && node
+            .child_by_field_name("name")
+            .and_then(|name| name.named_child(0))
+            .is_some_and(|expansion| {
+                expansion.kind() == "arithmetic_expansion"
+                    && expansion.child(0).is_some_and(|start| start.kind() == "((")
+            })
+}
+
+impl FileInfo<'_> {
+    /// Warns about variables holding strings that aren't numbers read in arithmetic, which reads
+    /// them as the name of another variable or fails, as in `flag=yes; (( flag ))`
+    pub(crate) fn check_arithmetic_operands(&mut self, expansion: Node) -> ParseResult<()> {
+        if self.force {
+            return Ok(());
+        }
+        let mut operands = Vec::new();
+        let mut worklist = vec![expansion];
+        while let Some(node) = worklist.pop() {
+            match node.kind() {
+                "variable_name" => operands.push(node),
+                "simple_expansion" | "expansion" => {
+                    if let Some(variable) = node
+                        .named_child(0)
+                        .filter(|_| node.named_child_count() == 1)
+                        .filter(|child| child.kind() == "variable_name")
+                    {
+                        operands.push(variable);
+                    }
+                }
+                // Checked on their own, and assigning a string is only a problem once it is read
+                "command_substitution" | "arithmetic_expansion" if node != expansion => (),
+                "subscript" => worklist.extend(node.child_by_field_name("index")),
+                "binary_expression" => {
+                    let assigns = node.child(1).is_some_and(|operator| operator.kind() == "=");
+                    for (i, child) in node.named_children(&mut node.walk()).enumerate() {
+                        if !(assigns && i == 0) {
+                            worklist.push(child);
+                        }
+                    }
+                }
+                _ => worklist.extend(node.named_children(&mut node.walk())),
+            }
+        }
+        operands.sort_by_key(Node::start_byte);
+        for variable in operands {
+            let Ok(BashType::String) = self.variable_type(variable) else {
+                continue;
+            };
+            let name = self.node_text(variable)?;
+            // An empty string is read as 0
+            let is_number = self
+                .resolve_name(name)
+                .and_then(|name| self.literal_values.get(name))
+                .is_some_and(|literal| literal.is_empty() || bash_integer(literal).is_some());
+            if is_number {
+                continue;
+            }
+            let help = if is_arithmetic_condition(expansion) {
+                format!("Test the string with `[[ -n ${name} ]]` or `[[ ${name} == ... ]]` instead")
+            } else {
+                String::from("Store a number in it, or compare it as a string with `[[ ]]`")
+            };
+            self.errors.push(
+                Diagnostic::new(
+                    format!("String {name} used in arithmetic"),
+                    variable.start_byte(),
+                )
+                .with_code(codes::ARITHMETIC_STRING)
+                .with_severity(Severity::Warning)
+                .with_label(
+                    Label::new(
+                        variable.start_byte()..variable.end_byte(),
+                        "Type inferred to be",
+                        LabelKind::Inferred,
+                    )
+                    .with_type(BashType::String),
+                )
+                .with_help(help),
+            );
+        }
+        Ok(())
+    }
+
+    /// Reports `/` and `%` by a divisor known to be 0, which bash fails on at runtime, and in
+    /// pedantic mode divisors that might be 0
+    pub(crate) fn check_division(&mut self, node: Node) -> ParseResult<()> {
+        let (Some(operator), Some(divisor)) = (node.child(1), node.child_by_field_name("right"))
+        else {
+            return Ok(());
+        };
+        if !matches!(operator.kind(), "/" | "%" | "/=" | "%=") || self.force || !in_arithmetic(node)
+        {
+            return Ok(());
+        }
+        let range = divisor.start_byte()..divisor.end_byte();
+        match self.divisor(divisor)? {
+            Divisor::Zero => self.errors.push(
+                Diagnostic::new("Division by zero", operator.start_byte())
+                    .with_code(codes::DIVISION_BY_ZERO)
+                    .with_label(Label::new(range, "This is always 0", LabelKind::ParseErr)),
+            ),
+            Divisor::MaybeZero(name) if self.config.pedantic => self.errors.push(
+                Diagnostic::new("Divisor may be zero", operator.start_byte())
+                    .with_code(codes::DIVISION_BY_ZERO)
+                    .with_severity(Severity::Warning)
+                    .with_label(Label::new(
+                        range,
+                        format!("{name} is 0 when it is unset or empty"),
+                        LabelKind::ParseErr,
+                    )),
+            ),
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn divisor(&self, node: Node) -> ParseResult<Divisor> {
+        let variable = match node.kind() {
+            "number" => {
+                return Ok(match bash_integer(self.node_text(node)?) {
+                    Some(0) => Divisor::Zero,
+                    _ => Divisor::Other,
+                })
+            }
+            "parenthesized_expression" if node.named_child_count() == 1 => {
+                return self.divisor(node.named_child(0).expect("child count to be one"))
+            }
+            // Names are expanded as variables in arithmetic
+            "variable_name" => node,
+            "simple_expansion" | "expansion" if node.named_child_count() == 1 => {
+                node.named_child(0).expect("child count to be one")
+            }
+            _ => return Ok(Divisor::Other),
+        };
+        if variable.kind() != "variable_name" {
+            return Ok(Divisor::Other);
+        }
+        let Some(name) = self.resolve_name(self.node_text(variable)?) else {
+            return Ok(Divisor::Other);
+        };
+        if let Some(literal) = self.literal_values.get(name) {
+            return Ok(match bash_integer(literal.trim()) {
+                Some(0) => Divisor::Zero,
+                None if literal.trim().is_empty() => Divisor::Zero,
+                _ => Divisor::Other,
+            });
+        }
+        let might_be_unset = self.conditionally_set.contains(name)
+            || self.unset_variables.contains(name)
+            || !self.variables.contains_key(name);
+        // Integers without a known value could be anything, but strings can be empty
+        let might_be_empty = self.variables.get(name).is_some_and(|declaration| {
+            let current = self.narrowed.get(name).unwrap_or(&declaration.bash_type);
+            !matches!(current, BashType::Any | BashType::Unknown)
+                && current.can_contain(&BashType::String)
+        });
+        Ok(if might_be_unset || might_be_empty {
+            Divisor::MaybeZero(name.to_owned())
+        } else {
+            Divisor::Other
+        })
+    }
+}
+
+/// Whether `expansion` is an arithmetic command testing a single variable, like `(( flag ))`
+fn is_arithmetic_condition(expansion: Node) -> bool {
+    expansion.child(0).is_some_and(|start| start.kind() == "((")
+        && expansion.named_child_count() == 1
+        && expansion
+            .named_child(0)
+            .is_some_and(|operand| operand.kind() == "variable_name")
+}
+
+/// Where the variables assigned in the arithmetic expression `text` are named, as in `i = 4`,
+/// `total += n` or `i++`. Found in the text since the grammar doesn't parse every arithmetic
+/// command, like `(( i++ ))`
+pub(crate) fn arithmetic_assignments(text: &str) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let is_name_byte = |byte: u8| byte.is_ascii_alphanumeric() || byte == b'_';
+    let mut assigned = Vec::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        if !(bytes[position].is_ascii_alphabetic() || bytes[position] == b'_')
+            || position > 0 && (is_name_byte(bytes[position - 1]) || bytes[position - 1] == b'$')
+        {
+            position += 1;
+            continue;
+        }
+        let start = position;
+        while position < bytes.len() && is_name_byte(bytes[position]) {
+            position += 1;
+        }
+        let before = text[..start].trim_end();
+        let after = text[position..].trim_start();
+        let assigns = ["++", "--"]
+            .iter()
+            .any(|operator| before.ends_with(operator) || after.starts_with(operator))
+            || after.starts_with('=') && !after.starts_with("==")
+            || ["+=", "-=", "*=", "/=", "%=", "&=", "^=", "|=", "<<=", ">>="]
+                .iter()
+                .any(|operator| after.starts_with(operator));
+        if assigns {
+            assigned.push(start..position);
+        }
+    }
+    assigned
+}
@@ -0,0 +1,804 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+use tree_sitter::Node;
+
+use crate::{
+    codes,
+    command_names::closest_name,
+    fix::{Applicability, Fix, FixKind, TextEdit},
+    label_from_type_declaration, literal_type, BashType, Comment, Diagnostic, FileInfo, Label,
+    LabelKind, Method, ParseResult, Severity, TypeDeclaration,
+};
+
+impl FileInfo<'_> {
+    /// Types a compound assignment like `(1 2 3)`, or `([key]=value)` for associative arrays
+    pub(crate) fn array_literal_type(
+        &mut self,
+        array: Node,
+        associative: bool,
+    ) -> ParseResult<BashType> {
+        let mut element_type: Option<BashType> = None;
+        for element in array.named_children(&mut array.walk()) {
+            let inferred_type = match self.keyed_element(element)? {
+                Some((_, value_type)) => value_type,
+                // Arrays can't be nested, so `("${other[@]}")` splices in the elements
+                None => {
+                    let inferred_type = self.infer_type(element)?;
+                    inferred_type.element_type().unwrap_or(inferred_type)
+                }
+            };
+            element_type = Some(match element_type {
+                Some(element_type) => element_type.union(inferred_type),
+                None => inferred_type,
+            });
+        }
+        let element_type = Box::new(element_type.unwrap_or(BashType::Unknown));
+        Ok(if associative {
+            BashType::Map(Box::new(BashType::String), element_type)
+        } else {
+            BashType::Array(element_type)
+        })
+    }
+
+    /// Types a compound assignment to a variable annotated as a tuple, keeping the type of each
+    /// position
+    pub(crate) fn array_literal_tuple(&mut self, array: Node) -> ParseResult<BashType> {
+        let mut elements = Vec::new();
+        for element in array.named_children(&mut array.walk()) {
+            let inferred_type = self.infer_type(element)?;
+            // Splicing in another array makes the length unknown
+            if inferred_type.element_type().is_some() {
+                return self.array_literal_type(array, false);
+            }
+            elements.push(inferred_type);
+        }
+        Ok(BashType::Tuple(elements))
+    }
+
+    /// Types a compound assignment to a variable annotated as a record, keeping the type of each
+    /// key
+    pub(crate) fn array_literal_record(&mut self, array: Node) -> ParseResult<BashType> {
+        let mut fields = BTreeMap::new();
+        for element in array.named_children(&mut array.walk()) {
+            let Some((key, value_type)) = self.keyed_element(element)? else {
+                // Not a `[key]=value` pair, so the keys can't be known
+                return self.array_literal_type(array, true);
+            };
+            fields.insert(key, value_type);
+        }
+        Ok(BashType::Record(fields))
+    }
+
+    /// Whether a compound assignment to `name` should keep the type of each position
+    pub(crate) fn is_tuple(&self, name: &str, annotation: Option<&Comment>) -> bool {
+        self.has_shape(name, annotation, |bash_type| {
+            matches!(bash_type, BashType::Tuple(_))
+        })
+    }
+
+    /// Whether a compound assignment to `name` should keep the type of each key
+    pub(crate) fn is_record(&self, name: &str, annotation: Option<&Comment>) -> bool {
+        self.has_shape(name, annotation, |bash_type| {
+            matches!(bash_type, BashType::Record(_))
+        })
+    }
+
+    fn has_shape(
+        &self,
+        name: &str,
+        annotation: Option<&Comment>,
+        shape: fn(&BashType) -> bool,
+    ) -> bool {
+        let annotated = annotation.is_some_and(|comment| {
+            comment
+                .text
+                .parse()
+                .is_ok_and(|bash_type: BashType| shape(&bash_type))
+        });
+        annotated
+            || self
+                .variables
+                .get(name)
+                .is_some_and(|declaration| shape(&declaration.bash_type))
+    }
+
+    /// Finds the key and the type of the value of a `[key]=value` element, which the grammar only
+    /// sees as a concatenation of words
+    fn keyed_element(&mut self, element: Node) -> ParseResult<Option<(String, BashType)>> {
+        if element.kind() != "concatenation"
+            || element
+                .child(0)
+                .map(|child| self.node_text(child))
+                .transpose()?
+                != Some("[")
+        {
+            return Ok(None);
+        }
+        let mut children = element
+            .named_children(&mut element.walk())
+            .collect::<Vec<_>>();
+        let Some(equals) = children.iter().position(|child| {
+            self.node_text(*child)
+                .is_ok_and(|text| text.starts_with('='))
+        }) else {
+            return Ok(None);
+        };
+        let value = children.split_off(equals);
+        // Everything between the brackets
+        let key = children[1..children.len().saturating_sub(1).max(1)]
+            .iter()
+            .map(|child| self.node_text(*child))
+            .collect::<ParseResult<String>>()?;
+        let value_type = match value.as_slice() {
+            // `[key]=3`
+            [equals] => literal_type(&self.node_text(*equals)?[1..]),
+            // `[key]="$value"`
+            [_, value] => self.infer_type(*value)?,
+            _ => BashType::String,
+        };
+        Ok(Some((key, value_type)))
+    }
+
+    /// Whether an assignment to `name` creates or updates an associative array
+    pub(crate) fn is_associative(&self, name: &str, node: Node) -> ParseResult<bool> {
+        if let Some(parent) = node
+            .parent()
+            .filter(|parent| parent.kind() == "declaration_command")
+        {
+            if self.declaration_flags(parent)?.contains('A') {
+                return Ok(true);
+            }
+        }
+        Ok(self.variables.get(name).is_some_and(|declaration| {
+            matches!(
+                declaration.bash_type,
+                BashType::Map(..) | BashType::Record(_)
+            )
+        }))
+    }
+
+    /// Types `arr[i]` when it's read in an expansion
+    pub(crate) fn subscript_type(&mut self, subscript: Node) -> ParseResult<BashType> {
+        let name = subscript
+            .child_by_field_name("name")
+            .expect("subscript to have a name");
+        let container = self.variable_type(name)?;
+        if let Some(index) = subscript.child_by_field_name("index") {
+            // `${arr[@]}` and `${arr[*]}` expand to the whole array
+            if matches!(self.node_text(index)?, "@" | "*") {
+                return Ok(container);
+            }
+        }
+        match &container {
+            BashType::Tuple(elements) => {
+                if let Some(position) = self.literal_index(subscript)? {
+                    return Ok(self.tuple_element(elements, position, subscript));
+                }
+            }
+            BashType::Record(fields) => {
+                if let Some(key) = self.literal_key(subscript)? {
+                    let name = self.node_text(name)?;
+                    let Some(name) = self.resolve_name(name).map(str::to_owned) else {
+                        return Ok(BashType::Any);
+                    };
+                    let Some(field) = fields.get(&key) else {
+                        if !self.force {
+                            self.undeclared_key(
+                                &name,
+                                &key,
+                                fields,
+                                subscript,
+                                subscript.start_byte(),
+                            );
+                        }
+                        return Ok(BashType::Unknown);
+                    };
+                    self.record_key_read(name, key, subscript)?;
+                    return Ok(field.clone());
+                }
+            }
+            _ => (),
+        }
+        // `${scalar[0]}` is the scalar itself
+        Ok(container.element_type().unwrap_or(container))
+    }
+
+    /// Reports a literal key that isn't one of the keys declared for the record `name`,
+    /// suggesting the closest declared key
+    fn undeclared_key(
+        &mut self,
+        name: &str,
+        key: &str,
+        fields: &BTreeMap<String, BashType>,
+        subscript: Node,
+        start: usize,
+    ) {
+        let message = format!("Key {key} is not declared for {name}");
+        // Reads in values are checked both with their assignment and where the expansion is
+        if self
+            .errors
+            .iter()
+            .any(|error| error.offset == start && error.message == message)
+        {
+            return;
+        }
+        let mut diagnostic = Diagnostic::new(message, start).with_code(codes::INVALID_SUBSCRIPT);
+        if let Some(declaration) = self.variables.get(name) {
+            diagnostic = diagnostic.with_label(label_from_type_declaration(declaration, false));
+        }
+        diagnostic = diagnostic.with_label(Label::new(
+            subscript.start_byte()..subscript.end_byte(),
+            "Undeclared key used here",
+            LabelKind::Inferred,
+        ));
+        let index = subscript
+            .child_by_field_name("index")
+            .expect("literal key to have an index");
+        if let Some(suggestion) = closest_name(key, fields.keys()) {
+            diagnostic = diagnostic
+                .with_help(format!("Did you mean {suggestion}?"))
+                .with_fix(Fix {
+                    kind: FixKind::Rename,
+                    applicability: Applicability::MaybeIncorrect,
+                    title: format!("Change to {suggestion}"),
+                    edits: vec![TextEdit {
+                        range: index.start_byte()..index.end_byte(),
+                        new_text: suggestion,
+                    }],
+                });
+        }
+        self.errors.push(diagnostic);
+    }
+
+    /// Remembers the keys of a compound assignment to the record `name`. Anything that isn't a
+    /// record could have set any key
+    pub(crate) fn record_written_keys(&mut self, name: &str, value_type: &BashType) {
+        let BashType::Record(fields) = value_type else {
+            self.written_keys.insert(name.to_owned(), None);
+            return;
+        };
+        if let Some(keys) = self
+            .written_keys
+            .entry(name.to_owned())
+            .or_insert_with(|| Some(BTreeSet::new()))
+        {
+            keys.extend(fields.keys().cloned());
+        }
+    }
+
+    /// Remembers a literal key read from the record `name`, to be checked against the keys that
+    /// are set once the whole file is known. Reads with a default, like `${cfg[port]:-80}`,
+    /// expect the key to be missing
+    fn record_key_read(&mut self, name: String, key: String, subscript: Node) -> ParseResult<()> {
+        if self.force {
+            return Ok(());
+        }
+        let has_default = subscript.parent().is_some_and(|expansion| {
+            expansion.kind() == "expansion"
+                && expansion.children(&mut expansion.walk()).any(|child| {
+                    matches!(
+                        child.kind(),
+                        ":-" | "-" | ":=" | "=" | ":+" | "+" | ":?" | "?"
+                    )
+                })
+        });
+        let index = subscript
+            .child_by_field_name("index")
+            .expect("literal key to have an index");
+        let read = (
+            name,
+            key,
+            subscript.start_byte()..subscript.end_byte(),
+            index.start_byte()..index.end_byte(),
+        );
+        if !has_default && !self.key_reads.contains(&read) {
+            self.key_reads.push(read);
+        }
+        Ok(())
+    }
+
+    /// Checks a literal key read from a record in any expansion, like `echo "${cfg[host]}"`.
+    /// Expansions whose type is needed, like values, are also checked when they are typed
+    pub(crate) fn check_record_key_read(&mut self, expansion: Node) -> ParseResult<()> {
+        let Some(subscript) = expansion
+            .named_child(0)
+            .filter(|child| child.kind() == "subscript")
+        else {
+            return Ok(());
+        };
+        let name = subscript
+            .child_by_field_name("name")
+            .expect("subscript to have a name");
+        // Unknown variables are reported on their own
+        let Ok(BashType::Record(_)) = self.variable_type(name) else {
+            return Ok(());
+        };
+        if let Some(index) = subscript.child_by_field_name("index") {
+            if matches!(self.node_text(index)?, "@" | "*") {
+                return Ok(());
+            }
+        }
+        self.subscript_type(subscript)?;
+        Ok(())
+    }
+
+    /// Warns about keys read from records that nothing in the file sets. Records from sourced
+    /// files may be set there
+    pub(crate) fn check_record_keys(&mut self) {
+        let reads = std::mem::take(&mut self.key_reads);
+        if !self.sources.is_empty() {
+            return;
+        }
+        for (name, key, range, index) in reads {
+            let set_keys = match self.written_keys.get(&name) {
+                Some(Some(keys)) => keys,
+                Some(None) => continue,
+                None => &BTreeSet::new(),
+            };
+            if set_keys.contains(&key) {
+                continue;
+            }
+            let suggestion = closest_name(&key, set_keys);
+            let mut diagnostic =
+                Diagnostic::new(format!("Key {key} of {name} is never set"), range.start)
+                    .with_code(codes::UNSET_KEY)
+                    .with_severity(Severity::Warning)
+                    .with_label(Label::new(
+                        range,
+                        "Read here, but no assignment sets it",
+                        LabelKind::Inferred,
+                    ));
+            diagnostic = match suggestion {
+                Some(suggestion) => diagnostic
+                    .with_help(format!("Did you mean {suggestion}?"))
+                    .with_fix(Fix {
+                        kind: FixKind::Rename,
+                        applicability: Applicability::MaybeIncorrect,
+                        title: format!("Change to {suggestion}"),
+                        edits: vec![TextEdit {
+                            range: index,
+                            new_text: suggestion,
+                        }],
+                    }),
+                None => diagnostic.with_help(format!(
+                    "Assign {name}[{key}] before reading it, or give it a default with \
+                     `${{{name}[{key}]:-default}}`"
+                )),
+            };
+            self.errors.push(diagnostic);
+        }
+    }
+
+    /// The index of `arr[2]`, if it's written as a number
+    fn literal_index(&self, subscript: Node) -> ParseResult<Option<usize>> {
+        let Some(index) = subscript.child_by_field_name("index") else {
+            return Ok(None);
+        };
+        Ok(self.node_text(index)?.parse().ok())
+    }
+
+    /// The key of `cfg[host]`, if it's written literally
+    fn literal_key(&self, subscript: Node) -> ParseResult<Option<String>> {
+        match subscript.child_by_field_name("index") {
+            Some(index) => self.static_text(index),
+            None => Ok(None),
+        }
+    }
+
+    fn tuple_element(
+        &mut self,
+        elements: &[BashType],
+        position: usize,
+        subscript: Node,
+    ) -> BashType {
+        if let Some(element) = elements.get(position) {
+            return element.clone();
+        }
+        if !self.force {
+            self.errors.push(
+                Diagnostic::new("Tuple index out of bounds", subscript.start_byte())
+                    .with_code(codes::INVALID_SUBSCRIPT)
+                    .with_label(
+                        Label::new(
+                            subscript.start_byte()..subscript.end_byte(),
+                            format!("Index {position} is past the end of"),
+                            LabelKind::Inferred,
+                        )
+                        .with_type(BashType::Tuple(elements.to_vec())),
+                    ),
+            );
+        }
+        BashType::Unknown
+    }
+
+    /// Checks `arr[i]=value` against the type of the array
+    pub(crate) fn handle_subscript_assignment(
+        &mut self,
+        node: Node,
+        subscript: Node,
+    ) -> ParseResult<()> {
+        let name = self.node_text(
+            subscript
+                .child_by_field_name("name")
+                .expect("subscript to have a name"),
+        )?;
+        let Some(name) = self.resolve_name(name).map(str::to_owned) else {
+            return Ok(());
+        };
+        let value_location = node.start_byte()..node.end_byte();
+        let value_type = match node.child_by_field_name("value") {
+            Some(value) => self.infer_type(value)?,
+            None => BashType::String,
+        };
+
+        let Some(declaration) = self.variables.get(&name).cloned() else {
+            // Assigning to an index of an unset variable creates an indexed array
+            self.check_index(subscript)?;
+            let declaration = TypeDeclaration {
+                bash_type: BashType::Array(Box::new(value_type)),
+                range: value_location,
+                method: Method::Inferred,
+                node_kind: node.kind().to_owned(),
+                annotation: None,
+                file: None,
+            };
+            self.set_variable(&name, declaration, node);
+            return Ok(());
+        };
+
+        let element_type = match &declaration.bash_type {
+            BashType::Array(element) => {
+                self.check_index(subscript)?;
+                (**element).clone()
+            }
+            BashType::Map(_, value) => (**value).clone(),
+            BashType::Tuple(elements) => {
+                self.check_index(subscript)?;
+                match self.literal_index(subscript)? {
+                    Some(position) => self.tuple_element(elements, position, subscript),
+                    None => declaration
+                        .bash_type
+                        .element_type()
+                        .unwrap_or(BashType::Unknown),
+                }
+            }
+            BashType::Record(fields) => match self.literal_key(subscript)? {
+                Some(key) => {
+                    if let Some(keys) = self
+                        .written_keys
+                        .entry(name.clone())
+                        .or_insert_with(|| Some(BTreeSet::new()))
+                    {
+                        keys.insert(key.clone());
+                    }
+                    match fields.get(&key) {
+                        Some(field) => field.clone(),
+                        None if self.force => return Ok(()),
+                        None => {
+                            self.undeclared_key(&name, &key, fields, subscript, node.start_byte());
+                            return Ok(());
+                        }
+                    }
+                }
+                // The key is only known at runtime, so it could be any of them
+                None => {
+                    self.written_keys.insert(name.clone(), None);
+                    declaration
+                        .bash_type
+                        .element_type()
+                        .unwrap_or(BashType::Unknown)
+                }
+            },
+            BashType::Any | BashType::Unknown | BashType::Or(..) => return Ok(()),
+            _ if self.force => return Ok(()),
+            _ => {
+                self.errors.push(
+                    Diagnostic::new(
+                        format!("Variable {name} is not an array"),
+                        node.start_byte(),
+                    )
+                    .with_code(codes::INVALID_SUBSCRIPT)
+                    .with_label(label_from_type_declaration(&declaration, false))
+                    .with_label(Label::new(
+                        subscript.start_byte()..subscript.end_byte(),
+                        "Used as an array here",
+                        LabelKind::Inferred,
+                    )),
+                );
+                return Ok(());
+            }
+        };
+
+        let value_type = self.coerce_numeric_string(
+            &element_type,
+            value_type,
+            node.child_by_field_name("value"),
+            codes::ELEMENT_MISMATCH,
+        )?;
+        if !element_type.can_contain(&value_type) && !self.force {
+            self.element_mismatch(&declaration, element_type, value_location, value_type, node);
+        }
+        Ok(())
+    }
+
+    /// The type of `name` after `name+=value`, which appends to arrays and concatenates
+    /// anything else. Returns `None` if the appended elements don't fit in the array
+    pub(crate) fn appended_type(
+        &mut self,
+        name: &str,
+        value_type: BashType,
+        value_location: Range<usize>,
+        node: Node,
+    ) -> Option<BashType> {
+        let Some(declaration) = self.variables.get(name).cloned() else {
+            return Some(value_type);
+        };
+        match (&declaration.bash_type, &value_type) {
+            (BashType::Array(element), BashType::Array(appended))
+            | (BashType::Map(_, element), BashType::Map(_, appended)) => {
+                if !element.can_contain(appended) && !self.force {
+                    self.element_mismatch(
+                        &declaration,
+                        (**element).clone(),
+                        value_location,
+                        (**appended).clone(),
+                        node,
+                    );
+                    return None;
+                }
+                Some(declaration.bash_type)
+            }
+            (BashType::Record(_), BashType::Record(_)) => {
+                if !declaration.bash_type.can_contain(&value_type) && !self.force {
+                    self.element_mismatch(
+                        &declaration,
+                        declaration.bash_type.clone(),
+                        value_location,
+                        value_type,
+                        node,
+                    );
+                    return None;
+                }
+                Some(declaration.bash_type)
+            }
+            (BashType::Tuple(elements), BashType::Tuple(appended)) => Some(BashType::Tuple(
+                [elements.clone(), appended.clone()].concat(),
+            )),
+            // The length is no longer known
+            (BashType::Tuple(_), BashType::Array(_)) => {
+                let element = declaration.bash_type.element_type()?;
+                Some(BashType::Array(Box::new(
+                    element.union(value_type.element_type()?),
+                )))
+            }
+            // Only variables with the integer attribute add instead of concatenating, but the
+            // difference can't be seen here
+            (BashType::Integer, BashType::Integer) => Some(BashType::Integer),
+            (BashType::Any | BashType::Unknown, _) => Some(declaration.bash_type),
+            _ => Some(BashType::String),
+        }
+    }
+
+    fn element_mismatch(
+        &mut self,
+        declaration: &TypeDeclaration,
+        element_type: BashType,
+        value_location: Range<usize>,
+        value_type: BashType,
+        node: Node,
+    ) {
+        let (kind, description) = match declaration.method {
+            Method::Inferred => (LabelKind::Inferred, "inferred"),
+            Method::Declared => (LabelKind::Specified, "declared"),
+        };
+        self.errors.push(
+            Diagnostic::new("Element type does not match", node.start_byte())
+                .with_code(codes::ELEMENT_MISMATCH)
+                .with_label(
+                    Label::new(
+                        declaration.range.clone(),
+                        format!("Elements {description} to be"),
+                        kind,
+                    )
+                    .with_type(element_type),
+                )
+                .with_label(
+                    Label::new(value_location, "Type inferred to be", LabelKind::Inferred)
+                        .with_type(value_type),
+                ),
+        );
+    }
+
+    /// Indexed arrays are indexed with arithmetic, so the index has to be an int
+    fn check_index(&mut self, subscript: Node) -> ParseResult<()> {
+        let Some(index) = subscript.child_by_field_name("index") else {
+            return Ok(());
+        };
+        let index_type = match index.kind() {
+            "word" => {
+                let text = self.node_text(index)?;
+                if is_identifier(text) {
+                    // Names are expanded as variables in arithmetic
+                    self.variables
+                        .get(text)
+                        .map_or(BashType::Unknown, |declaration| {
+                            declaration.bash_type.clone()
+                        })
+                } else {
+                    // Everything else is an arithmetic expression like `i+1`
+                    BashType::Integer
+                }
+            }
+            _ => self.infer_type(index)?,
+        };
+        if !BashType::Integer.can_contain(&index_type) && !self.force {
+            self.errors.push(
+                Diagnostic::new("Array index must be an int", subscript.start_byte())
+                    .with_code(codes::INVALID_SUBSCRIPT)
+                    .with_label(
+                        Label::new(
+                            index.start_byte()..index.end_byte(),
+                            "Index inferred to be",
+                            LabelKind::Inferred,
+                        )
+                        .with_type(index_type),
+                    ),
+            );
+        }
+        Ok(())
+    }
+
+    /// Warns about arrays expanded like scalars, as in `$arr` or `${#arr}`, which only use the
+    /// first element, and scalars expanded like arrays, as in `"${str[@]}"`
+    pub(crate) fn check_array_context(&mut self, expansion: Node) -> ParseResult<()> {
+        let Some(target) = expansion.named_child(0) else {
+            return Ok(());
+        };
+        // Operators like `${arr:-x}` and `${arr@Q}` are left alone
+        let is_plain = expansion
+            .children(&mut expansion.walk())
+            .all(|child| child.is_named() || matches!(child.kind(), "$" | "${" | "}" | "#"));
+        if expansion.named_child_count() != 1 || !is_plain || self.force {
+            return Ok(());
+        }
+        let length = expansion.child(1).is_some_and(|child| child.kind() == "#");
+        let (variable, whole_array) = match target.kind() {
+            "variable_name" => (target, false),
+            "subscript" => {
+                let whole_array = match target.child_by_field_name("index") {
+                    Some(index) => matches!(self.node_text(index)?, "@" | "*"),
+                    None => false,
+                };
+                if !whole_array {
+                    return Ok(());
+                }
+                (
+                    target
+                        .child_by_field_name("name")
+                        .expect("subscript to have a name"),
+                    true,
+                )
+            }
+            _ => return Ok(()),
+        };
+        // Unknown variables are reported on their own
+        let Ok(bash_type) = self.variable_type(variable) else {
+            return Ok(());
+        };
+        let members = bash_type.types_from_or();
+        let name = self.node_text(variable)?;
+        let range = expansion.start_byte()..expansion.end_byte();
+        let diagnostic = if !whole_array && members.iter().all(is_container) {
+            let quoted = expansion
+                .parent()
+                .is_some_and(|parent| parent.kind() == "string");
+            let mut diagnostic = if length {
+                Diagnostic::new(
+                    format!("{name} is an array, but this is the length of its first element"),
+                    range.start,
+                )
+                .with_fix(array_fix(
+                    format!("Count the elements of {name}"),
+                    &range,
+                    format!("${{#{name}[@]}}"),
+                ))
+            } else {
+                let every_element = if quoted {
+                    format!("${{{name}[@]}}")
+                } else {
+                    format!("\"${{{name}[@]}}\"")
+                };
+                Diagnostic::new(
+                    format!("{name} is an array, but only its first element is used"),
+                    range.start,
+                )
+                .with_fix(array_fix(
+                    format!("Expand every element of {name}"),
+                    &range,
+                    every_element,
+                ))
+            };
+            if !length && matches!(bash_type, BashType::Array(_) | BashType::Tuple(_)) {
+                diagnostic = diagnostic.with_fix(array_fix(
+                    format!("Use the first element of {name}"),
+                    &range,
+                    format!("${{{name}[0]}}"),
+                ));
+            }
+            diagnostic.with_label(
+                Label::new(
+                    range.clone(),
+                    "Expanded as a single value, but it is",
+                    LabelKind::Inferred,
+                )
+                .with_type(bash_type),
+            )
+        } else if whole_array
+            && members.iter().all(|member| {
+                matches!(
+                    member,
+                    BashType::String | BashType::Integer | BashType::Bool
+                )
+            })
+        {
+            let (message, title, replacement) = if length {
+                (
+                    format!("{name} is not an array, so this is always 1"),
+                    format!("Use the length of {name}"),
+                    format!("${{#{name}}}"),
+                )
+            } else {
+                (
+                    format!("{name} is not an array"),
+                    format!("Expand {name} as a single value"),
+                    format!("${{{name}}}"),
+                )
+            };
+            Diagnostic::new(message, range.start)
+                .with_label(
+                    Label::new(
+                        range.clone(),
+                        "Expanded as an array, but it is",
+                        LabelKind::Inferred,
+                    )
+                    .with_type(bash_type),
+                )
+                .with_fix(array_fix(title, &range, replacement))
+        } else {
+            return Ok(());
+        };
+        self.errors.push(
+            diagnostic
+                .with_code(codes::ARRAY_CONTEXT)
+                .with_severity(Severity::Warning),
+        );
+        Ok(())
+    }
+}
+
+pub(crate) fn is_container(bash_type: &BashType) -> bool {
+    matches!(
+        bash_type,
+        BashType::Array(_) | BashType::Map(..) | BashType::Tuple(_) | BashType::Record(_)
+    )
+}
+
+fn array_fix(title: String, range: &Range<usize>, new_text: String) -> Fix {
+    Fix {
+        kind: FixKind::Expansion,
+        applicability: Applicability::MaybeIncorrect,
+        title,
+        edits: vec![TextEdit {
+            range: range.clone(),
+            new_text,
+        }],
+    }
+}
+
+pub(crate) fn is_identifier(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
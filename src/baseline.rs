@@ -0,0 +1,165 @@
+//! Known diagnostics to ignore, so bashtyped can be adopted in a codebase before every existing
+//! problem is fixed
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Diagnostic;
+
+/// Diagnostics are matched by their code, message and the text of the line they are on, so
+/// unrelated edits that move them around the file don't make them count as new
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    entries: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct BaselineEntry {
+    path: String,
+    code: Option<String>,
+    message: String,
+    /// The line the diagnostic starts on, with whitespace collapsed
+    line: String,
+    /// How many identical diagnostics there are
+    count: usize,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// Records every diagnostic found in a file
+    pub fn add(&mut self, path: &str, source_code: &str, diagnostics: &[Diagnostic]) {
+        for (key, count) in counts(path, source_code, diagnostics) {
+            let (path, code, message, line) = key;
+            self.entries.push(BaselineEntry {
+                path,
+                code,
+                message,
+                line,
+                count,
+            });
+        }
+        self.entries.sort();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.iter().map(|entry| entry.count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The diagnostics of a file that aren't in the baseline. When a diagnostic appears more
+    /// often than it was recorded, the first ones are treated as known
+    pub fn new_diagnostics(
+        &self,
+        path: &str,
+        source_code: &str,
+        diagnostics: Vec<Diagnostic>,
+    ) -> Vec<Diagnostic> {
+        let mut known: BTreeMap<Key, usize> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.path == path)
+            .map(|entry| {
+                let key = (
+                    entry.path.clone(),
+                    entry.code.clone(),
+                    entry.message.clone(),
+                    entry.line.clone(),
+                );
+                (key, entry.count)
+            })
+            .collect();
+        diagnostics
+            .into_iter()
+            .filter(
+                |diagnostic| match known.get_mut(&key(path, source_code, diagnostic)) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                },
+            )
+            .collect()
+    }
+}
+
+/// How `file` is recorded in the baseline at `baseline`: relative to the directory the baseline
+/// is in, with `/` between components. This way the same baseline matches whichever directory
+/// bashtyped is run from
+pub fn entry_path(baseline: &Path, file: &Path) -> String {
+    let directory = absolute(baseline.parent().unwrap_or(Path::new("")));
+    let file = absolute(file);
+    let common = directory
+        .components()
+        .zip(file.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut components = vec![".."; directory.components().count() - common];
+    components.extend(
+        file.components()
+            .skip(common)
+            .filter_map(|component| component.as_os_str().to_str()),
+    );
+    components.join("/")
+}
+
+/// `path` resolved from the current directory, following symlinks when it exists
+fn absolute(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    let path = std::env::current_dir().unwrap_or_default().join(path);
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            component => resolved.push(component),
+        }
+    }
+    resolved
+}
+
+type Key = (String, Option<String>, String, String);
+
+fn key(path: &str, source_code: &str, diagnostic: &Diagnostic) -> Key {
+    let offset = diagnostic.offset.min(source_code.len());
+    let start = source_code
+        .get(..offset)
+        .and_then(|before| before.rfind('\n'))
+        .map_or(0, |i| i + 1);
+    let line = source_code[start..].lines().next().unwrap_or_default();
+    (
+        path.to_owned(),
+        diagnostic.code.clone(),
+        diagnostic.message.clone(),
+        line.split_whitespace().collect::<Vec<_>>().join(" "),
+    )
+}
+
+fn counts(path: &str, source_code: &str, diagnostics: &[Diagnostic]) -> BTreeMap<Key, usize> {
+    let mut counts = BTreeMap::new();
+    for diagnostic in diagnostics {
+        *counts
+            .entry(key(path, source_code, diagnostic))
+            .or_insert(0) += 1;
+    }
+    counts
+}
@@ -0,0 +1,103 @@
+//! Test files for BATS, the Bash Automated Testing System. Each `@test "name" { ... }` block runs
+//! in its own process with variables like `$status` and `$output` set by BATS
+
+use tree_sitter::Node;
+
+use crate::{BashType, FileInfo, ParseResult, SourceReference};
+
+/// Commands defined by BATS for test files. The grammar doesn't know `@test` blocks, so their
+/// closing `}` is parsed as a command too
+pub(crate) const BATS_COMMANDS: &[&str] = &[
+    "@test",
+    "}",
+    "run",
+    "load",
+    "skip",
+    "bats_load_library",
+    "bats_require_minimum_version",
+];
+
+/// Functions BATS calls around the tests of a file
+const BATS_HOOKS: &[&str] = &["setup", "teardown", "setup_file", "teardown_file"];
+
+/// The type of a variable BATS sets before running a test, or that `run` sets after running a
+/// command
+pub(crate) fn bats_variable_type(name: &str) -> Option<BashType> {
+    Some(match name {
+        "status" | "BATS_TEST_NUMBER" | "BATS_SUITE_TEST_NUMBER" => BashType::Integer,
+        "lines" | "stderr_lines" | "BATS_TEST_NAMES" => BashType::Array(Box::new(BashType::String)),
+        "output"
+        | "stderr"
+        | "BATS_TMPDIR"
+        | "BATS_RUN_TMPDIR"
+        | "BATS_SUITE_TMPDIR"
+        | "BATS_FILE_TMPDIR"
+        | "BATS_TEST_TMPDIR"
+        | "BATS_TEST_DIRNAME"
+        | "BATS_TEST_FILENAME"
+        | "BATS_TEST_NAME"
+        | "BATS_TEST_DESCRIPTION" => BashType::String,
+        _ => return None,
+    })
+}
+
+impl FileInfo<'_> {
+    /// Checks whether the file has a `@test` block, making it a BATS test file. The hooks BATS
+    /// calls count as used there
+    pub(crate) fn detect_bats(&mut self, program: Node) {
+        // Files analyzed in chunks only have `@test` blocks in some of them
+        if self.bats {
+            return;
+        }
+        self.bats = program
+            .named_children(&mut program.walk())
+            .filter(|child| child.kind() == "command")
+            .filter_map(|command| command.child_by_field_name("name"))
+            .any(|name| self.node_text(name).is_ok_and(|name| name == "@test"));
+        if self.bats {
+            self.references
+                .extend(BATS_HOOKS.iter().map(|hook| (*hook).to_owned()));
+        }
+    }
+
+    /// Starts analyzing the body of a `@test` block, which the grammar leaves as the statements
+    /// between the `@test` command and a `}` command. Tests run in separate processes, so what a
+    /// test assigns is undone at the end of its body
+    pub(crate) fn start_bats_test(&mut self, test: Node) -> ParseResult<()> {
+        let mut body = Vec::new();
+        let mut next = test.next_named_sibling();
+        while let Some(statement) = next {
+            let closes = statement.kind() == "command"
+                && statement
+                    .child_by_field_name("name")
+                    .map(|name| self.node_text(name))
+                    .transpose()?
+                    == Some("}");
+            if closes {
+                return self.start_subshell(&body, statement.end_byte(), false);
+            }
+            body.push(statement);
+            next = statement.next_named_sibling();
+        }
+        Ok(())
+    }
+
+    /// Records `load helper`, which sources `helper.bash` from the directory of the test file
+    pub(crate) fn handle_load(&mut self, node: Node) -> ParseResult<()> {
+        let Some(argument) = node.child_by_field_name("argument") else {
+            return Ok(());
+        };
+        let path = self.static_text(argument)?.map(|path| {
+            if path.ends_with(".bash") || path.ends_with(".sh") {
+                path
+            } else {
+                format!("{path}.bash")
+            }
+        });
+        self.sources.push(SourceReference {
+            path,
+            range: node.start_byte()..node.end_byte(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    ops::Range,
+    path::PathBuf,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    command_names::path_fingerprint, trace::TypeMerge, Call, Config, Diagnostic, FileInfo,
+    FunctionDeclaration, Heredoc, SourceReference, TypeDeclaration,
+};
+
+pub const DEFAULT_CACHE_DIR: &str = ".bashtyped/cache";
+/// How many bytes of results the cache keeps before the ones used least recently are removed
+pub const MAX_CACHE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Results of analyzing a file, stored on disk under the hash of its contents
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedAnalysis {
+    pub errors: Vec<Diagnostic>,
+    pub variables: BTreeMap<String, TypeDeclaration>,
+    pub history: BTreeMap<String, Vec<TypeDeclaration>>,
+    pub sources: Vec<SourceReference>,
+    #[serde(default)]
+    pub exports: HashMap<String, Range<usize>>,
+    pub functions: HashMap<String, FunctionDeclaration>,
+    #[serde(default)]
+    pub calls: Vec<Call>,
+    #[serde(default)]
+    pub references: BTreeSet<String>,
+    #[serde(default)]
+    pub globals: BTreeSet<String>,
+    #[serde(default)]
+    pub heredocs: Vec<Heredoc>,
+    #[serde(default)]
+    pub merges: Vec<TypeMerge>,
+}
+
+/// A hasher whose output only depends on what is hashed, unlike `DefaultHasher`, whose algorithm
+/// can change between Rust releases. Integers are hashed as little endian, and `usize` as 64 bits,
+/// so keys are the same on every platform too
+struct StableHasher(Sha256);
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64);
+    }
+
+    fn finish(&self) -> u64 {
+        let digest = self.0.clone().finalize();
+        u64::from_le_bytes(digest[..8].try_into().expect("a digest of 32 bytes"))
+    }
+}
+
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The key includes the crate version and configuration so upgrading bashtyped or changing
+    /// settings never serves stale results. With `check_path` it also includes the commands on
+    /// `PATH`
+    pub fn key(source_code: &str, config: &Config) -> String {
+        let mut hasher = StableHasher(Sha256::new());
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        config.hash(&mut hasher);
+        if config.check_path {
+            path_fingerprint().hash(&mut hasher);
+        }
+        source_code.hash(&mut hasher);
+        hasher
+            .0
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    fn path(&self, source_code: &str, config: &Config) -> PathBuf {
+        self.dir
+            .join(format!("{}.json", Self::key(source_code, config)))
+    }
+
+    pub fn load(&self, source_code: &str, config: &Config) -> Option<CachedAnalysis> {
+        let path = self.path(source_code, config);
+        let contents = fs::read_to_string(&path).ok()?;
+        // Results are pruned by when they were last used
+        let _ = fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn store(
+        &self,
+        source_code: &str,
+        config: &Config,
+        analysis: &CachedAnalysis,
+    ) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(
+            self.path(source_code, config),
+            serde_json::to_string(analysis)?,
+        )
+    }
+
+    /// Removes the results used least recently until the cache takes at most `max_size` bytes
+    pub fn prune(&self, max_size: u64) -> io::Result<()> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let mut results = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                results.push((metadata.modified()?, metadata.len(), entry.path()));
+            }
+        }
+        results.sort();
+        let mut size = results.iter().map(|(_, len, _)| len).sum::<u64>();
+        for (_, len, path) in results {
+            if size <= max_size {
+                break;
+            }
+            fs::remove_file(path)?;
+            size -= len;
+        }
+        Ok(())
+    }
+}
+
+impl FileInfo<'_> {
+    /// Same as [`FileInfo::parse_code`], but reuses the results of a previous run on identical
+    /// contents when they are available
+    pub fn parse_code_cached(&mut self, cache: &Cache) -> io::Result<()> {
+        if let Some(analysis) = cache.load(self.source_code, &self.config) {
+            log_event!(DEBUG, "Reusing the cached analysis");
+            self.errors = analysis.errors;
+            self.variables = analysis.variables;
+            self.history = analysis.history;
+            self.sources = analysis.sources;
+            self.exports = analysis.exports;
+            self.functions = analysis.functions;
+            self.calls = analysis.calls;
+            self.references = analysis.references;
+            self.globals = analysis.globals;
+            self.heredocs = analysis.heredocs;
+            self.merges = analysis.merges;
+            return Ok(());
+        }
+        self.parse_code();
+        let analysis = CachedAnalysis {
+            errors: std::mem::take(&mut self.errors),
+            variables: std::mem::take(&mut self.variables),
+            history: std::mem::take(&mut self.history),
+            sources: std::mem::take(&mut self.sources),
+            exports: std::mem::take(&mut self.exports),
+            functions: std::mem::take(&mut self.functions),
+            calls: std::mem::take(&mut self.calls),
+            references: std::mem::take(&mut self.references),
+            globals: std::mem::take(&mut self.globals),
+            heredocs: std::mem::take(&mut self.heredocs),
+            merges: std::mem::take(&mut self.merges),
+        };
+        let result = cache.store(self.source_code, &self.config, &analysis);
+        self.errors = analysis.errors;
+        self.variables = analysis.variables;
+        self.history = analysis.history;
+        self.sources = analysis.sources;
+        self.exports = analysis.exports;
+        self.functions = analysis.functions;
+        self.calls = analysis.calls;
+        self.references = analysis.references;
+        self.globals = analysis.globals;
+        self.heredocs = analysis.heredocs;
+        self.merges = analysis.merges;
+        result
+    }
+}
@@ -0,0 +1,419 @@
+//! Which functions call each other, across the files of a [`Workspace`], and the statuses
+//! functions return through the functions they end by calling, including recursive ones
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::PathBuf,
+};
+
+use serde::Serialize;
+use tree_sitter::Node;
+
+use crate::{
+    arithmetic::is_arithmetic_command,
+    bash_integer, codes,
+    functions::{parse_returns, path_endings},
+    symbols::Span,
+    symbols::Workspace,
+    BashType, Call, Diagnostic, FileInfo, Label, LabelKind, ParseResult, Severity,
+};
+
+/// A function defined in a file of a [`Workspace`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct FunctionId {
+    pub path: PathBuf,
+    pub name: String,
+}
+
+/// Where a call is made from
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum Caller {
+    /// The top level of a file
+    File(PathBuf),
+    Function(FunctionId),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct CallGraph {
+    /// Every function in the workspace, with where it is defined
+    pub functions: BTreeMap<FunctionId, Span>,
+    /// `(caller, callee)` pairs, like `(File("main.sh"), deploy)` for a `deploy` call at the top
+    /// level of `main.sh`
+    pub calls: BTreeSet<(Caller, FunctionId)>,
+}
+
+impl Workspace {
+    /// The calls between the functions of every file. A call runs the function defined in the
+    /// same file or the last file it sources that defines it, or any function with its name
+    /// when the file sourcing it can't be found
+    pub fn call_graph(&self) -> CallGraph {
+        let mut graph = CallGraph::default();
+        for (path, file) in &self.files {
+            for (name, span) in &file.functions {
+                let function = FunctionId {
+                    path: path.clone(),
+                    name: name.clone(),
+                };
+                graph.functions.insert(function, span.clone());
+            }
+            for call in &file.calls {
+                let caller = match &call.caller {
+                    Some(name) => Caller::Function(FunctionId {
+                        path: path.clone(),
+                        name: name.clone(),
+                    }),
+                    None => Caller::File(path.clone()),
+                };
+                for callee in self.resolve_call(path, &call.name) {
+                    graph.calls.insert((caller.clone(), callee));
+                }
+            }
+        }
+        graph
+    }
+
+    fn resolve_call(&self, path: &PathBuf, name: &str) -> Vec<FunctionId> {
+        let defining = self
+            .source_order(path)
+            .into_iter()
+            .rev()
+            .find(|sourced| self.files[*sourced].functions.contains_key(name));
+        let paths = match defining {
+            Some(defining) => vec![defining],
+            None => self
+                .files
+                .iter()
+                .filter(|(_, file)| file.functions.contains_key(name))
+                .map(|(path, _)| path)
+                .collect(),
+        };
+        paths
+            .into_iter()
+            .map(|path| FunctionId {
+                path: path.clone(),
+                name: name.to_owned(),
+            })
+            .collect()
+    }
+
+    /// Functions in scripts that nothing in the workspace calls or refers to, except for
+    /// functions that are only called by each other. Files that only define functions and source
+    /// other files are libraries, and their functions are used by code outside the workspace
+    pub(crate) fn unused_functions(&self) -> Vec<(PathBuf, String, Diagnostic)> {
+        let graph = self.call_graph();
+        let references = self
+            .files
+            .values()
+            .flat_map(|file| &file.references)
+            .collect::<HashSet<_>>();
+        // Libraries often source the libraries they build on
+        let is_library = |path: &PathBuf| {
+            self.files[path]
+                .calls
+                .iter()
+                .all(|call| call.caller.is_some() || matches!(call.name.as_str(), "source" | "."))
+        };
+        let mut reached = graph
+            .functions
+            .keys()
+            .filter(|function| is_library(&function.path) || references.contains(&function.name))
+            .cloned()
+            .collect::<HashSet<_>>();
+        reached.extend(
+            graph
+                .calls
+                .iter()
+                .filter_map(|(caller, callee)| match caller {
+                    Caller::File(_) => Some(callee.clone()),
+                    Caller::Function(_) => None,
+                }),
+        );
+        let mut worklist = reached.iter().cloned().collect::<Vec<_>>();
+        while let Some(function) = worklist.pop() {
+            for (caller, callee) in &graph.calls {
+                if *caller == Caller::Function(function.clone()) && reached.insert(callee.clone()) {
+                    worklist.push(callee.clone());
+                }
+            }
+        }
+
+        graph
+            .functions
+            .into_iter()
+            .filter(|(function, _)| !reached.contains(function))
+            .map(|(function, span)| {
+                let diagnostic = Diagnostic::new(
+                    format!("Function {} is never called", function.name),
+                    span.start,
+                )
+                .with_code(codes::UNUSED_FUNCTION)
+                .with_severity(Severity::Warning)
+                .with_label(Label::new(
+                    span.start..span.end,
+                    "Defined here",
+                    LabelKind::Specified,
+                ));
+                (function.path, function.name, diagnostic)
+            })
+            .collect()
+    }
+}
+
+/// How each path through a function ends
+enum Ending {
+    Status(BashType),
+    /// The status of calling another function
+    Call(String),
+}
+
+/// The strongly connected components of `edges`, each listed after every component it has an
+/// edge to. Functions in the same component call each other recursively
+fn components(names: &[String], edges: &HashMap<String, Vec<String>>) -> Vec<Vec<String>> {
+    struct Search<'a> {
+        edges: &'a HashMap<String, Vec<String>>,
+        index: HashMap<&'a str, usize>,
+        low_link: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        components: Vec<Vec<String>>,
+    }
+
+    // Tarjan's algorithm, which finishes components in reverse topological order
+    fn visit<'a>(search: &mut Search<'a>, name: &'a str) {
+        let index = search.index.len();
+        search.index.insert(name, index);
+        search.low_link.insert(name, index);
+        search.stack.push(name);
+        for callee in search.edges.get(name).into_iter().flatten() {
+            if !search.index.contains_key(callee.as_str()) {
+                visit(search, callee);
+                let low_link = search.low_link[name].min(search.low_link[callee.as_str()]);
+                search.low_link.insert(name, low_link);
+            } else if search.stack.contains(&callee.as_str()) {
+                let low_link = search.low_link[name].min(search.index[callee.as_str()]);
+                search.low_link.insert(name, low_link);
+            }
+        }
+        if search.low_link[name] == search.index[name] {
+            let mut component = Vec::new();
+            while let Some(member) = search.stack.pop() {
+                component.push(member.to_owned());
+                if member == name {
+                    break;
+                }
+            }
+            search.components.push(component);
+        }
+    }
+
+    let mut search = Search {
+        edges,
+        index: HashMap::new(),
+        low_link: HashMap::new(),
+        stack: Vec::new(),
+        components: Vec::new(),
+    };
+    for name in names {
+        if !search.index.contains_key(name.as_str()) {
+            visit(&mut search, name);
+        }
+    }
+    search.components
+}
+
+impl FileInfo<'_> {
+    /// Remembers the literal words passed to a command or assigned, in case they name a
+    /// function, and the names given to `export -f` or `declare -f`
+    pub(crate) fn record_references(&mut self, node: Node) -> ParseResult<()> {
+        let mut cursor = node.walk();
+        let words = match node.kind() {
+            "command" => node
+                .children_by_field_name("argument", &mut cursor)
+                .collect::<Vec<_>>(),
+            "variable_assignment" => node.child_by_field_name("value").into_iter().collect(),
+            _ => node.named_children(&mut cursor).collect(),
+        };
+        let exports_functions = node.kind() == "declaration_command"
+            && words.iter().any(|word| {
+                word.kind() == "word"
+                    && self
+                        .node_text(*word)
+                        .is_ok_and(|flag| flag.starts_with('-') && flag.contains('f'))
+            });
+        for word in words {
+            if word.kind() != "word" && !(exports_functions && word.kind() == "variable_name") {
+                continue;
+            }
+            let text = self.node_text(word)?;
+            let is_name = text
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && text
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | ':' | '.'));
+            if is_name {
+                self.references.insert(text.to_owned());
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the calls of code analyzed on its own, like a trap handler, as calls made from
+    /// `node`. Ranges are moved by `offset`, or point at `node` if the code isn't written out
+    pub(crate) fn merge_embedded_calls(
+        &mut self,
+        analysis: &mut FileInfo,
+        node: Node,
+        offset: Option<usize>,
+    ) {
+        let caller = self.function.clone();
+        for call in std::mem::take(&mut analysis.calls) {
+            self.calls.push(Call {
+                range: match offset {
+                    Some(offset) => call.range.start + offset..call.range.end + offset,
+                    None => node.start_byte()..node.end_byte(),
+                },
+                caller: call.caller.or_else(|| caller.clone()),
+                name: call.name,
+            });
+        }
+        self.references
+            .extend(std::mem::take(&mut analysis.references));
+    }
+
+    /// Infers the status of every function that doesn't have a `#[returns ...]` annotation.
+    /// Functions that call each other in a cycle are solved together, starting from nothing and
+    /// widening until their statuses stop changing
+    pub(crate) fn infer_statuses(&mut self, root: Node) -> ParseResult<()> {
+        let mut endings = HashMap::new();
+        let mut declared = HashMap::new();
+        let mut worklist = vec![root];
+        while let Some(node) = worklist.pop() {
+            worklist.extend(node.named_children(&mut node.walk()));
+            let (Some(name), Some(body)) = (
+                node.child_by_field_name("name"),
+                node.child_by_field_name("body"),
+            ) else {
+                continue;
+            };
+            let name = self.node_text(name)?;
+            // Only the definition that is kept in `functions` counts
+            if node.kind() != "function_definition"
+                || self
+                    .functions
+                    .get(name)
+                    .map(|function| function.range.start)
+                    != Some(node.start_byte())
+            {
+                continue;
+            }
+            if let Some(returns) = self.declared_returns(node)? {
+                declared.insert(name.to_owned(), returns);
+                continue;
+            }
+            // Paths can also end early with `return`
+            let mut function_endings = Vec::new();
+            for ending in path_endings(body)
+                .into_iter()
+                .chain(self.return_commands(body)?)
+            {
+                function_endings.push(self.ending(ending)?);
+            }
+            endings.insert(name.to_owned(), function_endings);
+        }
+
+        let mut names = endings.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        let edges = endings
+            .iter()
+            .map(|(name, function_endings)| {
+                let callees = function_endings
+                    .iter()
+                    .filter_map(|ending| match ending {
+                        Ending::Call(callee) if endings.contains_key(callee) => {
+                            Some(callee.clone())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                (name.clone(), callees)
+            })
+            .collect();
+
+        // Functions from the chunks of the file analyzed before keep their statuses
+        let mut statuses = std::mem::take(&mut self.statuses);
+        statuses.extend(declared);
+        for component in components(&names, &edges) {
+            // Each round can only widen a status, and there are few types a status can have
+            loop {
+                let mut changed = false;
+                for name in &component {
+                    let mut status: Option<BashType> = None;
+                    for ending in &endings[name] {
+                        let ending_type = match ending {
+                            Ending::Status(status) => Some(status.clone()),
+                            // A function that hasn't been seen to return yet adds nothing
+                            Ending::Call(callee) => statuses.get(callee).cloned(),
+                        };
+                        status = match (status, ending_type) {
+                            (Some(status), Some(ending_type)) => Some(status.union(ending_type)),
+                            (status, ending_type) => status.or(ending_type),
+                        };
+                    }
+                    if let Some(status) = status {
+                        if statuses.get(name) != Some(&status) {
+                            statuses.insert(name.clone(), status);
+                            changed = true;
+                        }
+                    }
+                }
+                if !changed {
+                    break;
+                }
+            }
+        }
+        self.statuses = statuses;
+        Ok(())
+    }
+
+    /// The status declared by a `#[returns ...]` annotation before the function
+    fn declared_returns(&self, function: Node) -> ParseResult<Option<BashType>> {
+        let status = self
+            .returns_annotation(function)?
+            .and_then(|returns| parse_returns(returns).ok()?.0);
+        Ok(status.and_then(|status| status.parse().ok()))
+    }
+
+    /// The status a path through a function ends with
+    fn ending(&self, ending: Node) -> ParseResult<Ending> {
+        let status = match ending.kind() {
+            "test_command" | "negated_command" => BashType::Bool,
+            "command" if is_arithmetic_command(ending) => BashType::Bool,
+            "command" => {
+                let Some(name) = ending.child_by_field_name("name") else {
+                    return Ok(Ending::Status(BashType::Integer));
+                };
+                let name = self.node_text(name)?;
+                match name {
+                    "return" | "exit" => {
+                        let literal = match ending.child_by_field_name("argument") {
+                            Some(argument) => self.static_text(argument)?,
+                            None => None,
+                        };
+                        match literal.as_deref().map(bash_integer) {
+                            Some(Some(0 | 1)) => BashType::Bool,
+                            _ => BashType::Integer,
+                        }
+                    }
+                    "true" | "false" | "test" | "[" | ":" => BashType::Bool,
+                    _ if self.functions.contains_key(name) => {
+                        return Ok(Ending::Call(name.to_owned()))
+                    }
+                    _ => BashType::Integer,
+                }
+            }
+            _ => BashType::Integer,
+        };
+        Ok(Ending::Status(status))
+    }
+}
@@ -0,0 +1,194 @@
+//! Analyzing a file a chunk of top-level statements at a time when the config has a
+//! `memory_limit`, so the syntax tree of a huge generated script never has to be in memory at
+//! once. Each chunk is parsed on its own but keeps the offsets it has in the file, and what the
+//! analysis knows carries over from one chunk to the next
+
+use tree_sitter::{Node, Point, Range, Tree};
+
+use crate::{codes, Diagnostic, FileInfo, Label, LabelKind, Severity};
+
+/// About how many bytes of syntax tree the parser builds for each byte of a script, at most
+const TREE_BYTES_PER_BYTE: usize = 80;
+/// How much of the file is parsed at first when looking for where a chunk ends
+const CHUNK_SIZE: usize = 64 * 1024;
+/// How many top-level statements a chunk has at most. Finding the parent or a sibling of a node
+/// goes through the children of its parent, so the time a chunk takes grows with the square of
+/// its number of statements
+const MAX_CHUNK_STATEMENTS: usize = 1000;
+
+/// A byte offset in the file along with its row and column
+#[derive(Clone, Copy, Default)]
+struct Position {
+    byte: usize,
+    point: Point,
+}
+
+impl FileInfo<'_> {
+    /// Parses and analyzes the file in chunks whose syntax trees take at most `memory_limit` MiB.
+    /// Chunks end between statements on separate lines, so annotations stay with the statements
+    /// they are written above. `chunk_analyzed` is called after each chunk
+    pub(crate) fn analyze_chunks(
+        &mut self,
+        memory_limit: usize,
+        chunk_analyzed: &mut dyn FnMut(&mut Self),
+    ) {
+        if self.is_disabled() {
+            return;
+        }
+        let max_size = memory_limit.saturating_mul(1024 * 1024) / TREE_BYTES_PER_BYTE;
+        let mut start = Position::default();
+        while start.byte < self.source_code.len() {
+            let Some((tree, end)) = self.next_chunk(start, max_size) else {
+                self.push_too_large(start.byte, memory_limit, max_size);
+                break;
+            };
+            self.handle_directives(tree.root_node());
+            self.detect_bats(tree.root_node());
+            self.analyze_statements(&tree);
+            drop(tree);
+            chunk_analyzed(self);
+            start = end;
+        }
+        self.parser
+            .set_included_ranges(&[])
+            .expect("the whole file to be a valid range");
+    }
+
+    /// Parses the chunk starting at `start`, returning its tree and where the next chunk starts.
+    /// The part of the file parsed grows until it has a statement that ends before it does, and
+    /// `None` is returned when that would take more than `max_size` bytes
+    fn next_chunk(&mut self, start: Position, max_size: usize) -> Option<(Tree, Position)> {
+        let mut size = CHUNK_SIZE.min(max_size);
+        loop {
+            let end = self.window_end(start.byte, size);
+            if size == 0 || end - start.byte > max_size {
+                return None;
+            }
+            let end = self.position_after(start, end);
+            let tree = self.parse_range(start, end);
+            let complete = end.byte == self.source_code.len();
+            match self.chunk_end(tree.root_node(), complete) {
+                Some(cut) => {
+                    let cut = Position {
+                        byte: cut.start_byte(),
+                        point: cut.start_position(),
+                    };
+                    return Some((self.parse_range(start, cut), cut));
+                }
+                None if complete => return Some((tree, end)),
+                None if size < max_size => size = size.saturating_mul(2).min(max_size),
+                None => return None,
+            }
+        }
+    }
+
+    /// The top-level statement the next chunk starts at, when the parsed part of the file is too
+    /// long for one chunk. Unless the parsed part reaches the end of the file, its last
+    /// statement may be cut off and has to start the next chunk
+    fn chunk_end<'tree>(&self, root: Node<'tree>, complete: bool) -> Option<Node<'tree>> {
+        let statements = root.named_children(&mut root.walk()).collect::<Vec<_>>();
+        if complete && statements.len() <= MAX_CHUNK_STATEMENTS {
+            return None;
+        }
+        let on_own_line = |index: usize| {
+            index == 0
+                || statements[index].start_position().row > statements[index - 1].end_position().row
+        };
+        let mut in_bats_test = false;
+        let mut cuts = Vec::new();
+        for (index, statement) in statements.iter().enumerate() {
+            // Bats tests are the statements between `@test` and `}`, so they can't be split
+            if let Some(name) = statement
+                .child_by_field_name("name")
+                .filter(|_| statement.kind() == "command")
+                .and_then(|name| self.node_text(name).ok())
+            {
+                in_bats_test = match name {
+                    "@test" => true,
+                    "}" => false,
+                    _ => in_bats_test,
+                };
+            }
+            // A comment on its own line can annotate the statement after it
+            let annotates =
+                index > 0 && statements[index - 1].kind() == "comment" && on_own_line(index - 1);
+            if index > 0 && on_own_line(index) && !annotates && !in_bats_test {
+                cuts.push(index);
+            }
+        }
+        let cut = cuts
+            .iter()
+            .rev()
+            .find(|&&index| index <= MAX_CHUNK_STATEMENTS)
+            .or(cuts.first())?;
+        Some(statements[*cut])
+    }
+
+    fn parse_range(&mut self, start: Position, end: Position) -> Tree {
+        self.parser
+            .set_included_ranges(&[Range {
+                start_byte: start.byte,
+                end_byte: end.byte,
+                start_point: start.point,
+                end_point: end.point,
+            }])
+            .expect("a single range to be valid");
+        self.parser
+            .parse(self.source_code, None)
+            .expect("treesitter to parse valid code")
+    }
+
+    /// Where the part of the file parsed from `start` ends, after the last line that starts
+    /// within `size` bytes of it. A line longer than that is parsed whole
+    fn window_end(&self, start: usize, size: usize) -> usize {
+        let bytes = self.source_code.as_bytes();
+        if start + size >= bytes.len() {
+            return bytes.len();
+        }
+        let newline = bytes[start..start + size]
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .or_else(|| {
+                let rest = bytes[start + size..].iter().position(|&byte| byte == b'\n');
+                rest.map(|newline| size + newline)
+            });
+        newline.map_or(bytes.len(), |newline| start + newline + 1)
+    }
+
+    /// The position of the byte `offset`, which comes after `from`
+    fn position_after(&self, from: Position, offset: usize) -> Position {
+        let between = &self.source_code[from.byte..offset];
+        let point = match between.rfind('\n') {
+            Some(newline) => Point::new(
+                from.point.row + between.matches('\n').count(),
+                between.len() - newline - 1,
+            ),
+            None => Point::new(from.point.row, from.point.column + between.len()),
+        };
+        Position {
+            byte: offset,
+            point,
+        }
+    }
+
+    fn push_too_large(&mut self, offset: usize, memory_limit: usize, max_size: usize) {
+        let line_end = self.source_code[offset..]
+            .find('\n')
+            .map_or(self.source_code.len(), |newline| offset + newline);
+        self.errors.push(
+            Diagnostic::new("Code is too large to check within the memory limit", offset)
+                .with_code(codes::TOO_LARGE)
+                .with_severity(Severity::Warning)
+                .with_label(Label::new(
+                    offset..line_end,
+                    "This statement and the rest of the file aren't checked",
+                    LabelKind::Unsupported,
+                ))
+                .with_note(format!(
+                    "A memory limit of {memory_limit} MiB fits statements of up to {} KiB",
+                    max_size / 1024
+                ))
+                .with_help("Raise `memory_limit` in the config, or split the statement up"),
+        );
+    }
+}
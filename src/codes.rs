@@ -0,0 +1,567 @@
+//! Stable identifiers for each kind of diagnostic, shown in reports and used to refer to a
+//! problem without depending on its message
+
+use std::fmt::Display;
+
+/// An annotation that doesn't match the type of the value
+pub const TYPE_MISMATCH: &str = "BT0001";
+/// A variable, or the target of a nameref, that was never defined
+pub const UNKNOWN_VARIABLE: &str = "BT0002";
+/// A variable given a type that doesn't fit an earlier declaration
+pub const REDEFINED_VARIABLE: &str = "BT0003";
+/// A variable read after being removed with `unset`
+pub const POSSIBLY_UNSET: &str = "BT0004";
+/// An annotation, directive or type that can't be parsed
+pub const INVALID_ANNOTATION: &str = "BT0005";
+/// A feature that needs a newer bash than the one targeted
+pub const UNSUPPORTED_VERSION: &str = "BT0006";
+/// Code whose types can't be checked, only reported in pedantic mode
+pub const UNCHECKED: &str = "BT0007";
+/// An array element that doesn't match the type of the array
+pub const ELEMENT_MISMATCH: &str = "BT0008";
+/// An index or key that the variable can't be subscripted with
+pub const INVALID_SUBSCRIPT: &str = "BT0009";
+/// Use of `eval`
+pub const EVAL: &str = "BT0010";
+/// A command argument that doesn't match the command's signature
+pub const ARGUMENT_MISMATCH: &str = "BT0011";
+/// An exit status bash can't return
+pub const EXIT_STATUS_RANGE: &str = "BT0012";
+/// A function status or output that doesn't match its `#[returns]` annotation
+pub const RETURN_MISMATCH: &str = "BT0013";
+/// A suspicious use of `$?`
+pub const STATUS_MISUSE: &str = "BT0014";
+/// A signal that can't be trapped
+pub const INVALID_SIGNAL: &str = "BT0015";
+/// A redirection to something that isn't a single path, or a file descriptor variable that isn't
+/// an int
+pub const REDIRECT_TARGET: &str = "BT0016";
+/// Case modification or substitution on a value that isn't a string
+pub const STRING_OPERATION: &str = "BT0017";
+/// An integer literal bash arithmetic rejects
+pub const INVALID_INTEGER: &str = "BT0018";
+/// A global defined with incompatible types by files that are sourced together
+pub const GLOBAL_COLLISION: &str = "BT0019";
+/// A variable that overwrites one exported by a file the script sources
+pub const SHADOWED_EXPORT: &str = "BT0020";
+/// A `#/` annotation that doesn't give any variable its type
+pub const UNUSED_ANNOTATION: &str = "BT0021";
+/// An annotation giving the type that would be inferred anyway, only reported in pedantic mode
+pub const REDUNDANT_ANNOTATION: &str = "BT0022";
+/// A variable whose type is a union of more types than the configured limit
+pub const LARGE_UNION: &str = "BT0023";
+/// Division or modulo by a value that is 0
+pub const DIVISION_BY_ZERO: &str = "BT0024";
+/// A command that isn't a function, alias, builtin or known program
+pub const UNKNOWN_COMMAND: &str = "BT0025";
+/// A call passing a different number of arguments than the function's `#[params ...]`
+pub const ARGUMENT_COUNT: &str = "BT0026";
+/// A function that nothing in the workspace calls
+pub const UNUSED_FUNCTION: &str = "BT0027";
+/// A condition that tests or runs the output of a command instead of its status
+pub const OUTPUT_CONDITION: &str = "BT0028";
+/// An unquoted expansion of a string, which is split into words and expanded as a glob
+pub const UNQUOTED_EXPANSION: &str = "BT0029";
+/// An array expanded as a single value, or a scalar expanded as an array
+pub const ARRAY_CONTEXT: &str = "BT0030";
+/// A record key that is read but never set
+pub const UNSET_KEY: &str = "BT0031";
+/// A change to `IFS` that is never restored
+pub const UNRESTORED_IFS: &str = "BT0032";
+/// `$!` read before any background job was started
+pub const NO_BACKGROUND_JOB: &str = "BT0033";
+/// An array or map expanded as a whole into a heredoc annotated with a language
+pub const HEREDOC_INTERPOLATION: &str = "BT0034";
+/// A test comparing ints as strings with `<` or `>`, or strings as ints with `-gt` and the like
+pub const COMPARISON_OPERATOR: &str = "BT0035";
+/// A variable holding a string that isn't a number read in arithmetic
+pub const ARITHMETIC_STRING: &str = "BT0036";
+/// A variable read after being assigned only in a job run in the background with `&`
+pub const BACKGROUND_ASSIGNMENT: &str = "BT0037";
+/// A file that isn't valid UTF-8, with the invalid bytes replaced
+pub const INVALID_UTF8: &str = "BT0038";
+/// Code that isn't checked since it doesn't fit in the `memory_limit` of the config
+pub const TOO_LARGE: &str = "BT0039";
+
+/// Codes that are only reported when checking a [`crate::symbols::Workspace`], since they are
+/// about more than one file
+pub const WORKSPACE_CODES: &[&str] = &[GLOBAL_COLLISION, SHADOWED_EXPORT, UNUSED_FUNCTION];
+
+/// Documentation for a diagnostic code, shown by `bashtyped explain`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub code: &'static str,
+    /// A short name for the rule, like `type-mismatch`
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub explanation: &'static str,
+    /// A script that triggers the diagnostic
+    pub example: &'static str,
+    /// How to fix the problem
+    pub fix: &'static str,
+}
+
+/// Every diagnostic code, in order
+pub const RULES: &[Rule] = &[
+    Rule {
+        code: TYPE_MISMATCH,
+        name: "type-mismatch",
+        summary: "An annotation that doesn't match the type of the value",
+        explanation: "A `#/` annotation declares the type a variable is meant to have. When the \
+                      value assigned on the same line has a different type, either the value or \
+                      the annotation is wrong.",
+        example: "retries=three #/ int",
+        fix: "Assign a value of the annotated type, or widen the annotation with a union such \
+              as `#/ int | string` if both are expected.",
+    },
+    Rule {
+        code: UNKNOWN_VARIABLE,
+        name: "unknown-variable",
+        summary: "A variable, or the target of a nameref, that was never defined",
+        explanation: "The variable is read, or a nameref points to it, but nothing in the file \
+                      assigns it. This is usually a typo, or a variable that comes from the \
+                      environment or from another file.",
+        example: "name=world\ngreeting=$nmae",
+        fix: "Correct the name, assign the variable before it is used, or source the file that \
+              defines it.",
+    },
+    Rule {
+        code: REDEFINED_VARIABLE,
+        name: "redefined-variable",
+        summary: "A variable given a type that doesn't fit an earlier declaration",
+        explanation: "Once a variable has an annotated type, later assignments have to fit in \
+                      it. Assigning a value of another type usually means the variable is being \
+                      reused for something else.",
+        example: "count=1 #/ int\ncount=many",
+        fix: "Use a separate variable, widen the first annotation to a union of both types, or \
+              put `#[force]` before the assignment to skip the check.",
+    },
+    Rule {
+        code: POSSIBLY_UNSET,
+        name: "possibly-unset",
+        summary: "A variable read after being removed with `unset`",
+        explanation: "After `unset name`, expanding `$name` gives an empty string, or an error \
+                      under `set -u`. The same goes for variables only assigned on the right \
+                      side of a `&&` or `||` list, which doesn't always run.",
+        example: "tmp=/tmp/work\nunset tmp\nbackup=$tmp",
+        fix: "Read the variable before unsetting it, assign it again, or use a default like \
+              `${tmp:-/tmp/work}`.",
+    },
+    Rule {
+        code: INVALID_ANNOTATION,
+        name: "invalid-annotation",
+        summary: "An annotation, directive or type that can't be parsed",
+        explanation: "Comments starting with `#/`, `#[` or `#![` are read by bashtyped, so \
+                      their contents have to be valid types or known directives.",
+        example: "port=8080 #/ integer",
+        fix: "Use one of the supported types, such as `int`, `string`, `bool`, `array<int>` \
+              or a union like `int | string`.",
+    },
+    Rule {
+        code: UNSUPPORTED_VERSION,
+        name: "unsupported-version",
+        summary: "A feature that needs a newer bash than the one targeted",
+        explanation: "The `bash_version` setting, or a `#![bash >= x.y]` directive, gives the \
+                      oldest bash the script has to run on. Features added in later releases \
+                      fail there, often silently.",
+        example: "#![bash >= 3.2]\ndeclare -A ports",
+        fix: "Rewrite the code without the feature, or raise the targeted version if older \
+              releases no longer need to be supported.",
+    },
+    Rule {
+        code: UNCHECKED,
+        name: "unchecked",
+        summary: "Code whose types can't be checked, only reported in pedantic mode",
+        explanation: "Some values, like indirect expansions of names only known at runtime, \
+                      can't be typed statically. They are treated as unknown, so mistakes \
+                      involving them go unnoticed. These warnings are only shown with \
+                      `pedantic = true`.",
+        example: "target=$1\nvalue=${!target}",
+        fix: "Annotate the variable holding the result, or restructure the code so the value \
+              is known, for example with an associative array instead of indirection.",
+    },
+    Rule {
+        code: ELEMENT_MISMATCH,
+        name: "element-mismatch",
+        summary: "An array element that doesn't match the type of the array",
+        explanation: "Arrays have a single element type, inferred from their first assignment \
+                      or given with an annotation like `#/ array<int>`. Assigning or appending \
+                      an element of another type breaks code that relies on it.",
+        example: "numbers=(1 2 3)\nnumbers[0]=text",
+        fix: "Store the value elsewhere, or annotate the array with a union element type such \
+              as `#/ array<int | string>`.",
+    },
+    Rule {
+        code: INVALID_SUBSCRIPT,
+        name: "invalid-subscript",
+        summary: "An index or key that the variable can't be subscripted with",
+        explanation: "Tuples have a fixed number of elements and records a fixed set of keys, \
+                      so indices past the end and undeclared keys are mistakes.",
+        example: "row=(1 text) #/ [int, string]\nmissing=\"${row[2]}\"",
+        fix: "Use an index or key the type declares, or add it to the annotation.",
+    },
+    Rule {
+        code: EVAL,
+        name: "eval",
+        summary: "Use of `eval`",
+        explanation: "Code run through `eval` is only known at runtime, so the variables it \
+                      defines and uses can't be checked.",
+        example: "eval \"$(generate_code)\"",
+        fix: "Replace `eval` with arrays, namerefs or functions where possible. When it is \
+              needed, annotate the variables it defines.",
+    },
+    Rule {
+        code: ARGUMENT_MISMATCH,
+        name: "argument-mismatch",
+        summary: "A command argument that doesn't match the command's signature",
+        explanation: "Commands like `sleep`, `head -n` and `return` only accept certain kinds \
+                      of arguments, and fail or misbehave at runtime with anything else.",
+        example: "lines=many\nhead -n \"$lines\" file.txt",
+        fix: "Pass a value of the type the command expects.",
+    },
+    Rule {
+        code: EXIT_STATUS_RANGE,
+        name: "exit-status-range",
+        summary: "An exit status bash can't return",
+        explanation: "Exit statuses are numbers from 0 to 255. Larger numbers wrap around, so \
+                      `exit 256` succeeds, and non-numeric statuses are an error.",
+        example: "exit 300",
+        fix: "Use a status between 0 and 255.",
+    },
+    Rule {
+        code: RETURN_MISMATCH,
+        name: "return-mismatch",
+        summary: "A function status or output that doesn't match its `#[returns]` annotation",
+        explanation: "`#[returns bool]` declares that a function only returns 0 or 1, and \
+                      `#[returns int]` that it returns a number. A `return` with another \
+                      status, or a path ending with a command whose status isn't known, \
+                      breaks callers that rely on it. `#[returns status: bool, stdout: int]` \
+                      also declares what the function prints, which is what `$(...)` \
+                      captures. Printing something else, or capturing the output of a \
+                      function that only returns a status, is reported too.",
+        example: "#[returns bool]\nis_ready() {\n    return 2\n}",
+        fix: "Return a status that fits the annotation, or change the annotation.",
+    },
+    Rule {
+        code: STATUS_MISUSE,
+        name: "status-misuse",
+        summary: "A suspicious use of `$?`",
+        explanation: "`$?` holds the exit status of the last command, which is always a \
+                      number. Reading it before any command ran, or comparing it with a \
+                      string, is almost always a mistake.",
+        example: "true\n[[ $? == \"ok\" ]]",
+        fix: "Compare `$?` with a number, or test the command directly as in \
+              `if command; then`.",
+    },
+    Rule {
+        code: INVALID_SIGNAL,
+        name: "invalid-signal",
+        summary: "A signal that can't be trapped",
+        explanation: "`trap` fails for unknown signal names, and `SIGKILL` and `SIGSTOP` can \
+                      never be caught.",
+        example: "trap 'echo bye' EXTI",
+        fix: "Correct the signal name, or remove signals that can't be trapped.",
+    },
+    Rule {
+        code: REDIRECT_TARGET,
+        name: "redirect-target",
+        summary: "A redirection to something that isn't a single path or file descriptor",
+        explanation: "A redirection needs exactly one file. Expanding an array there gives \
+                      an \"ambiguous redirect\" error when it has more than one element. \
+                      `{fd}>file` stores the number of a new file descriptor in `fd`, and \
+                      `{fd}>&-` closes it, so `fd` has to hold an int.",
+        example: "files=(a.txt b.txt)\necho done > ${files[@]}",
+        fix: "Redirect to a single element, or loop over the array.",
+    },
+    Rule {
+        code: STRING_OPERATION,
+        name: "string-operation",
+        summary: "Case modification or substitution on a value that isn't a string",
+        explanation: "Expansions like `${name^^}` and `${name/a/b}` work on text. Using them \
+                      on numbers or booleans is allowed by bash but usually a mistake. This \
+                      warning is only shown with `pedantic = true`.",
+        example: "count=3\nshown=${count^^}",
+        fix: "Apply the operation to a string, or convert the value explicitly first.",
+    },
+    Rule {
+        code: INVALID_INTEGER,
+        name: "invalid-integer",
+        summary: "An integer literal bash arithmetic rejects",
+        explanation: "In arithmetic, numbers with a leading zero are octal, so digits 8 and 9 \
+                      are errors, which often happens with zero-padded dates.",
+        example: "total=$((08 + 1))",
+        fix: "Remove the leading zero, or force base 10 with `10#08`.",
+    },
+    Rule {
+        code: GLOBAL_COLLISION,
+        name: "global-collision",
+        summary: "A global defined with incompatible types by files that are sourced together",
+        explanation: "Files sourced into the same script share their globals, so whichever file \
+                      runs last decides the value the others see. This is only checked for \
+                      files that are analyzed together.",
+        example: "# lib.sh\nretries=3\n\n# main.sh\nsource lib.sh\nretries=(1 2)",
+        fix: "Rename one of the variables, or give them the same type.",
+    },
+    Rule {
+        code: SHADOWED_EXPORT,
+        name: "shadowed-export",
+        summary: "A variable that overwrites one exported by a file the script sources",
+        explanation: "Assigning a variable that a sourced file exports changes it for every \
+                      command the script runs afterwards, which is easy to do by accident with \
+                      common names. This is only checked for files that are analyzed together.",
+        example: "# env.sh\nexport PREFIX=/usr\n\n# main.sh\nsource env.sh\nPREFIX=build",
+        fix: "Use another name for the variable of the script, or set it in the sourced file if \
+              it is meant to change.",
+    },
+    Rule {
+        code: UNUSED_ANNOTATION,
+        name: "unused-annotation",
+        summary: "A `#/` annotation that doesn't give any variable its type",
+        explanation: "An annotation describes the assignment on its own line, or the one on the \
+                      line after it. Annotations anywhere else, or ones overridden by an \
+                      annotation closer to the assignment, are ignored, so the variable isn't \
+                      checked against them.",
+        example: "#/ int\necho starting\ncount=1",
+        fix: "Move the annotation next to the assignment it describes, or remove it.",
+    },
+    Rule {
+        code: REDUNDANT_ANNOTATION,
+        name: "redundant-annotation",
+        summary: "An annotation giving the type that would be inferred anyway, only reported in \
+                  pedantic mode",
+        explanation: "When the annotation matches the type of the value exactly, it adds \
+                      nothing to what bashtyped already knows. These hints are only shown with \
+                      `pedantic = true`.",
+        example: "retries=3 #/ int",
+        fix: "Remove the annotation, or keep it if the type is meant to be enforced on later \
+              assignments.",
+    },
+    Rule {
+        code: LARGE_UNION,
+        name: "large-union",
+        summary: "A variable whose type is a union of more types than the configured limit",
+        explanation: "Long unions make reports hard to read, and usually mean a variable holds \
+                      unrelated values. The limit is 6 types, and can be changed with \
+                      `max_members` in a `[rules.large-union]` table. Unions longer than the \
+                      limit are also shortened in reports.",
+        example: "#/ int | string | bool | array<int> | array<string> | map<string, int> | \
+                  map<string, string>\nvalue=1",
+        fix: "Give the type a name with an alias, narrow it, or split the variable into \
+              several.",
+    },
+    Rule {
+        code: DIVISION_BY_ZERO,
+        name: "division-by-zero",
+        summary: "Division or modulo by a value that is 0",
+        explanation: "Bash stops evaluating an arithmetic expression that divides by 0 and \
+                      reports an error. Unset and empty variables are 0 in arithmetic, so with \
+                      `pedantic = true` dividing by a variable that might be unset or empty is \
+                      also reported.",
+        example: "count=0\naverage=$((10 / count))",
+        fix: "Check that the divisor isn't 0 before dividing.",
+    },
+    Rule {
+        code: UNKNOWN_COMMAND,
+        name: "unknown-command",
+        summary: "A command that isn't a function, alias, builtin or known program",
+        explanation: "A command that is close to the name of a function defined in the file is \
+                      probably a typo. With `check_path = true`, commands that aren't found in \
+                      the directories on `PATH` either are also reported, unless the file \
+                      sources others that could define them.",
+        example: "deploy() { echo done; }\ndepoly",
+        fix: "Fix the spelling, or define the function before the script runs it.",
+    },
+    Rule {
+        code: ARGUMENT_COUNT,
+        name: "argument-count",
+        summary: "A call passing a different number of arguments than the function's \
+                  `#[params ...]`",
+        explanation: "Bash lets functions be called with any number of arguments, and missing \
+                      ones are just empty. A `#[params ...]` annotation lists the arguments a \
+                      function takes, and ending it with `...: type` accepts any number of \
+                      extra ones. Calls whose arguments can expand to any number of words, like \
+                      `\"$@\"` or globs, aren't checked.",
+        example:
+            "#[params name: string, greeting: string]\ngreet() { echo \"$2 $1\"; }\ngreet world",
+        fix: "Pass the arguments the function expects, or change its `#[params ...]`.",
+    },
+    Rule {
+        code: UNUSED_FUNCTION,
+        name: "unused-function",
+        summary: "A function that nothing in the workspace calls",
+        explanation: "A function is used when a script calls it, when a function that is used \
+                      calls it, or when its name is passed to a command like `trap` or \
+                      `export -f`. Files that only define functions are treated as libraries, \
+                      so their functions aren't reported. This is only checked for files that \
+                      are analyzed together.",
+        example: "# main.sh\ncleanup() { rm -f /tmp/lock; }\necho done",
+        fix: "Remove the function, or call it where it was meant to run.",
+    },
+    Rule {
+        code: OUTPUT_CONDITION,
+        name: "output-condition",
+        summary: "A condition that tests or runs the output of a command instead of its status",
+        explanation: "`if`, `while` and `until` branch on the status of a command. Wrapping the \
+                      command in `[ $(...) ]` only checks whether it printed anything, which is \
+                      never true for quiet commands like `grep -q`, and `if $(...)` runs its \
+                      output as another command.",
+        example: "if [ $(grep -q error log.txt) ]; then\n    echo failed\nfi",
+        fix: "Use the command itself as the condition, like `if grep -q error log.txt; then`, \
+              or `[[ -n $(...) ]]` if the output is what should be tested.",
+    },
+    Rule {
+        code: UNQUOTED_EXPANSION,
+        name: "unquoted-expansion",
+        summary: "An unquoted expansion of a string, only reported in pedantic mode",
+        explanation: "Bash splits the value of an unquoted `$name` on whitespace and expands \
+                      any glob characters in it, so a path with a space becomes two arguments. \
+                      Only variables whose type can be a string are reported: integers can't \
+                      be split, literals without spaces or glob characters are left alone, and \
+                      `[[ ... ]]` and arithmetic don't split at all.",
+        example: "dir=\"$(pwd)/build\"\nrm -r $dir",
+        fix: "Quote the expansion, like `\"$dir\"`, or use an array if splitting is intended.",
+    },
+    Rule {
+        code: ARRAY_CONTEXT,
+        name: "array-context",
+        summary: "An array expanded as a single value, or a scalar expanded as an array",
+        explanation: "`$arr` and `${arr}` only expand to the first element of an array, and \
+                      `${#arr}` is the length of that element rather than the number of \
+                      elements. The other way around, `${str[@]}` on a variable that isn't an \
+                      array is just its value, which usually means the variable was meant to \
+                      be an array.",
+        example: "files=(a.txt b.txt)\nrm $files",
+        fix: "Use `\"${arr[@]}\"` for every element, `${arr[0]}` for the first one, or \
+              `${#arr[@]}` for the count.",
+    },
+    Rule {
+        code: UNSET_KEY,
+        name: "unset-key",
+        summary: "A record key that is read but never set",
+        explanation: "Records declare every key a map can have, but that doesn't mean each key \
+                      has a value. Reading a key that no assignment in the file sets always \
+                      expands to the empty string.",
+        example: "declare -A cfg #/ { host: string, port: int }\ncfg[host]=localhost\nport=\"${cfg[port]}\"",
+        fix: "Assign the key before reading it, or give the read a default like \
+              `${cfg[port]:-80}`.",
+    },
+    Rule {
+        code: UNRESTORED_IFS,
+        name: "unrestored-ifs",
+        summary: "A change to `IFS` that is never restored",
+        explanation: "`IFS` decides how unquoted expansions and `read` split words. Assigning \
+                      it in a function changes how the rest of the script splits once the \
+                      function returns. Changes at the top of a script are usually meant to \
+                      last, so they are only reported in pedantic mode.",
+        example: "read_fields() {\n    IFS=:\n    read -ra fields\n}",
+        fix: "Set it only for one command as in `IFS=: read -ra fields`, declare it with \
+              `local IFS`, or save it and assign it back afterwards.",
+    },
+    Rule {
+        code: NO_BACKGROUND_JOB,
+        name: "no-background-job",
+        summary: "`$!` read before any background job was started",
+        explanation: "`$!` is the process ID of the last command run in the background with \
+                      `&`. Reading it before anything was started that way gives an empty \
+                      string, or the ID of an unrelated job started by the caller of a \
+                      function.",
+        example: "server --port 8080\npid=$!",
+        fix: "Run the command with `&` and save `$!` right after it, as in \
+              `server --port 8080 & pid=$!`.",
+    },
+    Rule {
+        code: HEREDOC_INTERPOLATION,
+        name: "heredoc-interpolation",
+        summary: "An array or map expanded as a whole into a heredoc annotated with a language",
+        explanation: "A heredoc annotated with `#/ heredoc(json)`, `heredoc(yaml)` or \
+                      `heredoc(sql)` holds text in that language. Expanding a whole array \
+                      into it joins the elements with spaces, which is rarely valid there.",
+        example: "ports=(80 443)\n#/ heredoc(json)\ncat <<EOF\n{\"ports\": ${ports[@]}}\nEOF",
+        fix: "Format the elements in the heredoc's language first, for example with `printf` \
+              or `jq`, and interpolate the result.",
+    },
+    Rule {
+        code: COMPARISON_OPERATOR,
+        name: "comparison-operator",
+        summary: "A test comparing ints as strings, or strings as ints",
+        explanation: "In `[[ ]]`, `<` and `>` compare their operands as strings in dictionary \
+                      order, so `[[ 10 < 9 ]]` is true. The numeric operators `-eq`, `-ne`, \
+                      `-lt`, `-le`, `-gt` and `-ge` evaluate their operands as arithmetic, \
+                      which fails or reads a variable of the same name for strings that aren't \
+                      numbers.",
+        example: "count=10\nlimit=9\nif [[ $count > $limit ]]; then\n    echo over\nfi",
+        fix: "Use `-lt` and `-gt` to compare ints, and `<`, `>`, `==` and `!=` to compare \
+              strings.",
+    },
+    Rule {
+        code: ARITHMETIC_STRING,
+        name: "arithmetic-string",
+        summary: "A variable holding a string that isn't a number read in arithmetic",
+        explanation: "Arithmetic in `(( ))` and `$(( ))` reads a string that isn't a number as \
+                      the name of another variable, or fails if it isn't one. `(( flag ))` is \
+                      only true when `flag` holds a nonzero number, so `flag=yes` makes it \
+                      false.",
+        example: "verbose=yes\nif (( verbose )); then\n    echo debugging\nfi",
+        fix: "Test strings with `[[ ]]`, as in `[[ $verbose == yes ]]`, or store a number in \
+              the variable.",
+    },
+    Rule {
+        code: BACKGROUND_ASSIGNMENT,
+        name: "background-assignment",
+        summary: "A variable read after being assigned only in a background job",
+        explanation: "A command or group run with `&` runs in a subshell, so variables it \
+                      assigns keep their old value, or stay unset, in the rest of the script. \
+                      Waiting for the job with `wait` doesn't change that.",
+        example: "{ build_dir=$(mktemp -d); } &\nwait\noutput=$build_dir",
+        fix: "Run the assignment in the foreground, or have the job write its result to a file \
+              and read it after `wait`.",
+    },
+    Rule {
+        code: INVALID_UTF8,
+        name: "invalid-utf8",
+        summary: "A file with bytes that aren't valid UTF-8",
+        explanation: "Scripts saved in another encoding, like Latin-1, are read with the invalid \
+                      bytes replaced by U+FFFD so that the rest can still be checked, but words \
+                      and quotes around them may not be what bash sees. A replacement character \
+                      already in the file is reported too, since it usually means the original \
+                      bytes were lost by an earlier conversion. Fixes aren't written to files \
+                      with invalid bytes.",
+        example: "greeting=\"caf\u{fffd}\"",
+        fix: "Convert the file to UTF-8 with a tool like `iconv`.",
+    },
+    Rule {
+        code: TOO_LARGE,
+        name: "too-large",
+        summary: "Code that isn't checked since it doesn't fit in the memory limit",
+        explanation: "With `memory_limit` set, files are parsed a chunk of top-level statements \
+                      at a time so the syntax tree stays within the limit. A single statement \
+                      too large for the limit, like a function spanning most of a generated \
+                      script, can't be parsed on its own, so it and the rest of the file aren't \
+                      checked.",
+        example: "setup() {\n    # ...megabytes of generated code\n}",
+        fix: "Raise `memory_limit`, or split the code into smaller functions.",
+    },
+];
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} ({}): {}", self.code, self.name, self.summary)?;
+        writeln!(f)?;
+        writeln!(f, "{}", self.explanation)?;
+        writeln!(f)?;
+        writeln!(f, "Example:")?;
+        writeln!(f)?;
+        for line in self.example.lines() {
+            writeln!(f, "    {line}")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Fix: {}", self.fix)
+    }
+}
+
+/// Finds a rule by its code, like `BT0003`, or its name, like `redefined-variable`
+pub fn rule(code_or_name: &str) -> Option<&'static Rule> {
+    RULES
+        .iter()
+        .find(|rule| rule.code.eq_ignore_ascii_case(code_or_name) || rule.name == code_or_name)
+}
@@ -0,0 +1,79 @@
+//! Strings holding a number, like `"5"`, used where an int is expected. Bash doesn't tell them
+//! apart, so the `numeric_strings` setting decides whether they are reported
+
+use tree_sitter::Node;
+
+use crate::{
+    bash_integer, BashType, Diagnostic, FileInfo, Label, LabelKind, NumericStrings, ParseResult,
+    Severity,
+};
+
+impl FileInfo<'_> {
+    /// The type `value` is used as where `expected` is wanted. A string holding a number becomes
+    /// an int if the config allows it, with an advice under the code of the check it would have
+    /// failed when set to `hint`
+    pub(crate) fn coerce_numeric_string(
+        &mut self,
+        expected: &BashType,
+        value_type: BashType,
+        value: Option<Node>,
+        code: &'static str,
+    ) -> ParseResult<BashType> {
+        let policy = self.config.numeric_strings;
+        let Some(value) = value else {
+            return Ok(value_type);
+        };
+        let applies = policy != NumericStrings::Forbid
+            && value_type == BashType::String
+            && expected.can_contain(&BashType::Integer)
+            && !expected.can_contain(&BashType::String);
+        if !applies || !self.is_numeric_string(value)? {
+            return Ok(value_type);
+        }
+        if policy == NumericStrings::Hint && !self.force {
+            self.errors.push(
+                Diagnostic::new("Quoted number used as an int", value.start_byte())
+                    .with_code(code)
+                    .with_severity(Severity::Advice)
+                    .with_label(
+                        Label::new(
+                            value.start_byte()..value.end_byte(),
+                            "This string holds a number, so it is used as",
+                            LabelKind::Inferred,
+                        )
+                        .with_type(BashType::Integer),
+                    )
+                    .with_help(
+                        "Remove the quotes, or set `numeric_strings = \"allow\"` to stop \
+                         reporting this",
+                    ),
+            );
+        }
+        Ok(BashType::Integer)
+    }
+
+    /// Whether `value` is a literal number like `"5"`, or expands a single variable last set to
+    /// one, as in `"$count"`
+    pub(crate) fn is_numeric_string(&self, value: Node) -> ParseResult<bool> {
+        if let Some(text) = self.static_text(value)? {
+            return Ok(bash_integer(&text).is_some());
+        }
+        let expansion = if value.kind() == "string" && value.named_child_count() == 1 {
+            value.named_child(0)
+        } else {
+            Some(value)
+        };
+        let Some(variable) = expansion
+            .filter(|expansion| matches!(expansion.kind(), "simple_expansion" | "expansion"))
+            .filter(|expansion| expansion.named_child_count() == 1)
+            .and_then(|expansion| expansion.named_child(0))
+            .filter(|variable| variable.kind() == "variable_name")
+        else {
+            return Ok(false);
+        };
+        Ok(self
+            .resolve_name(self.node_text(variable)?)
+            .and_then(|name| self.literal_values.get(name))
+            .is_some_and(|literal| bash_integer(literal).is_some()))
+    }
+}
@@ -0,0 +1,187 @@
+//! Diagnostics about globals shared by files that source each other
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::{Component, Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    codes, files::FileId, symbols::Workspace, Diagnostic, Label, LabelKind, Method, Severity,
+    TypeDeclaration,
+};
+
+/// A diagnostic about a file of a [`Workspace`], whose labels can point into other files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkspaceDiagnostic {
+    pub path: PathBuf,
+    pub file: FileId,
+    pub diagnostic: Diagnostic,
+}
+
+impl Workspace {
+    /// Finds globals that are defined with incompatible types by files sourced into the same
+    /// script, variables that overwrite one exported by a file the script sources, and functions
+    /// that are never called. Sourced
+    /// paths are looked up next to the file sourcing them and then in the current directory, and
+    /// files that aren't in the workspace are skipped
+    pub fn check(&mut self) -> Vec<WorkspaceDiagnostic> {
+        // The file with the diagnostic and the variable it is about
+        let mut reported = BTreeSet::new();
+        let mut found = Vec::new();
+        for path in self.files.keys() {
+            let order = self.source_order(path);
+            let script = &self.files[path];
+            for &sourced in &order[..order.len() - 1] {
+                for (name, (exported_in, export)) in &self.files[sourced].exports {
+                    let Some(global) = script.globals.get(name) else {
+                        continue;
+                    };
+                    if !reported.insert((path, name)) {
+                        continue;
+                    }
+                    let diagnostic = Diagnostic::new(
+                        format!(
+                            "{name} overwrites the variable exported by {}",
+                            sourced.display()
+                        ),
+                        global.range.start,
+                    )
+                    .with_severity(Severity::Warning)
+                    .with_code(codes::SHADOWED_EXPORT)
+                    .with_label(declaration_label(global, "Assigned here", script.id))
+                    .with_label(
+                        Label::new(export.clone(), "Exported here", LabelKind::Specified)
+                            .with_file(*exported_in),
+                    );
+                    found.push((path.clone(), name.clone(), diagnostic));
+                }
+            }
+            for (i, &first) in order.iter().enumerate() {
+                for &second in &order[i + 1..] {
+                    for (name, defined) in &self.files[first].globals {
+                        let Some(redefined) = self.files[second].globals.get(name) else {
+                            continue;
+                        };
+                        if redefined.bash_type.matches(&defined.bash_type)
+                            || !reported.insert((second, name))
+                        {
+                            continue;
+                        }
+                        let diagnostic = Diagnostic::new(
+                            format!(
+                                "{name} is also a global in {} with an incompatible type",
+                                first.display()
+                            ),
+                            redefined.range.start,
+                        )
+                        .with_code(codes::GLOBAL_COLLISION)
+                        .with_label(
+                            declaration_label(redefined, "Defined here as", self.files[second].id)
+                                .with_type(redefined.bash_type.clone()),
+                        )
+                        .with_label(
+                            declaration_label(defined, "Defined here as", self.files[second].id)
+                                .with_type(defined.bash_type.clone()),
+                        );
+                        found.push((second.clone(), name.clone(), diagnostic));
+                    }
+                }
+            }
+        }
+        found.extend(self.unused_functions());
+
+        let mut diagnostics = Vec::new();
+        for (path, name, diagnostic) in found {
+            let Ok(config) = self.config_for(&path) else {
+                continue;
+            };
+            let code = diagnostic.code.as_deref().unwrap_or_default();
+            if config.is_allowed_variable(code, &name) {
+                continue;
+            }
+            let mut kept = vec![diagnostic];
+            config.apply_rules(&mut kept);
+            let file = self.files[&path].id;
+            diagnostics.extend(kept.into_iter().map(|diagnostic| WorkspaceDiagnostic {
+                path: path.clone(),
+                file,
+                diagnostic,
+            }));
+        }
+        diagnostics.sort_by(|a, b| {
+            a.diagnostic
+                .sort_key(&a.path)
+                .cmp(&b.diagnostic.sort_key(&b.path))
+        });
+        diagnostics
+    }
+
+    /// The files sourced by `path` that are in the workspace, each after the ones it sources, and
+    /// then `path` itself
+    pub(crate) fn source_order<'a>(&'a self, path: &'a PathBuf) -> Vec<&'a PathBuf> {
+        let by_path = self
+            .files
+            .keys()
+            .map(|path| (normalize(path), path))
+            .collect::<HashMap<_, _>>();
+        let mut order = Vec::new();
+        self.sourced_files(path, &by_path, &mut HashSet::new(), &mut order);
+        order
+    }
+
+    fn sourced_files<'a>(
+        &'a self,
+        path: &'a PathBuf,
+        by_path: &HashMap<PathBuf, &'a PathBuf>,
+        seen: &mut HashSet<&'a PathBuf>,
+        order: &mut Vec<&'a PathBuf>,
+    ) {
+        if !seen.insert(path) {
+            return;
+        }
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for source in &self.files[path].sources {
+            let resolved = [dir.join(source), PathBuf::from(source)]
+                .iter()
+                .find_map(|candidate| by_path.get(&normalize(candidate)).copied());
+            if let Some(sourced) = resolved {
+                self.sourced_files(sourced, by_path, seen, order);
+            }
+        }
+        order.push(path);
+    }
+}
+
+/// A label on a declaration the workspace keeps, for a diagnostic about `file`. It only names the
+/// file of the declaration when that is another one
+fn declaration_label(declaration: &TypeDeclaration, text: &str, file: FileId) -> Label {
+    let kind = match declaration.method {
+        Method::Declared => LabelKind::Specified,
+        Method::Inferred => LabelKind::Inferred,
+    };
+    Label::new(declaration.range.clone(), text, kind)
+        .in_file(declaration.file.filter(|&declared_in| declared_in != file))
+}
+
+/// Removes `.` components and the directories before `..` components without touching the file
+/// system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
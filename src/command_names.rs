@@ -0,0 +1,265 @@
+//! Finding commands that aren't defined anywhere, which are often misspelled function names
+
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashSet},
+    env, fs,
+    hash::{Hash, Hasher},
+    ops::Range,
+    sync::OnceLock,
+};
+
+use tree_sitter::Node;
+
+use crate::{
+    bats::BATS_COMMANDS,
+    codes,
+    commands::CommandRegistry,
+    fix::{Applicability, Fix, FixKind, TextEdit},
+    Call, Diagnostic, Dialect, FileInfo, Label, LabelKind, ParseResult, Severity,
+};
+
+/// Builtins and keywords bash runs without looking anything up on `PATH`
+const BASH_BUILTINS: &[&str] = &[
+    ".",
+    ":",
+    "[",
+    "alias",
+    "bg",
+    "bind",
+    "break",
+    "builtin",
+    "caller",
+    "cd",
+    "command",
+    "compgen",
+    "complete",
+    "compopt",
+    "continue",
+    "coproc",
+    "declare",
+    "dirs",
+    "disown",
+    "echo",
+    "enable",
+    "eval",
+    "exec",
+    "exit",
+    "export",
+    "false",
+    "fc",
+    "fg",
+    "getopts",
+    "hash",
+    "help",
+    "history",
+    "jobs",
+    "kill",
+    "let",
+    "local",
+    "logout",
+    "mapfile",
+    "popd",
+    "printf",
+    "pushd",
+    "pwd",
+    "read",
+    "readarray",
+    "readonly",
+    "return",
+    "set",
+    "shift",
+    "shopt",
+    "source",
+    "suspend",
+    "test",
+    "time",
+    "times",
+    "trap",
+    "true",
+    "type",
+    "typeset",
+    "ulimit",
+    "umask",
+    "unalias",
+    "unset",
+    "wait",
+];
+
+/// The names of the files in the directories on `PATH`, read once
+pub(crate) fn path_commands() -> &'static HashSet<String> {
+    static COMMANDS: OnceLock<HashSet<String>> = OnceLock::new();
+    COMMANDS.get_or_init(|| {
+        let Some(path) = env::var_os("PATH") else {
+            return HashSet::new();
+        };
+        env::split_paths(&path)
+            .filter_map(|directory| fs::read_dir(directory).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect()
+    })
+}
+
+/// A hash of the commands on `PATH`, so cached results of `check_path` are thrown away when
+/// the machine has different tools
+pub(crate) fn path_fingerprint() -> u64 {
+    static FINGERPRINT: OnceLock<u64> = OnceLock::new();
+    *FINGERPRINT.get_or_init(|| {
+        let mut commands = path_commands().iter().collect::<Vec<_>>();
+        commands.sort();
+        let mut hasher = DefaultHasher::new();
+        commands.hash(&mut hasher);
+        hasher.finish()
+    })
+}
+
+/// How many single character insertions, deletions, substitutions and swaps of neighbouring
+/// characters turn `a` into `b`, so `hots` is one mistake away from `host`. This is the optimal
+/// string alignment distance, which doesn't edit a swapped pair again
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut before_previous: Vec<usize> = Vec::new();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for i in 0..a.len() {
+        let mut current = vec![i + 1];
+        for j in 0..b.len() {
+            let substitution = previous[j] + usize::from(a[i] != b[j]);
+            let mut distance = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                distance = distance.min(before_previous[j - 1] + 1);
+            }
+            current.push(distance);
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
+/// The candidate closest to `name`, if it is close enough to be a typo. Names of up to four
+/// characters allow one mistake and longer ones two
+pub(crate) fn closest_name<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a String>,
+) -> Option<String> {
+    let allowed = match name.chars().count() {
+        0..=2 => return None,
+        3..=4 => 1,
+        _ => 2,
+    };
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| (1..=allowed).contains(distance))
+        .min()
+        .map(|(_, candidate)| candidate.clone())
+}
+
+impl FileInfo<'_> {
+    /// Remembers where a command is run by a literal name, to be looked up once every function
+    /// in the file is known
+    pub(crate) fn record_command_name(&mut self, node: Node) -> ParseResult<()> {
+        let Some(command_name) = node.child_by_field_name("name") else {
+            return Ok(());
+        };
+        let Some(word) = command_name
+            .named_child(0)
+            .filter(|word| command_name.named_child_count() == 1 && word.kind() == "word")
+        else {
+            return Ok(());
+        };
+        let name = self.node_text(word)?;
+        self.calls.push(Call {
+            name: name.to_owned(),
+            range: word.start_byte()..word.end_byte(),
+            caller: self.function.clone(),
+        });
+        // Paths like `./build.sh` are run directly
+        if name.contains('/') || self.force {
+            return Ok(());
+        }
+        if name == "alias" {
+            for argument in node.children_by_field_name("argument", &mut node.walk()) {
+                // Only the name has to be literal, as in `alias ll='ls -l'`
+                if let Some((alias, _)) = self.node_text(argument)?.split_once('=') {
+                    self.aliases.insert(alias.to_owned());
+                }
+            }
+        }
+        self.invoked_commands
+            .push((name.to_owned(), word.start_byte()..word.end_byte()));
+        Ok(())
+    }
+
+    /// Warns about commands that aren't functions, aliases, builtins or known commands. Names
+    /// close to a function are reported as typos, and with `check_path` so is anything that
+    /// isn't found on `PATH` either
+    pub(crate) fn check_command_names(&mut self) {
+        let mut unknown = BTreeMap::<String, Vec<Range<usize>>>::new();
+        for (name, range) in std::mem::take(&mut self.invoked_commands) {
+            // Having a signature doesn't mean the command is installed on this machine
+            let known = BASH_BUILTINS.contains(&name.as_str())
+                || (!self.config.check_path && CommandRegistry::builtin().get(&name).is_some())
+                || self.functions.contains_key(&name)
+                || self.aliases.contains(&name)
+                || (self.bats && BATS_COMMANDS.contains(&name.as_str()));
+            if !known {
+                unknown.entry(name).or_default().push(range);
+            }
+        }
+        // Functions from sourced files aren't known, and neither are zsh builtins
+        let report_missing = self.config.check_path
+            && self.sources.is_empty()
+            && self.config.dialect == Dialect::Bash;
+        for (name, ranges) in unknown {
+            let suggestion = self.similar_function(&name);
+            if (suggestion.is_none() && !report_missing) || path_commands().contains(&name) {
+                continue;
+            }
+            let mut diagnostic =
+                Diagnostic::new(format!("Unknown command {name}"), ranges[0].start)
+                    .with_code(codes::UNKNOWN_COMMAND)
+                    .with_severity(Severity::Warning)
+                    .with_label(Label::new(
+                        ranges[0].clone(),
+                        "Not a function, builtin or command on PATH",
+                        LabelKind::ParseErr,
+                    ));
+            for range in &ranges[1..] {
+                diagnostic = diagnostic.with_label(Label::new(
+                    range.clone(),
+                    "Also used here",
+                    LabelKind::ParseErr,
+                ));
+            }
+            if let Some(function) = suggestion {
+                let range = self.functions[&function].range.clone();
+                diagnostic = diagnostic
+                    .with_label(Label::new(
+                        range,
+                        format!("Function {function} defined here"),
+                        LabelKind::Specified,
+                    ))
+                    .with_help(format!("Did you mean {function}?"))
+                    .with_fix(Fix {
+                        kind: FixKind::Rename,
+                        applicability: Applicability::MaybeIncorrect,
+                        title: format!("Change to {function}"),
+                        edits: ranges
+                            .iter()
+                            .map(|range| TextEdit {
+                                range: range.clone(),
+                                new_text: function.clone(),
+                            })
+                            .collect(),
+                    });
+            }
+            self.errors.push(diagnostic);
+        }
+    }
+
+    /// The defined function closest to `name`, if it is close enough to be a typo
+    fn similar_function(&self, name: &str) -> Option<String> {
+        closest_name(name, self.functions.keys())
+    }
+}
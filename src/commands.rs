@@ -0,0 +1,205 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use serde::{Deserialize, Deserializer};
+use tree_sitter::Node;
+
+use crate::{
+    codes, scan::matches_glob, BashType, Diagnostic, FileInfo, Label, LabelKind, ParseResult,
+    Severity,
+};
+
+/// The signatures bashtyped knows about, written in the same format as `commands.toml`
+const BUILTIN_COMMANDS: &str = include_str!("commands.toml");
+
+/// How the arguments of a command are typed
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommandSignature {
+    #[serde(deserialize_with = "types_from_strings")]
+    pub args: Vec<BashType>,
+    #[serde(deserialize_with = "option_types_from_strings")]
+    pub options: HashMap<String, BashType>,
+    /// What the command prints, tried in order
+    pub outputs: Vec<OutputRule>,
+}
+
+impl CommandSignature {
+    /// The type of the positional argument at `position`, where the last type repeats
+    pub fn argument_type(&self, position: usize) -> Option<&BashType> {
+        self.args.get(position).or(self.args.last())
+    }
+
+    /// The type of what the command prints when run with `arguments`, joined by spaces
+    pub fn output_type(&self, arguments: &str) -> Option<&BashType> {
+        self.outputs
+            .iter()
+            .find(|rule| {
+                rule.args
+                    .as_ref()
+                    .is_none_or(|pattern| matches_glob(pattern, arguments))
+            })
+            .map(|rule| &rule.bash_type)
+    }
+}
+
+/// The type a command prints when its arguments match a pattern, like
+/// `{ args = "*length*", type = "int" }` for `jq '.items | length'`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputRule {
+    /// A glob matched against the arguments joined by spaces, or any arguments if it's missing
+    #[serde(default)]
+    pub args: Option<String>,
+    #[serde(rename = "type", deserialize_with = "type_from_string")]
+    pub bash_type: BashType,
+}
+
+#[derive(Debug, Default)]
+pub struct CommandRegistry {
+    commands: HashMap<String, CommandSignature>,
+}
+
+impl CommandRegistry {
+    pub fn from_toml(contents: &str) -> Result<Self, toml::de::Error> {
+        Ok(Self {
+            commands: toml::from_str(contents)?,
+        })
+    }
+
+    /// The signatures that ship with bashtyped
+    pub fn builtin() -> &'static Self {
+        static BUILTIN: OnceLock<CommandRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            Self::from_toml(BUILTIN_COMMANDS).expect("builtin command signatures to be valid")
+        })
+    }
+
+    pub fn get(&self, command: &str) -> Option<&CommandSignature> {
+        self.commands.get(command)
+    }
+}
+
+fn type_from_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BashType, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+fn types_from_strings<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<BashType>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|bash_type| bash_type.parse().map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn option_types_from_strings<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, BashType>, D::Error> {
+    HashMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(option, bash_type)| {
+            let bash_type = bash_type.parse().map_err(serde::de::Error::custom)?;
+            Ok((option, bash_type))
+        })
+        .collect()
+}
+
+impl FileInfo<'_> {
+    /// The type of what `command` prints according to its signature, matching the literal text of
+    /// its arguments
+    pub(crate) fn registry_output(&self, command: Node) -> ParseResult<Option<BashType>> {
+        let Some(name) = command.child_by_field_name("name") else {
+            return Ok(None);
+        };
+        let Some(signature) = CommandRegistry::builtin().get(self.node_text(name)?) else {
+            return Ok(None);
+        };
+        let arguments = command
+            .children_by_field_name("argument", &mut command.walk())
+            .map(|argument| match self.static_text(argument)? {
+                Some(text) => Ok(text),
+                None => self.node_text(argument).map(str::to_owned),
+            })
+            .collect::<ParseResult<Vec<_>>>()?;
+        Ok(signature.output_type(&arguments.join(" ")).cloned())
+    }
+
+    /// Checks the arguments of commands with a known signature, such as `sleep "$seconds"`
+    pub(crate) fn check_command_arguments(&mut self, node: Node) -> ParseResult<()> {
+        let Some(command_name) = node.child_by_field_name("name") else {
+            return Ok(());
+        };
+        let command = self.node_text(command_name)?;
+        let Some(signature) = CommandRegistry::builtin().get(command) else {
+            return Ok(());
+        };
+
+        let mut position = 0;
+        let mut option_value: Option<&BashType> = None;
+        let mut options_ended = false;
+        for argument in node.children_by_field_name("argument", &mut node.walk()) {
+            let literal = self.static_text(argument)?;
+            let expected = if let Some(expected) = option_value.take() {
+                Some(expected)
+            } else if let Some(flag) = literal
+                .as_deref()
+                .filter(|text| !options_ended && text.starts_with('-') && text.len() > 1)
+            {
+                if flag == "--" {
+                    options_ended = true;
+                }
+                option_value = signature.options.get(flag);
+                continue;
+            } else {
+                position += 1;
+                signature.argument_type(position - 1)
+            };
+            let Some(expected) = expected else {
+                continue;
+            };
+            // Literal arguments like `5m` are often accepted even if they don't look like the type
+            if literal.is_some() {
+                continue;
+            }
+            // Anything that can't be typed, like positional parameters, is left alone
+            let Ok(inferred_type) = self.infer_type(argument) else {
+                continue;
+            };
+            let inferred_type = self.coerce_numeric_string(
+                expected,
+                inferred_type,
+                Some(argument),
+                codes::ARGUMENT_MISMATCH,
+            )?;
+            if !expected.can_contain(&inferred_type) && !self.force {
+                self.errors.push(
+                    Diagnostic::new(
+                        format!("Argument to {command} has the wrong type"),
+                        argument.start_byte(),
+                    )
+                    .with_code(codes::ARGUMENT_MISMATCH)
+                    .with_severity(Severity::Warning)
+                    .with_label(
+                        Label::new(
+                            command_name.start_byte()..command_name.end_byte(),
+                            "Argument expected to be",
+                            LabelKind::Specified,
+                        )
+                        .with_type(expected.clone()),
+                    )
+                    .with_label(
+                        Label::new(
+                            argument.start_byte()..argument.end_byte(),
+                            "Type inferred to be",
+                            LabelKind::Inferred,
+                        )
+                        .with_type(inferred_type),
+                    ),
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+//! Suggestions for editors while an annotation is being written
+
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::{ANNOTATION_COMMANDS, TYPE_NAMES};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CompletionKind {
+    Type,
+    Command,
+    Operator,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: String,
+    /// The part of the source the completion replaces, which is what was already typed of it
+    pub range: Range<usize>,
+}
+
+/// The completions at `offset`: type names in `#/` annotations and after `#[returns`, the
+/// commands after `#[`, and `|` after a complete type
+pub fn complete(source_code: &str, offset: usize) -> Vec<Completion> {
+    let Some(before) = source_code.get(..offset) else {
+        return Vec::new();
+    };
+    let line = &before[before.rfind('\n').map_or(0, |i| i + 1)..];
+    let partial_len = line
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .map(char::len_utf8)
+        .sum::<usize>();
+    let partial = &line[line.len() - partial_len..];
+    let before_word = &line[..line.len() - partial_len];
+    let range = offset - partial_len..offset;
+
+    if let Some(start) = before_word.find("#/") {
+        return type_completions(&before_word[start + 2..], partial, range);
+    }
+    let Some(start) = before_word.find("#[") else {
+        return Vec::new();
+    };
+    let command = before_word[start + 2..].trim_start();
+    if command.contains(']') {
+        return Vec::new();
+    }
+    if command.is_empty() {
+        return matching(ANNOTATION_COMMANDS, partial, CompletionKind::Command, range);
+    }
+    if let Some(return_type) = command.strip_prefix("returns ") {
+        return type_completions(return_type, partial, range);
+    }
+    match command
+        .strip_prefix("set_var(")
+        .and_then(|arguments| arguments.split_once(','))
+    {
+        Some((_, variable_type)) => type_completions(variable_type, partial, range),
+        None => Vec::new(),
+    }
+}
+
+/// Completions inside a type, where `annotation` is the text before the word being typed
+fn type_completions(annotation: &str, partial: &str, range: Range<usize>) -> Vec<Completion> {
+    let mut open = Vec::new();
+    for c in annotation.chars() {
+        match c {
+            '<' | '[' | '{' => open.push(c),
+            '>' | ']' | '}' => {
+                open.pop();
+            }
+            _ => (),
+        }
+    }
+    let trimmed = annotation.trim_end();
+    let expects_type = match open.last() {
+        // Record keys are names, so only the values after `:` are types
+        Some('{') => trimmed.ends_with(':') || trimmed.ends_with('|'),
+        _ => trimmed.is_empty() || trimmed.ends_with(['|', '<', ',', '[']),
+    };
+    if expects_type {
+        matching(TYPE_NAMES, partial, CompletionKind::Type, range)
+    } else if partial.is_empty() && annotation.ends_with(char::is_whitespace) {
+        vec![Completion {
+            label: String::from("|"),
+            kind: CompletionKind::Operator,
+            detail: String::from("Allows another type as well"),
+            range,
+        }]
+    } else {
+        Vec::new()
+    }
+}
+
+fn matching(
+    keywords: &[(&str, &str)],
+    partial: &str,
+    kind: CompletionKind,
+    range: Range<usize>,
+) -> Vec<Completion> {
+    keywords
+        .iter()
+        .filter(|(name, _)| name.starts_with(partial))
+        .map(|(name, detail)| Completion {
+            label: (*name).to_owned(),
+            kind,
+            detail: (*detail).to_owned(),
+            range: range.clone(),
+        })
+        .collect()
+}
@@ -0,0 +1,245 @@
+//! Conditions of `if`, `while` and `until`, which branch on the status of a command rather than
+//! on a value
+
+use tree_sitter::Node;
+
+use crate::{
+    codes,
+    fix::{Applicability, Fix, FixKind, TextEdit},
+    BashType, Diagnostic, FileInfo, Label, LabelKind, ParseResult, Severity,
+};
+
+/// The operators of `[[ ]]` and `[ ]` that compare their operands as integers
+const NUMERIC_OPERATORS: &[&str] = &["-eq", "-ne", "-lt", "-le", "-gt", "-ge"];
+
+impl FileInfo<'_> {
+    /// Warns about conditions that use what a command prints instead of its status, like
+    /// `if [ $(cmd) ]; then` or `if $(cmd); then`
+    pub(crate) fn check_conditions(&mut self, node: Node) -> ParseResult<()> {
+        if self.force {
+            return Ok(());
+        }
+        let conditions = node
+            .children(&mut node.walk())
+            .skip(1)
+            .take_while(|child| !matches!(child.kind(), "then" | "do_group"))
+            .filter(|child| child.is_named() && child.kind() != "comment")
+            .collect::<Vec<_>>();
+        for condition in conditions {
+            self.check_condition(condition)?;
+        }
+        Ok(())
+    }
+
+    fn check_condition(&mut self, condition: Node) -> ParseResult<()> {
+        match condition.kind() {
+            "negated_command" | "list" => {
+                for child in condition.named_children(&mut condition.walk()) {
+                    self.check_condition(child)?;
+                }
+            }
+            "test_command" if condition.named_child_count() == 1 => {
+                let operand = condition.named_child(0).expect("child count to be one");
+                self.check_tested_output(operand)?;
+            }
+            "command" => {
+                let Some(name) = condition.child_by_field_name("name") else {
+                    return Ok(());
+                };
+                let arguments = condition
+                    .children_by_field_name("argument", &mut condition.walk())
+                    .collect::<Vec<_>>();
+                if let ("test", [operand]) = (self.node_text(name)?, arguments.as_slice()) {
+                    return self.check_tested_output(*operand);
+                }
+                let substitution = name
+                    .named_child(0)
+                    .filter(|child| child.kind() == "command_substitution");
+                if let Some(substitution) = substitution {
+                    self.errors.push(
+                        Diagnostic::new(
+                            "Output of a command run as the condition",
+                            substitution.start_byte(),
+                        )
+                        .with_code(codes::OUTPUT_CONDITION)
+                        .with_severity(Severity::Warning)
+                        .with_label(Label::new(
+                            substitution.start_byte()..substitution.end_byte(),
+                            "What this prints is run as another command",
+                            LabelKind::Inferred,
+                        ))
+                        .with_help("Remove `$(...)` to branch on the status of the command"),
+                    );
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Reports a test whose only operand is a command substitution, which checks whether the
+    /// output is empty
+    fn check_tested_output(&mut self, operand: Node) -> ParseResult<()> {
+        let substitution = match operand.kind() {
+            "string" if operand.named_child_count() == 1 => {
+                operand.named_child(0).expect("child count to be one")
+            }
+            _ => operand,
+        };
+        if substitution.kind() != "command_substitution" {
+            return Ok(());
+        }
+        let function = substitution
+            .named_child(0)
+            .filter(|command| substitution.named_child_count() == 1 && command.kind() == "command")
+            .and_then(|command| command.child_by_field_name("name"))
+            .map(|name| self.node_text(name))
+            .transpose()?
+            .filter(|name| {
+                self.functions
+                    .get(*name)
+                    .is_some_and(|function| function.returns == Some(BashType::Bool))
+            });
+        let help = match function {
+            Some(name) => format!("{name} returns a bool status, so use `if {name}; then`"),
+            None => String::from(
+                "Run the command as the condition to branch on its status, or use `-n` to test \
+                 its output",
+            ),
+        };
+        self.errors.push(
+            Diagnostic::new(
+                "Output of a command used as a test",
+                substitution.start_byte(),
+            )
+            .with_code(codes::OUTPUT_CONDITION)
+            .with_severity(Severity::Warning)
+            .with_label(Label::new(
+                operand.start_byte()..operand.end_byte(),
+                "Only checks whether this output is empty",
+                LabelKind::Inferred,
+            ))
+            .with_help(help),
+        );
+        Ok(())
+    }
+
+    /// Warns about `<` and `>` in `[[ ]]` comparing ints as strings, where `[[ 10 < 9 ]]` is true,
+    /// and about numeric operators like `-gt` comparing strings that aren't numbers
+    pub(crate) fn check_comparison_operator(&mut self, comparison: Node) -> ParseResult<()> {
+        let (Some(left), Some(right)) = (
+            comparison.child_by_field_name("left"),
+            comparison.child_by_field_name("right"),
+        ) else {
+            return Ok(());
+        };
+        let Some(operator) = comparison
+            .children(&mut comparison.walk())
+            .find(|child| matches!(child.kind(), "<" | ">" | "test_operator"))
+        else {
+            return Ok(());
+        };
+        let operator_text = self.node_text(operator)?;
+        let numeric = NUMERIC_OPERATORS.contains(&operator_text);
+        if !numeric && operator.kind() == "test_operator" || self.force {
+            return Ok(());
+        }
+        let Some(bracket) = test_bracket(comparison) else {
+            return Ok(());
+        };
+        let operands = [left, right]
+            .into_iter()
+            .filter_map(|operand| Some((operand, self.infer_type(operand).ok()?)))
+            .collect::<Vec<_>>();
+        let (message, labelled, replacement) = if numeric {
+            let mut strings = Vec::new();
+            for (operand, bash_type) in operands {
+                if bash_type == BashType::String && !self.is_numeric_string(operand)? {
+                    strings.push((operand, bash_type));
+                }
+            }
+            if strings.is_empty() {
+                return Ok(());
+            }
+            let replacement = match (operator_text, bracket) {
+                ("-eq", "[[") => Some("=="),
+                ("-eq", _) => Some("="),
+                ("-ne", _) => Some("!="),
+                ("-lt", "[[") => Some("<"),
+                ("-lt", _) => Some("\\<"),
+                ("-gt", "[[") => Some(">"),
+                ("-gt", _) => Some("\\>"),
+                _ => None,
+            };
+            (
+                format!("Strings compared as integers with {operator_text}"),
+                strings,
+                replacement,
+            )
+        } else {
+            if bracket != "[["
+                || operands.len() != 2
+                || operands
+                    .iter()
+                    .any(|(_, bash_type)| *bash_type != BashType::Integer)
+            {
+                return Ok(());
+            }
+            let replacement = if operator_text == "<" { "-lt" } else { "-gt" };
+            (
+                format!("Integers compared as strings with {operator_text}"),
+                operands,
+                Some(replacement),
+            )
+        };
+        let mut diagnostic = Diagnostic::new(message, comparison.start_byte())
+            .with_code(codes::COMPARISON_OPERATOR)
+            .with_severity(Severity::Warning);
+        for (operand, bash_type) in labelled {
+            diagnostic = diagnostic.with_label(
+                Label::new(
+                    operand.start_byte()..operand.end_byte(),
+                    "Type inferred to be",
+                    LabelKind::Inferred,
+                )
+                .with_type(bash_type),
+            );
+        }
+        if let Some(replacement) = replacement {
+            let compared = if numeric { "strings" } else { "integers" };
+            diagnostic = diagnostic
+                .with_help(format!("Use {replacement} to compare them as {compared}"))
+                .with_fix(Fix {
+                    kind: FixKind::Operator,
+                    applicability: Applicability::MaybeIncorrect,
+                    title: format!("Replace {operator_text} with {replacement}"),
+                    edits: vec![TextEdit {
+                        range: operator.start_byte()..operator.end_byte(),
+                        new_text: replacement.to_owned(),
+                    }],
+                });
+        }
+        self.errors.push(diagnostic);
+        Ok(())
+    }
+}
+
+/// The bracket, `[[` or `[`, of the test a comparison is part of. Comparisons in arithmetic
+/// have none
+fn test_bracket(comparison: Node) -> Option<&'static str> {
+    let mut node = comparison;
+    while let Some(parent) = node.parent() {
+        match parent.kind() {
+            "binary_expression" | "unary_expression" | "parenthesized_expression" => node = parent,
+            "test_command" => {
+                return match parent.child(0)?.kind() {
+                    "[[" => Some("[["),
+                    "[" => Some("["),
+                    _ => None,
+                }
+            }
+            _ => return None,
+        }
+    }
+    None
+}
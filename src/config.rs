@@ -0,0 +1,559 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    fs, io,
+    path::{self, Path, PathBuf},
+    str::FromStr,
+};
+
+use ariadne::Color;
+use serde::Deserialize;
+
+use crate::{codes, Severity};
+
+pub const CONFIG_FILE_NAME: &str = "bashtyped.toml";
+
+#[derive(Clone, Debug, Hash)]
+pub struct Config {
+    /// Oldest bash release the scripts have to run on. Features added after it are reported
+    pub bash_version: Option<BashVersion>,
+    pub dialect: Dialect,
+    /// Also report code that is probably fine but can't be checked
+    pub pedantic: bool,
+    /// Assigning one branch of a union-typed variable, as in `x=5` for an `int | string`, narrows
+    /// the type seen by later reads until the next assignment
+    pub narrow_on_assignment: bool,
+    /// Whether a quoted number like `"5"` can be used where an int is expected
+    pub numeric_strings: NumericStrings,
+    /// Let an annotation on the line before a statement apply even when blank lines separate
+    /// them. Otherwise such annotations are reported and ignored
+    pub detached_annotations: bool,
+    /// Report commands that aren't functions, builtins or programs on `PATH`. Otherwise only
+    /// names that look like misspelled functions are reported
+    pub check_path: bool,
+    /// Whether reports are printed with colors
+    pub color: bool,
+    /// Patterns of paths to skip when searching directories for scripts, with the same syntax as
+    /// [`crate::scan::IGNORE_FILE_NAME`]
+    pub ignore: Vec<String>,
+    /// Where `bashtyped annotate` puts `#/` annotations
+    pub annotation_style: AnnotationStyle,
+    /// Settings for single rules from `[rules.BT0004]` tables, by code
+    pub rules: BTreeMap<&'static str, RuleConfig>,
+    /// How the command line prints diagnostics unless `--format` is given
+    pub format: Option<OutputFormat>,
+    /// The `[profile.NAME]` table applied after the other settings of each config file
+    pub profile: Option<String>,
+    /// How much memory the syntax tree of a file may take, in MiB. With a limit, files are
+    /// parsed and analyzed a chunk of top-level statements at a time, so huge generated scripts
+    /// can be checked. Statements too large for the limit aren't checked
+    pub memory_limit: Option<usize>,
+    /// Heredoc bodies larger than this many KiB aren't checked, since generated scripts can
+    /// embed whole files in them
+    pub max_heredoc_size: Option<usize>,
+    pub(crate) specified_color: Color,
+    pub(crate) inferred_color: Color,
+    pub(crate) parse_err_color: Color,
+    pub(crate) unknown_color: Color,
+    pub(crate) error_color: Color,
+    pub(crate) warning_color: Color,
+    pub(crate) advice_color: Color,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut config = Self {
+            bash_version: None,
+            dialect: Dialect::default(),
+            pedantic: false,
+            narrow_on_assignment: false,
+            numeric_strings: NumericStrings::default(),
+            detached_annotations: false,
+            check_path: false,
+            color: true,
+            ignore: Vec::new(),
+            annotation_style: AnnotationStyle::default(),
+            rules: BTreeMap::new(),
+            format: None,
+            profile: None,
+            memory_limit: None,
+            max_heredoc_size: None,
+            specified_color: Color::Unset,
+            inferred_color: Color::Unset,
+            parse_err_color: Color::Unset,
+            unknown_color: Color::Unset,
+            error_color: Color::Unset,
+            warning_color: Color::Unset,
+            advice_color: Color::Unset,
+        };
+        config.apply_theme(Theme::default());
+        config
+    }
+}
+
+/// How many types a union can have when `max_members` isn't set for BT0023
+pub const DEFAULT_MAX_UNION_MEMBERS: usize = 6;
+
+/// Sets of colors for reports, chosen with `theme` in the config file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Default,
+    /// Blue and orange, which can be told apart with the common kinds of color blindness
+    Colorblind,
+}
+
+/// Where annotations go, chosen with `annotation_style` in the config file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotationStyle {
+    /// Leaves annotations where they are, adding new ones at the end of the line
+    #[default]
+    Preserve,
+    /// At the end of the line of the statement, like `count=0 #/ int`
+    Inline,
+    /// On their own line before the statement
+    Above,
+}
+
+/// How strings holding a number are treated where an int is expected, chosen with
+/// `numeric_strings` in the config file. Bash stores both as text, so `"5"` works anywhere `5`
+/// does
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NumericStrings {
+    /// Reports them like any other string
+    #[default]
+    Forbid,
+    /// Treats them as ints, with an advice saying so
+    Hint,
+    /// Treats them as ints without reporting anything
+    Allow,
+}
+
+/// How the command line prints diagnostics, chosen with `format` in the config file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Full,
+    Short,
+    Checkstyle,
+    Junit,
+    Html,
+}
+
+/// The settings of a rule, from a `[rules.BT0004]` or `[rules.possibly-unset]` table
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleConfig {
+    /// Turns the rule off, or on for rules only checked with `pedantic = true`
+    pub enabled: Option<bool>,
+    pub severity: Option<RuleSeverity>,
+    /// Names of variables not to report, with `*`, `?` and `[...]` wildcards. Only used by the
+    /// rules about variables, BT0002 and BT0004
+    pub allow: Vec<String>,
+    /// How many types a union can have before it is reported. Only used by BT0023
+    pub max_members: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSeverity {
+    Error,
+    Warning,
+    Advice,
+}
+
+impl From<RuleSeverity> for Severity {
+    fn from(severity: RuleSeverity) -> Self {
+        match severity {
+            RuleSeverity::Error => Self::Error,
+            RuleSeverity::Warning => Self::Warning,
+            RuleSeverity::Advice => Self::Advice,
+        }
+    }
+}
+
+/// Colors from the config file that override the theme
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ColorsFile {
+    specified: Option<ConfigColor>,
+    inferred: Option<ConfigColor>,
+    parse_error: Option<ConfigColor>,
+    unknown: Option<ConfigColor>,
+    error: Option<ConfigColor>,
+    warning: Option<ConfigColor>,
+    advice: Option<ConfigColor>,
+}
+
+/// The contents of a `bashtyped.toml` file, where settings that aren't given are inherited from
+/// the config files above it
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    /// Stops settings from being inherited from the config files above this one
+    root: bool,
+    bash_version: Option<BashVersion>,
+    dialect: Option<Dialect>,
+    pedantic: Option<bool>,
+    narrow_on_assignment: Option<bool>,
+    numeric_strings: Option<NumericStrings>,
+    detached_annotations: Option<bool>,
+    check_path: Option<bool>,
+    ignore: Vec<String>,
+    annotation_style: Option<AnnotationStyle>,
+    rules: BTreeMap<String, RuleConfig>,
+    theme: Option<Theme>,
+    colors: ColorsFile,
+    format: Option<OutputFormat>,
+    memory_limit: Option<usize>,
+    max_heredoc_size: Option<usize>,
+    /// Settings that override the others when the profile is selected, like for CI or editors
+    profile: BTreeMap<String, ConfigFile>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Invalid(toml::de::Error),
+    /// A `[rules.X]` table for a code or name that doesn't exist
+    UnknownRule(String),
+    /// `ignore` in a config file below the root one, which isn't used when searching for scripts
+    NestedIgnore(PathBuf),
+    /// A profile that the root config file has no `[profile.NAME]` table for
+    UnknownProfile(String),
+    /// A profile table that sets `root` or has profiles of its own
+    InvalidProfile(String),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Unable to read config: {e}"),
+            Self::Invalid(e) => write!(f, "Invalid config: {e}"),
+            Self::UnknownRule(rule) => write!(f, "Invalid config: unknown rule {rule}"),
+            Self::NestedIgnore(path) => write!(
+                f,
+                "Invalid config {}: ignore can only be set in the root config",
+                path.display()
+            ),
+            Self::UnknownProfile(name) => write!(f, "Invalid config: unknown profile {name}"),
+            Self::InvalidProfile(name) => write!(
+                f,
+                "Invalid config: profile {name} can't set root or contain other profiles"
+            ),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_toml(contents: &str) -> Result<Self, ConfigError> {
+        Self::from_toml_with_profile(contents, None)
+    }
+
+    /// Reads a config file, applying its `[profile.NAME]` table for the profile if one is given
+    pub fn from_toml_with_profile(
+        contents: &str,
+        profile: Option<&str>,
+    ) -> Result<Self, ConfigError> {
+        let file: ConfigFile = toml::from_str(contents).map_err(ConfigError::Invalid)?;
+        if let Some(name) = profile {
+            if !file.profile.contains_key(name) {
+                return Err(ConfigError::UnknownProfile(name.to_owned()));
+            }
+        }
+        let mut config = Self {
+            profile: profile.map(str::to_owned),
+            ..Default::default()
+        };
+        config.merge(file)?;
+        Ok(config)
+    }
+
+    /// Overrides the settings given in a config file. Rules are merged setting by setting, with
+    /// their `allow` lists combined
+    fn merge(&mut self, mut file: ConfigFile) -> Result<(), ConfigError> {
+        if file.root {
+            *self = Self {
+                color: self.color,
+                ignore: std::mem::take(&mut self.ignore),
+                profile: self.profile.take(),
+                ..Default::default()
+            };
+        }
+        self.format = file.format.or(self.format);
+        if let Some(bash_version) = file.bash_version {
+            self.bash_version = Some(bash_version);
+        }
+        self.dialect = file.dialect.unwrap_or(self.dialect);
+        self.pedantic = file.pedantic.unwrap_or(self.pedantic);
+        self.narrow_on_assignment = file
+            .narrow_on_assignment
+            .unwrap_or(self.narrow_on_assignment);
+        self.numeric_strings = file.numeric_strings.unwrap_or(self.numeric_strings);
+        self.detached_annotations = file
+            .detached_annotations
+            .unwrap_or(self.detached_annotations);
+        self.check_path = file.check_path.unwrap_or(self.check_path);
+        self.annotation_style = file.annotation_style.unwrap_or(self.annotation_style);
+        self.memory_limit = file.memory_limit.or(self.memory_limit);
+        self.max_heredoc_size = file.max_heredoc_size.or(self.max_heredoc_size);
+        self.ignore.extend(file.ignore);
+        for (key, rule) in file.rules {
+            let Some(found) = codes::rule(&key) else {
+                return Err(ConfigError::UnknownRule(key));
+            };
+            let merged = self.rules.entry(found.code).or_default();
+            merged.enabled = rule.enabled.or(merged.enabled);
+            merged.severity = rule.severity.or(merged.severity);
+            merged.allow.extend(rule.allow);
+            merged.max_members = rule.max_members.or(merged.max_members);
+        }
+        if let Some(theme) = file.theme {
+            self.apply_theme(theme);
+        }
+        let colors = file.colors;
+        for (color, setting) in [
+            (colors.specified, &mut self.specified_color),
+            (colors.inferred, &mut self.inferred_color),
+            (colors.parse_error, &mut self.parse_err_color),
+            (colors.unknown, &mut self.unknown_color),
+            (colors.error, &mut self.error_color),
+            (colors.warning, &mut self.warning_color),
+            (colors.advice, &mut self.advice_color),
+        ] {
+            if let Some(ConfigColor(color)) = color {
+                *setting = color;
+            }
+        }
+        let selected = self
+            .profile
+            .as_ref()
+            .and_then(|name| file.profile.remove(name));
+        if let Some(profile) = selected {
+            if profile.root || !profile.profile.is_empty() {
+                return Err(ConfigError::InvalidProfile(
+                    self.profile.clone().unwrap_or_default(),
+                ));
+            }
+            self.merge(profile)?;
+        }
+        Ok(())
+    }
+
+    pub fn apply_theme(&mut self, theme: Theme) {
+        let [specified, inferred, parse_err, unknown, error, warning, advice] = match theme {
+            Theme::Default => [
+                Color::Blue,
+                Color::Magenta,
+                Color::Red,
+                Color::Yellow,
+                Color::Red,
+                Color::Yellow,
+                Color::Fixed(147),
+            ],
+            Theme::Colorblind => [
+                Color::RGB(0, 114, 178),
+                Color::RGB(230, 159, 0),
+                Color::RGB(213, 94, 0),
+                Color::RGB(240, 228, 66),
+                Color::RGB(213, 94, 0),
+                Color::RGB(230, 159, 0),
+                Color::RGB(86, 180, 233),
+            ],
+        };
+        self.specified_color = specified;
+        self.inferred_color = inferred;
+        self.parse_err_color = parse_err;
+        self.unknown_color = unknown;
+        self.error_color = error;
+        self.warning_color = warning;
+        self.advice_color = advice;
+    }
+
+    /// Whether diagnostics with `code` are reported, where `default` is used when the config
+    /// doesn't say
+    pub fn rule_enabled(&self, code: &str, default: bool) -> bool {
+        self.rules
+            .get(code)
+            .and_then(|rule| rule.enabled)
+            .unwrap_or(default)
+    }
+
+    /// How many types a union can have before BT0023 reports it, which is also how long a union
+    /// can get before it is abbreviated in reports
+    pub fn max_union_members(&self) -> usize {
+        self.rules
+            .get(codes::LARGE_UNION)
+            .and_then(|rule| rule.max_members)
+            .unwrap_or(DEFAULT_MAX_UNION_MEMBERS)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        Self::load_with_profile(path, None)
+    }
+
+    pub fn load_with_profile(path: &Path, profile: Option<&str>) -> Result<Self, ConfigError> {
+        Self::from_toml_with_profile(&fs::read_to_string(path).map_err(ConfigError::Io)?, profile)
+    }
+}
+
+/// Finds the config of each file from the root config and the `bashtyped.toml` files in the
+/// directories between the root and the file. Settings in deeper files override the ones above
+/// them, except `[rules]` tables which are merged setting by setting with their `allow` lists
+/// combined, and `root = true` stops anything from being inherited
+#[derive(Clone, Debug)]
+pub struct ConfigHierarchy {
+    root_dir: PathBuf,
+    root: Config,
+    /// The config of each directory that has been looked up
+    directories: HashMap<PathBuf, Config>,
+}
+
+impl ConfigHierarchy {
+    /// `root` is the config of `root_dir`. Files outside of it always use `root`
+    pub fn new(root_dir: &Path, root: Config) -> Self {
+        Self {
+            root_dir: path::absolute(root_dir).unwrap_or_else(|_| root_dir.to_path_buf()),
+            root,
+            directories: HashMap::new(),
+        }
+    }
+
+    pub fn config_for(&mut self, path: &Path) -> Result<Config, ConfigError> {
+        match path::absolute(path).ok().as_deref().and_then(Path::parent) {
+            Some(dir) => self.directory_config(dir),
+            None => Ok(self.root.clone()),
+        }
+    }
+
+    fn directory_config(&mut self, dir: &Path) -> Result<Config, ConfigError> {
+        if dir == self.root_dir || !dir.starts_with(&self.root_dir) {
+            return Ok(self.root.clone());
+        }
+        if let Some(config) = self.directories.get(dir) {
+            return Ok(config.clone());
+        }
+        let parent = dir
+            .parent()
+            .expect("directories below the root to have a parent");
+        let mut config = self.directory_config(parent)?;
+        let path = dir.join(CONFIG_FILE_NAME);
+        match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let file: ConfigFile = toml::from_str(&contents).map_err(ConfigError::Invalid)?;
+                let ignores = file
+                    .profile
+                    .values()
+                    .any(|profile| !profile.ignore.is_empty());
+                if !file.ignore.is_empty() || ignores {
+                    return Err(ConfigError::NestedIgnore(path));
+                }
+                config.merge(file)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(ConfigError::Io(e)),
+        }
+        self.directories.insert(dir.to_path_buf(), config.clone());
+        Ok(config)
+    }
+}
+
+/// The shell the scripts are written for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    #[default]
+    Bash,
+    /// Zsh scripts are parsed with the bash grammar, with zsh-only builtins such as `integer`
+    /// mapped onto the same types
+    #[cfg(feature = "zsh")]
+    Zsh,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub struct BashVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl BashVersion {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl Display for BashVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for BashVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (major, minor) = s.split_once('.').unwrap_or((s, "0"));
+        match (major.parse(), minor.parse()) {
+            (Ok(major), Ok(minor)) => Ok(Self { major, minor }),
+            _ => Err(format!("{s} is not a valid bash version")),
+        }
+    }
+}
+
+impl TryFrom<String> for BashVersion {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// A color name like `blue` or `bright-red`, a 256-color index, or a hex code like `#ff8800`
+#[derive(Deserialize)]
+#[serde(try_from = "String")]
+struct ConfigColor(Color);
+
+impl TryFrom<String> for ConfigColor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let color = match value.trim().to_lowercase().as_str() {
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "white" => Color::White,
+            "bright-black" => Color::Fixed(8),
+            "bright-red" => Color::Fixed(9),
+            "bright-green" => Color::Fixed(10),
+            "bright-yellow" => Color::Fixed(11),
+            "bright-blue" => Color::Fixed(12),
+            "bright-magenta" => Color::Fixed(13),
+            "bright-cyan" => Color::Fixed(14),
+            "bright-white" => Color::Fixed(15),
+            hex if hex.starts_with('#') && hex.len() == 7 => {
+                let channel = |i: usize| {
+                    hex.get(i..i + 2)
+                        .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+                };
+                match (channel(1), channel(3), channel(5)) {
+                    (Some(r), Some(g), Some(b)) => Color::RGB(r, g, b),
+                    _ => return Err(format!("{value} is not a valid hex color")),
+                }
+            }
+            other => match other.parse() {
+                Ok(index) => Color::Fixed(index),
+                Err(_) => return Err(format!("{value} is not a valid color")),
+            },
+        };
+        Ok(Self(color))
+    }
+}
@@ -0,0 +1,63 @@
+//! Finding where a variable is defined, following `source` into the other files of a workspace
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    symbols::{Span, Workspace},
+    FileInfo,
+};
+
+/// A span in one of the files of a [`Workspace`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+impl FileInfo<'_> {
+    /// The name of the variable at `offset`, in an expansion, an assignment or a `#[set_var]`
+    /// annotation
+    pub fn variable_at(&mut self, offset: usize) -> Option<String> {
+        self.named_ranges()
+            .into_iter()
+            .find(|(_, range)| range.contains(&offset) || range.end == offset)
+            .map(|(name, _)| name)
+    }
+
+    /// The first declaration or assignment in this file of the variable at `offset`, using the
+    /// declarations found by [`FileInfo::parse_code`]
+    pub fn definition(&mut self, offset: usize) -> Option<Range<usize>> {
+        let name = self.variable_at(offset)?;
+        Some(self.history.get(&name)?.first()?.range.clone())
+    }
+}
+
+impl Workspace {
+    /// Where the variable at `offset` of `file`, the analyzed contents of the file at `path`, is
+    /// first defined. Variables the file doesn't define are looked up in the files it sources,
+    /// starting with the one sourced last since its value is the one the script sees
+    pub fn definition(&self, path: &Path, file: &mut FileInfo, offset: usize) -> Option<Location> {
+        let name = file.variable_at(offset)?;
+        if let Some(first) = file.history.get(&name).and_then(|history| history.first()) {
+            return Some(Location {
+                path: path.to_path_buf(),
+                span: Span::new(file.source_index(), &first.range),
+            });
+        }
+        let (path, _) = self.files.get_key_value(path)?;
+        let mut order = self.source_order(path);
+        order.pop();
+        order.into_iter().rev().find_map(|sourced| {
+            Some(Location {
+                path: sourced.clone(),
+                span: self.files[sourced].definitions.get(&name)?.clone(),
+            })
+        })
+    }
+}
@@ -0,0 +1,394 @@
+use std::ops::Range;
+
+use ariadne::{Color, Fmt, Report, ReportKind, Span};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    files::{FileId, Files},
+    fix::Fix,
+    BashType, Config,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// A stable identifier for the kind of problem, like `BT0010`
+    #[serde(default)]
+    pub code: Option<String>,
+    pub message: String,
+    pub offset: usize,
+    pub labels: Vec<Label>,
+    /// Extra context shown below the labels
+    #[serde(default)]
+    pub note: Option<String>,
+    /// A suggestion for fixing the problem
+    #[serde(default)]
+    pub help: Option<String>,
+    /// Alternative edits that resolve the problem, the most likely first
+    #[serde(default)]
+    pub fixes: Vec<Fix>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Label {
+    pub range: Range<usize>,
+    pub text: String,
+    pub bash_type: Option<BashType>,
+    pub kind: LabelKind,
+    /// The file the label is in, when it isn't the file of its diagnostic
+    #[serde(default)]
+    pub file: Option<FileId>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    Warning,
+    Advice,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum LabelKind {
+    Specified,
+    Inferred,
+    ParseErr,
+    Unsupported,
+    Unknown,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, offset: usize) -> Self {
+        Self {
+            severity: Severity::Error,
+            code: None,
+            message: message.into(),
+            offset,
+            labels: Vec::new(),
+            note: None,
+            help: None,
+            fixes: Vec::new(),
+        }
+    }
+
+    /// Orders diagnostics by the file they are in, then by where they start and then by code, so
+    /// they come out the same way whichever check or file found them first. The diagnostics of a
+    /// single file are sorted with `()` as the file
+    pub fn sort_key<F: Ord>(&self, file: F) -> (F, usize, Option<&str>) {
+        (file, self.offset, self.code.as_deref())
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
+    /// Moves the diagnostic `offset` bytes later, for code analyzed separately from the file it
+    /// is embedded in
+    pub(crate) fn shifted(mut self, offset: usize) -> Self {
+        self.offset += offset;
+        for label in &mut self.labels {
+            label.range = label.range.start + offset..label.range.end + offset;
+        }
+        self
+    }
+
+    /// Points the diagnostic and all of its labels at `range`, for code that was rewritten before
+    /// it was analyzed
+    pub(crate) fn relocated(mut self, range: Range<usize>) -> Self {
+        self.offset = range.start;
+        for label in &mut self.labels {
+            label.range = range.clone();
+        }
+        self
+    }
+
+    /// A single line like `script.sh:3:1: error[BT0001]: Types do not match`, in the format
+    /// used by compilers and understood by editors and CI log parsers
+    pub fn short(&self, path: &str, source_code: &str) -> String {
+        self.short_indexed(path, &SourceIndex::unindexed(source_code))
+    }
+
+    /// Like [`Diagnostic::short`], finding the line and column with an index of the file built
+    /// once for all of its diagnostics
+    pub fn short_indexed(&self, path: &str, index: &SourceIndex) -> String {
+        let (line, column) = index.line_column(self.offset);
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Advice => "advice",
+        };
+        match &self.code {
+            Some(code) => format!(
+                "{path}:{line}:{column}: {severity}[{code}]: {}",
+                self.message
+            ),
+            None => format!("{path}:{line}:{column}: {severity}: {}", self.message),
+        }
+    }
+
+    /// A report for a diagnostic in the indexed file, whose labels are all in it
+    pub fn report(&self, config: &Config, index: &SourceIndex) -> Report<'static> {
+        self.build_report(config, (), index.char_offset(self.offset), |label| {
+            index.char_range(&label.range)
+        })
+    }
+
+    /// A report for a diagnostic in `file` whose labels can be in other files, printed with the
+    /// [`Files`] holding every file it points into
+    pub fn report_in(
+        &self,
+        file: FileId,
+        config: &Config,
+        files: &Files,
+    ) -> Report<'static, (FileId, Range<usize>)> {
+        let offset = files.source_code(file).map_or(self.offset, |source_code| {
+            SourceIndex::unindexed(source_code).char_offset(self.offset)
+        });
+        self.build_report(config, file, offset, |label| {
+            let file = label.file.unwrap_or(file);
+            let range = files
+                .source_code(file)
+                .map_or(label.range.clone(), |source_code| {
+                    SourceIndex::unindexed(source_code).char_range(&label.range)
+                });
+            (file, range)
+        })
+    }
+
+    /// Builds a report from offsets counted in characters, which ariadne expects, rather than in
+    /// bytes
+    fn build_report<S: Span>(
+        &self,
+        config: &Config,
+        source: <S::SourceId as ToOwned>::Owned,
+        offset: usize,
+        span: impl Fn(&Label) -> S,
+    ) -> Report<'static, S> {
+        // Ariadne colors custom kinds even when colors are turned off
+        let kind = match (self.severity, config.color) {
+            (Severity::Error, true) => ReportKind::Custom("Error", config.error_color),
+            (Severity::Warning, true) => ReportKind::Custom("Warning", config.warning_color),
+            (Severity::Advice, true) => ReportKind::Custom("Advice", config.advice_color),
+            (Severity::Error, false) => ReportKind::Error,
+            (Severity::Warning, false) => ReportKind::Warning,
+            (Severity::Advice, false) => ReportKind::Advice,
+        };
+        let mut report = Report::build(kind, source, offset)
+            .with_message(&self.message)
+            .with_config(ariadne::Config::default().with_color(config.color));
+        if let Some(code) = &self.code {
+            report = report.with_code(code);
+        }
+        for label in &self.labels {
+            let color = label.kind.color(config);
+            let message = match &label.bash_type {
+                Some(bash_type) => {
+                    let bash_type = bash_type.abbreviated(config.max_union_members());
+                    if config.color {
+                        format!("{} {}", label.text, bash_type.fg(color))
+                    } else {
+                        format!("{} {bash_type}", label.text)
+                    }
+                }
+                None => label.text.clone(),
+            };
+            report = report.with_label(
+                ariadne::Label::new(span(label))
+                    .with_message(message)
+                    .with_color(color),
+            );
+        }
+        if let Some(note) = &self.note {
+            report = report.with_note(note);
+        }
+        if let Some(help) = &self.help {
+            report = report.with_help(help);
+        }
+        report.finish()
+    }
+}
+
+impl Label {
+    pub fn new(range: Range<usize>, text: impl Into<String>, kind: LabelKind) -> Self {
+        Self {
+            range,
+            text: text.into(),
+            bash_type: None,
+            kind,
+            file: None,
+        }
+    }
+
+    pub fn with_type(mut self, bash_type: BashType) -> Self {
+        self.bash_type = Some(bash_type);
+        self
+    }
+
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    /// Points the label into `file`, or into the file of its diagnostic for `None`
+    pub fn in_file(mut self, file: Option<FileId>) -> Self {
+        self.file = file;
+        self
+    }
+}
+
+/// How many bytes apart the positions kept by a [`SourceIndex`] are
+const CHECKPOINT_INTERVAL: usize = 4096;
+
+/// The characters and lines before positions spread through a file, so the line, column or
+/// character offset of a byte only has to be counted from the position before it. Reports for a
+/// large file would otherwise count from its start for each diagnostic
+pub struct SourceIndex<'a> {
+    source_code: &'a str,
+    checkpoints: Vec<Checkpoint>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Checkpoint {
+    offset: usize,
+    chars: usize,
+    /// The UTF-16 code units before the offset, which is how JavaScript indexes strings
+    utf16: usize,
+    newlines: usize,
+}
+
+impl<'a> SourceIndex<'a> {
+    pub fn new(source_code: &'a str) -> Self {
+        let mut checkpoints = vec![Checkpoint::default()];
+        let mut last = Checkpoint::default();
+        for offset in (CHECKPOINT_INTERVAL..source_code.len()).step_by(CHECKPOINT_INTERVAL) {
+            // Characters are at most 4 bytes long
+            let Some(offset) = (offset - 3..=offset)
+                .rev()
+                .find(|&offset| source_code.is_char_boundary(offset))
+            else {
+                continue;
+            };
+            let between = &source_code[last.offset..offset];
+            last = Checkpoint {
+                offset,
+                chars: last.chars + between.chars().count(),
+                utf16: last.utf16 + between.encode_utf16().count(),
+                newlines: last.newlines + between.matches('\n').count(),
+            };
+            checkpoints.push(last);
+        }
+        Self {
+            source_code,
+            checkpoints,
+        }
+    }
+
+    /// An index that counts from the start of the file, for looking up a single offset
+    fn unindexed(source_code: &'a str) -> Self {
+        Self {
+            source_code,
+            checkpoints: vec![Checkpoint::default()],
+        }
+    }
+
+    /// The last position kept before `offset`
+    fn checkpoint(&self, offset: usize) -> Checkpoint {
+        let index = (offset / CHECKPOINT_INTERVAL).min(self.checkpoints.len() - 1);
+        self.checkpoints[index]
+    }
+
+    /// The number of characters before the byte `offset`
+    pub fn char_offset(&self, offset: usize) -> usize {
+        let checkpoint = self.checkpoint(offset);
+        self.source_code
+            .get(checkpoint.offset..offset)
+            .map_or(offset, |between| checkpoint.chars + between.chars().count())
+    }
+
+    /// The number of UTF-16 code units before the byte `offset`
+    pub fn utf16_offset(&self, offset: usize) -> usize {
+        let checkpoint = self.checkpoint(offset);
+        self.source_code
+            .get(checkpoint.offset..offset)
+            .map_or(offset, |between| {
+                checkpoint.utf16 + between.encode_utf16().count()
+            })
+    }
+
+    /// The byte offset of the UTF-16 code unit at `offset`, or of the end of the file when it is
+    /// past it. An offset inside a surrogate pair gives the character the pair encodes
+    pub fn from_utf16(&self, offset: usize) -> usize {
+        let index = self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.utf16 <= offset)
+            .saturating_sub(1);
+        let checkpoint = self.checkpoints[index];
+        let mut utf16 = checkpoint.utf16;
+        for (i, c) in self.source_code[checkpoint.offset..].char_indices() {
+            utf16 += c.len_utf16();
+            if utf16 > offset {
+                return checkpoint.offset + i;
+            }
+        }
+        self.source_code.len()
+    }
+
+    fn char_range(&self, range: &Range<usize>) -> Range<usize> {
+        self.char_offset(range.start)..self.char_offset(range.end)
+    }
+
+    /// The 1-based line and column of `offset`, counting columns in characters
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        let offset = if self.source_code.is_char_boundary(offset) {
+            offset
+        } else {
+            self.source_code.len()
+        };
+        let checkpoint = self.checkpoint(offset);
+        let before = &self.source_code[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        // A byte order mark at the start of the file isn't a column
+        let line = match line_start {
+            0 => before.strip_prefix('\u{feff}').unwrap_or(before),
+            _ => &before[line_start..],
+        };
+        let newlines = checkpoint.newlines + before[checkpoint.offset..].matches('\n').count();
+        (newlines + 1, line.chars().count() + 1)
+    }
+}
+
+impl LabelKind {
+    fn color(self, config: &Config) -> Color {
+        match self {
+            Self::Specified => config.specified_color,
+            Self::Inferred => config.inferred_color,
+            Self::ParseErr | Self::Unsupported => config.parse_err_color,
+            Self::Unknown => config.unknown_color,
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Lines changed according to `git diff`, so that only problems in new code are reported
+
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{files::Files, Diagnostic, SourceIndex};
+
+/// The changed lines of each file, numbered from 1 as in the new version of the file
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChangedLines {
+    /// Keyed by the path from the repository root, or as given when the root isn't known
+    files: HashMap<PathBuf, Vec<Range<usize>>>,
+    /// The repository root, which the paths in a diff are relative to
+    root: Option<PathBuf>,
+}
+
+impl ChangedLines {
+    /// Reads the output of `git diff --unified=0`. Lines that were only removed aren't counted
+    /// as changed
+    pub fn from_diff(diff: &str) -> Self {
+        let mut changed = Self::default();
+        let mut current = None;
+        for line in diff.lines() {
+            if let Some(path) = line.strip_prefix("+++ ") {
+                current = (path != "/dev/null").then(|| {
+                    let path = path.strip_prefix("b/").unwrap_or(path);
+                    normalize(Path::new(path.trim_matches('"')))
+                });
+            } else if let (Some(hunk), Some(path)) = (line.strip_prefix("@@ "), &current) {
+                if let Some(lines) = hunk_lines(hunk) {
+                    changed.files.entry(path.clone()).or_default().push(lines);
+                }
+            }
+        }
+        changed
+    }
+
+    /// Sets the root of the repository the diff is from. Paths looked up afterwards are resolved
+    /// from the current directory, so `./lib.sh`, `../repo/lib.sh` and a symlink to it all match
+    /// the `lib.sh` of the diff
+    pub fn with_root(mut self, root: &Path) -> Self {
+        self.root = Some(root.canonicalize().unwrap_or_else(|_| root.to_path_buf()));
+        self
+    }
+
+    /// Treats every line of a file as changed, like for files git doesn't track yet
+    pub fn add_file(&mut self, path: &Path) {
+        self.files
+            .insert(self.key(path), std::iter::once(1..usize::MAX).collect());
+    }
+
+    pub fn contains(&self, path: &Path, line: usize) -> bool {
+        self.overlaps(path, line..line + 1)
+    }
+
+    fn overlaps(&self, path: &Path, lines: Range<usize>) -> bool {
+        self.files.get(&self.key(path)).is_some_and(|changed| {
+            changed
+                .iter()
+                .any(|range| range.start < lines.end && lines.start < range.end)
+        })
+    }
+
+    /// The diagnostics that start on a changed line or point at one with a label
+    pub fn changed_diagnostics(
+        &self,
+        path: &Path,
+        source_code: &str,
+        diagnostics: Vec<Diagnostic>,
+    ) -> Vec<Diagnostic> {
+        self.changed_workspace_diagnostics(path, source_code, diagnostics, &Files::new())
+    }
+
+    /// Like [`ChangedLines::changed_diagnostics`], but also keeps diagnostics with a label on a
+    /// changed line of another file of `files`, like the file a conflicting global is declared in
+    pub fn changed_workspace_diagnostics(
+        &self,
+        path: &Path,
+        source_code: &str,
+        diagnostics: Vec<Diagnostic>,
+        files: &Files,
+    ) -> Vec<Diagnostic> {
+        let index = SourceIndex::new(source_code);
+        let mut others = HashMap::new();
+        diagnostics
+            .into_iter()
+            .filter(|diagnostic| {
+                self.contains(path, index.line_column(diagnostic.offset).0)
+                    || diagnostic.labels.iter().any(|label| {
+                        let (path, index) = match label.file {
+                            None => (path, &index),
+                            Some(file) => {
+                                let (Some(other), Some(other_source)) =
+                                    (files.path(file), files.source_code(file))
+                                else {
+                                    return false;
+                                };
+                                let other_index = others
+                                    .entry(file)
+                                    .or_insert_with(|| SourceIndex::new(other_source));
+                                (Path::new(other), &*other_index)
+                            }
+                        };
+                        let line = |offset| index.line_column(offset).0;
+                        let end = label.range.end.max(label.range.start + 1);
+                        self.overlaps(path, line(label.range.start)..line(end - 1) + 1)
+                    })
+            })
+            .collect()
+    }
+
+    /// The key of `path` in `files`
+    fn key(&self, path: &Path) -> PathBuf {
+        let Some(root) = &self.root else {
+            return normalize(path);
+        };
+        let absolute = path
+            .canonicalize()
+            .unwrap_or_else(|_| normalize(&std::env::current_dir().unwrap_or_default().join(path)));
+        match absolute.strip_prefix(root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => absolute,
+        }
+    }
+}
+
+/// The new lines of a hunk header like `-12,3 +14,5 @@`
+fn hunk_lines(hunk: &str) -> Option<Range<usize>> {
+    let new = hunk
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix('+'))?;
+    let (start, count) = match new.split_once(',') {
+        Some((start, count)) => (start.parse().ok()?, count.parse().ok()?),
+        None => (new.parse().ok()?, 1),
+    };
+    (count > 0).then_some(start..start + count)
+}
+
+/// `path` without `.` components, and with the components before `..` removed
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
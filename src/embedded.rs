@@ -0,0 +1,100 @@
+use tree_sitter::Node;
+
+use crate::{FileInfo, ParseErrType, ParseError, ParseResult};
+
+impl FileInfo<'_> {
+    /// Analyzes code embedded in a string, like a trap handler or the argument to `eval`, with
+    /// the variables known at this point. Offsets in the result are relative to `source`
+    pub(crate) fn analyze_embedded<'b>(&self, source: &'b str) -> ParseResult<FileInfo<'b>> {
+        let mut analysis = FileInfo::with_config(source, self.config.clone());
+        analysis.variables = self.variables.clone();
+        analysis.namerefs = self.namerefs.clone();
+        analysis.literal_values = self.literal_values.clone();
+        analysis.unset_variables = self.unset_variables.clone();
+        analysis.conditionally_set = self.conditionally_set.clone();
+        analysis.narrowed = self.narrowed.clone();
+        let tree = analysis.parse_tree();
+        analysis.analyze_tree(&tree);
+        for variable in analysis.unknown_variables(tree.root_node())? {
+            // Variables in redirections have already been reported
+            let range = variable.start_byte()..variable.end_byte();
+            let reported = analysis
+                .errors
+                .iter()
+                .any(|error| error.labels.iter().any(|label| label.range == range));
+            if reported {
+                continue;
+            }
+            let error = analysis.unknown_variable_error(variable)?;
+            analysis.push_parse_error(error, variable);
+        }
+        Ok(analysis)
+    }
+
+    /// Where the code in `argument` starts in the file, if its value is exactly what was written
+    /// there so offsets in it can be mapped back. Quotes are skipped, but escapes like `\$` in a
+    /// double quoted string change the value
+    pub(crate) fn embedded_offset(&self, argument: Node) -> ParseResult<Option<usize>> {
+        let Some(text) = self.static_text(argument)? else {
+            return Ok(None);
+        };
+        Ok(match argument.kind() {
+            "raw_string" | "string" => {
+                let written = self.node_text(argument)?;
+                (written.len() >= 2 && text == written[1..written.len() - 1])
+                    .then_some(argument.start_byte() + 1)
+            }
+            _ => Some(argument.start_byte()),
+        })
+    }
+
+    /// The error for reading `variable` when it isn't defined
+    pub(crate) fn unknown_variable_error(&self, variable: Node) -> ParseResult<ParseError> {
+        let name = self.node_text(variable)?;
+        let err_type = if self.conditionally_set.contains(name) {
+            ParseErrType::ConditionallySet(name.to_owned())
+        } else if self.unset_variables.contains(name) {
+            ParseErrType::PossiblyUnset(name.to_owned())
+        } else {
+            ParseErrType::UnknownVariable(name.to_owned())
+        };
+        Ok(ParseError {
+            err_type,
+            start: variable.start_byte(),
+            end: variable.end_byte(),
+        })
+    }
+
+    /// Variables expanded anywhere in `root` that were never defined
+    pub(crate) fn unknown_variables<'tree>(
+        &self,
+        root: Node<'tree>,
+    ) -> ParseResult<Vec<Node<'tree>>> {
+        let mut unknown = Vec::new();
+        let mut worklist = vec![root];
+        while let Some(node) = worklist.pop() {
+            if matches!(node.kind(), "simple_expansion" | "expansion") {
+                let variable = node
+                    .named_children(&mut node.walk())
+                    .find_map(|child| match child.kind() {
+                        "variable_name" => Some(child),
+                        "subscript" => child.child_by_field_name("name"),
+                        _ => None,
+                    });
+                if let Some(variable) = variable {
+                    let name = self.node_text(variable)?;
+                    // Namerefs whose target is only known at runtime resolve to nothing
+                    let known = self
+                        .resolve_name(name)
+                        .is_none_or(|name| self.variables.contains_key(name));
+                    if !known {
+                        unknown.push(variable);
+                    }
+                }
+            }
+            worklist.extend(node.named_children(&mut node.walk()));
+        }
+        unknown.sort_by_key(Node::start_byte);
+        Ok(unknown)
+    }
+}
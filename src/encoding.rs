@@ -0,0 +1,100 @@
+//! Reading scripts that aren't valid UTF-8 or start with a byte order mark. Invalid bytes are
+//! replaced with U+FFFD so the rest of the file can still be analyzed, and reported once by the
+//! analysis with [`crate::codes::INVALID_UTF8`]
+
+use std::{borrow::Cow, fs, io, path::Path};
+
+use crate::{codes, Diagnostic, FileInfo, Label, LabelKind, Severity};
+
+const BYTE_ORDER_MARK: &str = "\u{feff}";
+
+/// The text of a script, decoded from the bytes of its file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedSource {
+    /// The text without its byte order mark, with every sequence of bytes that isn't valid UTF-8
+    /// replaced with U+FFFD
+    pub source_code: String,
+    /// Whether the file started with a UTF-8 byte order mark, which is added back when it is
+    /// written
+    pub byte_order_mark: bool,
+    /// Whether bytes were replaced, so the file can't be written back without losing them
+    pub lossy: bool,
+}
+
+impl DecodedSource {
+    pub fn new(bytes: &[u8]) -> Self {
+        let rest = bytes.strip_prefix(BYTE_ORDER_MARK.as_bytes());
+        let (source_code, lossy) = match String::from_utf8_lossy(rest.unwrap_or(bytes)) {
+            Cow::Borrowed(text) => (text.to_owned(), false),
+            Cow::Owned(text) => (text, true),
+        };
+        Self {
+            source_code,
+            byte_order_mark: rest.is_some(),
+            lossy,
+        }
+    }
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(&fs::read(path)?))
+    }
+
+    pub fn from_reader(mut reader: impl io::Read) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Ok(Self::new(&bytes))
+    }
+
+    /// Writes `source_code`, an edited version of this file, to `path` with the same byte order
+    /// mark. Files that had bytes replaced aren't written, since the original bytes are lost
+    pub fn write(&self, path: &Path, source_code: &str) -> io::Result<()> {
+        if self.lossy {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "it isn't valid UTF-8, so writing it would replace the invalid bytes",
+            ));
+        }
+        let mark = if self.byte_order_mark {
+            BYTE_ORDER_MARK
+        } else {
+            ""
+        };
+        fs::write(path, format!("{mark}{source_code}"))
+    }
+}
+
+impl FileInfo<'_> {
+    /// Warns once about the replacement characters in the file, which stand for bytes that
+    /// weren't valid UTF-8 and may have been part of words or quotes bash sees differently
+    pub(crate) fn check_encoding(&mut self) {
+        if self.is_disabled() {
+            return;
+        }
+        let mut replaced = self.source_code.match_indices(char::REPLACEMENT_CHARACTER);
+        let Some((first, character)) = replaced.next() else {
+            return;
+        };
+        let mut diagnostic = Diagnostic::new("File is not valid UTF-8", first)
+            .with_code(codes::INVALID_UTF8)
+            .with_severity(Severity::Warning)
+            .with_label(Label::new(
+                first..first + character.len(),
+                "Bytes that aren't valid UTF-8 were replaced here",
+                LabelKind::ParseErr,
+            ))
+            .with_help("Convert the file to UTF-8, like with `iconv -f latin1 -t utf-8`");
+        let note = match replaced.count() {
+            0 => None,
+            1 => Some(String::from(
+                "1 more invalid sequence was replaced later in the file",
+            )),
+            others => Some(format!(
+                "{others} more invalid sequences were replaced later in the file"
+            )),
+        };
+        if let Some(note) = note {
+            diagnostic = diagnostic.with_note(note);
+        }
+        self.errors.push(diagnostic);
+    }
+}
@@ -0,0 +1,141 @@
+use tree_sitter::Node;
+
+use crate::{
+    codes, unescape_double_quoted, Diagnostic, FileInfo, Label, LabelKind, ParseResult, Severity,
+    TypeDeclaration,
+};
+
+impl FileInfo<'_> {
+    /// Warns about `eval`, and checks its contents when they can be worked out from literals,
+    /// as in `name=count; eval "$name=5"`
+    pub(crate) fn handle_eval(&mut self, node: Node) -> ParseResult<()> {
+        let arguments = node
+            .children_by_field_name("argument", &mut node.walk())
+            .collect::<Vec<_>>();
+        let (Some(first), Some(last)) = (arguments.first(), arguments.last()) else {
+            return Ok(());
+        };
+        let range = first.start_byte()..last.end_byte();
+
+        let mut words = Vec::new();
+        for argument in &arguments {
+            match self.substituted_text(*argument)? {
+                Some(word) => words.push(word),
+                None => break,
+            }
+        }
+        let known = words.len() == arguments.len();
+        if !self.force {
+            let (label, kind) = if known {
+                ("Checked using the values known here", LabelKind::Inferred)
+            } else {
+                (
+                    "The code run here is only known at runtime",
+                    LabelKind::Unknown,
+                )
+            };
+            self.errors.push(
+                Diagnostic::new("Use of eval", node.start_byte())
+                    .with_severity(Severity::Warning)
+                    .with_code(codes::EVAL)
+                    .with_label(Label::new(range.clone(), label, kind)),
+            );
+        }
+        if !known {
+            return Ok(());
+        }
+
+        let source = words.join(" ");
+        let mut analysis = self.analyze_embedded(&source)?;
+        // Offsets can only be mapped back if the code is exactly what was written
+        let offset = match arguments.as_slice() {
+            [argument] => self.embedded_offset(*argument)?,
+            _ => None,
+        };
+        self.merge_embedded_calls(&mut analysis, node, offset);
+        self.errors
+            .extend(analysis.errors.into_iter().map(|error| match offset {
+                Some(offset) => error.shifted(offset),
+                None => error.relocated(range.clone()),
+            }));
+
+        // Unlike a trap, eval runs right away, so its assignments are visible afterwards
+        let shift = |declaration: &mut TypeDeclaration| {
+            declaration.range = match offset {
+                Some(offset) => declaration.range.start + offset..declaration.range.end + offset,
+                None => range.clone(),
+            };
+        };
+        for (name, declarations) in std::mem::take(&mut analysis.history) {
+            for mut declaration in declarations {
+                shift(&mut declaration);
+                self.history
+                    .entry(name.clone())
+                    .or_default()
+                    .push(declaration);
+            }
+            if !self.is_local(&name) {
+                self.globals.insert(name.clone());
+            }
+            self.background_assignments.remove(&name);
+            match analysis.variables.remove(&name) {
+                // A declaration from before the eval that kept its type still points into
+                // this file
+                Some(declaration) if self.variables.get(&name) == Some(&declaration) => (),
+                Some(mut declaration) => {
+                    shift(&mut declaration);
+                    self.variables.insert(name.clone(), declaration);
+                }
+                None => {
+                    self.variables.remove(&name);
+                }
+            }
+            self.set_literal(&name, analysis.literal_values.get(&name).cloned());
+        }
+        for name in &analysis.unset_variables {
+            self.variables.remove(name);
+        }
+        self.unset_variables = analysis.unset_variables;
+        self.conditionally_set = analysis.conditionally_set;
+        self.narrowed = analysis.narrowed;
+        self.namerefs = analysis.namerefs;
+        self.literal_values = analysis.literal_values;
+        Ok(())
+    }
+
+    /// The text of `node` with variables that hold literals substituted, or `None` if it
+    /// depends on anything else
+    fn substituted_text(&self, node: Node) -> ParseResult<Option<String>> {
+        if let Some(text) = self.static_text(node)? {
+            return Ok(Some(text));
+        }
+        let parts = match node.kind() {
+            "string" | "concatenation" => node.named_children(&mut node.walk()).collect(),
+            "simple_expansion" | "expansion" => vec![node],
+            _ => return Ok(None),
+        };
+        let mut text = String::new();
+        for part in parts {
+            let substituted = match part.kind() {
+                "string_content" => Some(unescape_double_quoted(self.node_text(part)?)),
+                "simple_expansion" | "expansion" => match part
+                    .named_children(&mut part.walk())
+                    .collect::<Vec<_>>()
+                    .as_slice()
+                {
+                    [variable] if variable.kind() == "variable_name" => self
+                        .resolve_name(self.node_text(*variable)?)
+                        .and_then(|name| self.literal_values.get(name))
+                        .cloned(),
+                    _ => None,
+                },
+                _ => self.substituted_text(part)?,
+            };
+            match substituted {
+                Some(substituted) => text.push_str(&substituted),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(text))
+    }
+}
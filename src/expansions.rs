@@ -0,0 +1,283 @@
+use tree_sitter::Node;
+
+use crate::{
+    codes, BashType, Diagnostic, FileInfo, Label, LabelKind, Method, ParseErrType, ParseError,
+    ParseResult, Severity, TypeDeclaration,
+};
+
+impl FileInfo<'_> {
+    /// Types `${...}` expansions
+    pub(crate) fn expansion_type(&mut self, node: Node) -> ParseResult<BashType> {
+        let Some(operator) = node.child(1) else {
+            return Ok(BashType::Unknown);
+        };
+        match operator.kind() {
+            // `${#name}` and `${#arr[@]}` are lengths
+            "#" => {
+                if let Some(target) = node.named_child(0) {
+                    self.reference_type(target)?;
+                }
+                return Ok(BashType::Integer);
+            }
+            "!" => {
+                if let Some(variable) = node
+                    .named_child(0)
+                    .filter(|child| child.kind() == "variable_name")
+                {
+                    return self.indirect_type(variable, node);
+                }
+            }
+            _ => (),
+        }
+
+        // `${name:-default}`, `${name:?message}` and the other operators for unset variables
+        if let Some(operator) = node.child(2).filter(|child| {
+            matches!(
+                child.kind(),
+                ":-" | "-" | ":=" | "=" | ":?" | "?" | ":+" | "+"
+            )
+        }) {
+            let target = node.named_child(0).expect("default to have a target");
+            return self.default_type(node, target, operator.kind());
+        }
+
+        // `${str:3:4}` and `${arr[@]:1:2}`
+        if node.child(2).is_some_and(|child| child.kind() == ":") {
+            let target = node.named_child(0).expect("slice to have a target");
+            return self.slice_type(target);
+        }
+
+        // `${name^^}`, `${name,,}` and `${name/pattern/replacement}`
+        if node.child(2).is_some_and(|child| {
+            matches!(
+                child.kind(),
+                "^" | "^^" | "," | ",," | "/" | "//" | "/#" | "/%"
+            )
+        }) {
+            let target = node
+                .named_child(0)
+                .expect("string operation to have a target");
+            return self.string_operation_type(target);
+        }
+
+        // `${name@Q}` and the other transformations quote, expand or describe the value as text
+        if node.child(2).is_some_and(|child| child.kind() == "@") {
+            let target = node
+                .named_child(0)
+                .expect("transformation to have a target");
+            return Ok(match self.reference_type(target)?.element_type() {
+                Some(_) => BashType::Array(Box::new(BashType::String)),
+                None => BashType::String,
+            });
+        }
+
+        // `${name}` and `${arr[i]}`
+        if node.named_child_count() == 1 {
+            return self.reference_type(node.named_child(0).expect("child count to be one"));
+        }
+        self.warn_unknown(
+            node,
+            "Unable to infer the type of this expansion",
+            "Type inferred to be unknown",
+        );
+        Ok(BashType::Unknown)
+    }
+
+    /// Types the variable or array element named inside an expansion
+    fn reference_type(&mut self, node: Node) -> ParseResult<BashType> {
+        match node.kind() {
+            "variable_name" | "special_variable_name" => self.variable_type(node),
+            "subscript" => self.subscript_type(node),
+            _ => Ok(BashType::Unknown),
+        }
+    }
+
+    /// Runs `${name:=default}` and `${name:?message}` outside of assignments, like in
+    /// `: "${name:=default}"`, so the variable is known to be set afterwards
+    pub(crate) fn check_default_assignment(&mut self, node: Node) -> ParseResult<()> {
+        let (Some(target), Some(operator)) = (node.named_child(0), node.child(2)) else {
+            return Ok(());
+        };
+        if !matches!(operator.kind(), ":=" | "=" | ":?" | "?") || target.kind() != "variable_name" {
+            return Ok(());
+        }
+        // Expansions in values were already typed when the assignment was handled
+        let name = self.node_text(target)?;
+        if self.resolve_name(name).is_none_or(|name| {
+            self.variables.contains_key(name) && !self.conditionally_set.contains(name)
+        }) {
+            return Ok(());
+        }
+        self.default_type(node, target, operator.kind())?;
+        Ok(())
+    }
+
+    /// Reports reads through a nameref whose target was never set, like `echo "$ref"`, wherever
+    /// they are. Unknown variables are otherwise only reported where their type is needed, since
+    /// they can come from the environment, but the target of a nameref is set by the script
+    pub(crate) fn check_nameref_target(&mut self, node: Node) -> ParseResult<()> {
+        let Some(variable) = node
+            .named_child(0)
+            .filter(|child| child.kind() == "variable_name")
+        else {
+            return Ok(());
+        };
+        // `${!ref}` gives the name of the target, and the operators for unset variables handle it
+        // being missing
+        let indirect = node.child(1).is_some_and(|child| child.kind() == "!");
+        let handles_unset = node.child(2).is_some_and(|child| {
+            matches!(
+                child.kind(),
+                ":-" | "-" | ":=" | "=" | ":?" | "?" | ":+" | "+"
+            )
+        });
+        if self.force
+            || indirect
+            || handles_unset
+            || !self.namerefs.contains_key(self.node_text(variable)?)
+        {
+            return Ok(());
+        }
+        match self.variable_type(variable) {
+            Err(
+                e @ ParseError {
+                    err_type: ParseErrType::UnknownNamerefTarget { .. },
+                    ..
+                },
+            ) => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Types the expansions that handle unset variables. `:-` and `:=` give the value or the
+    /// default, `:+` gives the alternative or nothing, and `:?` exits when the variable is unset,
+    /// so it is known to be set afterwards
+    fn default_type(&mut self, node: Node, target: Node, operator: &str) -> ParseResult<BashType> {
+        let value_type = match node.named_child(1) {
+            Some(value) => self.infer_type(value)?,
+            // `${name:-}` gives the empty string
+            None => BashType::String,
+        };
+        if operator.ends_with('+') {
+            return Ok(value_type.union(BashType::String));
+        }
+        let name = match target.kind() {
+            "variable_name" => self
+                .resolve_name(self.node_text(target)?)
+                .map(str::to_owned),
+            _ => None,
+        };
+        let declared = name
+            .as_ref()
+            .and_then(|name| self.variables.get(name))
+            .map(|declaration| declaration.bash_type.clone());
+        let conditional = name
+            .as_ref()
+            .is_some_and(|name| self.conditionally_set.contains(name));
+        // Variables from the environment and positional parameters hold text when they are set
+        let target_type = match (target.kind(), declared.clone()) {
+            ("variable_name", Some(_)) if !conditional => self.variable_type(target)?,
+            ("variable_name", Some(declared)) => declared,
+            ("variable_name", None) => BashType::String,
+            _ => self.reference_type(target).unwrap_or(BashType::String),
+        };
+        let expansion_type = if operator.ends_with('?') {
+            target_type
+        } else {
+            target_type.union(value_type)
+        };
+        let assigns = operator.ends_with('?') || operator.ends_with('=');
+        if let Some(name) = name.filter(|_| assigns) {
+            if declared.is_none() {
+                let declaration = TypeDeclaration {
+                    bash_type: expansion_type.clone(),
+                    range: node.start_byte()..node.end_byte(),
+                    method: Method::Inferred,
+                    node_kind: node.kind().to_owned(),
+                    annotation: None,
+                    file: None,
+                };
+                self.set_variable(&name, declaration, node);
+            } else {
+                self.conditionally_set.remove(&name);
+            }
+        }
+        Ok(expansion_type)
+    }
+
+    /// Slicing the whole array gives an array of the same elements, anything else is sliced as a
+    /// string
+    fn slice_type(&mut self, target: Node) -> ParseResult<BashType> {
+        let target_type = self.reference_type(target)?;
+        Ok(match target_type.element_type() {
+            Some(element) => BashType::Array(Box::new(element)),
+            None if matches!(target_type, BashType::Any | BashType::Unknown) => target_type,
+            None => BashType::String,
+        })
+    }
+
+    /// Case modification and substitution work on the text of a value, so they give strings, or
+    /// an array of strings when applied to every element
+    fn string_operation_type(&mut self, target: Node) -> ParseResult<BashType> {
+        let target_type = self.reference_type(target)?;
+        let element_type = target_type.element_type();
+        let operand = element_type.clone().unwrap_or(target_type);
+        if self
+            .config
+            .rule_enabled(codes::STRING_OPERATION, self.config.pedantic)
+            && !matches!(operand, BashType::Any)
+            && !BashType::String.can_contain(&operand)
+        {
+            self.errors.push(
+                Diagnostic::new(
+                    "String operation on a value that isn't a string",
+                    target.start_byte(),
+                )
+                .with_code(codes::STRING_OPERATION)
+                .with_severity(Severity::Warning)
+                .with_label(
+                    Label::new(
+                        target.start_byte()..target.end_byte(),
+                        "Type inferred to be",
+                        LabelKind::Inferred,
+                    )
+                    .with_type(operand),
+                ),
+            );
+        }
+        Ok(match element_type {
+            Some(_) => BashType::Array(Box::new(BashType::String)),
+            None => BashType::String,
+        })
+    }
+
+    /// Types `${!name}`, which expands to the variable whose name is stored in `name`
+    fn indirect_type(&mut self, variable: Node, expansion: Node) -> ParseResult<BashType> {
+        // `${!prefix*}` and `${!prefix@}` list the names of variables instead
+        if expansion
+            .child(3)
+            .is_some_and(|child| matches!(child.kind(), "*" | "@"))
+        {
+            return Ok(BashType::String);
+        }
+        let name = self.node_text(variable)?;
+        let Some(target) = self.literal_values.get(name) else {
+            self.warn_unknown(
+                expansion,
+                "Indirect expansion can't be checked",
+                format!("The value of {name} is only known at runtime"),
+            );
+            return Ok(BashType::Unknown);
+        };
+        Ok(self
+            .variables
+            .get(target)
+            .ok_or_else(|| ParseError {
+                err_type: ParseErrType::UnknownVariable(target.to_owned()),
+                start: expansion.start_byte(),
+                end: expansion.end_byte(),
+            })?
+            .bash_type
+            .clone())
+    }
+}
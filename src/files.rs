@@ -0,0 +1,70 @@
+//! Identifiers for the files a diagnostic can point into, and the sources reports spanning
+//! several of them are printed from
+
+use std::{collections::BTreeMap, fmt};
+
+use ariadne::{Cache, Source};
+use serde::{Deserialize, Serialize};
+
+/// A file of a [`crate::symbols::Workspace`], which labels use to point into another file than
+/// the one their diagnostic is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct FileId(pub usize);
+
+/// The paths and contents of the files a set of reports point into, used as the ariadne cache
+/// when printing them
+#[derive(Default)]
+pub struct Files {
+    files: BTreeMap<FileId, SourceFile>,
+}
+
+struct SourceFile {
+    path: String,
+    source_code: String,
+    /// The lines of the file, found the first time a report is printed from it
+    source: Option<Source<String>>,
+}
+
+impl Files {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, replacing the contents it had before
+    pub fn insert(&mut self, id: FileId, path: impl Into<String>, source_code: impl Into<String>) {
+        self.files.insert(
+            id,
+            SourceFile {
+                path: path.into(),
+                source_code: source_code.into(),
+                source: None,
+            },
+        );
+    }
+
+    pub fn path(&self, id: FileId) -> Option<&str> {
+        self.files.get(&id).map(|file| file.path.as_str())
+    }
+
+    pub fn source_code(&self, id: FileId) -> Option<&str> {
+        self.files.get(&id).map(|file| file.source_code.as_str())
+    }
+}
+
+impl Cache<FileId> for Files {
+    type Storage = String;
+
+    fn fetch(&mut self, id: &FileId) -> Result<&Source, Box<dyn fmt::Debug + '_>> {
+        match self.files.get_mut(id) {
+            Some(file) => Ok(file
+                .source
+                .get_or_insert_with(|| Source::from(file.source_code.clone()))),
+            None => Err(Box::new(format!("No contents for file {}", id.0))),
+        }
+    }
+
+    fn display<'a>(&self, id: &'a FileId) -> Option<Box<dyn fmt::Display + 'a>> {
+        let path = self.files.get(id)?.path.clone();
+        Some(Box::new(path))
+    }
+}
@@ -0,0 +1,180 @@
+//! Changes to the source that resolve a diagnostic, which editors and `--fix` can apply
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::{AnnotationPlacement, BashType, FileInfo};
+
+/// A replacement of part of the source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FixKind {
+    /// Widens an annotation into a union with the type that didn't match it
+    Widen,
+    /// Adds `#[force]` before the statement, skipping its checks
+    Force,
+    /// Removes an annotation that only repeats the inferred type
+    RemoveAnnotation,
+    /// Replaces a misspelled name with the one it is close to
+    Rename,
+    /// Wraps an expansion in double quotes
+    Quote,
+    /// Expands an array as a whole or by element, or a scalar without a subscript
+    Expansion,
+    /// Swaps a comparison operator for the one that compares the operands' type
+    Operator,
+}
+
+/// How safe a fix is to apply without looking at it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Applicability {
+    /// Only adds or corrects type information, keeping what the checks can catch. Applied by
+    /// `--fix`
+    MachineApplicable,
+    /// Probably what was meant, but it may hide a real mistake. Only applied with `--fix-unsafe`
+    #[default]
+    MaybeIncorrect,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fix {
+    pub kind: FixKind,
+    #[serde(default)]
+    pub applicability: Applicability,
+    /// What the fix does, like `Change the annotation to bool | int`
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// Applies `fixes` to the source, skipping any fix that overlaps one earlier in the file. Returns
+/// the new source and how many fixes were applied
+pub fn apply_fixes<'a>(
+    source_code: &str,
+    fixes: impl IntoIterator<Item = &'a Fix>,
+) -> (String, usize) {
+    let mut fixes = fixes
+        .into_iter()
+        .filter(|fix| !fix.edits.is_empty())
+        .collect::<Vec<_>>();
+    fixes.sort_by_key(|fix| fix.edits.iter().map(|edit| edit.range.start).min());
+    let mut edits = Vec::<&TextEdit>::new();
+    let mut applied = 0;
+    for fix in fixes {
+        let overlaps = fix.edits.iter().any(|edit| {
+            edits.iter().any(|other| {
+                edit.range.start < other.range.end && other.range.start < edit.range.end
+            })
+        });
+        if !overlaps {
+            edits.extend(&fix.edits);
+            applied += 1;
+        }
+    }
+    (apply_edits(source_code, edits), applied)
+}
+
+/// Applies edits that don't overlap each other, where insertions at the same offset keep their
+/// order
+pub fn apply_edits<'a>(source_code: &str, edits: impl IntoIterator<Item = &'a TextEdit>) -> String {
+    let mut edits = edits.into_iter().collect::<Vec<_>>();
+    edits.sort_by_key(|edit| edit.range.start);
+    let mut fixed = source_code.to_owned();
+    for edit in edits.into_iter().rev() {
+        fixed.replace_range(edit.range.clone(), &edit.new_text);
+    }
+    fixed
+}
+
+impl FileInfo<'_> {
+    /// How the lines of the file end, so that inserted lines end the same way
+    pub(crate) fn line_ending(&self) -> &'static str {
+        match self.source_code.find('\n') {
+            Some(newline) if self.source_code[..newline].ends_with('\r') => "\r\n",
+            _ => "\n",
+        }
+    }
+
+    /// Rewrites the `#/` annotation at `range` to `widened`. Annotations implied by flags like
+    /// `declare -i` have no comment to rewrite
+    pub(crate) fn widen_fix(&self, range: &Range<usize>, widened: &BashType) -> Option<Fix> {
+        self.source_code[range.clone()]
+            .starts_with("#/")
+            .then(|| Fix {
+                kind: FixKind::Widen,
+                applicability: Applicability::MaybeIncorrect,
+                title: format!("Change the annotation to {widened}"),
+                edits: vec![TextEdit {
+                    range: range.clone(),
+                    new_text: format!("#/ {widened}"),
+                }],
+            })
+    }
+
+    /// Adds `#[force]` on its own line before the statement containing `node`
+    pub(crate) fn force_fix(&self, node: Node) -> Option<Fix> {
+        let statement = node
+            .parent()
+            .filter(|parent| parent.kind() == "declaration_command")
+            .unwrap_or(node);
+        let line_start = self.source_code[..statement.start_byte()]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        let indent = &self.source_code[line_start..statement.start_byte()];
+        if !indent.chars().all(|c| c == ' ' || c == '\t') {
+            return None;
+        }
+        Some(Fix {
+            kind: FixKind::Force,
+            applicability: Applicability::MaybeIncorrect,
+            title: String::from("Skip the checks of this statement with #[force]"),
+            edits: vec![TextEdit {
+                range: line_start..line_start,
+                new_text: format!("{indent}#[force]{}", self.line_ending()),
+            }],
+        })
+    }
+
+    /// Deletes the `#/` annotation at `range`, along with the space before an inline one or the
+    /// whole line of one written before the statement
+    pub(crate) fn remove_annotation_fix(
+        &self,
+        range: &Range<usize>,
+        placement: AnnotationPlacement,
+    ) -> Option<Fix> {
+        let before = &self.source_code[..range.start];
+        let range = match placement {
+            AnnotationPlacement::Inline => before.trim_end_matches([' ', '\t']).len()..range.end,
+            AnnotationPlacement::Preceding => {
+                let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+                if !before[line_start..].chars().all(|c| c == ' ' || c == '\t') {
+                    return None;
+                }
+                let after = &self.source_code[range.end..];
+                let line_end = match after.find('\n') {
+                    Some(newline) if after[..newline].trim_end_matches('\r').is_empty() => {
+                        range.end + newline + 1
+                    }
+                    _ => range.end,
+                };
+                line_start..line_end
+            }
+            AnnotationPlacement::Attribute | AnnotationPlacement::Command => return None,
+        };
+        Some(Fix {
+            kind: FixKind::RemoveAnnotation,
+            applicability: Applicability::MaybeIncorrect,
+            title: String::from("Remove the annotation"),
+            edits: vec![TextEdit {
+                range,
+                new_text: String::new(),
+            }],
+        })
+    }
+}
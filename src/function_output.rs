@@ -0,0 +1,255 @@
+//! Inferring what functions print, so that `port=$(get_port)` gets a type without an annotation
+
+use tree_sitter::Node;
+
+use crate::{
+    bash_integer, codes, literal_type, BashType, Diagnostic, FileInfo, Label, LabelKind,
+    ParseResult, Severity,
+};
+
+/// Commands that don't print anything when they succeed
+const QUIET_COMMANDS: &[&str] = &[
+    ":",
+    "[",
+    "break",
+    "cd",
+    "continue",
+    "exit",
+    "false",
+    "getopts",
+    "let",
+    "mapfile",
+    "read",
+    "readarray",
+    "return",
+    "set",
+    "shift",
+    "test",
+    "trap",
+    "true",
+    "unset",
+    "wait",
+];
+
+impl FileInfo<'_> {
+    /// Adds what a command run inside a function prints to the function's output. Output that is
+    /// captured, piped, redirected or printed just before returning a failure is left out
+    pub(crate) fn record_output(&mut self, command: Node) -> ParseResult<()> {
+        let Some(function) = self.function.clone() else {
+            return Ok(());
+        };
+        if !self.reaches_output(command)? || self.precedes_failure(command)? {
+            return Ok(());
+        }
+        let Some(output) = self.command_output(command)? else {
+            return Ok(());
+        };
+        if let Some(printed) = output
+            .clone()
+            .filter(|printed| *printed != BashType::Unknown)
+        {
+            self.printed
+                .entry(function.clone())
+                .or_default()
+                .push((command.start_byte()..command.end_byte(), printed));
+        }
+        let merged = match self.outputs.remove(&function) {
+            None => output,
+            Some(previous) => previous.zip(output).map(|(a, b)| a.union(b)),
+        };
+        self.outputs.insert(function, merged);
+        Ok(())
+    }
+
+    /// The type of `$(...)` when it runs a function whose output is declared or known, or a
+    /// command whose signature gives its output. In a pipeline, that's the last command. Warns
+    /// about capturing a function that returns a status and doesn't print anything
+    pub(crate) fn substitution_type(&mut self, node: Node) -> ParseResult<BashType> {
+        let command = node
+            .named_child(0)
+            .filter(|_| node.named_child_count() == 1)
+            .and_then(|child| match child.kind() {
+                "pipeline" => child.named_child(child.named_child_count() - 1),
+                _ => Some(child),
+            })
+            .filter(|command| command.kind() == "command");
+        if let Some(name) = command.and_then(|command| command.child_by_field_name("name")) {
+            let name = self.node_text(name)?;
+            if let Some(declaration) = self.functions.get(name) {
+                if let Some(stdout) = &declaration.stdout {
+                    return Ok(stdout.clone());
+                }
+                // A recursive call runs before the rest of the function has printed anything
+                let prints_nothing =
+                    !self.outputs.contains_key(name) && self.function.as_deref() != Some(name);
+                if declaration.returns.is_some() && prints_nothing && !self.force {
+                    self.errors.push(
+                        Diagnostic::new(
+                            format!("{name} returns a status and doesn't print anything"),
+                            node.start_byte(),
+                        )
+                        .with_code(codes::RETURN_MISMATCH)
+                        .with_severity(Severity::Warning)
+                        .with_label(Label::new(
+                            node.start_byte()..node.end_byte(),
+                            "The output captured here is always empty",
+                            LabelKind::Inferred,
+                        ))
+                        .with_help(format!("Check its status with `if {name}; then` instead")),
+                    );
+                    return Ok(BashType::String);
+                }
+            }
+            if let Some(Some(output)) = self.outputs.get(name) {
+                return Ok(output.clone());
+            }
+        }
+        if let Some(output) = command
+            .map(|command| self.registry_output(command))
+            .transpose()?
+            .flatten()
+        {
+            return Ok(output);
+        }
+        self.warn_unknown(
+            node,
+            "Unable to infer the type of command_substitution",
+            "Type inferred to be unknown",
+        );
+        Ok(BashType::Unknown)
+    }
+
+    /// Whether the standard output of `command` is the output of the function it is in
+    fn reaches_output(&self, command: Node) -> ParseResult<bool> {
+        let mut node = command;
+        while let Some(parent) = node.parent() {
+            match parent.kind() {
+                "function_definition" => return Ok(true),
+                "command_substitution" | "process_substitution" => return Ok(false),
+                // Only the last command of a pipeline prints to the function's output
+                "pipeline" if parent.named_child(parent.named_child_count() - 1) != Some(node) => {
+                    return Ok(false)
+                }
+                "redirected_statement" => {
+                    for redirect in parent.children_by_field_name("redirect", &mut parent.walk()) {
+                        let text = self.node_text(redirect)?;
+                        let descriptor = text
+                            .chars()
+                            .take_while(char::is_ascii_digit)
+                            .collect::<String>();
+                        if redirect.kind() == "file_redirect"
+                            && !text[descriptor.len()..].starts_with('<')
+                            && (descriptor.is_empty() || descriptor == "1")
+                        {
+                            return Ok(false);
+                        }
+                    }
+                }
+                _ => (),
+            }
+            node = parent;
+        }
+        Ok(false)
+    }
+
+    /// Whether a later statement next to `command` returns or exits with a literal failure, like
+    /// `echo "missing" ; return 1`
+    fn precedes_failure(&self, command: Node) -> ParseResult<bool> {
+        let mut sibling = command.next_named_sibling();
+        while let Some(statement) = sibling {
+            if statement.kind() == "command" {
+                let name = statement.child_by_field_name("name");
+                let is_ending = name.is_some_and(|name| {
+                    self.node_text(name)
+                        .is_ok_and(|name| matches!(name, "return" | "exit"))
+                });
+                if is_ending {
+                    let status = match statement.child_by_field_name("argument") {
+                        Some(argument) => self.static_text(argument)?.as_deref().map(bash_integer),
+                        None => None,
+                    };
+                    return Ok(matches!(status, Some(Some(status)) if status != 0));
+                }
+            }
+            sibling = statement.next_named_sibling();
+        }
+        Ok(false)
+    }
+
+    /// What `command` prints: `None` for nothing, and `Some(None)` when it isn't known
+    fn command_output(&self, command: Node) -> ParseResult<Option<Option<BashType>>> {
+        let Some(name) = command.child_by_field_name("name") else {
+            return Ok(Some(None));
+        };
+        let name = self.node_text(name)?;
+        let arguments = command
+            .children_by_field_name("argument", &mut command.walk())
+            .collect::<Vec<_>>();
+        Ok(match name {
+            "echo" => {
+                let mut values = arguments.as_slice();
+                while let Some((flag, rest)) = values.split_first() {
+                    let text = self.node_text(*flag)?;
+                    let is_flag = text.len() > 1
+                        && text.starts_with('-')
+                        && text[1..].chars().all(|c| matches!(c, 'n' | 'e' | 'E'));
+                    if !is_flag {
+                        break;
+                    }
+                    values = rest;
+                }
+                Some(Some(match values {
+                    [value] => self.printed_type(*value)?,
+                    _ => BashType::String,
+                }))
+            }
+            "printf" => {
+                let Some((format, values)) = arguments.split_first() else {
+                    return Ok(Some(None));
+                };
+                let Some(format) = self.static_text(*format)? else {
+                    return Ok(Some(Some(BashType::String)));
+                };
+                // `printf -v name` assigns instead of printing
+                if format == "-v" {
+                    return Ok(None);
+                }
+                let format = format
+                    .strip_suffix("\\n")
+                    .or_else(|| format.strip_suffix('\n'))
+                    .unwrap_or(&format);
+                Some(Some(match (format, values) {
+                    ("%d" | "%i", [_]) => BashType::Integer,
+                    ("%s", [value]) => self.printed_type(*value)?,
+                    _ => BashType::String,
+                }))
+            }
+            _ if QUIET_COMMANDS.contains(&name) => None,
+            // Functions that haven't printed anything yet, including the one being defined
+            _ if self.functions.contains_key(name) => self.outputs.get(name).cloned(),
+            _ => Some(self.registry_output(command)?),
+        })
+    }
+
+    /// The type of a value that is printed, without reporting anything about it since the
+    /// arguments of commands aren't checked
+    fn printed_type(&self, value: Node) -> ParseResult<BashType> {
+        Ok(match value.kind() {
+            "number" | "word" => literal_type(self.node_text(value)?),
+            "string" if value.named_child_count() == 1 => {
+                let content = value.named_child(0).expect("named child count to be one");
+                match content.kind() {
+                    "string_content" => BashType::String,
+                    _ => self.printed_type(content)?,
+                }
+            }
+            "simple_expansion" => match value.named_child(0) {
+                Some(variable) => self.variable_type(variable).unwrap_or(BashType::Unknown),
+                None => BashType::Unknown,
+            },
+            "arithmetic_expansion" => BashType::Integer,
+            "string" | "raw_string" | "concatenation" => BashType::String,
+            _ => BashType::Unknown,
+        })
+    }
+}
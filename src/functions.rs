@@ -0,0 +1,521 @@
+use std::ops::Range;
+
+use tree_sitter::Node;
+
+use crate::{
+    arithmetic::is_arithmetic_command, bash_integer, codes, split_list, split_top_level, BashType,
+    Diagnostic, FileInfo, FunctionDeclaration, Label, LabelKind, Parameter, ParseErrType,
+    ParseError, ParseResult, Severity,
+};
+
+/// A call to a function, checked against its `#[params ...]` once every annotation is applied
+pub(crate) struct FunctionCall {
+    name: String,
+    range: Range<usize>,
+    /// How many arguments are passed, unless something like `"$@"` or a glob can expand to any
+    /// number of them
+    arguments: Option<usize>,
+}
+
+impl FileInfo<'_> {
+    /// Records a function, replacing any earlier definition with the same name
+    pub(crate) fn handle_function_definition(&mut self, node: Node) -> ParseResult<()> {
+        let Some(name) = node.child_by_field_name("name") else {
+            return Ok(());
+        };
+        let name = self.node_text(name)?.to_owned();
+        self.outputs.remove(&name);
+        self.printed.remove(&name);
+        // The annotation is checked once the whole file is analyzed, but calls made before then
+        // already need to know what the function returns and prints
+        let (returns, stdout) = match self.returns_annotation(node)?.map(parse_returns) {
+            Some(Ok((status, stdout))) => (
+                status.and_then(|status| status.parse().ok()),
+                stdout.and_then(|stdout| stdout.parse().ok()),
+            ),
+            _ => (None, None),
+        };
+        self.functions.insert(
+            name,
+            FunctionDeclaration {
+                range: node.start_byte()..node.end_byte(),
+                returns,
+                stdout,
+                params: Vec::new(),
+                file: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// The text of a `#[returns ...]` annotation before `function`, allowing other annotations in
+    /// between
+    pub(crate) fn returns_annotation(&self, function: Node) -> ParseResult<Option<&str>> {
+        let mut sibling = function.prev_named_sibling();
+        while let Some(comment) = sibling.filter(|node| node.kind() == "comment") {
+            if let Some(returns) = self
+                .node_text(comment)?
+                .strip_prefix("#[returns ")
+                .and_then(|returns| returns.trim_end().strip_suffix(']'))
+            {
+                return Ok(Some(returns));
+            }
+            sibling = comment.prev_named_sibling();
+        }
+        Ok(None)
+    }
+
+    /// Warns about literal statuses bash can't return, like `exit 300` or `return failed`
+    pub(crate) fn check_exit_status(&mut self, node: Node) -> ParseResult<()> {
+        let Some(argument) = node.child_by_field_name("argument") else {
+            return Ok(());
+        };
+        let Some(literal) = self.static_text(argument)? else {
+            return Ok(());
+        };
+        let label = match bash_integer(&literal) {
+            Some(status) if (0..=255).contains(&status) => return Ok(()),
+            Some(status) => format!("Bash wraps this to {}", status.rem_euclid(256)),
+            None => String::from("Bash only accepts numeric statuses"),
+        };
+        self.errors.push(
+            Diagnostic::new("Exit status out of range", argument.start_byte())
+                .with_code(codes::EXIT_STATUS_RANGE)
+                .with_severity(Severity::Warning)
+                .with_label(Label::new(
+                    argument.start_byte()..argument.end_byte(),
+                    label,
+                    LabelKind::Inferred,
+                )),
+        );
+        Ok(())
+    }
+
+    /// Handles `#[returns int]` before a function, checking every status the function can end
+    /// with
+    pub(crate) fn handle_returns_annotation(
+        &mut self,
+        return_type: &str,
+        comment: Node,
+    ) -> ParseResult<()> {
+        let range = comment.start_byte()..comment.end_byte();
+        let (status, stdout) = parse_returns(return_type).map_err(|reason| ParseError {
+            err_type: ParseErrType::InvalidType(reason),
+            start: range.start,
+            end: range.end,
+        })?;
+        let status = status
+            .map(|status| self.type_from_string(status, range.clone()))
+            .transpose()?;
+        let stdout = stdout
+            .map(|stdout| self.type_from_string(stdout, range.clone()))
+            .transpose()?;
+        let Some(function) = annotated_function(comment) else {
+            return Err(ParseError {
+                err_type: ParseErrType::MisplacedAnnotation(String::from("returns")),
+                start: range.start,
+                end: range.end,
+            });
+        };
+        if let Some(name) = function.child_by_field_name("name") {
+            let name = self.node_text(name)?;
+            if let Some(declaration) = self.functions.get_mut(name) {
+                declaration.returns.clone_from(&status);
+                declaration.stdout.clone_from(&stdout);
+            }
+            if let Some(stdout) = &stdout {
+                self.check_printed_values(name, stdout, &range);
+            }
+        }
+        let Some(expected) = status else {
+            return Ok(());
+        };
+        let Some(body) = function.child_by_field_name("body") else {
+            return Ok(());
+        };
+        for command in self.return_commands(body)? {
+            if let Some(argument) = command.child_by_field_name("argument") {
+                self.check_returned_status(argument, &expected, &range)?;
+            }
+        }
+        // Any other path ends with the status of its last command, which is only known to be a
+        // bool for tests and functions that return bools
+        if expected == BashType::Bool && !self.force {
+            for ending in path_endings(body) {
+                if !self.is_boolean_status(ending)? {
+                    self.errors.push(
+                        Diagnostic::new(
+                            "Function may end with a status that isn't a bool",
+                            ending.start_byte(),
+                        )
+                        .with_code(codes::RETURN_MISMATCH)
+                        .with_severity(Severity::Warning)
+                        .with_label(
+                            Label::new(range.clone(), "Returns declared as", LabelKind::Specified)
+                                .with_type(expected.clone()),
+                        )
+                        .with_label(Label::new(
+                            ending.start_byte()..ending.end_byte(),
+                            "The function returns the status of this command",
+                            LabelKind::Inferred,
+                        )),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reports values a function prints that don't fit the type declared with
+    /// `#[returns stdout: ...]`
+    fn check_printed_values(
+        &mut self,
+        function: &str,
+        expected: &BashType,
+        annotation: &Range<usize>,
+    ) {
+        if self.force {
+            return;
+        }
+        for (range, printed) in self.printed.get(function).cloned().unwrap_or_default() {
+            if expected.can_contain(&printed) {
+                continue;
+            }
+            self.errors.push(
+                Diagnostic::new("Output does not match", range.start)
+                    .with_code(codes::RETURN_MISMATCH)
+                    .with_label(
+                        Label::new(
+                            annotation.clone(),
+                            "Output declared as",
+                            LabelKind::Specified,
+                        )
+                        .with_type(expected.clone()),
+                    )
+                    .with_label(
+                        Label::new(range, "Type inferred to be", LabelKind::Inferred)
+                            .with_type(printed),
+                    ),
+            );
+        }
+    }
+
+    /// Handles `#[params name: type, ...]` before a function, recording its arguments for editors
+    pub(crate) fn handle_params_annotation(
+        &mut self,
+        params: &str,
+        comment: Node,
+    ) -> ParseResult<()> {
+        let range = comment.start_byte()..comment.end_byte();
+        let Some(function) = annotated_function(comment) else {
+            return Err(ParseError {
+                err_type: ParseErrType::MisplacedAnnotation(String::from("params")),
+                start: range.start,
+                end: range.end,
+            });
+        };
+        let parameters = parse_params(params).map_err(|reason| ParseError {
+            err_type: ParseErrType::InvalidType(reason),
+            start: range.start,
+            end: range.end,
+        })?;
+        if let Some(name) = function.child_by_field_name("name") {
+            let name = self.node_text(name)?;
+            if let Some(declaration) = self.functions.get_mut(name) {
+                declaration.params = parameters;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remembers a command that may call a function, to check how many arguments it passes
+    pub(crate) fn record_function_call(&mut self, node: Node) -> ParseResult<()> {
+        let Some(name) = node.child_by_field_name("name") else {
+            return Ok(());
+        };
+        if self.force {
+            return Ok(());
+        }
+        let mut arguments = Some(0);
+        for argument in node.children_by_field_name("argument", &mut node.walk()) {
+            if self.expands_to_any_count(argument)? {
+                arguments = None;
+            }
+            arguments = arguments.map(|count| count + 1);
+        }
+        self.function_calls.push(FunctionCall {
+            name: self.node_text(name)?.to_owned(),
+            range: node.start_byte()..node.end_byte(),
+            arguments,
+        });
+        Ok(())
+    }
+
+    /// Whether an argument can become any number of arguments, like `"$@"`, `"${arr[@]}"`,
+    /// `*.txt` or `{a,b}`
+    fn expands_to_any_count(&self, argument: Node) -> ParseResult<bool> {
+        if matches!(argument.kind(), "word" | "concatenation")
+            && self.node_text(argument)?.contains(['*', '?', '[', '{'])
+        {
+            return Ok(true);
+        }
+        let mut worklist = vec![argument];
+        while let Some(node) = worklist.pop() {
+            let spreads = match node.kind() {
+                "special_variable_name" => matches!(self.node_text(node)?, "@" | "*"),
+                "subscript" => node
+                    .child_by_field_name("index")
+                    .map(|index| self.node_text(index))
+                    .transpose()?
+                    .is_some_and(|index| matches!(index, "@" | "*")),
+                // The output of a command substitution is split too, but usually on purpose
+                "command_substitution" => continue,
+                _ => false,
+            };
+            if spreads {
+                return Ok(true);
+            }
+            worklist.extend(node.named_children(&mut node.walk()));
+        }
+        Ok(false)
+    }
+
+    /// Reports calls that pass fewer arguments than the `#[params ...]` of the function, or
+    /// more when it doesn't end with `...`
+    pub(crate) fn check_function_calls(&mut self) {
+        for call in std::mem::take(&mut self.function_calls) {
+            let (Some(function), Some(count)) = (self.functions.get(&call.name), call.arguments)
+            else {
+                continue;
+            };
+            if function.params.is_empty() {
+                continue;
+            }
+            let variadic = function
+                .params
+                .last()
+                .is_some_and(|param| param.name == "...");
+            let required = function.params.len() - usize::from(variadic);
+            let (message, takes) = if count < required && variadic {
+                (
+                    "Too few arguments",
+                    format!("at least {}", arguments(required)),
+                )
+            } else if count < required {
+                ("Too few arguments", arguments(required))
+            } else if count > required && !variadic {
+                ("Too many arguments", arguments(required))
+            } else {
+                continue;
+            };
+            self.errors.push(
+                Diagnostic::new(format!("{message} to {}", call.name), call.range.start)
+                    .with_code(codes::ARGUMENT_COUNT)
+                    .with_severity(Severity::Warning)
+                    .with_label(Label::new(
+                        call.range,
+                        format!("Called with {}", arguments(count)),
+                        LabelKind::Inferred,
+                    ))
+                    .with_label(Label::new(
+                        function.range.clone(),
+                        format!("{} takes {takes}", call.name),
+                        LabelKind::Specified,
+                    )),
+            );
+        }
+    }
+
+    fn check_returned_status(
+        &mut self,
+        argument: Node,
+        expected: &BashType,
+        annotation: &Range<usize>,
+    ) -> ParseResult<()> {
+        let inferred_type = match self.static_text(argument)?.as_deref().map(bash_integer) {
+            // `return 0` and `return 1` are how bools are returned
+            Some(Some(0 | 1)) if expected.can_contain(&BashType::Bool) => return Ok(()),
+            Some(Some(_)) => BashType::Integer,
+            Some(None) => BashType::String,
+            // Anything that can't be typed, like positional parameters, is left alone
+            None => match self.infer_type(argument) {
+                Ok(inferred_type) => inferred_type,
+                Err(_) => return Ok(()),
+            },
+        };
+        if !expected.can_contain(&inferred_type) && !self.force {
+            self.errors.push(
+                Diagnostic::new("Return status does not match", argument.start_byte())
+                    .with_code(codes::RETURN_MISMATCH)
+                    .with_label(
+                        Label::new(
+                            annotation.clone(),
+                            "Returns declared as",
+                            LabelKind::Specified,
+                        )
+                        .with_type(expected.clone()),
+                    )
+                    .with_label(
+                        Label::new(
+                            argument.start_byte()..argument.end_byte(),
+                            "Type inferred to be",
+                            LabelKind::Inferred,
+                        )
+                        .with_type(inferred_type),
+                    ),
+            );
+        }
+        Ok(())
+    }
+
+    /// Every `return` in a function body, except for those in nested functions
+    pub(crate) fn return_commands<'tree>(
+        &self,
+        body: Node<'tree>,
+    ) -> ParseResult<Vec<Node<'tree>>> {
+        let mut commands = Vec::new();
+        let mut worklist = vec![body];
+        while let Some(node) = worklist.pop() {
+            match node.kind() {
+                "function_definition" => (),
+                "command" => {
+                    let name = node
+                        .child_by_field_name("name")
+                        .map(|name| self.node_text(name))
+                        .transpose()?;
+                    if name == Some("return") {
+                        commands.push(node);
+                    }
+                }
+                _ => worklist.extend(node.named_children(&mut node.walk())),
+            }
+        }
+        Ok(commands)
+    }
+
+    fn is_boolean_status(&self, statement: Node) -> ParseResult<bool> {
+        Ok(match statement.kind() {
+            "test_command" | "negated_command" => true,
+            "command" if is_arithmetic_command(statement) => true,
+            "command" => {
+                let name = statement
+                    .child_by_field_name("name")
+                    .map(|name| self.node_text(name))
+                    .transpose()?;
+                matches!(
+                    name,
+                    Some("return" | "exit" | "true" | "false" | "test" | "[" | ":")
+                ) || name.is_some_and(|name| self.statuses.get(name) == Some(&BashType::Bool))
+            }
+            _ => false,
+        })
+    }
+}
+
+/// The last command run on each path through `statement`
+pub(crate) fn path_endings(statement: Node) -> Vec<Node> {
+    let branches: Vec<Node> = match statement.kind() {
+        "compound_statement" | "subshell" | "else_clause" => {
+            last_statement(statement).into_iter().collect()
+        }
+        "if_statement" | "elif_clause" => {
+            let mut branches: Vec<Node> = then_statement(statement).into_iter().collect();
+            branches.extend(
+                statement
+                    .named_children(&mut statement.walk())
+                    .filter(|child| matches!(child.kind(), "elif_clause" | "else_clause")),
+            );
+            branches
+        }
+        "case_statement" => statement
+            .named_children(&mut statement.walk())
+            .filter(|child| child.kind() == "case_item")
+            .filter_map(last_statement)
+            .collect(),
+        // `a && b` and `a | b` end with the status of `b`
+        "list" | "pipeline" => last_statement(statement).into_iter().collect(),
+        _ => return vec![statement],
+    };
+    branches.into_iter().flat_map(path_endings).collect()
+}
+
+fn last_statement(node: Node) -> Option<Node> {
+    node.named_children(&mut node.walk())
+        .filter(|child| child.kind() != "comment")
+        .last()
+}
+
+/// The last statement after `then` in an `if` or `elif`
+fn then_statement(clause: Node) -> Option<Node> {
+    clause
+        .children(&mut clause.walk())
+        .skip_while(|child| child.kind() != "then")
+        .skip(1)
+        .take_while(|child| !matches!(child.kind(), "elif_clause" | "else_clause" | "fi"))
+        .filter(|child| child.is_named() && child.kind() != "comment")
+        .last()
+}
+
+/// The function an annotation comment is placed before, allowing other annotations in between
+fn annotated_function(comment: Node) -> Option<Node> {
+    let mut sibling = comment.next_named_sibling();
+    while let Some(node) = sibling.filter(|node| node.kind() == "comment") {
+        sibling = node.next_named_sibling();
+    }
+    sibling.filter(|sibling| sibling.kind() == "function_definition")
+}
+
+fn arguments(count: usize) -> String {
+    match count {
+        1 => String::from("1 argument"),
+        count => format!("{count} arguments"),
+    }
+}
+
+/// Splits a `#[returns ...]` annotation into the types of the status and the output. A single
+/// type like `#[returns bool]` is the status, and `#[returns status: bool, stdout: string]` names
+/// either or both
+pub(crate) fn parse_returns(returns: &str) -> Result<(Option<&str>, Option<&str>), String> {
+    let items = split_list(returns);
+    let (mut status, mut stdout) = (None, None);
+    for item in &items {
+        let Some((channel, channel_type)) = split_top_level(item, ':') else {
+            if items.len() == 1 {
+                return Ok((Some(returns.trim()), None));
+            }
+            return Err(format!(
+                "Expected `status: type` or `stdout: type`, found `{}`",
+                item.trim()
+            ));
+        };
+        let declared = match channel.trim() {
+            "status" => &mut status,
+            "stdout" => &mut stdout,
+            other => {
+                return Err(format!(
+                    "Unknown channel `{other}`, expected `status` or `stdout`"
+                ))
+            }
+        };
+        if declared.replace(channel_type.trim()).is_some() {
+            return Err(format!("`{}` is declared more than once", channel.trim()));
+        }
+    }
+    Ok((status, stdout))
+}
+
+/// Parses the `name: type` pairs of a `#[params ...]` annotation
+pub(crate) fn parse_params(params: &str) -> Result<Vec<Parameter>, String> {
+    split_list(params)
+        .into_iter()
+        .map(|param| {
+            let Some((name, param_type)) = param.split_once(':') else {
+                return Err(format!("Expected a type for {}", param.trim()));
+            };
+            Ok(Parameter {
+                name: name.trim().to_owned(),
+                bash_type: param_type.parse()?,
+            })
+        })
+        .collect()
+}
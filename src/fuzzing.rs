@@ -0,0 +1,257 @@
+//! Random scripts for fuzzing the analysis, behind the `arbitrary` feature. [`Script`] builds
+//! scripts that parse, using the constructs the analysis has rules for and annotations drawn at
+//! random, so that a fuzzer spends its time in the analysis rather than on syntax errors
+//!
+//! The `fuzz` directory has `cargo fuzz` targets for these scripts and for raw bytes, which check
+//! with [`crate::testing::check_ranges`] that every reported range is inside the script. Run
+//! them from there with `cargo +nightly fuzz run scripts` or `cargo +nightly fuzz run parse_code`
+
+use std::fmt::Write;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+/// How deep compound statements are nested
+const MAX_DEPTH: usize = 4;
+/// How many statements a block has at most
+const MAX_STATEMENTS: usize = 8;
+
+const NAMES: &[&str] = &["a", "count", "name", "items", "map", "REPLY", "i", "_x1"];
+const FUNCTIONS: &[&str] = &["f", "greet", "sum"];
+const TYPES: &[&str] = &[
+    "int",
+    "integer",
+    "string",
+    "bool",
+    "float",
+    "path",
+    "any",
+    "fd",
+    "array<int>",
+    "array<string>",
+    "map<string, int>",
+    "int | string",
+    "bool | array<int>",
+    "bogus",
+    "array<",
+];
+const VALUES: &[&str] = &[
+    "1",
+    "-42",
+    "0x1f",
+    "08",
+    "3.14",
+    "true",
+    "false",
+    "text",
+    "''",
+    "\"\"",
+    "\"a b\"",
+    "'it''s'",
+    "é",
+    "日本",
+    "$(pwd)",
+    "`date`",
+    "$((1 + 2))",
+    "/tmp/x",
+    "~/file",
+];
+/// Values that can only be assigned
+const ARRAYS: &[&str] = &["(1 2 3)", "(a b)", "()", "([k]=1 [j]=2)"];
+const COMMANDS: &[&str] = &["echo", "printf", "true", ":", "cd", "ls", "cat", "wait"];
+
+/// A bash script that tree-sitter parses without errors, most of the time
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Script(pub String);
+
+impl<'a> Arbitrary<'a> for Script {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut script = String::new();
+        if u.ratio(1, 4)? {
+            script.push_str("#!/bin/bash\n");
+        }
+        block(u, &mut script, 0, "")?;
+        Ok(Self(script))
+    }
+}
+
+fn block(u: &mut Unstructured, out: &mut String, depth: usize, indent: &str) -> Result<()> {
+    let count = u.int_in_range(1..=MAX_STATEMENTS)?;
+    for index in 0..count {
+        if u.is_empty() {
+            // Blocks can't be empty
+            if index == 0 {
+                let _ = writeln!(out, "{indent}:");
+            }
+            break;
+        }
+        statement(u, out, depth, indent)?;
+    }
+    Ok(())
+}
+
+fn statement(u: &mut Unstructured, out: &mut String, depth: usize, indent: &str) -> Result<()> {
+    let nested = format!("{indent}    ");
+    let compound = depth < MAX_DEPTH;
+    match u.int_in_range(0..=if compound { 17 } else { 12 })? {
+        0..=3 => {
+            if u.ratio(1, 4)? {
+                let _ = writeln!(out, "{indent}#/ {}", u.choose(TYPES)?);
+            }
+            let _ = write!(out, "{indent}{}", assignment(u)?);
+            if u.ratio(1, 3)? {
+                let _ = write!(out, " #/ {}", u.choose(TYPES)?);
+            }
+            out.push('\n');
+        }
+        4 => {
+            let flag = u.choose(&["-a", "-A", "-i", "-r", "-x", "-ia", "-g"])?;
+            let keyword = u.choose(&["declare", "local", "readonly", "typeset"])?;
+            let _ = writeln!(out, "{indent}{keyword} {flag} {}", assignment(u)?);
+        }
+        5 => {
+            let name = u.choose(NAMES)?;
+            let _ = match u.int_in_range(0..=3)? {
+                0 => writeln!(out, "{indent}unset {name}"),
+                1 => writeln!(out, "{indent}read -r {name}"),
+                2 => writeln!(out, "{indent}(( {name} += {} ))", u.int_in_range(-9..=99)?),
+                _ => writeln!(out, "{indent}{name}+={}", u.choose(VALUES)?),
+            };
+        }
+        6 => {
+            let _ = writeln!(out, "{indent}{}", command(u)?);
+        }
+        7 => {
+            let operator = u.choose(&["&&", "||", "|"])?;
+            let _ = writeln!(out, "{indent}{} {operator} {}", test(u)?, assignment(u)?);
+        }
+        8 => {
+            let _ = writeln!(out, "{indent}{} &", assignment(u)?);
+        }
+        9 => {
+            let _ = writeln!(
+                out,
+                "{indent}#[set_var({}, {})]\n{indent}{}",
+                u.choose(NAMES)?,
+                u.choose(TYPES)?,
+                command(u)?
+            );
+        }
+        10 => {
+            let _ = writeln!(out, "{indent}#[force]\n{indent}{}", assignment(u)?);
+        }
+        11 => {
+            let _ = writeln!(
+                out,
+                "{indent}cat <<EOF\n{} ${}\nEOF",
+                u.choose(VALUES)?,
+                u.choose(NAMES)?
+            );
+        }
+        12 => {
+            let _ = writeln!(out, "{indent}{}", test(u)?);
+        }
+        13 => {
+            let _ = writeln!(out, "{indent}if {}; then", test(u)?);
+            block(u, out, depth + 1, &nested)?;
+            if u.ratio(1, 2)? {
+                let _ = writeln!(out, "{indent}else");
+                block(u, out, depth + 1, &nested)?;
+            }
+            let _ = writeln!(out, "{indent}fi");
+        }
+        14 => {
+            let values = (0..u.int_in_range(1..=3)?)
+                .map(|_| u.choose(VALUES).map(|value| value.to_string()))
+                .collect::<Result<Vec<_>>>()?;
+            let _ = writeln!(
+                out,
+                "{indent}for {} in {}; do",
+                u.choose(NAMES)?,
+                values.join(" ")
+            );
+            block(u, out, depth + 1, &nested)?;
+            let _ = writeln!(out, "{indent}done");
+        }
+        15 => {
+            let _ = writeln!(out, "{indent}while {}; do", test(u)?);
+            block(u, out, depth + 1, &nested)?;
+            let _ = writeln!(out, "{indent}done");
+        }
+        16 => {
+            let name = u.choose(FUNCTIONS)?;
+            if u.ratio(1, 2)? {
+                let _ = writeln!(
+                    out,
+                    "{indent}#[params {}: {}, ...: {}]",
+                    u.choose(NAMES)?,
+                    u.choose(TYPES)?,
+                    u.choose(TYPES)?
+                );
+            }
+            if u.ratio(1, 3)? {
+                let _ = writeln!(out, "{indent}#[returns {}]", u.choose(TYPES)?);
+            }
+            let _ = writeln!(out, "{indent}{name}() {{");
+            block(u, out, depth + 1, &nested)?;
+            let _ = writeln!(out, "{indent}}}");
+        }
+        _ => {
+            let _ = writeln!(out, "{indent}{{");
+            block(u, out, depth + 1, &nested)?;
+            let _ = writeln!(out, "{indent}}} &");
+        }
+    }
+    Ok(())
+}
+
+fn assignment(u: &mut Unstructured) -> Result<String> {
+    let name = u.choose(NAMES)?;
+    let subscript = u.ratio(1, 5)?;
+    let value = match u.int_in_range(0..=6)? {
+        0 => format!("${}", u.choose(NAMES)?),
+        1 => format!("${{{}:-{}}}", u.choose(NAMES)?, u.choose(VALUES)?),
+        2 => format!("\"${{{}[@]}}\"", u.choose(NAMES)?),
+        3 => format!("$({})", command(u)?),
+        5 if !subscript => u.choose(ARRAYS)?.to_string(),
+        _ => u.choose(VALUES)?.to_string(),
+    };
+    Ok(if subscript {
+        format!("{name}[{}]={value}", u.choose(&["0", "k", "$i", "@"])?)
+    } else {
+        format!("{name}={value}")
+    })
+}
+
+fn command(u: &mut Unstructured) -> Result<String> {
+    let name = if u.ratio(1, 4)? {
+        u.choose(FUNCTIONS)?
+    } else {
+        u.choose(COMMANDS)?
+    };
+    let arguments = (0..u.int_in_range(0..=3)?)
+        .map(|_| {
+            Ok(if u.ratio(1, 2)? {
+                format!("\"${}\"", u.choose(NAMES)?)
+            } else {
+                u.choose(VALUES)?.to_string()
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(format!("{name} {}", arguments.join(" "))
+        .trim_end()
+        .to_owned())
+}
+
+fn test(u: &mut Unstructured) -> Result<String> {
+    let left = u.choose(NAMES)?;
+    let right = u.choose(VALUES)?;
+    Ok(match u.int_in_range(0..=3)? {
+        0 => format!(
+            "[[ ${left} {} {right} ]]",
+            u.choose(&["==", "<", ">", "-eq", "-lt"])?
+        ),
+        1 => format!("(( {left} {} 1 ))", u.choose(&["<", "==", "+", "&&"])?),
+        2 => format!("[ -n \"${left}\" ]"),
+        _ => command(u)?,
+    })
+}
@@ -0,0 +1,206 @@
+//! Which variables and functions the type of each variable depends on
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt::Write,
+};
+
+use tree_sitter::Node;
+
+use crate::FileInfo;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Symbol {
+    Variable(String),
+    Function(String),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// Every symbol in the graph with the text to show for it
+    pub nodes: BTreeMap<Symbol, String>,
+    /// `(dependent, dependency)` pairs, like `(a, b)` for `a=$b`
+    pub edges: BTreeSet<(Symbol, Symbol)>,
+}
+
+impl DependencyGraph {
+    /// A Graphviz graph with an edge from each variable or function to the ones it uses
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph bashtyped {\n");
+        for (symbol, label) in &self.nodes {
+            let shape = match symbol {
+                Symbol::Variable(_) => "ellipse",
+                Symbol::Function(_) => "box",
+            };
+            writeln!(
+                dot,
+                "  {} [shape={shape}, label={}];",
+                dot_id(symbol),
+                quote(label)
+            )
+            .unwrap();
+        }
+        for (dependent, dependency) in &self.edges {
+            writeln!(dot, "  {} -> {};", dot_id(dependent), dot_id(dependency)).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn add_edge(&mut self, dependent: Symbol, dependency: Symbol) {
+        if dependent != dependency {
+            self.edges.insert((dependent, dependency));
+        }
+    }
+}
+
+fn dot_id(symbol: &Symbol) -> String {
+    match symbol {
+        Symbol::Variable(name) => quote(name),
+        Symbol::Function(name) => quote(&format!("{name}()")),
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl FileInfo<'_> {
+    /// Finds the dependencies of every assignment, loop and function in the file. Variables are
+    /// labeled with their types, so this is most useful after [`FileInfo::parse_code`]
+    pub fn dependency_graph(&mut self) -> DependencyGraph {
+        let tree = self.parse_tree();
+        let root = tree.root_node();
+        let mut definitions = Vec::new();
+        let mut worklist = vec![root];
+        while let Some(node) = worklist.pop() {
+            match node.kind() {
+                "variable_assignment" => {
+                    if let (Some(name), Some(value)) = (
+                        node.child_by_field_name("name"),
+                        node.child_by_field_name("value"),
+                    ) {
+                        let target = match name.kind() {
+                            "subscript" => name.child_by_field_name("name").unwrap_or(name),
+                            _ => name,
+                        };
+                        definitions.push((target, vec![value]));
+                    }
+                }
+                "for_statement" => {
+                    if let Some(variable) = node.child_by_field_name("variable") {
+                        let values = node
+                            .children_by_field_name("value", &mut node.walk())
+                            .collect();
+                        definitions.push((variable, values));
+                    }
+                }
+                "function_definition" => {
+                    if let (Some(name), Some(body)) = (
+                        node.child_by_field_name("name"),
+                        node.child_by_field_name("body"),
+                    ) {
+                        definitions.push((name, vec![body]));
+                    }
+                }
+                _ => (),
+            }
+            worklist.extend(node.named_children(&mut node.walk()));
+        }
+
+        let functions = definitions
+            .iter()
+            .filter(|(name, _)| name.parent().unwrap_or(root).kind() == "function_definition")
+            .filter_map(|(name, _)| self.node_text(*name).ok())
+            .collect::<HashSet<_>>();
+        let mut graph = DependencyGraph::default();
+        for (name, values) in definitions {
+            let Ok(text) = self.node_text(name) else {
+                continue;
+            };
+            let dependent = self.add_symbol(&mut graph, name, text);
+            // The names being assigned inside a function aren't dependencies of it
+            let mut assigned = HashSet::new();
+            for value in &values {
+                for (reference, is_command) in references(*value, &mut assigned) {
+                    let Ok(reference_text) = self.node_text(reference) else {
+                        continue;
+                    };
+                    let dependency = if is_command {
+                        if !functions.contains(reference_text) {
+                            continue;
+                        }
+                        Symbol::Function(reference_text.to_owned())
+                    } else {
+                        Symbol::Variable(reference_text.to_owned())
+                    };
+                    graph
+                        .nodes
+                        .entry(dependency.clone())
+                        .or_insert_with(|| self.symbol_label(&dependency));
+                    graph.add_edge(dependent.clone(), dependency);
+                }
+            }
+        }
+        graph
+    }
+
+    fn add_symbol(&self, graph: &mut DependencyGraph, name: Node, text: &str) -> Symbol {
+        let symbol = if name
+            .parent()
+            .is_some_and(|parent| parent.kind() == "function_definition")
+        {
+            Symbol::Function(text.to_owned())
+        } else {
+            Symbol::Variable(text.to_owned())
+        };
+        graph
+            .nodes
+            .entry(symbol.clone())
+            .or_insert_with(|| self.symbol_label(&symbol));
+        symbol
+    }
+
+    fn symbol_label(&self, symbol: &Symbol) -> String {
+        match symbol {
+            Symbol::Variable(name) => match self.variables.get(name) {
+                Some(declaration) => format!("{name}: {}", declaration.bash_type),
+                None => name.clone(),
+            },
+            Symbol::Function(name) => format!("{name}()"),
+        }
+    }
+}
+
+/// The variables read and commands run inside a node, with `true` for commands
+fn references<'tree>(node: Node<'tree>, assigned: &mut HashSet<usize>) -> Vec<(Node<'tree>, bool)> {
+    let mut references = Vec::new();
+    let mut worklist = vec![node];
+    while let Some(node) = worklist.pop() {
+        match node.kind() {
+            "variable_name" if !assigned.contains(&node.id()) => references.push((node, false)),
+            "command_name" => references.push((node, true)),
+            "variable_assignment" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    let target = match name.kind() {
+                        "subscript" => name.child_by_field_name("name").unwrap_or(name),
+                        _ => name,
+                    };
+                    assigned.insert(target.id());
+                }
+            }
+            "for_statement" => {
+                if let Some(variable) = node.child_by_field_name("variable") {
+                    assigned.insert(variable.id());
+                }
+            }
+            _ => (),
+        }
+        // Children are visited after their parent, so assigned names are known before they are
+        // reached
+        let mut children = node.named_children(&mut node.walk()).collect::<Vec<_>>();
+        children.reverse();
+        worklist.extend(children);
+    }
+    references
+}
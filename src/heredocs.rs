@@ -0,0 +1,206 @@
+//! Heredocs, which can be annotated with the language of their body as in `#/ heredoc(json)`.
+//! Values interpolated into an annotated heredoc have to be single values, and the body and its
+//! language are kept so other tools can check it
+
+use std::{fmt::Display, ops::Range, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::{
+    arrays::is_container, codes, Comment, Diagnostic, FileInfo, Label, LabelKind, ParseResult,
+    Severity,
+};
+
+/// A heredoc passed to a command
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Heredoc {
+    /// The body, from the line after `<<` up to the delimiter
+    pub range: Range<usize>,
+    /// The language given by a `#/ heredoc(...)` annotation
+    pub language: Option<HeredocLanguage>,
+    /// Whether variables and commands in the body are expanded, which they aren't when the
+    /// delimiter is quoted as in `<<'EOF'`
+    pub expands: bool,
+}
+
+/// The language of a heredoc's body
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeredocLanguage {
+    Json,
+    Yaml,
+    Sql,
+}
+
+impl Display for HeredocLanguage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Sql => "sql",
+        })
+    }
+}
+
+impl FromStr for HeredocLanguage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "json" => Ok(Self::Json),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "sql" => Ok(Self::Sql),
+            other => Err(format!(
+                "Unknown heredoc language {other}, expected json, yaml or sql"
+            )),
+        }
+    }
+}
+
+/// The language named by the text of a `#/ heredoc(json)` annotation, if it is one
+pub(crate) fn heredoc_annotation(text: &str) -> Option<&str> {
+    text.strip_prefix("heredoc(")?.strip_suffix(')')
+}
+
+impl FileInfo<'_> {
+    /// Records the heredocs of a statement, with the language from the annotation on the line
+    /// before it or on the line with `<<`, and checks what is interpolated into them
+    pub(crate) fn handle_heredocs(
+        &mut self,
+        statement: Node,
+        annotation: Option<Comment>,
+    ) -> ParseResult<()> {
+        let redirects = statement
+            .children_by_field_name("redirect", &mut statement.walk())
+            .filter(|redirect| redirect.kind() == "heredoc_redirect")
+            .collect::<Vec<_>>();
+        for redirect in redirects {
+            let Some(body) = redirect
+                .named_children(&mut redirect.walk())
+                .find(|child| child.kind() == "heredoc_body")
+            else {
+                continue;
+            };
+            let range = body.start_byte()..body.end_byte();
+            if self.heredocs.iter().any(|heredoc| heredoc.range == range) {
+                continue;
+            }
+            let inline = redirect
+                .named_children(&mut redirect.walk())
+                .filter(|child| child.kind() == "comment")
+                .map(|comment| self.handle_comment(comment))
+                .collect::<ParseResult<Vec<_>>>()?
+                .into_iter()
+                .flatten()
+                .find(|comment| heredoc_annotation(&comment.text).is_some());
+            let language = match inline.as_ref().or(annotation.as_ref()) {
+                Some(comment) => self.heredoc_language(comment),
+                None => None,
+            };
+            let expands = redirect
+                .named_children(&mut redirect.walk())
+                .find(|child| child.kind() == "heredoc_start")
+                .map(|start| self.node_text(start))
+                .transpose()?
+                .is_some_and(|delimiter| !delimiter.contains(['\'', '"', '\\']));
+            let oversized = self.is_oversized_heredoc(body);
+            if oversized {
+                self.warn_unknown(
+                    body,
+                    "Heredoc is too large to check",
+                    "This body is larger than `max_heredoc_size`",
+                );
+            }
+            if let Some(language) = language.filter(|_| expands && !self.force && !oversized) {
+                self.check_interpolations(body, language)?;
+            }
+            self.heredocs.push(Heredoc {
+                range,
+                language,
+                expands,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether the body of a heredoc is larger than the `max_heredoc_size` of the config, so
+    /// nothing in it is checked
+    pub(crate) fn is_oversized_heredoc(&self, body: Node) -> bool {
+        self.config
+            .max_heredoc_size
+            .is_some_and(|kib| body.byte_range().len() > kib.saturating_mul(1024))
+    }
+
+    /// Parses the language of a `#/ heredoc(...)` annotation, reporting languages that aren't
+    /// known
+    fn heredoc_language(&mut self, comment: &Comment) -> Option<HeredocLanguage> {
+        let language = heredoc_annotation(&comment.text)?;
+        match language.parse() {
+            Ok(language) => Some(language),
+            Err(message) => {
+                if !self.force {
+                    self.errors.push(
+                        Diagnostic::new(message, comment.range.start)
+                            .with_code(codes::INVALID_ANNOTATION)
+                            .with_label(Label::new(
+                                comment.text_range.clone(),
+                                "Annotation used here",
+                                LabelKind::ParseErr,
+                            )),
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    /// Reports arrays and maps expanded as a whole into the body of a heredoc, which joins their
+    /// elements with spaces instead of writing them in the heredoc's language. `$arr` on its own
+    /// is already reported for only using the first element
+    fn check_interpolations(&mut self, body: Node, language: HeredocLanguage) -> ParseResult<()> {
+        let expansions = body
+            .named_children(&mut body.walk())
+            .filter(|child| matches!(child.kind(), "simple_expansion" | "expansion"))
+            .filter(|expansion| {
+                expansion
+                    .named_child(0)
+                    .is_some_and(|target| target.kind() != "variable_name")
+            })
+            .collect::<Vec<_>>();
+        for expansion in expansions {
+            // Unknown variables aren't reported in heredocs
+            let Ok(bash_type) = self.infer_type(expansion) else {
+                continue;
+            };
+            if !is_container(&bash_type) {
+                continue;
+            }
+            let range = expansion.start_byte()..expansion.end_byte();
+            self.errors.push(
+                Diagnostic::new(
+                    format!(
+                        "{} interpolated into a {language} heredoc",
+                        self.node_text(expansion)?
+                    ),
+                    range.start,
+                )
+                .with_code(codes::HEREDOC_INTERPOLATION)
+                .with_severity(Severity::Warning)
+                .with_label(
+                    Label::new(
+                        range,
+                        "Its elements are joined with spaces, since it is",
+                        LabelKind::Inferred,
+                    )
+                    .with_type(bash_type),
+                )
+                .with_help(format!(
+                    "Format the elements as {language} before writing them, for example with \
+                     `printf` or `jq`"
+                )),
+            );
+        }
+        Ok(())
+    }
+}
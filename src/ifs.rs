@@ -0,0 +1,214 @@
+//! `IFS`, which decides how unquoted expansions and `read` split their input, and the prefix
+//! assignments like `IFS= read -r line` that only set it for one command
+
+use tree_sitter::Node;
+
+use crate::{
+    arguments::READ_OPTIONS_WITH_ARGUMENT, codes, BashType, Diagnostic, FileInfo, Label, LabelKind,
+    Method, ParseResult, Severity, TypeDeclaration,
+};
+
+impl FileInfo<'_> {
+    /// Handles `name=value` before a command, which only sets `name` while the command runs. The
+    /// value is still checked, but the variable keeps its type and value afterwards
+    pub(crate) fn handle_prefix_assignment(&mut self, node: Node) -> ParseResult<()> {
+        if let Some(value) = node.child_by_field_name("value") {
+            self.infer_type(value)?;
+        }
+        Ok(())
+    }
+
+    /// Declares the variables `read` assigns as strings, or an array of strings with `-a`. With
+    /// no names, the line goes to `REPLY`. Variables that already have a type keep it
+    pub(crate) fn handle_read(&mut self, node: Node) -> ParseResult<()> {
+        let mut names = Vec::new();
+        let mut array = None;
+        let arguments = node
+            .children_by_field_name("argument", &mut node.walk())
+            .collect::<Vec<_>>();
+        let mut arguments = arguments.into_iter();
+        let mut options_done = false;
+        while let Some(argument) = arguments.next() {
+            let Some(text) = self.static_text(argument)? else {
+                // A name only known at runtime could be any variable
+                if options_done || !self.node_text(argument)?.starts_with('-') {
+                    return Ok(());
+                }
+                continue;
+            };
+            if options_done || !text.starts_with('-') || text == "-" {
+                options_done = true;
+                names.push(text);
+                continue;
+            }
+            if text == "--" {
+                options_done = true;
+                continue;
+            }
+            let flags = &text[1..];
+            let Some(position) = flags.find(READ_OPTIONS_WITH_ARGUMENT) else {
+                continue;
+            };
+            // The argument is either the rest of the word, as in `-d,`, or the next one
+            let joined = &flags[position + 1..];
+            let value = if joined.is_empty() {
+                match arguments.next() {
+                    Some(value) => self.static_text(value)?,
+                    None => None,
+                }
+            } else {
+                Some(joined.to_owned())
+            };
+            if flags[position..].starts_with('a') {
+                let Some(value) = value else {
+                    return Ok(());
+                };
+                array = Some(value);
+            }
+        }
+        if names.is_empty() && array.is_none() {
+            names.push(String::from("REPLY"));
+        }
+        let declarations = names
+            .into_iter()
+            .map(|name| (name, BashType::String))
+            .chain(array.map(|name| (name, BashType::Array(Box::new(BashType::String)))));
+        for (name, bash_type) in declarations {
+            let Some(name) = self.resolve_name(&name).map(str::to_owned) else {
+                continue;
+            };
+            if self.variables.contains_key(&name) {
+                continue;
+            }
+            let declaration = TypeDeclaration {
+                bash_type,
+                range: node.start_byte()..node.end_byte(),
+                method: Method::Inferred,
+                node_kind: node.kind().to_owned(),
+                annotation: None,
+                file: None,
+            };
+            self.set_variable(&name, declaration, node);
+        }
+        Ok(())
+    }
+
+    /// Keeps track of changes to `IFS` that outlive the statement making them, and of the
+    /// assignments or `unset IFS` that restore it
+    pub(crate) fn track_ifs(&mut self, node: Node) -> ParseResult<()> {
+        let restores = match node.kind() {
+            "variable_assignment" => {
+                let Some(name) = node
+                    .child_by_field_name("name")
+                    .filter(|name| name.kind() == "variable_name")
+                else {
+                    return Ok(());
+                };
+                if self.node_text(name)? != "IFS" {
+                    return Ok(());
+                }
+                match node.child_by_field_name("value") {
+                    Some(value) => self.restores_ifs(value)?,
+                    None => false,
+                }
+            }
+            "unset_command" => {
+                let unsets_ifs = node
+                    .named_children(&mut node.walk())
+                    .filter(|child| child.kind() == "variable_name")
+                    .any(|child| self.node_text(child).is_ok_and(|name| name == "IFS"));
+                if !unsets_ifs {
+                    return Ok(());
+                }
+                true
+            }
+            _ => return Ok(()),
+        };
+        if self.is_local("IFS") || in_subshell(node) {
+            return Ok(());
+        }
+        let scope = self.function.clone();
+        if restores {
+            self.ifs_changes.remove(&scope);
+        } else {
+            self.ifs_changes
+                .entry(scope)
+                .or_insert(node.start_byte()..node.end_byte());
+        }
+        Ok(())
+    }
+
+    /// Whether assigning `value` to `IFS` puts back an earlier value, as in `IFS="$old_ifs"`, or
+    /// the default `$' \t\n'`
+    fn restores_ifs(&self, value: Node) -> ParseResult<bool> {
+        let expansion = match value.kind() {
+            "string" if value.named_child_count() == 1 => {
+                value.named_child(0).expect("child count to be one")
+            }
+            _ => value,
+        };
+        Ok(match expansion.kind() {
+            "simple_expansion" | "expansion" => expansion.named_child_count() == 1,
+            "ansi_c_string" => self.node_text(expansion)? == "$' \\t\\n'",
+            _ => false,
+        })
+    }
+
+    /// Warns about changes to `IFS` that are never restored. A change in a function leaks into
+    /// whatever calls it, while one at the top of a script is usually meant to last, so those
+    /// are only reported in pedantic mode
+    pub(crate) fn check_ifs_restored(&mut self) {
+        let mut changes = std::mem::take(&mut self.ifs_changes)
+            .into_iter()
+            .collect::<Vec<_>>();
+        changes.sort_by_key(|(_, range)| range.start);
+        for (function, range) in changes {
+            let (label, help) = match &function {
+                Some(function) => (
+                    format!("Still set after {function} returns"),
+                    format!(
+                        "Declare it with `local IFS` in {function}, or set it only for one \
+                         command as in `IFS=, read -r ...`"
+                    ),
+                ),
+                None if self
+                    .config
+                    .rule_enabled(codes::UNRESTORED_IFS, self.config.pedantic) =>
+                {
+                    (
+                        String::from("Every later unquoted expansion is split with this"),
+                        String::from(
+                            "Set it only for one command as in `IFS=, read -r ...`, or save it \
+                             and assign it back afterwards",
+                        ),
+                    )
+                }
+                None => continue,
+            };
+            self.errors.push(
+                Diagnostic::new("IFS is changed without being restored", range.start)
+                    .with_code(codes::UNRESTORED_IFS)
+                    .with_severity(Severity::Warning)
+                    .with_label(Label::new(range, label, LabelKind::Inferred))
+                    .with_help(help),
+            );
+        }
+    }
+}
+
+/// Whether `node` runs in a subshell of the function or script it is in, so that its changes to
+/// variables are thrown away
+fn in_subshell(node: Node) -> bool {
+    let mut node = node;
+    while let Some(parent) = node.parent() {
+        match parent.kind() {
+            "function_definition" => return false,
+            "subshell" | "command_substitution" | "process_substitution" | "pipeline" => {
+                return true
+            }
+            _ => (),
+        }
+        node = parent;
+    }
+    false
+}
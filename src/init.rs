@@ -0,0 +1,146 @@
+//! A starting `bashtyped.toml` for a repository, filled in from the scripts it contains
+
+use std::{fmt::Write, io, path::Path};
+
+use crate::{
+    encoding::DecodedSource,
+    scan::{find_scripts, script_shell, IgnoreRules},
+};
+
+/// Directories that usually hold code from elsewhere, suggested as ignore patterns when they exist
+const VENDORED_DIRECTORIES: &[&str] = &[
+    "vendor",
+    "third_party",
+    "third-party",
+    "external",
+    "node_modules",
+    "deps",
+];
+
+/// What was found in a repository to base its configuration on
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Detected {
+    /// Scripts found outside the ignored directories
+    pub scripts: usize,
+    /// Scripts that already contain `#/` annotations
+    pub annotated: usize,
+    pub bash_scripts: usize,
+    pub zsh_scripts: usize,
+    pub ignore: Vec<String>,
+}
+
+impl Detected {
+    pub fn new(root: &Path) -> io::Result<Self> {
+        let mut detected = Self::default();
+        for directory in VENDORED_DIRECTORIES {
+            if root.join(directory).is_dir() {
+                detected.ignore.push(format!("{directory}/"));
+            }
+        }
+        let rules = IgnoreRules::new(&detected.ignore);
+        for script in find_scripts(&[root.to_path_buf()], &rules, false)? {
+            detected.scripts += 1;
+            match script_shell(&script) {
+                Some("zsh") => detected.zsh_scripts += 1,
+                _ => detected.bash_scripts += 1,
+            }
+            let contents = DecodedSource::read(&script)
+                .map(|decoded| decoded.source_code)
+                .unwrap_or_default();
+            if contents
+                .lines()
+                .any(|line| line.trim_start().starts_with("#/") || line.contains(" #/ "))
+            {
+                detected.annotated += 1;
+            }
+        }
+        Ok(detected)
+    }
+
+    /// A commented config file, with the settings that weren't detected left at their defaults
+    pub fn config_file(&self) -> String {
+        let mut config =
+            String::from("# Configuration for bashtyped, created by `bashtyped init`\n");
+        write!(
+            config,
+            "#\n# Variables are annotated with `#/ type` comments, like `count=0 #/ int`. {} of the \
+             {} scripts\n# found use them so far.\n\n",
+            self.annotated, self.scripts
+        )
+        .unwrap();
+
+        config.push_str("# The shell the scripts are written for\n");
+        if cfg!(feature = "zsh") && self.zsh_scripts > self.bash_scripts {
+            config.push_str("dialect = \"zsh\"\n\n");
+        } else {
+            config.push_str("dialect = \"bash\"\n\n");
+        }
+
+        config.push_str(
+            "# Oldest bash release the scripts have to run on. Features added after it are \
+             reported\n# bash_version = \"4.4\"\n\n",
+        );
+        config.push_str(
+            "# Also report code that is probably fine but can't be checked\npedantic = false\n\n",
+        );
+        config.push_str(
+            "# Let assigning one type of a union, like `x=5` for an `int | string`, narrow the \
+             type\n# until the next assignment\nnarrow_on_assignment = false\n\n",
+        );
+        config.push_str(
+            "# Whether quoted numbers like \"5\" can be used as ints: \"forbid\", \"hint\" or \
+             \"allow\"\n# numeric_strings = \"forbid\"\n\n",
+        );
+        config.push_str(
+            "# Let an annotation apply to the statement after it even when blank lines separate \
+             them\ndetached_annotations = false\n\n",
+        );
+        config.push_str(
+            "# Report commands that aren't functions, builtins or programs on PATH, instead of \
+             only\n# misspelled function names\ncheck_path = false\n\n",
+        );
+
+        config.push_str(
+            "# Where `bashtyped annotate` puts annotations: \"preserve\", \"inline\" or \"above\"\n\
+             # annotation_style = \"preserve\"\n\n",
+        );
+        config.push_str(
+            "# For huge generated scripts, the MiB the syntax tree of a file may take, which has \
+             files\n# analyzed in chunks, and the KiB a heredoc body may have before it isn't \
+             checked\n# memory_limit = 512\n# max_heredoc_size = 1024\n\n",
+        );
+
+        config.push_str(
+            "# Paths to skip when searching directories for scripts, with the same syntax as \
+             .gitignore\n",
+        );
+        if self.ignore.is_empty() {
+            config.push_str("ignore = []\n\n");
+        } else {
+            config.push_str("ignore = [\n");
+            for pattern in &self.ignore {
+                writeln!(config, "    \"{pattern}\",").unwrap();
+            }
+            config.push_str("]\n\n");
+        }
+
+        config.push_str(
+            "# Settings for single rules, by code or name like `bashtyped explain` shows. Rules \
+             can be\n# turned off or on, given another severity, and for the rules about \
+             variables, told which\n# names to allow\n# [rules.BT0004]\n# enabled = true\n\
+             # severity = \"warning\"\n# allow = [\"CI_*\"]\n\n",
+        );
+
+        config.push_str(
+            "# Colors for reports: \"default\" or \"colorblind\", with single colors set in a \
+             [colors] table\n# theme = \"default\"\n\n",
+        );
+
+        config.push_str(
+            "# Settings that override the ones above with `--profile ci`, like stricter checks \
+             and\n# another output format\n# [profile.ci]\n# pedantic = true\n\
+             # format = \"short\"\n",
+        );
+        config
+    }
+}
@@ -0,0 +1,230 @@
+//! Background jobs started with `&`, whose process ID is read from `$!` and passed to `wait` or
+//! `kill`. They run in a subshell, so what they assign isn't seen by the rest of the script
+
+use tree_sitter::Node;
+
+use crate::{
+    codes,
+    trace::{MergeReason, TypeMerge},
+    BashType, BashVersion, Diagnostic, FileInfo, Label, LabelKind, Method, ParseResult, Severity,
+    TypeDeclaration,
+};
+
+/// Statements run in another process, like a job started with `&`. What they change is undone
+/// once the analysis moves past them
+pub(crate) struct BackgroundJob {
+    end: usize,
+    /// Variables assigned in the job, with their declarations from before it
+    assigned: Vec<(String, Option<TypeDeclaration>)>,
+    /// Whether reading what the job assigned is reported afterwards. Nothing after a BATS test
+    /// can expect to see its variables
+    reported: bool,
+}
+
+impl FileInfo<'_> {
+    /// Starts analyzing `statement`, which is followed by `&`
+    pub(crate) fn start_background_job(&mut self, statement: Node) -> ParseResult<()> {
+        self.start_subshell(&[statement], statement.end_byte(), true)
+    }
+
+    /// Starts analyzing `statements`, which run in another process until `end`
+    pub(crate) fn start_subshell(
+        &mut self,
+        statements: &[Node],
+        end: usize,
+        reported: bool,
+    ) -> ParseResult<()> {
+        let mut job = BackgroundJob {
+            end,
+            assigned: Vec::new(),
+            reported,
+        };
+        for statement in statements {
+            for name in self.assigned_names(*statement)? {
+                if job.assigned.iter().all(|(other, _)| *other != name) {
+                    let previous = self.variables.get(&name).cloned();
+                    job.assigned.push((name, previous));
+                }
+            }
+        }
+        self.background_jobs.push(job);
+        Ok(())
+    }
+
+    /// Finishes the background jobs that end before `offset`, restoring the variables they
+    /// assigned. Reading one of them afterwards is reported until it is assigned again
+    pub(crate) fn end_background_jobs(&mut self, offset: usize) {
+        while let Some(job) = self.background_jobs.pop_if(|job| job.end <= offset) {
+            for (name, previous) in job.assigned {
+                match previous {
+                    Some(previous) => {
+                        self.merges.push(TypeMerge {
+                            name: name.clone(),
+                            offset: job.end,
+                            bash_type: previous.bash_type.clone(),
+                            reason: MergeReason::Subshell,
+                        });
+                        self.variables.insert(name.clone(), previous)
+                    }
+                    None => self.variables.remove(&name),
+                };
+                self.narrowed.remove(&name);
+                self.literal_values.remove(&name);
+                if job.reported {
+                    self.background_assignments.insert(name);
+                }
+            }
+        }
+    }
+
+    /// Warns about `$!` being read before anything in the function or script could have started a
+    /// background job. Calling a function counts, since it may start one
+    pub(crate) fn check_job_read(&mut self, expansion: Node) -> ParseResult<()> {
+        let is_job = expansion.kind() == "simple_expansion"
+            && expansion.named_child(0).is_some_and(|child| {
+                child.kind() == "special_variable_name"
+                    && self.node_text(child).is_ok_and(|name| name == "!")
+            });
+        if !is_job || self.force {
+            return Ok(());
+        }
+        let mut node = expansion;
+        while let Some(parent) = node.parent() {
+            let mut previous = node.prev_sibling();
+            while let Some(sibling) = previous {
+                if self.may_start_job(sibling)? {
+                    return Ok(());
+                }
+                previous = sibling.prev_sibling();
+            }
+            if parent.kind() == "function_definition" {
+                break;
+            }
+            node = parent;
+        }
+        self.errors.push(
+            Diagnostic::new(
+                "$! read before any background job started",
+                expansion.start_byte(),
+            )
+            .with_code(codes::NO_BACKGROUND_JOB)
+            .with_severity(Severity::Warning)
+            .with_label(Label::new(
+                expansion.start_byte()..expansion.end_byte(),
+                "Nothing has been run with `&` before this in the current block",
+                LabelKind::Inferred,
+            ))
+            .with_help("Start the job first and save its ID right away, as in `cmd & pid=$!`"),
+        );
+        Ok(())
+    }
+
+    /// Whether running `node` may start a background job: it ends in `&`, starts a coprocess or
+    /// calls a function, which could. Without knowing the functions of sourced files, any command could be one
+    fn may_start_job(&self, node: Node) -> ParseResult<bool> {
+        match node.kind() {
+            "&" => return Ok(true),
+            // Defining a function doesn't run it
+            "function_definition" => return Ok(false),
+            "command" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    let name = self.node_text(name)?;
+                    if name == "coproc"
+                        || self.functions.contains_key(name)
+                        || !self.sources.is_empty()
+                    {
+                        return Ok(true);
+                    }
+                }
+            }
+            _ => (),
+        }
+        for child in node.children(&mut node.walk()) {
+            if self.may_start_job(child)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Declares the variable `wait -p name` assigns the process ID of the job that finished to
+    pub(crate) fn handle_wait(&mut self, node: Node) -> ParseResult<()> {
+        let arguments = node
+            .children_by_field_name("argument", &mut node.walk())
+            .collect::<Vec<_>>();
+        let Some(position) = arguments
+            .iter()
+            .position(|argument| self.node_text(*argument).is_ok_and(|text| text == "-p"))
+        else {
+            return Ok(());
+        };
+        let Some(name) = arguments
+            .get(position + 1)
+            .map(|name| self.static_text(*name))
+            .transpose()?
+            .flatten()
+        else {
+            return Ok(());
+        };
+        let Some(name) = self.resolve_name(&name).map(str::to_owned) else {
+            return Ok(());
+        };
+        self.require_bash_version("wait -p", BashVersion::new(5, 1), node);
+        let declaration = TypeDeclaration {
+            bash_type: BashType::Integer,
+            range: node.start_byte()..node.end_byte(),
+            method: Method::Inferred,
+            node_kind: node.kind().to_owned(),
+            annotation: None,
+            file: None,
+        };
+        self.set_variable(&name, declaration, node);
+        Ok(())
+    }
+
+    /// Declares the variables set by `coproc NAME { ...; }`, or `COPROC` for `coproc cmd`: an
+    /// array with the file descriptors to read from and write to the coprocess, and `NAME_PID`.
+    /// The grammar doesn't know `coproc`, so it is parsed as a command with the rest as arguments
+    pub(crate) fn handle_coproc(&mut self, node: Node) -> ParseResult<()> {
+        self.require_bash_version("coproc", BashVersion::new(4, 0), node);
+        let words = node
+            .named_children(&mut node.walk())
+            .skip(1)
+            .take(2)
+            .map(|child| match child.kind() {
+                // `coproc NAME (cmd)` doesn't parse, leaving the name in an error
+                "ERROR" => child.named_child(0).unwrap_or(child),
+                _ => child,
+            })
+            .collect::<Vec<_>>();
+        // A name is only taken when a compound command follows it
+        let name = match words[..] {
+            [name, body]
+                if name.kind() == "word"
+                    && (body.kind() == "subshell" || self.node_text(body)? == "{") =>
+            {
+                self.node_text(name)?
+            }
+            _ => "COPROC",
+        };
+        let range = node.start_byte()..node.end_byte();
+        for (name, bash_type) in [
+            (
+                name.to_owned(),
+                BashType::Array(Box::new(BashType::Integer)),
+            ),
+            (format!("{name}_PID"), BashType::Integer),
+        ] {
+            let declaration = TypeDeclaration {
+                bash_type,
+                range: range.clone(),
+                method: Method::Inferred,
+                node_kind: node.kind().to_owned(),
+                annotation: None,
+                file: None,
+            };
+            self.set_variable(&name, declaration, node);
+        }
+        Ok(())
+    }
+}
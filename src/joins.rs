@@ -0,0 +1,161 @@
+//! Keeping the literal values of variables only where every way through branches, loops and
+//! functions agrees on them, so a value assigned in one branch isn't taken to hold after it
+
+use std::collections::HashMap;
+
+use tree_sitter::Node;
+
+use crate::{arithmetic::arithmetic_assignments, FileInfo, ParseResult};
+
+/// A statement with parts that may or may not run, whose literal values are joined once the
+/// analysis moves past it
+pub(crate) struct LiteralJoin {
+    end: usize,
+    /// Where each branch after the first starts
+    branches: Vec<usize>,
+    kind: JoinKind,
+    /// The literal values from before the statement
+    before: HashMap<String, String>,
+    /// The literal values at the end of each branch analyzed so far
+    results: Vec<HashMap<String, String>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum JoinKind {
+    /// One of the branches always runs, like in an `if` with an `else`
+    Exhaustive,
+    /// Every branch may be skipped, like in a loop that runs no times
+    MaySkip,
+    /// Runs apart from the statements after it, like a function body or a subshell
+    Separate,
+}
+
+impl FileInfo<'_> {
+    /// Sets or forgets the literal value of `name`, remembering that the function being analyzed
+    /// changes it
+    pub(crate) fn set_literal(&mut self, name: &str, literal: Option<String>) {
+        match literal {
+            Some(literal) => self.literal_values.insert(name.to_owned(), literal),
+            None => self.literal_values.remove(name),
+        };
+        if let Some(function) = self.function.clone() {
+            if !self.is_local(name) {
+                self.function_writes
+                    .entry(function)
+                    .or_default()
+                    .insert(name.to_owned());
+            }
+        }
+    }
+
+    /// Starts joining the literal values assigned in `node` if it is a branch, loop, function or
+    /// subshell
+    pub(crate) fn start_literal_join(&mut self, node: Node) {
+        let (kind, branches) = match node.kind() {
+            "if_statement" => {
+                let branches = node
+                    .named_children(&mut node.walk())
+                    .filter(|child| matches!(child.kind(), "elif_clause" | "else_clause"))
+                    .collect::<Vec<_>>();
+                let kind = if branches.iter().any(|branch| branch.kind() == "else_clause") {
+                    JoinKind::Exhaustive
+                } else {
+                    JoinKind::MaySkip
+                };
+                (kind, branches)
+            }
+            "case_statement" => {
+                let items = node
+                    .named_children(&mut node.walk())
+                    .filter(|child| child.kind() == "case_item")
+                    .skip(1)
+                    .collect();
+                (JoinKind::MaySkip, items)
+            }
+            "while_statement" | "for_statement" | "c_style_for_statement" => {
+                (JoinKind::MaySkip, Vec::new())
+            }
+            "function_definition" | "subshell" | "command_substitution" => {
+                (JoinKind::Separate, Vec::new())
+            }
+            _ => return,
+        };
+        self.literal_joins.push(LiteralJoin {
+            end: node.end_byte(),
+            branches: branches.iter().map(Node::start_byte).collect(),
+            kind,
+            before: self.literal_values.clone(),
+            results: Vec::new(),
+        });
+    }
+
+    /// Finishes the branches and statements that end before `offset`. A variable keeps a literal
+    /// value after the statement only if every way through it ends with that value
+    pub(crate) fn end_literal_joins(&mut self, offset: usize) {
+        while let Some(join) = self.literal_joins.last_mut() {
+            if join.branches.first().is_some_and(|&start| start <= offset) {
+                join.branches.remove(0);
+                // The next branch starts from the values from before the statement
+                let values = std::mem::replace(&mut self.literal_values, join.before.clone());
+                join.results.push(values);
+                continue;
+            }
+            if join.end > offset {
+                break;
+            }
+            let mut join = self.literal_joins.pop().expect("a join to finish");
+            let last = std::mem::take(&mut self.literal_values);
+            match join.kind {
+                JoinKind::Separate => {
+                    self.literal_values = join.before;
+                    continue;
+                }
+                JoinKind::MaySkip => join.results.extend([last, join.before]),
+                JoinKind::Exhaustive => join.results.push(last),
+            }
+            let mut results = join.results.into_iter();
+            let mut joined = results.next().unwrap_or_default();
+            for values in results {
+                joined.retain(|name, literal| values.get(name) == Some(literal));
+            }
+            self.literal_values = joined;
+        }
+    }
+
+    /// Forgets the literal values of the variables a command assigns through its arguments, like
+    /// `read line`, or that the function it calls assigns
+    pub(crate) fn forget_assigned_literals(&mut self, command: Node) -> ParseResult<()> {
+        for argument in self.assigned_arguments(command)? {
+            if let Some(name) = self.resolve_name(&argument.name).map(str::to_owned) {
+                self.set_literal(&name, None);
+            }
+        }
+        let Some(name) = command.child_by_field_name("name") else {
+            return Ok(());
+        };
+        let name = self.node_text(name)?;
+        let writes = self
+            .function_writes
+            .get(name)
+            .map(|writes| writes.iter().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        for name in writes {
+            self.set_literal(&name, None);
+        }
+        Ok(())
+    }
+
+    /// Forgets the literal values of the variables assigned in an arithmetic expression, like
+    /// `i` in `$((i += 1))` or `(( i++ ))`
+    pub(crate) fn forget_arithmetic_literals(&mut self, node: Node) -> ParseResult<()> {
+        let text = self.node_text(node)?;
+        let names = arithmetic_assignments(text)
+            .into_iter()
+            .filter_map(|range| self.resolve_name(&text[range]).map(str::to_owned))
+            .collect::<Vec<_>>();
+        for name in names {
+            self.set_literal(&name, None);
+        }
+        Ok(())
+    }
+}
@@ -1,14 +1,186 @@
-use std::{collections::HashMap, fmt::Display, ops::Range};
+use std::{
+    cell::OnceCell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::Display,
+    ops::Range,
+    str::FromStr,
+};
 
-use ariadne::{Color, Fmt, Label, Report, ReportKind};
-use tree_sitter::{Node, Parser, TreeCursor};
+use ariadne::Report;
+use files::FileId;
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Parser, Tree};
+
+// Declared first so its macros can be used by the other modules
+#[macro_use]
+mod logging;
+
+pub mod annotate;
+pub mod annotations;
+mod arguments;
+mod arithmetic;
+mod arrays;
+pub mod baseline;
+mod bats;
+pub mod cache;
+pub mod call_graph;
+mod chunks;
+pub mod codes;
+mod coercion;
+pub mod collisions;
+mod command_names;
+pub mod commands;
+pub mod completion;
+mod conditions;
+pub mod config;
+pub mod definition;
+mod diagnostic;
+pub mod diff;
+mod embedded;
+pub mod encoding;
+mod eval;
+mod expansions;
+pub mod files;
+pub mod fix;
+mod function_output;
+mod functions;
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
+pub mod graph;
+mod heredocs;
+mod ifs;
+pub mod init;
+mod jobs;
+mod joins;
+mod lists;
+mod loops;
+pub mod output;
+mod quoting;
+mod redirects;
+pub mod rename;
+mod rules;
+pub mod scan;
+pub mod signature;
+mod sources;
+mod status;
+pub mod symbols;
+pub mod testing;
+pub mod tokens;
+pub mod trace;
+mod traps;
+#[cfg(feature = "zsh")]
+mod zsh;
+
+use bats::bats_variable_type;
+pub use config::{
+    AnnotationStyle, BashVersion, Config, Dialect, NumericStrings, OutputFormat, Theme,
+};
+pub use diagnostic::{Diagnostic, Label, LabelKind, Severity, SourceIndex};
+use functions::FunctionCall;
+use heredocs::heredoc_annotation;
+pub use heredocs::{Heredoc, HeredocLanguage};
+use jobs::BackgroundJob;
+use lists::ShortCircuit;
+pub use sources::SourceReference;
+use trace::TypeMerge;
 
 pub struct FileInfo<'src> {
     pub source_code: &'src str,
     parser: Parser,
-    pub variables: HashMap<String, TypeDeclaration>,
-    pub errors: Vec<Report<'src>>,
+    /// The type of each variable at the end of the file
+    pub variables: BTreeMap<String, TypeDeclaration>,
+    /// Every declaration and assignment of each variable, oldest first
+    pub history: BTreeMap<String, Vec<TypeDeclaration>>,
+    pub errors: Vec<Diagnostic>,
+    pub sources: Vec<SourceReference>,
+    /// Variables marked for export with `export` or `declare -x`, with the first statement that
+    /// exported them
+    pub exports: HashMap<String, Range<usize>>,
+    /// The last definition of each function
+    pub functions: HashMap<String, FunctionDeclaration>,
+    /// Every command run by a literal name, which includes the calls to functions
+    pub calls: Vec<Call>,
+    /// Literal words passed to commands or assigned, which can name a function that is called
+    /// indirectly, like `export -f helper` or `handler=on_exit`
+    pub references: BTreeSet<String>,
+    /// Variables set outside of functions, or in a function that doesn't declare them local
+    pub globals: BTreeSet<String>,
+    /// Every heredoc, with the language it is annotated with
+    pub heredocs: Vec<Heredoc>,
+    /// Variables declared with `declare -n`, mapped to the name of the variable they refer to
+    namerefs: HashMap<String, Option<String>>,
+    /// The values of variables last assigned a literal, as in `a=text`
+    literal_values: HashMap<String, String>,
+    /// Branches, loops and functions being analyzed whose literal values are joined after them,
+    /// innermost last
+    literal_joins: Vec<joins::LiteralJoin>,
+    /// Variables outside of each function that it assigns, which lose their literal values when
+    /// it's called
+    function_writes: HashMap<String, HashSet<String>>,
+    /// Variables removed with `unset` that haven't been set again
+    unset_variables: HashSet<String>,
+    /// Variables only assigned on one side of a `&&` or `||` list
+    conditionally_set: HashSet<String>,
+    /// The right sides of `&&` and `||` lists being analyzed, innermost last
+    short_circuits: Vec<ShortCircuit>,
+    /// Statements run with `&` that are being analyzed, innermost last
+    background_jobs: Vec<BackgroundJob>,
+    /// Variables only assigned in background jobs since they were last assigned otherwise
+    background_assignments: HashSet<String>,
+    /// Changes to types made when paths through the script are joined, in order
+    merges: Vec<TypeMerge>,
+    /// Types of declared variables narrowed by their latest assignment, when
+    /// `narrow_on_assignment` is set
+    narrowed: HashMap<String, BashType>,
+    /// Variables that already have an error about them, mapped to its index in `errors`. Later
+    /// uses are added to that error instead of being reported again
+    reported_variables: HashMap<String, usize>,
+    /// How many diagnostics `parse_code_streaming` has taken out of `errors`, which the indexes in
+    /// `reported_variables` include
+    taken_errors: usize,
+    /// `#[returns ...]` and `#[params ...]` annotations, applied once the variables in the
+    /// function are known
+    function_annotations: Vec<FunctionAnnotation>,
+    /// Commands that may call functions, checked once every `#[params ...]` is applied
+    function_calls: Vec<FunctionCall>,
+    /// The status each function returns, declared with `#[returns ...]` or inferred from how it
+    /// ends
+    statuses: HashMap<String, BashType>,
+    /// What each function prints on its success paths, or `None` when that includes the output
+    /// of a command that isn't known. Functions that haven't printed anything are left out
+    outputs: HashMap<String, Option<BashType>>,
+    /// The range and type of each value a function prints, checked against
+    /// `#[returns stdout: ...]`
+    printed: HashMap<String, Vec<(Range<usize>, BashType)>>,
+    /// The literal keys assigned to each record, or `None` once one is assigned with a key that
+    /// is only known at runtime
+    written_keys: HashMap<String, Option<BTreeSet<String>>>,
+    /// Each literal key read from a record with the ranges of its subscript and key, checked once
+    /// every assignment in the file is known
+    key_reads: Vec<(String, String, Range<usize>, Range<usize>)>,
+    /// The first change to `IFS` in each function, or at the top level for `None`, that hasn't
+    /// been restored yet
+    ifs_changes: HashMap<Option<String>, Range<usize>>,
+    /// The variables each function declares local, with `local`, `declare` or `typeset`
+    locals: HashMap<String, HashSet<String>>,
+    /// Names defined with `alias`
+    aliases: HashSet<String>,
+    /// Commands run by a literal name, checked once every function is known
+    invoked_commands: Vec<(String, Range<usize>)>,
     config: Config,
+    bash_version: Option<BashVersion>,
+    force: bool,
+    /// The function the node being visited is defined in
+    function: Option<String>,
+    /// Whether the file is a BATS test file, with `@test` blocks
+    bats: bool,
+    /// Lines and characters of the file, counted the first time a line number is needed
+    source_index: OnceCell<SourceIndex<'src>>,
+}
+
+struct FunctionAnnotation {
+    command: String,
+    range: Range<usize>,
     force: bool,
 }
 
@@ -16,14 +188,49 @@ pub struct FileInfo<'src> {
 struct Comment {
     text: String,
     range: Range<usize>,
+    /// Where `text` is, which is the whole range for annotations implied by flags
+    text_range: Range<usize>,
+    kind: CommentKind,
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CommentKind {
+    /// `#/ type`
+    Type,
+    /// `#[command]`
+    Command,
+    /// `#![directive]`, applying to the whole file
+    Directive,
+}
+
+/// A node waiting to be visited, along with the annotation written on the line before it
+struct WorkItem<'tree> {
+    node: Node<'tree>,
+    annotation: Option<Comment>,
+    /// Whether the statement containing the node follows a `#[force]` comment
+    force: bool,
+    /// The innermost function whose body contains the node
+    function: Option<String>,
+    /// Whether the node is a statement run in the background with `&`
+    background: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Eq, Serialize, Deserialize)]
 pub enum BashType {
     String,
     Integer,
     Bool,
     Any,
+    /// The type of a value that can't be inferred without running the script
+    Unknown,
+    /// An indexed array, with the type of its elements
+    Array(Box<BashType>),
+    /// An associative array, with the types of its keys and values
+    Map(Box<BashType>, Box<BashType>),
+    /// An indexed array with a fixed length, where each position has its own type
+    Tuple(Vec<BashType>),
+    /// An associative array with a fixed set of keys, where each key has its own type
+    Record(BTreeMap<String, BashType>),
     Or(Box<BashType>, Box<BashType>),
 }
 
@@ -37,6 +244,35 @@ enum ParseErrType {
     MissingArgument { expected: usize, received: usize },
     InvalidUnicode,
     UnknownVariable(String),
+    InvalidDirective(String),
+    InvalidType(String),
+    MisplacedAnnotation(String),
+    UnknownNamerefTarget { nameref: String, target: String },
+    PossiblyUnset(String),
+    ConditionallySet(String),
+    SetInBackground(String),
+}
+
+impl ParseErrType {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::UnknownVariable(_) => "Unknown variable",
+            Self::UnknownNamerefTarget { .. } => "Unknown nameref target",
+            Self::InvalidType(_) => "Invalid type",
+            Self::PossiblyUnset(_) | Self::ConditionallySet(_) => "Variable may be unset",
+            Self::SetInBackground(_) => "Variable set in a background job",
+            _ => "Error while parsing comment",
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Self::UnknownVariable(_) | Self::UnknownNamerefTarget { .. } => codes::UNKNOWN_VARIABLE,
+            Self::PossiblyUnset(_) | Self::ConditionallySet(_) => codes::POSSIBLY_UNSET,
+            Self::SetInBackground(_) => codes::BACKGROUND_ASSIGNMENT,
+            _ => codes::INVALID_ANNOTATION,
+        }
+    }
 }
 
 impl Display for ParseErrType {
@@ -48,24 +284,214 @@ impl Display for ParseErrType {
                 received: r,
             } => write!(f, "Expected {e} arguments, but found {r}"),
             Self::UnknownVariable(var_name) => write!(f, "Found unknown variable {var_name}"),
+            Self::InvalidDirective(directive) => write!(f, "Invalid directive {directive}"),
+            Self::InvalidType(reason) => write!(f, "{reason}"),
+            Self::MisplacedAnnotation(annotation) => {
+                write!(f, "#[{annotation}] has to be placed before a function")
+            }
+            Self::UnknownNamerefTarget { nameref, target } => {
+                write!(f, "{nameref} refers to unknown variable {target}")
+            }
+            Self::PossiblyUnset(var_name) => write!(f, "{var_name} is unset before this"),
+            Self::ConditionallySet(var_name) => write!(
+                f,
+                "{var_name} is only set when one side of a && or || list runs"
+            ),
+            Self::SetInBackground(var_name) => write!(
+                f,
+                "{var_name} is only set in a job run with &, which doesn't change this shell"
+            ),
         }
     }
 }
 
+impl ParseError {
+    fn into_diagnostic(self, offset: usize) -> Diagnostic {
+        // The variable may have been set again on a path that can't be followed
+        let severity = match self.err_type {
+            ParseErrType::PossiblyUnset(_)
+            | ParseErrType::ConditionallySet(_)
+            | ParseErrType::SetInBackground(_) => Severity::Warning,
+            _ => Severity::Error,
+        };
+        Diagnostic::new(self.err_type.title(), offset)
+            .with_severity(severity)
+            .with_code(self.err_type.code())
+            .with_label(Label::new(
+                self.start..self.end,
+                self.err_type.to_string(),
+                LabelKind::ParseErr,
+            ))
+    }
+}
+
 type ParseResult<T> = std::result::Result<T, ParseError>;
 
 impl Display for BashType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             BashType::Any => write!(f, "any"),
+            BashType::Unknown => write!(f, "unknown"),
             BashType::String => write!(f, "string"),
             BashType::Bool => write!(f, "bool"),
             BashType::Integer => write!(f, "int"),
+            BashType::Array(element) => write!(f, "array<{element}>"),
+            BashType::Map(key, value) => write!(f, "map<{key}, {value}>"),
+            BashType::Tuple(elements) => {
+                let elements = elements.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            BashType::Record(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(key, value)| format!("{key}: {value}"))
+                    .collect::<Vec<_>>();
+                write!(f, "{{ {} }}", fields.join(", "))
+            }
             BashType::Or(t1, t2) => write!(f, "{t1} | {t2}"),
         }
     }
 }
 
+/// A type printed with long unions cut short, like `int | string | … (5 more)`, from
+/// [`BashType::abbreviated`]
+pub struct AbbreviatedType<'a> {
+    bash_type: &'a BashType,
+    max_members: usize,
+}
+
+impl Display for AbbreviatedType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let members = self.bash_type.types_from_or();
+        if members.len() <= self.max_members.max(2) {
+            return write!(f, "{}", self.bash_type);
+        }
+        write!(
+            f,
+            "{} | {} | … ({} more)",
+            members[0],
+            members[1],
+            members.len() - 2
+        )
+    }
+}
+
+/// The type names accepted in `#/` annotations, with a description of each
+pub const TYPE_NAMES: &[(&str, &str)] = &[
+    ("string", "Any text"),
+    ("int", "A whole number"),
+    ("bool", "true or false, or a status of 0 or 1"),
+    ("any", "A value that isn't checked"),
+    ("array", "An indexed array, like `array<int>`"),
+    ("map", "An associative array, like `map<string, int>`"),
+];
+
+/// The commands accepted in `#[...]` comments, with a description of each
+pub const ANNOTATION_COMMANDS: &[(&str, &str)] = &[
+    ("force", "Skips the type checks of the next statement"),
+    (
+        "returns",
+        "Declares the status the next function returns and what it prints, like \
+         `#[returns bool]` or `#[returns status: bool, stdout: string]`",
+    ),
+    (
+        "params",
+        "Names and types the arguments of the next function, like `#[params path: string, \
+         retries: int]`. Ending the list with `...: type` accepts any number of extra arguments",
+    ),
+    (
+        "set_var",
+        "Declares a variable set somewhere bashtyped can't see, like `#[set_var(name, int)]`",
+    ),
+];
+
+/// Parses the type in an annotation, such as `int | string` or `map<string, array<int>>`
+impl FromStr for BashType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some((first, second)) = split_top_level(s, '|') {
+            return Ok(BashType::Or(
+                Box::new(first.parse()?),
+                Box::new(second.parse()?),
+            ));
+        }
+        if let Some(elements) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            let types = split_list(elements)
+                .into_iter()
+                .map(str::parse)
+                .collect::<Result<_, _>>()?;
+            return Ok(BashType::Tuple(types));
+        }
+        if let Some(fields) = s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            let mut types = BTreeMap::new();
+            for field in split_list(fields) {
+                let Some((key, value)) = field.split_once(':') else {
+                    return Err(format!("Expected a type for {}", field.trim()));
+                };
+                types.insert(key.trim().to_owned(), value.parse()?);
+            }
+            return Ok(BashType::Record(types));
+        }
+        if let Some((name, parameters)) = s.strip_suffix('>').and_then(|s| s.split_once('<')) {
+            return match name.trim() {
+                "array" => Ok(BashType::Array(Box::new(parameters.parse()?))),
+                "map" => {
+                    let Some((key, value)) = split_top_level(parameters, ',') else {
+                        return Err(String::from("map takes a key and a value type"));
+                    };
+                    Ok(BashType::Map(
+                        Box::new(key.parse()?),
+                        Box::new(value.parse()?),
+                    ))
+                }
+                name => Err(format!("{name} does not take type parameters")),
+            };
+        }
+        match s {
+            "string" => Ok(BashType::String),
+            "int" => Ok(BashType::Integer),
+            "bool" => Ok(BashType::Bool),
+            "any" => Ok(BashType::Any),
+            "array" => Ok(BashType::Array(Box::new(BashType::Any))),
+            "map" => Ok(BashType::Map(
+                Box::new(BashType::String),
+                Box::new(BashType::Any),
+            )),
+            "" => Err(String::from("Expected a type")),
+            unknown => Err(format!("Unknown type {unknown}")),
+        }
+    }
+}
+
+/// Splits the comma separated contents of a tuple or record, allowing a trailing comma
+pub(crate) fn split_list(mut s: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    while let Some((item, rest)) = split_top_level(s, ',') {
+        items.push(item);
+        s = rest;
+    }
+    if !s.trim().is_empty() {
+        items.push(s);
+    }
+    items
+}
+
+/// Splits `s` at the first `separator` that isn't nested inside `<...>`, `[...]` or `{...}`
+fn split_top_level(s: &str, separator: char) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' | '[' | '{' => depth += 1,
+            '>' | ']' | '}' => depth -= 1,
+            c if c == separator && depth == 0 => return Some((&s[..i], &s[i + 1..])),
+            _ => (),
+        }
+    }
+    None
+}
+
 impl BashType {
     fn matches(&self, other: &Self) -> bool {
         if let BashType::Or(t1, t2) = self {
@@ -74,7 +500,39 @@ impl BashType {
         if let BashType::Or(t1, t2) = other {
             return t1.matches(self) || t2.matches(self);
         }
-        self == &BashType::Any || other == &BashType::Any || self == other
+        match (self, other) {
+            (BashType::Array(e1), BashType::Array(e2)) => return e1.matches(e2),
+            (BashType::Tuple(t1), BashType::Tuple(t2)) => {
+                return t1.len() == t2.len() && t1.iter().zip(t2).all(|(t1, t2)| t1.matches(t2))
+            }
+            (BashType::Record(r1), BashType::Record(r2)) => {
+                return r1.len() == r2.len()
+                    && r1
+                        .iter()
+                        .all(|(key, t1)| r2.get(key).is_some_and(|t2| t1.matches(t2)))
+            }
+            (BashType::Map(k1, v1), BashType::Map(k2, v2)) => {
+                return k1.matches(k2) && v1.matches(v2)
+            }
+            _ => (),
+        }
+        [self, other]
+            .iter()
+            .any(|t| matches!(t, BashType::Any | BashType::Unknown))
+            || self == other
+    }
+
+    /// How many different types a union is made of, or 1 for any other type
+    pub fn union_size(&self) -> usize {
+        self.normalized().types_from_or().len()
+    }
+
+    /// Displays the type, showing only the first members of unions with more than `max_members`
+    pub fn abbreviated(&self, max_members: usize) -> AbbreviatedType<'_> {
+        AbbreviatedType {
+            bash_type: self,
+            max_members,
+        }
     }
 
     fn types_from_or(&self) -> Vec<Self> {
@@ -87,43 +545,211 @@ impl BashType {
         }
     }
 
-    fn can_contain(&self, other: &Self) -> bool {
-        if let BashType::Or(t1, t2) = self {
-            if let BashType::Or(_, _) = other {
-                let self_types = self.types_from_or();
-                other.types_from_or().iter().all(|v| self_types.contains(v))
-            } else {
-                t1.matches(other) || t2.matches(other)
+    /// Whether every value of `other` is also a value of this type. `any` contains every type.
+    /// Nothing can be proven about values whose type couldn't be inferred, so `unknown` both
+    /// contains and is contained by every type, which makes this transitive only for types
+    /// without it
+    pub fn can_contain(&self, other: &Self) -> bool {
+        match (self, other) {
+            (_, BashType::Unknown) | (BashType::Any | BashType::Unknown, _) => true,
+            (_, BashType::Or(t1, t2)) => self.can_contain(t1) && self.can_contain(t2),
+            (BashType::Or(t1, t2), _) => t1.can_contain(other) || t2.can_contain(other),
+            (BashType::Array(e1), BashType::Array(e2)) => e1.can_contain(e2),
+            (BashType::Array(e1), BashType::Tuple(t2)) => t2.iter().all(|t2| e1.can_contain(t2)),
+            (BashType::Tuple(t1), BashType::Tuple(t2)) => {
+                t1.len() == t2.len() && t1.iter().zip(t2).all(|(t1, t2)| t1.can_contain(t2))
             }
+            (BashType::Map(_, v1), BashType::Record(r2)) => {
+                r2.values().all(|t2| v1.can_contain(t2))
+            }
+            // Keys can be left unset, but every key that is set has to be declared
+            (BashType::Record(r1), BashType::Record(r2)) => r2
+                .iter()
+                .all(|(key, t2)| r1.get(key).is_some_and(|t1| t1.can_contain(t2))),
+            // The empty associative array made by `declare -A` can become any record
+            (BashType::Record(_), BashType::Map(_, value)) => **value == BashType::Unknown,
+            (BashType::Map(k1, v1), BashType::Map(k2, v2)) => {
+                k1.can_contain(k2) && v1.can_contain(v2)
+            }
+            _ => self == other,
+        }
+    }
+
+    /// The type of any element of an array, or `None` for scalars
+    fn element_type(&self) -> Option<BashType> {
+        match self {
+            BashType::Array(element) | BashType::Map(_, element) => Some((**element).clone()),
+            BashType::Tuple(elements) => Some(
+                elements
+                    .iter()
+                    .cloned()
+                    .reduce(BashType::union)
+                    .unwrap_or(BashType::Unknown),
+            ),
+            BashType::Record(fields) => Some(
+                fields
+                    .values()
+                    .cloned()
+                    .reduce(BashType::union)
+                    .unwrap_or(BashType::Unknown),
+            ),
+            _ => None,
+        }
+    }
+
+    /// The narrowest type that can contain both `self` and `other`
+    pub fn union(self, other: Self) -> Self {
+        if self.can_contain(&other) {
+            self
+        } else if other.can_contain(&self) {
+            other
         } else {
-            self == &BashType::Any || self == other
+            BashType::Or(Box::new(self), Box::new(other))
         }
     }
-}
 
-struct Config {
-    specified_color: Color,
-    inferred_color: Color,
-    parse_err_color: Color,
-}
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            specified_color: Color::Blue,
-            inferred_color: Color::Magenta,
-            parse_err_color: Color::Red,
+    /// The same type written in a canonical way, with the members of unions in the order of
+    /// [`TYPE_NAMES`] and without members that other members contain, so types that contain
+    /// each other normalize to the same type
+    pub fn normalized(&self) -> Self {
+        match self {
+            BashType::Array(element) => BashType::Array(Box::new(element.normalized())),
+            BashType::Map(key, value) => {
+                BashType::Map(Box::new(key.normalized()), Box::new(value.normalized()))
+            }
+            BashType::Tuple(elements) => {
+                BashType::Tuple(elements.iter().map(BashType::normalized).collect())
+            }
+            BashType::Record(fields) => BashType::Record(
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.normalized()))
+                    .collect(),
+            ),
+            BashType::Or(..) => {
+                let mut members = Vec::new();
+                self.union_members(&mut members);
+                let mut members = members
+                    .into_iter()
+                    .map(|member| (member.canonical_rank(), member.to_string(), member))
+                    .collect::<Vec<_>>();
+                members.sort_by(|(a_rank, a, _), (b_rank, b, _)| (a_rank, a).cmp(&(b_rank, b)));
+                members.dedup_by(|(_, a, _), (_, b, _)| a == b);
+                let members = members
+                    .into_iter()
+                    .map(|(_, _, member)| member)
+                    .collect::<Vec<_>>();
+                members
+                    .iter()
+                    .filter(|member| {
+                        !members
+                            .iter()
+                            .any(|other| other.can_contain(member) && !member.can_contain(other))
+                    })
+                    .cloned()
+                    .rev()
+                    .reduce(|rest, member| BashType::Or(Box::new(member), Box::new(rest)))
+                    .expect("unions to have members")
+            }
+            simple => simple.clone(),
+        }
+    }
+
+    fn union_members(&self, members: &mut Vec<BashType>) {
+        match self {
+            BashType::Or(first, second) => {
+                first.union_members(members);
+                second.union_members(members);
+            }
+            member => members.push(member.normalized()),
+        }
+    }
+
+    fn canonical_rank(&self) -> usize {
+        match self {
+            BashType::String => 0,
+            BashType::Integer => 1,
+            BashType::Bool => 2,
+            BashType::Any => 3,
+            BashType::Array(_) => 4,
+            BashType::Map(..) => 5,
+            BashType::Tuple(_) => 6,
+            BashType::Record(_) => 7,
+            BashType::Unknown | BashType::Or(..) => 8,
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TypeDeclaration {
     pub range: Range<usize>,
     pub bash_type: BashType,
     pub method: Method,
+    /// The kind of syntax node the type comes from, like `variable_assignment` or
+    /// `for_statement`
+    #[serde(default)]
+    pub node_kind: String,
+    /// Where the annotation giving a declared type is
+    #[serde(default)]
+    pub annotation: Option<AnnotationSpan>,
+    /// The file the declaration is in, when it isn't the file being analyzed. Set on the
+    /// declarations a [`symbols::Workspace`] keeps of each file
+    #[serde(default)]
+    pub file: Option<FileId>,
+}
+
+/// Where an annotation, or a flag acting as one, is in the source
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnnotationSpan {
+    pub range: Range<usize>,
+    /// Only the type, like `int | string` in `#/ int | string`
+    pub type_range: Range<usize>,
+    pub placement: AnnotationPlacement,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnnotationPlacement {
+    /// At the end of the line of the statement
+    Inline,
+    /// On its own line before the statement
+    Preceding,
+    /// A flag of the declaration, like `-i` in `declare -i count=1`
+    Attribute,
+    /// A `#[set_var(name, type)]` annotation
+    Command,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionDeclaration {
+    pub range: Range<usize>,
+    /// The status given by a `#[returns ...]` annotation
+    pub returns: Option<BashType>,
+    /// The output given by a `#[returns stdout: ...]` annotation
+    #[serde(default)]
+    pub stdout: Option<BashType>,
+    /// The arguments named by a `#[params ...]` annotation, in order
+    pub params: Vec<Parameter>,
+    /// The file the function is defined in, when it isn't the file being analyzed
+    #[serde(default)]
+    pub file: Option<FileId>,
+}
+
+/// A command run by a literal name
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Call {
+    pub name: String,
+    pub range: Range<usize>,
+    /// The function the command is run in, or `None` at the top level of the file
+    pub caller: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    pub bash_type: BashType,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Method {
     Inferred,
     Declared,
@@ -131,6 +757,10 @@ pub enum Method {
 
 impl<'a> FileInfo<'a> {
     pub fn new(source_code: &'a str) -> FileInfo<'a> {
+        Self::with_config(source_code, Config::default())
+    }
+
+    pub fn with_config(source_code: &'a str, config: Config) -> FileInfo<'a> {
         let mut parser = Parser::new();
         parser
             .set_language(tree_sitter_bash::language())
@@ -139,293 +769,1611 @@ impl<'a> FileInfo<'a> {
         Self {
             source_code,
             parser,
-            variables: HashMap::new(),
+            variables: BTreeMap::new(),
+            history: BTreeMap::new(),
             errors: Vec::new(),
-            config: Config::default(),
+            sources: Vec::new(),
+            exports: HashMap::new(),
+            functions: HashMap::new(),
+            calls: Vec::new(),
+            references: BTreeSet::new(),
+            globals: BTreeSet::new(),
+            heredocs: Vec::new(),
+            namerefs: HashMap::new(),
+            literal_values: HashMap::new(),
+            literal_joins: Vec::new(),
+            function_writes: HashMap::new(),
+            unset_variables: HashSet::new(),
+            conditionally_set: HashSet::new(),
+            short_circuits: Vec::new(),
+            background_jobs: Vec::new(),
+            background_assignments: HashSet::new(),
+            merges: Vec::new(),
+            narrowed: HashMap::new(),
+            reported_variables: HashMap::new(),
+            taken_errors: 0,
+            function_annotations: Vec::new(),
+            function_calls: Vec::new(),
+            statuses: HashMap::new(),
+            outputs: HashMap::new(),
+            printed: HashMap::new(),
+            written_keys: HashMap::new(),
+            key_reads: Vec::new(),
+            ifs_changes: HashMap::new(),
+            locals: HashMap::new(),
+            aliases: HashSet::new(),
+            invoked_commands: Vec::new(),
+            bash_version: config.bash_version,
+            config,
             force: false,
+            function: None,
+            bats: false,
+            source_index: OnceCell::new(),
         }
     }
 
-    fn handle_comment(&self, cursor: &mut TreeCursor) -> ParseResult<Option<Comment>> {
-        let comment = cursor
-            .node()
-            .utf8_text(self.source_code.as_bytes())
+    fn node_text(&self, node: Node) -> ParseResult<&'a str> {
+        node.utf8_text(self.source_code.as_bytes())
             .map_err(|_| ParseError {
                 err_type: ParseErrType::InvalidUnicode,
-                start: cursor.node().start_byte(),
-                end: cursor.node().end_byte(),
-            })?;
-        let range = cursor.node().start_byte()..cursor.node().end_byte();
+                start: node.start_byte(),
+                end: node.end_byte(),
+            })
+    }
+
+    /// The range of a comment, without the carriage return comments on lines ending with CRLF
+    /// include
+    pub(crate) fn comment_range(&self, node: Node) -> Range<usize> {
+        let text = self.node_text(node).unwrap_or_default();
+        node.start_byte()..node.start_byte() + text.trim_end_matches('\r').len()
+    }
+
+    fn handle_comment(&self, node: Node) -> ParseResult<Option<Comment>> {
+        let comment = self.node_text(node)?.trim_end_matches('\r');
+        let range = self.comment_range(node);
 
+        if let Some(type_info) = comment.strip_prefix("#/") {
+            return Ok(Some(Comment {
+                text: type_info.trim().to_owned(),
+                text_range: self.text_range(&range, type_info.trim()),
+                range,
+                kind: CommentKind::Type,
+            }));
+        }
+        if let Some(directive) = comment
+            .strip_prefix("#![")
+            .and_then(|directive| directive.trim().strip_suffix(']'))
+        {
+            return Ok(Some(Comment {
+                text: directive.trim().to_owned(),
+                text_range: self.text_range(&range, directive.trim()),
+                range,
+                kind: CommentKind::Directive,
+            }));
+        }
         Ok(comment
-            .strip_prefix("#/")
-            .or(comment.strip_prefix("#["))
-            .map(|comment_info| Comment {
-                text: comment_info.trim().to_owned(),
+            .strip_prefix("#[")
+            .and_then(|command| command.trim().strip_suffix(']'))
+            .map(|command| Comment {
+                text: command.trim().to_owned(),
+                text_range: self.text_range(&range, command.trim()),
                 range,
+                kind: CommentKind::Command,
             }))
     }
 
-    fn infer_type(&self, node: Node) -> ParseResult<BashType> {
+    /// The range of the last `text` inside `range`, or all of `range` if it isn't there
+    fn text_range(&self, range: &Range<usize>, text: &str) -> Range<usize> {
+        match self.source_code[range.clone()].rfind(text) {
+            Some(start) => range.start + start..range.start + start + text.len(),
+            None => range.clone(),
+        }
+    }
+
+    fn infer_type(&mut self, node: Node) -> ParseResult<BashType> {
         match node.kind() {
-            "number" => Ok(BashType::Integer),
-            "word" => Ok(BashType::String),
+            // Hex literals like `0xff` aren't numbers to the grammar, and `08` isn't a valid one
+            "number" | "word" => Ok(literal_type(self.node_text(node)?)),
             "string" => {
                 if node.named_child_count() == 1 {
                     let content = node.child(1).expect("named child count to be one");
-                    let inferred_type = if content.kind() == "string_content" {
+                    if content.kind() == "string_content" {
                         Ok(BashType::String)
                     } else {
                         self.infer_type(content)
-                    };
-                    inferred_type
+                    }
                 } else {
                     Ok(BashType::String)
                 }
             }
             "simple_expansion" => {
                 let variable = node.child(1).expect("Variable to have a name");
-                let var_name = variable
-                    .utf8_text(self.source_code.as_bytes())
-                    .map_err(|_| ParseError {
-                        err_type: ParseErrType::InvalidUnicode,
-                        start: variable.start_byte(),
-                        end: variable.end_byte(),
-                    })?;
-                Ok(self
-                    .variables
-                    .get(var_name)
-                    .ok_or_else(|| ParseError {
-                        err_type: ParseErrType::UnknownVariable(var_name.to_owned()),
-                        start: variable.start_byte(),
-                        end: variable.end_byte(),
-                    })?
-                    .bash_type
-                    .clone())
+                self.variable_type(variable)
             }
+            "expansion" => self.expansion_type(node),
+            "command_substitution" => self.substitution_type(node),
+            "arithmetic_expansion" => Ok(BashType::Integer),
+            "array" => self.array_literal_type(node, false),
             _ => {
-                println!("{:?}", node.kind());
-                todo!()
+                self.warn_unknown(
+                    node,
+                    format!("Unable to infer the type of {}", node.kind()),
+                    "Type inferred to be unknown",
+                );
+                Ok(BashType::Unknown)
             }
         }
     }
 
-    fn type_from_string(&self, input_type: &str) -> BashType {
-        match input_type.trim() {
-            "string" => BashType::String,
-            "int" => BashType::Integer,
-            "bool" => BashType::Bool,
-            "any" => BashType::Any,
-            v => {
-                let Some((first, second)) = v.split_once('|') else {
-                    todo!();
-                };
-                BashType::Or(
-                    Box::new(self.type_from_string(first)),
-                    Box::new(self.type_from_string(second)),
-                )
+    fn variable_type(&self, variable: Node) -> ParseResult<BashType> {
+        let var_name = self.node_text(variable)?;
+        // `$?`, `$#`, `$$` and `$!`
+        if variable.kind() == "special_variable_name" && matches!(var_name, "?" | "#" | "$" | "!") {
+            return Ok(BashType::Integer);
+        }
+        let Some(name) = self.resolve_name(var_name) else {
+            // The nameref's target is only known at runtime
+            return Ok(BashType::Any);
+        };
+        let err_type = if self.unset_variables.contains(name) {
+            ParseErrType::PossiblyUnset(name.to_owned())
+        } else if name == var_name {
+            ParseErrType::UnknownVariable(var_name.to_owned())
+        } else {
+            ParseErrType::UnknownNamerefTarget {
+                nameref: var_name.to_owned(),
+                target: name.to_owned(),
             }
+        };
+        if self.background_assignments.contains(name) {
+            return Err(ParseError {
+                err_type: ParseErrType::SetInBackground(name.to_owned()),
+                start: variable.start_byte(),
+                end: variable.end_byte(),
+            });
+        }
+        if self.conditionally_set.contains(name) {
+            return Err(ParseError {
+                err_type: ParseErrType::ConditionallySet(name.to_owned()),
+                start: variable.start_byte(),
+                end: variable.end_byte(),
+            });
+        }
+        // Bash sets `IFS` before the script runs, so saving it as in `old_ifs=$IFS` is fine
+        if name == "IFS"
+            && !self.variables.contains_key(name)
+            && !self.unset_variables.contains(name)
+        {
+            return Ok(BashType::String);
         }
+        if let Some(bash_type) = bats_variable_type(name).filter(|_| {
+            self.bats && !self.variables.contains_key(name) && !self.unset_variables.contains(name)
+        }) {
+            return Ok(bash_type);
+        }
+        let declaration = self.variables.get(name).ok_or(ParseError {
+            err_type,
+            start: variable.start_byte(),
+            end: variable.end_byte(),
+        })?;
+        Ok(self
+            .narrowed
+            .get(name)
+            .unwrap_or(&declaration.bash_type)
+            .clone())
     }
 
-    fn handle_node(
+    /// Reports code whose types can't be checked, when running in pedantic mode or with the rule
+    /// enabled
+    pub(crate) fn warn_unknown(
         &mut self,
-        cursor: &mut TreeCursor,
-        possible_comment: Option<Comment>,
-    ) -> ParseResult<()> {
-        match cursor.node().kind() {
-            "comment" => {
-                let possible_comment = self.handle_comment(cursor)?.to_owned();
-                let available_sibling = cursor.goto_next_sibling();
-                if let Some(command) = possible_comment
-                    .as_ref()
-                    .and_then(|v| v.text.strip_suffix(']'))
+        node: Node,
+        message: impl Into<String>,
+        label: impl Into<String>,
+    ) {
+        if self
+            .config
+            .rule_enabled(codes::UNCHECKED, self.config.pedantic)
+        {
+            self.errors.push(
+                Diagnostic::new(message, node.start_byte())
+                    .with_code(codes::UNCHECKED)
+                    .with_severity(Severity::Warning)
+                    .with_label(Label::new(
+                        node.start_byte()..node.end_byte(),
+                        label,
+                        LabelKind::Unknown,
+                    )),
+            );
+        }
+    }
+
+    /// Follows namerefs to the variable they point to. Returns `None` when that can only be
+    /// known at runtime
+    fn resolve_name<'n>(&'n self, mut name: &'n str) -> Option<&'n str> {
+        // Any longer chain has to contain a cycle
+        for _ in 0..=self.namerefs.len() {
+            match self.namerefs.get(name) {
+                Some(Some(target)) => name = target,
+                Some(None) => return None,
+                None => return Some(name),
+            }
+        }
+        None
+    }
+
+    fn type_from_string(&self, input_type: &str, range: Range<usize>) -> ParseResult<BashType> {
+        input_type.parse().map_err(|reason| ParseError {
+            err_type: ParseErrType::InvalidType(reason),
+            start: range.start,
+            end: range.end,
+        })
+    }
+
+    fn handle_annotation_command(&mut self, command: &str, node: Node) -> ParseResult<()> {
+        match command {
+            // Applied to the following statement when it is queued
+            "force" => (),
+            func_command
+                if func_command.starts_with("returns ") || func_command.starts_with("params ") =>
+            {
+                self.function_annotations.push(FunctionAnnotation {
+                    command: func_command.to_owned(),
+                    range: node.start_byte()..node.end_byte(),
+                    force: self.force,
+                });
+            }
+            func_command => {
+                if let Some(info) = func_command
+                    .strip_prefix("set_var(")
+                    .and_then(|conts| conts.strip_suffix(')'))
                 {
-                    match command {
-                        "force" => self.force = true,
-                        func_command => {
-                            if let Some(info) = func_command
-                                .strip_prefix("set_var(")
-                                .and_then(|conts| conts.strip_suffix(')'))
-                            {
-                                let args = info.split(',').collect::<Vec<_>>();
-                                if args.len() != 2 {
-                                    return Err(ParseError {
-                                        err_type: ParseErrType::MissingArgument {
-                                            expected: 2,
-                                            received: args.len(),
-                                        },
-                                        start: cursor.node().start_byte(),
-                                        end: cursor.node().end_byte(),
-                                    });
-                                }
-                                let final_type = TypeDeclaration {
-                                    range: cursor.node().start_byte()..cursor.node().end_byte(),
-                                    bash_type: self.type_from_string(args[1]),
-                                    method: Method::Declared,
-                                };
-                                self.set_variable(args[0], final_type, cursor);
-                            }
-                        }
+                    // Only split once so the type can be `map<string, int>`
+                    let args = info.splitn(2, ',').collect::<Vec<_>>();
+                    if args.len() != 2 {
+                        return Err(ParseError {
+                            err_type: ParseErrType::MissingArgument {
+                                expected: 2,
+                                received: args.len(),
+                            },
+                            start: node.start_byte(),
+                            end: node.end_byte(),
+                        });
                     }
+                    let range = node.start_byte()..node.end_byte();
+                    let final_type = TypeDeclaration {
+                        bash_type: self.type_from_string(args[1], range.clone())?,
+                        method: Method::Declared,
+                        node_kind: node.kind().to_owned(),
+                        annotation: Some(AnnotationSpan {
+                            type_range: self.text_range(&range, args[1].trim()),
+                            range: range.clone(),
+                            placement: AnnotationPlacement::Command,
+                        }),
+                        range,
+                        file: None,
+                    };
+                    self.set_variable(args[0].trim(), final_type, node);
                 }
-                if !available_sibling {
-                    return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_assignment(&mut self, node: Node, annotation: Option<Comment>) -> ParseResult<()> {
+        let name_node = node
+            .child_by_field_name("name")
+            .expect("assignment to have a name");
+        if name_node.kind() == "subscript" {
+            return self.handle_subscript_assignment(node, name_node);
+        }
+        let name = self.node_text(name_node)?;
+        if let Some(parent) = node
+            .parent()
+            .filter(|parent| parent.kind() == "declaration_command")
+        {
+            if self.declaration_flags(parent)?.contains('n') {
+                let target = match node.child_by_field_name("value") {
+                    Some(value) => self.static_text(value)?,
+                    None => None,
+                };
+                self.namerefs.insert(name.to_owned(), target);
+                return Ok(());
+            }
+        }
+        // Assigning to a nameref assigns to its target
+        let Some(name) = self.resolve_name(name).map(str::to_owned) else {
+            return Ok(());
+        };
+        let annotation = self
+            .inline_annotation(node)?
+            .or(annotation)
+            .or(self.attribute_annotation(node)?);
+        let inferred_type = match node.child_by_field_name("value") {
+            Some(value) if value.kind() == "array" => {
+                self.set_literal(&name, None);
+                if self.is_tuple(&name, annotation.as_ref()) {
+                    self.array_literal_tuple(value)?
+                } else if self.is_record(&name, annotation.as_ref()) {
+                    let record = self.array_literal_record(value)?;
+                    self.record_written_keys(&name, &record);
+                    record
+                } else {
+                    let associative = self.is_associative(&name, node)?;
+                    self.array_literal_type(value, associative)?
                 }
-                self.handle_node(cursor, possible_comment)?;
             }
-            "variable_assignment" => {
-                cursor.goto_first_child();
-                let name = cursor
-                    .node()
-                    .utf8_text(self.source_code.as_bytes())
-                    .map_err(|_| ParseError {
-                        err_type: ParseErrType::InvalidUnicode,
-                        start: cursor.node().start_byte(),
-                        end: cursor.node().end_byte(),
-                    })?;
-                cursor.goto_next_sibling();
-                cursor.goto_next_sibling();
-                let inferred_type = self.infer_type(cursor.node())?;
-
-                let inferred_location = cursor
-                    .node()
-                    .prev_sibling()
-                    .and_then(|v| v.prev_sibling())
-                    .expect("already to have checked previous siblings")
-                    .start_byte()..cursor.node().end_byte();
-
-                cursor.goto_parent();
-                let inline_type = (cursor
-                    .node()
-                    .next_sibling()
-                    .is_some_and(|node| node.kind() == "comment"))
-                .then(|| {
-                    cursor.goto_next_sibling();
-                    self.handle_comment(cursor)
-                })
-                .transpose()?
-                .flatten();
-
-                let final_type = if let Some(comment) = inline_type.or(possible_comment) {
-                    let suggested_type = self.type_from_string(&comment.text);
-                    if suggested_type.can_contain(&inferred_type) || self.force {
-                        TypeDeclaration {
-                            bash_type: suggested_type,
-                            range: combine_ranges(comment.range, inferred_location),
-                            method: Method::Declared,
-                        }
-                    } else {
-                        self.errors.push(
-                            Report::build(ReportKind::Error, (), cursor.node().start_byte())
-                                .with_message("Types do not match")
-                                .with_label(
-                                    Label::new(comment.range)
-                                        .with_message(format!(
-                                            "Type specified as {}",
-                                            suggested_type.fg(self.config.specified_color)
-                                        ))
-                                        .with_color(self.config.specified_color),
-                                )
-                                .with_label(
-                                    Label::new(inferred_location)
-                                        .with_message(format!(
-                                            "Type inferred to be {}",
-                                            inferred_type.fg(self.config.inferred_color)
-                                        ))
-                                        .with_color(self.config.inferred_color),
-                                )
-                                .finish(),
-                        );
-                        return Ok(());
+            Some(value) => {
+                let rest = node
+                    .parent()
+                    .and_then(|command| self.continued_value(command));
+                match rest {
+                    Some(rest) => self.continued_value_type(&name, value, rest)?,
+                    None => {
+                        let literal = self.static_text(value)?;
+                        self.set_literal(&name, literal);
+                        self.infer_type(value)?
                     }
+                }
+            }
+            // `a=` assigns the empty string
+            None => {
+                self.set_literal(&name, Some(String::new()));
+                BashType::String
+            }
+        };
+        let end = node
+            .parent()
+            .and_then(|command| self.continued_value(command))
+            .map_or(node.end_byte(), |rest| rest.end_byte());
+        let inferred_location = node.start_byte()..end;
+        let inferred_type = if node.child(1).is_some_and(|child| child.kind() == "+=") {
+            // The value is no longer the literal that was just recorded
+            self.set_literal(&name, None);
+            let Some(appended_type) =
+                self.appended_type(&name, inferred_type, inferred_location.clone(), node)
+            else {
+                return Ok(());
+            };
+            appended_type
+        } else {
+            inferred_type
+        };
+        self.declare_variable(&name, inferred_type, inferred_location, annotation, node)
+    }
+
+    /// Finds a `#/` comment on the same line as `node`, which may be nested in a command like
+    /// `local`
+    fn inline_annotation(&self, node: Node) -> ParseResult<Option<Comment>> {
+        let statement = match node.parent() {
+            Some(parent) if parent.kind() == "declaration_command" => parent,
+            Some(parent) if self.continued_value(parent).is_some() => parent,
+            _ => node,
+        };
+        Ok(statement
+            .next_sibling()
+            .filter(|sibling| is_inline_comment(*sibling, statement))
+            .map(|comment| self.handle_comment(comment))
+            .transpose()?
+            .flatten()
+            .filter(|comment| comment.kind == CommentKind::Type))
+    }
+
+    /// The rest of a value continued on the next line, as in `name=abc\⏎def`. The grammar ends
+    /// the word at the line continuation, so `command` looks like `def` run with a prefix
+    /// assignment, while bash joins both halves into one assignment
+    fn continued_value<'tree>(&self, command: Node<'tree>) -> Option<Node<'tree>> {
+        if command.kind() != "command" || command.named_child_count() != 2 {
+            return None;
+        }
+        let assignment = command
+            .named_child(0)
+            .filter(|child| child.kind() == "variable_assignment")?;
+        let name = command
+            .named_child(1)
+            .filter(|child| child.kind() == "command_name")?;
+        let between = self
+            .source_code
+            .get(assignment.end_byte()..name.start_byte())?;
+        matches!(between, "\\\n" | "\\\r\n")
+            .then(|| name.named_child(0))
+            .flatten()
+    }
+
+    /// The type of `value` joined with the `rest` continued on the next line. Both halves are
+    /// checked, and the joined literal is recorded when both are known
+    fn continued_value_type(
+        &mut self,
+        name: &str,
+        value: Node,
+        rest: Node,
+    ) -> ParseResult<BashType> {
+        let value_type = self.infer_type(value)?;
+        let rest_type = self.infer_type(rest)?;
+        let literal = match (self.static_text(value)?, self.static_text(rest)?) {
+            (Some(value), Some(rest)) => Some(value + &rest),
+            _ => None,
+        };
+        Ok(match literal {
+            Some(literal) => {
+                let joined_type = if bash_integer(&literal).is_some() {
+                    BashType::Integer
+                } else {
+                    BashType::String
+                };
+                self.set_literal(name, Some(literal));
+                joined_type
+            }
+            None => {
+                self.set_literal(name, None);
+                if value_type == BashType::Integer && rest_type == BashType::Integer {
+                    BashType::Integer
+                } else {
+                    BashType::String
+                }
+            }
+        })
+    }
+
+    /// Treats the `-i` attribute of `declare -i a=1` like an `int` annotation on the flag, or
+    /// `array<int>` when combined with `-a`
+    fn attribute_annotation(&self, node: Node) -> ParseResult<Option<Comment>> {
+        let Some(parent) = node
+            .parent()
+            .filter(|parent| parent.kind() == "declaration_command")
+        else {
+            return Ok(None);
+        };
+        for child in parent.named_children(&mut parent.walk()) {
+            let text = self.node_text(child)?;
+            if child.kind() == "word" && text.starts_with('-') && text.contains('i') {
+                // `-ai` makes the elements ints rather than the array itself
+                let flags = self.declaration_flags(parent)?;
+                let annotation = if flags.contains('A') {
+                    "map<string, int>"
+                } else if flags.contains('a') {
+                    "array<int>"
                 } else {
-                    TypeDeclaration {
-                        bash_type: inferred_type,
-                        range: inferred_location,
-                        method: Method::Inferred,
+                    "int"
+                };
+                return Ok(Some(Comment {
+                    text: String::from(annotation),
+                    range: child.start_byte()..child.end_byte(),
+                    text_range: child.start_byte()..child.end_byte(),
+                    kind: CommentKind::Type,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Records `name` with the annotated type if there is one (checking it against what was
+    /// inferred), or with the inferred type otherwise
+    fn declare_variable(
+        &mut self,
+        name: &str,
+        inferred_type: BashType,
+        inferred_location: Range<usize>,
+        annotation: Option<Comment>,
+        node: Node,
+    ) -> ParseResult<()> {
+        let value = node.child_by_field_name("value");
+        let final_type = if let Some(comment) = annotation {
+            let suggested_type = self.type_from_string(&comment.text, comment.range.clone())?;
+            let inferred_type = self.coerce_numeric_string(
+                &suggested_type,
+                inferred_type,
+                value,
+                codes::TYPE_MISMATCH,
+            )?;
+            if suggested_type.can_contain(&inferred_type) || self.force {
+                let placement = if !self.source_code[comment.range.clone()].starts_with('#') {
+                    AnnotationPlacement::Attribute
+                } else if comment.range.start >= node.start_byte() {
+                    AnnotationPlacement::Inline
+                } else {
+                    AnnotationPlacement::Preceding
+                };
+                if suggested_type.normalized() == inferred_type.normalized() {
+                    self.check_redundant_annotation(&comment, placement, &inferred_location);
+                }
+                TypeDeclaration {
+                    bash_type: suggested_type,
+                    range: combine_ranges(comment.range.clone(), inferred_location),
+                    method: Method::Declared,
+                    node_kind: node.kind().to_owned(),
+                    annotation: Some(AnnotationSpan {
+                        range: comment.range,
+                        type_range: comment.text_range,
+                        placement,
+                    }),
+                    file: None,
+                }
+            } else {
+                let widened = suggested_type.clone().union(inferred_type.clone());
+                let fixes = [
+                    self.widen_fix(&comment.range, &widened),
+                    self.force_fix(node),
+                ];
+                let mut diagnostic = Diagnostic::new("Types do not match", node.start_byte())
+                    .with_code(codes::TYPE_MISMATCH)
+                    .with_label(
+                        Label::new(
+                            comment.text_range,
+                            "Type specified as",
+                            LabelKind::Specified,
+                        )
+                        .with_type(suggested_type),
+                    )
+                    .with_label(
+                        Label::new(
+                            inferred_location,
+                            "Type inferred to be",
+                            LabelKind::Inferred,
+                        )
+                        .with_type(inferred_type),
+                    );
+                for fix in fixes.into_iter().flatten() {
+                    diagnostic = diagnostic.with_fix(fix);
+                }
+                self.errors.push(diagnostic);
+                // Reads of a variable that was never recorded would only repeat this error
+                if !self.variables.contains_key(name) {
+                    self.reported_variables
+                        .entry(name.to_owned())
+                        .or_insert(self.taken_errors + self.errors.len() - 1);
+                }
+                return Ok(());
+            }
+        } else {
+            let inferred_type = match self.variables.get(name).map(|previous| &previous.bash_type) {
+                Some(previous) => self.coerce_numeric_string(
+                    &previous.clone(),
+                    inferred_type,
+                    value,
+                    codes::REDEFINED_VARIABLE,
+                )?,
+                None => inferred_type,
+            };
+            TypeDeclaration {
+                bash_type: inferred_type,
+                range: inferred_location,
+                method: Method::Inferred,
+                node_kind: node.kind().to_owned(),
+                annotation: None,
+                file: None,
+            }
+        };
+        self.set_variable(name, final_type, node);
+        Ok(())
+    }
+
+    /// Hints that an annotation matching the inferred type exactly can be removed, in pedantic
+    /// mode
+    fn check_redundant_annotation(
+        &mut self,
+        comment: &Comment,
+        placement: AnnotationPlacement,
+        inferred_location: &Range<usize>,
+    ) {
+        if !self
+            .config
+            .rule_enabled(codes::REDUNDANT_ANNOTATION, self.config.pedantic)
+        {
+            return;
+        }
+        let Some(fix) = self.remove_annotation_fix(&comment.range, placement) else {
+            return;
+        };
+        self.errors.push(
+            Diagnostic::new(
+                "Annotation is the same as the inferred type",
+                comment.range.start,
+            )
+            .with_code(codes::REDUNDANT_ANNOTATION)
+            .with_severity(Severity::Advice)
+            .with_label(Label::new(
+                comment.text_range.clone(),
+                "This annotation can be removed",
+                LabelKind::Specified,
+            ))
+            .with_label(Label::new(
+                inferred_location.clone(),
+                "Type inferred to be the same",
+                LabelKind::Inferred,
+            ))
+            .with_fix(fix),
+        );
+    }
+
+    /// Forgets variables removed with `unset`, so they can be declared again with a new type
+    fn handle_unset(&mut self, node: Node) -> ParseResult<()> {
+        let mut unset_nameref = false;
+        for argument in node.named_children(&mut node.walk()) {
+            match argument.kind() {
+                "word" => match self.node_text(argument)? {
+                    // Functions aren't tracked
+                    "-f" => return Ok(()),
+                    "-n" => unset_nameref = true,
+                    _ => (),
+                },
+                "variable_name" => {
+                    let name = self.node_text(argument)?;
+                    let name = if unset_nameref {
+                        if self.namerefs.remove(name).is_some() {
+                            continue;
+                        }
+                        name
+                    } else {
+                        match self.resolve_name(name) {
+                            Some(name) => name,
+                            None => continue,
+                        }
                     }
+                    .to_owned();
+                    self.variables.remove(&name);
+                    self.set_literal(&name, None);
+                    self.history.remove(&name);
+                    self.merges.retain(|merge| merge.name != name);
+                    self.narrowed.remove(&name);
+                    self.conditionally_set.remove(&name);
+                    self.unset_variables.insert(name);
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_declaration(&mut self, node: Node, annotation: Option<Comment>) -> ParseResult<()> {
+        let flags = self.declaration_flags(node)?;
+        let keyword = node
+            .child(0)
+            .map(|child| self.node_text(child))
+            .transpose()?;
+        // `export -n` removes the export instead
+        if (keyword == Some("export") && !flags.contains('n')) || flags.contains('x') {
+            for child in node.named_children(&mut node.walk()) {
+                let name = match child.kind() {
+                    "variable_name" => child,
+                    "variable_assignment" => match child.child_by_field_name("name") {
+                        Some(name) => name,
+                        None => continue,
+                    },
+                    _ => continue,
                 };
-                self.set_variable(name, final_type, cursor);
+                self.exports
+                    .entry(self.node_text(name)?.to_owned())
+                    .or_insert(node.start_byte()..node.end_byte());
+            }
+        }
+        self.record_locals(node, keyword)?;
+        if flags.contains('A') {
+            self.require_bash_version("associative arrays", BashVersion::new(4, 0), node);
+        }
+        if flags.contains('n') {
+            self.require_bash_version("namerefs", BashVersion::new(4, 3), node);
+        }
+        // Names declared without a value, as in `declare -i count`
+        let element_type = if flags.contains('i') {
+            BashType::Integer
+        } else {
+            BashType::Unknown
+        };
+        let bash_type = if flags.contains('A') {
+            BashType::Map(Box::new(BashType::String), Box::new(element_type))
+        } else if flags.contains('a') {
+            BashType::Array(Box::new(element_type))
+        } else if flags.contains('i') {
+            BashType::Integer
+        } else {
+            BashType::Unknown
+        };
+        let has_assignment = node
+            .named_children(&mut node.walk())
+            .any(|child| child.kind() == "variable_assignment");
+        // Otherwise the annotation is used by the assignment
+        let annotation = if has_assignment {
+            None
+        } else {
+            self.inline_annotation(node)?.or(annotation)
+        };
+        if bash_type == BashType::Unknown && annotation.is_none() {
+            return Ok(());
+        }
+        // The flags giving the type, as in `-ai`
+        let flag = node
+            .named_children(&mut node.walk())
+            .filter(|child| child.kind() == "word")
+            .find(|child| {
+                self.node_text(*child)
+                    .is_ok_and(|text| text.starts_with('-') && text.contains(['a', 'A', 'i']))
+            })
+            .map(|flag| flag.start_byte()..flag.end_byte());
+        for child in node.named_children(&mut node.walk()) {
+            if child.kind() == "variable_name" {
+                let location = node.start_byte()..node.end_byte();
+                match &annotation {
+                    // `#/ map<string, int>` before `declare -A ages`
+                    Some(_) => self.declare_variable(
+                        self.node_text(child)?,
+                        bash_type.clone(),
+                        location,
+                        annotation.clone(),
+                        node,
+                    )?,
+                    None => {
+                        let declaration = TypeDeclaration {
+                            bash_type: bash_type.clone(),
+                            range: location,
+                            method: Method::Declared,
+                            node_kind: node.kind().to_owned(),
+                            annotation: flag.clone().map(|flag| AnnotationSpan {
+                                range: flag.clone(),
+                                type_range: flag,
+                                placement: AnnotationPlacement::Attribute,
+                            }),
+                            file: None,
+                        };
+                        self.set_variable(self.node_text(child)?, declaration, node);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Remembers the variables a function declares with `local`, `declare` or `typeset`, which
+    /// keeps any change to them inside the function
+    fn record_locals(&mut self, node: Node, keyword: Option<&str>) -> ParseResult<()> {
+        let Some(function) = self.function.clone() else {
+            return Ok(());
+        };
+        let is_global = matches!(keyword, Some("export" | "readonly"))
+            || self.declaration_flags(node)?.contains('g');
+        if is_global {
+            return Ok(());
+        }
+        for child in node.named_children(&mut node.walk()) {
+            let name = match child.kind() {
+                "variable_name" => child,
+                "variable_assignment" => match child.child_by_field_name("name") {
+                    Some(name) => name,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let name = self.node_text(name)?.to_owned();
+            self.locals
+                .entry(function.clone())
+                .or_default()
+                .insert(name);
+        }
+        Ok(())
+    }
+
+    /// Whether the function being analyzed declares `name` local
+    fn is_local(&self, name: &str) -> bool {
+        self.function
+            .as_ref()
+            .and_then(|function| self.locals.get(function))
+            .is_some_and(|locals| locals.contains(name))
+    }
+
+    fn handle_node(&mut self, node: Node, annotation: Option<Comment>) -> ParseResult<()> {
+        match node.kind() {
+            "comment" => {
+                if let Some(comment) = self.handle_comment(node)? {
+                    if comment.kind == CommentKind::Command {
+                        self.handle_annotation_command(&comment.text, node)?;
+                    }
+                }
+            }
+            "variable_assignment" => {
+                self.record_references(node)?;
+                // `IFS= read -r line` only sets `IFS` for `read`
+                if node.parent().is_some_and(|parent| {
+                    parent.kind() == "command" && self.continued_value(parent).is_none()
+                }) {
+                    self.handle_prefix_assignment(node)?;
+                } else {
+                    self.track_ifs(node)?;
+                    self.handle_assignment(node, annotation)?;
+                }
+            }
+            "declaration_command" => {
+                self.record_references(node)?;
+                self.handle_declaration(node, annotation)?;
+            }
+            "unset_command" => {
+                self.handle_unset(node)?;
+                self.track_ifs(node)?;
+            }
+            "function_definition" => self.handle_function_definition(node)?,
+            "command" => {
+                self.record_references(node)?;
+                if self.continued_value(node).is_none() {
+                    self.forget_assigned_literals(node)?;
+                    self.handle_command(node)?;
+                }
+            }
+            "for_statement" => self.handle_loop(node, annotation)?,
+            "if_statement" | "elif_clause" | "while_statement" => self.check_conditions(node)?,
+            "test_command" => {
+                if self.node_text(node)?.starts_with("((") {
+                    self.forget_arithmetic_literals(node)?;
+                }
+                self.check_unquoted(node)?;
+            }
+            "redirected_statement" => {
+                self.handle_descriptor_variables(node)?;
+                self.handle_heredocs(node, annotation)?;
+            }
+            "list" => self.handle_list(node)?,
+            "process_substitution" | "file_redirect" | "herestring_redirect" => {
+                self.check_redirect(node)?
+            }
+            "number" => self.check_arithmetic_literal(node)?,
+            "arithmetic_expansion" => {
+                self.forget_arithmetic_literals(node)?;
+                self.check_arithmetic_operands(node)?;
+            }
+            "simple_expansion" => {
+                self.check_status_read(node)?;
+                self.check_job_read(node)?;
+                self.check_array_context(node)?;
+                self.check_nameref_target(node)?;
+            }
+            "binary_expression" => {
+                self.check_status_comparison(node)?;
+                self.check_comparison_operator(node)?;
+                self.check_division(node)?;
+            }
+            "expansion" => {
+                self.check_status_read(node)?;
+                self.check_default_assignment(node)?;
+                self.check_array_context(node)?;
+                self.check_nameref_target(node)?;
+                self.check_record_key_read(node)?;
+                let transformation = node
+                    .children(&mut node.walk())
+                    .skip_while(|child| child.kind() != "@")
+                    .nth(1);
+                if let Some(transformation) = transformation {
+                    let required = match transformation.kind() {
+                        "U" | "u" | "L" | "K" => BashVersion::new(5, 1),
+                        "k" => BashVersion::new(5, 2),
+                        _ => BashVersion::new(4, 4),
+                    };
+                    self.require_bash_version(
+                        &format!("the @{} transformation", transformation.kind()),
+                        required,
+                        node,
+                    );
+                }
             }
             _ => (),
         }
         Ok(())
     }
 
+    /// Warns about literals like `08` that bash arithmetic rejects, since a leading zero means
+    /// octal
+    fn check_arithmetic_literal(&mut self, node: Node) -> ParseResult<()> {
+        let text = self.node_text(node)?;
+        if !arithmetic::in_arithmetic(node) || bash_integer(text).is_some() {
+            return Ok(());
+        }
+        let message = if text.trim_start_matches(['-', '+']).starts_with('0') {
+            "Invalid octal literal"
+        } else {
+            "Invalid integer literal"
+        };
+        self.errors.push(
+            Diagnostic::new(message, node.start_byte())
+                .with_code(codes::INVALID_INTEGER)
+                .with_severity(Severity::Warning)
+                .with_label(Label::new(
+                    node.start_byte()..node.end_byte(),
+                    "Bash arithmetic fails on this value",
+                    LabelKind::ParseErr,
+                )),
+        );
+        Ok(())
+    }
+
+    fn handle_command(&mut self, node: Node) -> ParseResult<()> {
+        let Some(command_name) = node.child_by_field_name("name") else {
+            return Ok(());
+        };
+        match self.node_text(command_name)? {
+            "source" | "." => self.handle_source(node)?,
+            "exit" | "return" => self.check_exit_status(node)?,
+            "trap" => self.handle_trap(node)?,
+            "eval" => self.handle_eval(node)?,
+            "read" => self.handle_read(node)?,
+            "wait" => self.handle_wait(node)?,
+            "coproc" => self.handle_coproc(node)?,
+            "@test" if self.bats => self.start_bats_test(node)?,
+            "load" if self.bats => self.handle_load(node)?,
+            #[cfg(feature = "zsh")]
+            _ if self.config.dialect == Dialect::Zsh => self.handle_zsh_command(node)?,
+            _ => (),
+        }
+        self.record_command_name(node)?;
+        self.record_function_call(node)?;
+        self.record_output(node)?;
+        self.check_unquoted(node)?;
+        self.check_command_arguments(node)
+    }
+
+    /// Returns the value of a word or quoted string that contains no expansions
+    fn static_text(&self, node: Node) -> ParseResult<Option<String>> {
+        let text = self.node_text(node)?;
+        Ok(match node.kind() {
+            "word" | "number" => Some(text.to_owned()),
+            "raw_string" => Some(text.trim_matches('\'').to_owned()),
+            "string" => node
+                .named_children(&mut node.walk())
+                .all(|child| child.kind() == "string_content")
+                .then(|| {
+                    let inner = text.strip_prefix('"').unwrap_or(text);
+                    unescape_double_quoted(inner.strip_suffix('"').unwrap_or(inner))
+                }),
+            _ => None,
+        })
+    }
+
+    /// Returns every single letter option passed to a command such as `declare -gA`
+    fn declaration_flags(&self, node: Node) -> ParseResult<String> {
+        let mut flags = String::new();
+        for child in node.named_children(&mut node.walk()) {
+            if child.kind() == "word" {
+                if let Some(child_flags) = self.node_text(child)?.strip_prefix('-') {
+                    flags.push_str(child_flags);
+                }
+            }
+        }
+        Ok(flags)
+    }
+
+    fn require_bash_version(&mut self, feature: &str, required: BashVersion, node: Node) {
+        let Some(minimum) = self.bash_version else {
+            return;
+        };
+        if self.config.dialect != Dialect::Bash {
+            return;
+        }
+        if minimum < required {
+            self.errors.push(
+                Diagnostic::new(
+                    format!("Using {feature} requires bash {required}"),
+                    node.start_byte(),
+                )
+                .with_code(codes::UNSUPPORTED_VERSION)
+                .with_label(Label::new(
+                    node.start_byte()..node.end_byte(),
+                    format!("Scripts are expected to support bash {minimum}"),
+                    LabelKind::Unsupported,
+                )),
+            );
+        }
+    }
+
+    /// Applies the `#![...]` directives at the top level of the file before anything else is
+    /// checked, since they affect the file as a whole
+    fn handle_directives(&mut self, root: Node) {
+        for child in root.named_children(&mut root.walk()) {
+            let Ok(Some(comment)) = self.handle_comment(child) else {
+                continue;
+            };
+            if comment.kind != CommentKind::Directive {
+                continue;
+            }
+            match directive_version(&comment.text) {
+                Some(version) => self.bash_version = Some(version),
+                None => self.errors.push(
+                    Diagnostic::new("Error while parsing comment", child.start_byte())
+                        .with_code(codes::INVALID_ANNOTATION)
+                        .with_label(Label::new(
+                            comment.range,
+                            ParseErrType::InvalidDirective(comment.text).to_string(),
+                            LabelKind::ParseErr,
+                        )),
+                ),
+            }
+        }
+    }
+
+    /// Adds the children of a node to the worklist so they are visited in source order, attaching
+    /// each `#/` annotation written on its own line to the statement that follows it
+    fn queue_children<'tree>(
+        &mut self,
+        parent: &WorkItem<'tree>,
+        worklist: &mut Vec<WorkItem<'tree>>,
+    ) {
+        let node = parent.node;
+        if node.kind() == "heredoc_body" && self.is_oversized_heredoc(node) {
+            return;
+        }
+        // An annotation before `local a=1` describes the assignment inside it
+        let mut inherited = parent.annotation.clone().filter(|_| {
+            node.kind() == "declaration_command" || self.continued_value(node).is_some()
+        });
+        let mut children = Vec::new();
+        let mut annotation = None;
+        let mut force = parent.force;
+        let mut previous = None;
+        let function = match node.kind() {
+            "function_definition" => node
+                .child_by_field_name("name")
+                .and_then(|name| self.node_text(name).ok())
+                .map(str::to_owned),
+            _ => parent.function.clone(),
+        };
+        let backgrounded = node
+            .children(&mut node.walk())
+            .zip(node.children(&mut node.walk()).skip(1))
+            .filter(|(_, next)| next.kind() == "&")
+            .map(|(statement, _)| statement.id())
+            .collect::<Vec<_>>();
+        for child in node.named_children(&mut node.walk()) {
+            if child.kind() == "comment" {
+                // Inline comments belong to the statement before them. Errors are ignored here
+                // since they are reported when the comment itself is visited
+                let is_inline = previous.is_some_and(|previous| is_inline_comment(child, previous));
+                match self.handle_comment(child) {
+                    Ok(Some(comment))
+                        if comment.kind == CommentKind::Type
+                            && is_inline
+                            && !previous.is_some_and(|previous| self.uses_annotation(previous))
+                            // `cat <<EOF #/ heredoc(json)`
+                            && !(node.kind() == "heredoc_redirect"
+                                && heredoc_annotation(&comment.text).is_some()) =>
+                    {
+                        self.push_unused_annotation(
+                            "Annotation on a line without an assignment",
+                            &comment,
+                            None,
+                        );
+                    }
+                    Ok(Some(comment)) if !is_inline => match comment.kind {
+                        CommentKind::Type => {
+                            if let Some(shadowed) = annotation.replace(comment.clone()) {
+                                self.push_unused_annotation(
+                                    "Annotation is overridden by a closer one",
+                                    &shadowed,
+                                    Some(&comment),
+                                );
+                            }
+                        }
+                        CommentKind::Command if comment.text == "force" => force = true,
+                        _ => (),
+                    },
+                    _ => (),
+                }
+                children.push(WorkItem {
+                    node: child,
+                    annotation: None,
+                    force,
+                    function: function.clone(),
+                    background: false,
+                });
+            } else {
+                previous = Some(child);
+                if let Some(comment) = annotation.take_if(|comment| {
+                    !self.config.detached_annotations && self.is_detached(comment, child)
+                }) {
+                    self.errors.push(
+                        unused_annotation("Annotation does not attach to anything", &comment, None)
+                            .with_help(
+                                "Remove the blank lines between the annotation and the statement, \
+                                 or set `detached_annotations = true`",
+                            ),
+                    );
+                }
+                if let Some(comment) = &annotation {
+                    self.check_annotated_statement(comment, child);
+                }
+                let annotation = annotation
+                    .take()
+                    .or_else(|| inherited.take_if(|_| child.kind() == "variable_assignment"));
+                children.push(WorkItem {
+                    node: child,
+                    annotation,
+                    force,
+                    function: function.clone(),
+                    background: backgrounded.contains(&child.id()),
+                });
+                force = parent.force;
+            }
+        }
+        if let Some(comment) = annotation {
+            self.push_unused_annotation(
+                "Annotation is not followed by an assignment",
+                &comment,
+                None,
+            );
+        }
+        worklist.extend(children.into_iter().rev());
+    }
+
+    /// Warns when the annotation on the line before `statement` is ignored, either because the
+    /// statement doesn't assign anything or because it has an inline annotation of its own
+    fn check_annotated_statement(&mut self, comment: &Comment, statement: Node) {
+        if heredoc_annotation(&comment.text).is_some() && statement.kind() == "redirected_statement"
+        {
+            return;
+        }
+        if !self.uses_annotation(statement) {
+            self.push_unused_annotation(
+                "Annotation is not followed by an assignment",
+                comment,
+                None,
+            );
+            return;
+        }
+        let inline = statement
+            .next_sibling()
+            .filter(|sibling| is_inline_comment(*sibling, statement))
+            .and_then(|sibling| self.handle_comment(sibling).ok().flatten())
+            .filter(|inline| inline.kind == CommentKind::Type);
+        if let Some(inline) = inline {
+            self.push_unused_annotation(
+                "Annotation is overridden by a closer one",
+                comment,
+                Some(&inline),
+            );
+        }
+    }
+
+    /// Whether a `#/` annotation before or after `statement` gives a variable its type
+    fn uses_annotation(&self, statement: Node) -> bool {
+        matches!(
+            statement.kind(),
+            "variable_assignment" | "declaration_command" | "for_statement"
+        ) || self.continued_value(statement).is_some()
+    }
+
+    /// Whether a blank line separates an annotation from the statement after it
+    fn is_detached(&self, comment: &Comment, statement: Node) -> bool {
+        self.source_code
+            .get(comment.range.end..statement.start_byte())
+            .is_some_and(|between| {
+                let lines = between.split('\n').collect::<Vec<_>>();
+                lines.len() > 2
+                    && lines[1..lines.len() - 1]
+                        .iter()
+                        .any(|line| line.trim().is_empty())
+            })
+    }
+
+    fn push_unused_annotation(
+        &mut self,
+        message: &str,
+        comment: &Comment,
+        closer: Option<&Comment>,
+    ) {
+        let diagnostic = unused_annotation(message, comment, closer);
+        self.errors.push(diagnostic);
+    }
+
+    pub fn reports(&self) -> impl Iterator<Item = Report<'static>> + '_ {
+        let config = &self.config;
+        let index = SourceIndex::new(self.source_code);
+        self.errors
+            .iter()
+            .map(move |error| error.report(config, &index))
+    }
+
     pub fn parse_code(&mut self) {
-        let tree = self
-            .parser
+        self.analyze(&mut |_| ());
+    }
+
+    /// Like [`FileInfo::parse_code`], but hands the diagnostics to `emit` instead of keeping them
+    /// in `errors`. With a `memory_limit`, the diagnostics of each chunk are handed over once it is
+    /// analyzed, and the ones only found after the whole file come last
+    pub fn parse_code_streaming(&mut self, mut emit: impl FnMut(Vec<Diagnostic>)) {
+        self.analyze(&mut |file| emit(file.take_errors()));
+        emit(self.take_errors());
+    }
+
+    /// Analyzes the file, calling `chunk_analyzed` after each chunk when it is analyzed in chunks
+    fn analyze(&mut self, chunk_analyzed: &mut dyn FnMut(&mut Self)) {
+        enter_span!(DEBUG, "analysis", bytes = self.source_code.len());
+        match self.config.memory_limit {
+            Some(memory_limit) => self.analyze_chunks(memory_limit, chunk_analyzed),
+            None => {
+                let tree = self.parse_tree();
+                self.analyze_tree(&tree);
+            }
+        }
+        // Code analyzed on its own, like a trap handler, can call functions defined after it
+        self.run_rule("command_names", Self::check_command_names);
+        self.run_rule("function_calls", Self::check_function_calls);
+        self.run_rule("record_keys", Self::check_record_keys);
+        self.run_rule("ifs_restored", Self::check_ifs_restored);
+        self.run_rule("encoding", Self::check_encoding);
+        self.apply_rule_config();
+        self.errors
+            .sort_by(|a, b| a.sort_key(()).cmp(&b.sort_key(())));
+    }
+
+    /// Takes the diagnostics found so far, with the rules of the config applied
+    fn take_errors(&mut self) -> Vec<Diagnostic> {
+        self.taken_errors += self.errors.len();
+        let mut errors = std::mem::take(&mut self.errors);
+        self.config.apply_rules(&mut errors);
+        errors.sort_by(|a, b| a.sort_key(()).cmp(&b.sort_key(())));
+        errors
+    }
+
+    fn parse_tree(&mut self) -> Tree {
+        self.parser
             .parse(self.source_code, None)
-            .expect("treesitter to parse valid code");
-        let root_node = tree.root_node();
-        let mut cursor = root_node.walk();
-
-        loop {
-            if let Err(e) = self.handle_node(&mut cursor, None) {
-                self.errors.push(
-                    Report::build(ReportKind::Error, (), cursor.node().start_byte())
-                        .with_message("Error while parsing comment")
-                        .with_label(
-                            Label::new(e.start..e.end)
-                                .with_message(e.err_type)
-                                .with_color(self.config.parse_err_color),
-                        )
-                        .finish(),
-                );
+            .expect("treesitter to parse valid code")
+    }
+
+    /// Whether the comments at the top of the file contain `# bashtyped: off`, opting the whole
+    /// file out of checking
+    fn is_disabled(&self) -> bool {
+        self.source_code
+            .lines()
+            .map(str::trim)
+            .take_while(|line| line.is_empty() || line.starts_with('#'))
+            .filter_map(|line| line.strip_prefix('#'))
+            .any(|comment| {
+                comment
+                    .trim()
+                    .strip_prefix("bashtyped:")
+                    .is_some_and(|setting| setting.trim() == "off")
+            })
+    }
+
+    fn analyze_tree(&mut self, tree: &Tree) {
+        if self.is_disabled() {
+            return;
+        }
+        self.handle_directives(tree.root_node());
+        self.detect_bats(tree.root_node());
+        self.analyze_statements(tree);
+    }
+
+    /// Visits every node of `tree`, then infers the statuses of its functions and applies their
+    /// annotations
+    fn analyze_statements(&mut self, tree: &Tree) {
+        let mut worklist = vec![WorkItem {
+            node: tree.root_node(),
+            annotation: None,
+            force: false,
+            function: None,
+            background: false,
+        }];
+
+        while let Some(item) = worklist.pop() {
+            let node = item.node;
+            enter_span!(
+                TRACE,
+                "node",
+                kind = node.kind(),
+                line = node.start_position().row + 1
+            );
+            let reported = self.errors.len();
+            self.end_short_circuits(node.start_byte());
+            self.end_background_jobs(node.start_byte());
+            self.end_literal_joins(node.start_byte());
+            self.force = item.force;
+            if item.background {
+                if let Err(e) = self.start_background_job(node) {
+                    self.push_parse_error(e, node);
+                }
+            }
+            self.function.clone_from(&item.function);
+            self.start_literal_join(node);
+            if let Err(e) = self.handle_node(node, item.annotation.clone()) {
+                self.push_parse_error(e, node);
             }
+            self.queue_children(&item, &mut worklist);
+            self.log_diagnostics(reported);
+        }
+        self.end_short_circuits(usize::MAX);
+        self.end_background_jobs(usize::MAX);
+        self.end_literal_joins(usize::MAX);
+        if let Err(e) = self.infer_statuses(tree.root_node()) {
+            self.push_parse_error(e, tree.root_node());
+        }
 
-            if cursor.goto_first_child() {
-                continue;
+        for annotation in std::mem::take(&mut self.function_annotations) {
+            let comment = tree
+                .root_node()
+                .descendant_for_byte_range(annotation.range.start, annotation.range.end)
+                .expect("annotation to be in the tree");
+            self.force = annotation.force;
+            let result = match annotation.command.strip_prefix("returns ") {
+                Some(return_type) => self.handle_returns_annotation(return_type, comment),
+                None => {
+                    self.handle_params_annotation(&annotation.command["params ".len()..], comment)
+                }
+            };
+            if let Err(e) = result {
+                self.push_parse_error(e, comment);
             }
+        }
+    }
 
-            while !cursor.goto_next_sibling() {
-                if !cursor.goto_parent() {
-                    return;
+    fn push_parse_error(&mut self, e: ParseError, node: Node) {
+        let variable = match &e.err_type {
+            ParseErrType::UnknownVariable(name)
+            | ParseErrType::PossiblyUnset(name)
+            | ParseErrType::ConditionallySet(name)
+            | ParseErrType::SetInBackground(name) => Some(name),
+            ParseErrType::UnknownNamerefTarget { target, .. } => Some(target),
+            _ => None,
+        };
+        if let Some(variable) = variable {
+            if self.is_allowed_variable(e.err_type.code(), variable) {
+                return;
+            }
+            if let Some(&index) = self.reported_variables.get(variable) {
+                // The error may already have been handed over by `parse_code_streaming`
+                let reported = index
+                    .checked_sub(self.taken_errors)
+                    .and_then(|index| self.errors.get_mut(index));
+                // Values are typed both with their assignment and where the expansion is
+                if let Some(reported) = reported.filter(|reported| {
+                    !reported
+                        .labels
+                        .iter()
+                        .any(|label| label.range == (e.start..e.end))
+                }) {
+                    reported.labels.push(Label::new(
+                        e.start..e.end,
+                        "Also used here",
+                        LabelKind::ParseErr,
+                    ));
                 }
+                return;
             }
-            self.force = false;
+            self.reported_variables
+                .insert(variable.clone(), self.taken_errors + self.errors.len());
         }
+        self.errors.push(e.into_diagnostic(node.start_byte()));
     }
 
-    fn set_variable(&mut self, name: &str, final_type: TypeDeclaration, cursor: &TreeCursor) {
-        if let Some(previous_type) = self.variables.get(name) {
-            if !final_type.bash_type.can_contain(&previous_type.bash_type) && !self.force {
-                self.errors.push(
-                    Report::build(ReportKind::Error, (), cursor.node().start_byte())
-                        .with_message(format!("Variable {name} defined with different type"))
-                        .with_label(label_from_type_declaration(
-                            previous_type,
-                            &self.config,
-                            false,
-                        ))
-                        .with_label(label_from_type_declaration(&final_type, &self.config, true))
-                        .finish(),
-                );
+    /// Records a declaration or assignment of `name`. A declared type has to be kept by later
+    /// assignments, while a new declaration or an inferred type has to contain the old one
+    fn set_variable(&mut self, name: &str, final_type: TypeDeclaration, node: Node) {
+        log_event!(
+            DEBUG,
+            name,
+            bash_type = %final_type.bash_type,
+            method = ?final_type.method,
+            "Variable set"
+        );
+        self.check_union_size(name, &final_type);
+        self.history
+            .entry(name.to_owned())
+            .or_default()
+            .push(final_type.clone());
+        if !self.is_local(name) {
+            self.globals.insert(name.to_owned());
+        }
+        self.conditionally_set.remove(name);
+        self.background_assignments.remove(name);
+        let Some(previous_type) = self.variables.get(name) else {
+            self.unset_variables.remove(name);
+            self.narrowed.remove(name);
+            self.variables.insert(name.to_owned(), final_type);
+            return;
+        };
+        let keeps_declaration =
+            previous_type.method == Method::Declared && final_type.method == Method::Inferred;
+        let compatible = if keeps_declaration {
+            previous_type.bash_type.can_contain(&final_type.bash_type)
+        } else {
+            final_type.bash_type.can_contain(&previous_type.bash_type)
+        };
+        if !compatible && !self.force {
+            let widened = previous_type
+                .bash_type
+                .clone()
+                .union(final_type.bash_type.clone());
+            let mut diagnostic = Diagnostic::new(
+                format!("Variable {name} defined with different type"),
+                node.start_byte(),
+            )
+            .with_code(codes::REDEFINED_VARIABLE)
+            .with_label(label_from_type_declaration(previous_type, false))
+            .with_label(label_from_type_declaration(&final_type, true))
+            .with_help(format!(
+                "Annotate the earlier declaration with `#/ {widened}` to allow both, or put \
+                 `#[force]` before this one to skip the check"
+            ));
+            // Declarations copied into embedded code point into the outer file
+            if let Some((line, text)) = self.line_at(previous_type.range.start) {
+                diagnostic = diagnostic.with_note(format!(
+                    "{name} was declared on line {line}: {}",
+                    text.trim()
+                ));
             }
+            self.errors.push(diagnostic);
+        }
+        let narrows = self.config.narrow_on_assignment
+            && keeps_declaration
+            && compatible
+            && !matches!(final_type.bash_type, BashType::Any | BashType::Unknown);
+        if narrows {
+            self.narrowed
+                .insert(name.to_owned(), final_type.bash_type.clone());
         } else {
+            self.narrowed.remove(name);
+        }
+        if !keeps_declaration || !compatible {
             self.variables.insert(name.to_owned(), final_type);
         }
     }
+
+    /// Hints that a variable's type is a union too large to read easily, unless the variable
+    /// already had that type
+    fn check_union_size(&mut self, name: &str, final_type: &TypeDeclaration) {
+        let max_members = self.config.max_union_members();
+        let size = final_type.bash_type.union_size();
+        if size <= max_members
+            || !self.config.rule_enabled(codes::LARGE_UNION, true)
+            || self
+                .variables
+                .get(name)
+                .is_some_and(|previous| previous.bash_type == final_type.bash_type)
+        {
+            return;
+        }
+        self.errors.push(
+            Diagnostic::new(
+                format!("The type of {name} is a union of {size} types"),
+                final_type.range.start,
+            )
+            .with_code(codes::LARGE_UNION)
+            .with_severity(Severity::Advice)
+            .with_label(label_from_type_declaration(final_type, false))
+            .with_help(format!(
+                "Unions of more than {max_members} types are hard to read in reports. Consider \
+                 a named type alias, or a narrower type"
+            )),
+        );
+    }
+
+    /// The 1-based line number containing `offset`, and the text of that line
+    fn line_at(&self, offset: usize) -> Option<(usize, &str)> {
+        let before = self.source_code.get(..offset)?;
+        let start = before.rfind('\n').map_or(0, |i| i + 1);
+        let end = self.source_code[offset..]
+            .find('\n')
+            .map_or(self.source_code.len(), |i| offset + i);
+        let (line, _) = self.source_index().line_column(offset);
+        Some((line, &self.source_code[start..end]))
+    }
+
+    /// An index of the lines of the file, built the first time it is needed
+    pub(crate) fn source_index(&self) -> &SourceIndex<'_> {
+        self.source_index
+            .get_or_init(|| SourceIndex::new(self.source_code))
+    }
+
+    /// The declarations of `name` before the latest one
+    pub fn previous_declarations(&self, name: &str) -> &[TypeDeclaration] {
+        self.history
+            .get(name)
+            .map_or(&[], |history| &history[..history.len().saturating_sub(1)])
+    }
+}
+
+fn unused_annotation(message: &str, comment: &Comment, closer: Option<&Comment>) -> Diagnostic {
+    let mut diagnostic = Diagnostic::new(message, comment.range.start)
+        .with_code(codes::UNUSED_ANNOTATION)
+        .with_severity(Severity::Warning)
+        .with_label(Label::new(
+            comment.range.clone(),
+            "This annotation is ignored",
+            LabelKind::Unknown,
+        ));
+    if let Some(closer) = closer {
+        diagnostic = diagnostic.with_label(Label::new(
+            closer.range.clone(),
+            "This annotation is used instead",
+            LabelKind::Specified,
+        ));
+    }
+    diagnostic
 }
 
-fn label_from_type_declaration(
-    decl_type: &TypeDeclaration,
-    config: &Config,
-    is_later: bool,
-) -> Label {
-    let (color, description) = match decl_type.method {
-        Method::Inferred => (config.inferred_color, "inferred"),
-        Method::Declared => (config.specified_color, "declared"),
+fn label_from_type_declaration(decl_type: &TypeDeclaration, is_later: bool) -> Label {
+    let (kind, description) = match decl_type.method {
+        Method::Inferred => (LabelKind::Inferred, "inferred"),
+        Method::Declared => (LabelKind::Specified, "declared"),
     };
-    Label::new(decl_type.range.clone())
-        .with_message(format!(
-            "Type {}{} to be {}",
-            is_later.then_some("later ").unwrap_or_default(),
+    Label::new(
+        decl_type.range.clone(),
+        format!(
+            "Type {}{} to be",
+            if is_later { "later " } else { "" },
             description,
-            decl_type.bash_type.clone().fg(color)
-        ))
-        .with_color(color)
+        ),
+        kind,
+    )
+    .with_type(decl_type.bash_type.clone())
+    .in_file(decl_type.file)
+}
+
+/// The version required by a `#![bash >= 4.4]` directive
+fn directive_version(directive: &str) -> Option<BashVersion> {
+    directive
+        .strip_prefix("bash")
+        .and_then(|version| version.trim_start().strip_prefix(">="))
+        .and_then(|version| version.parse().ok())
+}
+
+/// The type of an unquoted literal like the `3` in `[key]=3`
+fn literal_type(value: &str) -> BashType {
+    if bash_integer(value).is_some() {
+        BashType::Integer
+    } else {
+        BashType::String
+    }
+}
+
+/// Parses an integer the way bash arithmetic does, so `0xff` is hex, `0755` is octal and
+/// `2#101` is binary
+fn bash_integer(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (base, digits) = if let Some((base, digits)) = digits.split_once('#') {
+        (
+            base.parse().ok().filter(|base| (2..=64).contains(base))?,
+            digits,
+        )
+    } else if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        (16, hex)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        (8, &digits[1..])
+    } else {
+        (10, digits)
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let mut value: i64 = 0;
+    for c in digits.chars() {
+        let digit = match c {
+            '0'..='9' => c as u32 - '0' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 10,
+            // Upper case letters are only different from lower case ones above base 36
+            'A'..='Z' if base <= 36 => c as u32 - 'A' as u32 + 10,
+            'A'..='Z' => c as u32 - 'A' as u32 + 36,
+            '@' => 62,
+            '_' => 63,
+            _ => return None,
+        };
+        if digit >= base {
+            return None;
+        }
+        value = value
+            .wrapping_mul(i64::from(base))
+            .wrapping_add(i64::from(digit));
+    }
+    Some(if negative {
+        value.wrapping_neg()
+    } else {
+        value
+    })
+}
+
+fn is_inline_comment(comment: Node, statement: Node) -> bool {
+    comment.kind() == "comment" && comment.start_position().row == statement.end_position().row
 }
 
 fn combine_ranges(r1: Range<usize>, r2: Range<usize>) -> Range<usize> {
@@ -434,3 +2382,23 @@ fn combine_ranges(r1: Range<usize>, r2: Range<usize>) -> Range<usize> {
         end: r2.end.max(r1.end),
     }
 }
+
+/// The value of the text between double quotes, where a backslash only escapes `$`, `` ` ``,
+/// `"`, `\` and a newline, which it removes along with itself
+pub(crate) fn unescape_double_quoted(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some('\n')) => {
+                chars.next();
+            }
+            ('\\', Some(&escaped @ ('$' | '`' | '"' | '\\'))) => {
+                chars.next();
+                unescaped.push(escaped);
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
@@ -0,0 +1,255 @@
+use tree_sitter::Node;
+
+use crate::{
+    trace::{MergeReason, TypeMerge},
+    BashType, FileInfo, ParseResult, TypeDeclaration,
+};
+
+/// The right side of a `&&` or `||` list, which only runs depending on the status of the left
+/// side. What it changes is undone or merged once the analysis moves past it
+pub(crate) struct ShortCircuit {
+    end: usize,
+    /// Variables the condition showed to be set, which were only conditionally set before
+    guarded: Vec<String>,
+    /// Types narrowed by the condition, with the narrowed types from before the list
+    narrowed: Vec<(String, Option<BashType>)>,
+    /// Variables assigned on the right side, with their declarations from before the list and
+    /// whether they were only conditionally set then
+    assigned: Vec<(String, Option<TypeDeclaration>, bool)>,
+}
+
+/// What holds for a variable when the right side of a list runs
+enum Fact {
+    Set(String),
+    Integer(String),
+}
+
+/// Regular expressions that only match integers, without the `^` and `$` anchors
+const INTEGER_PATTERNS: &[&str] = &[
+    "[0-9]+",
+    "-?[0-9]+",
+    "[+-]?[0-9]+",
+    "[[:digit:]]+",
+    "-?[[:digit:]]+",
+    "[+-]?[[:digit:]]+",
+];
+
+impl FileInfo<'_> {
+    /// Handles `left && right` and `left || right`, narrowing the variables tested by `left`
+    /// while `right` is analyzed
+    pub(crate) fn handle_list(&mut self, node: Node) -> ParseResult<()> {
+        let (Some(left), Some(operator), Some(right)) =
+            (node.child(0), node.child(1), node.child(2))
+        else {
+            return Ok(());
+        };
+        let holds = match operator.kind() {
+            "&&" => true,
+            "||" => false,
+            _ => return Ok(()),
+        };
+        let mut facts = Vec::new();
+        self.condition_facts(left, holds, &mut facts)?;
+
+        let mut short_circuit = ShortCircuit {
+            end: right.end_byte(),
+            guarded: Vec::new(),
+            narrowed: Vec::new(),
+            assigned: Vec::new(),
+        };
+        for fact in facts {
+            match fact {
+                Fact::Set(name) => {
+                    if self.conditionally_set.remove(&name) {
+                        short_circuit.guarded.push(name);
+                    }
+                }
+                Fact::Integer(name) => {
+                    let Some(declaration) = self.variables.get(&name) else {
+                        continue;
+                    };
+                    let current = self.narrowed.get(&name).unwrap_or(&declaration.bash_type);
+                    if *current != BashType::Integer && current.can_contain(&BashType::Integer) {
+                        let previous = self.narrowed.insert(name.clone(), BashType::Integer);
+                        short_circuit.narrowed.push((name, previous));
+                    }
+                }
+            }
+        }
+        for name in self.assigned_names(right)? {
+            if short_circuit
+                .assigned
+                .iter()
+                .all(|(other, ..)| *other != name)
+            {
+                let previous = self.variables.get(&name).cloned();
+                let was_conditional = self.conditionally_set.contains(&name);
+                short_circuit
+                    .assigned
+                    .push((name, previous, was_conditional));
+            }
+        }
+        self.short_circuits.push(short_circuit);
+        Ok(())
+    }
+
+    /// Finishes the right sides of lists that end before `offset`. A variable assigned there
+    /// keeps the union of its types from both paths, or is only conditionally set if it didn't
+    /// exist before
+    pub(crate) fn end_short_circuits(&mut self, offset: usize) {
+        while let Some(short_circuit) = self
+            .short_circuits
+            .pop_if(|short_circuit| short_circuit.end <= offset)
+        {
+            for name in short_circuit.guarded {
+                self.conditionally_set.insert(name);
+            }
+            for (name, previous) in short_circuit.narrowed.into_iter().rev() {
+                match previous {
+                    Some(previous) => self.narrowed.insert(name, previous),
+                    None => self.narrowed.remove(&name),
+                };
+            }
+            for (name, previous, was_conditional) in short_circuit.assigned {
+                let Some(current) = self.variables.get_mut(&name) else {
+                    continue;
+                };
+                match previous {
+                    Some(previous) if previous != *current => {
+                        current.bash_type = previous.bash_type.union(current.bash_type.clone());
+                        self.merges.push(TypeMerge {
+                            name: name.clone(),
+                            offset: short_circuit.end,
+                            bash_type: current.bash_type.clone(),
+                            reason: MergeReason::ShortCircuit,
+                        });
+                    }
+                    Some(_) => (),
+                    None => {
+                        self.conditionally_set.insert(name.clone());
+                    }
+                }
+                if was_conditional {
+                    self.conditionally_set.insert(name.clone());
+                }
+                self.narrowed.remove(&name);
+                self.literal_values.remove(&name);
+            }
+        }
+    }
+
+    /// Collects what is known about variables when `condition` exits with a status that is
+    /// successful if `holds`
+    fn condition_facts(
+        &self,
+        condition: Node,
+        holds: bool,
+        facts: &mut Vec<Fact>,
+    ) -> ParseResult<()> {
+        match condition.kind() {
+            "test_command" | "parenthesized_expression" => {
+                for child in condition.named_children(&mut condition.walk()) {
+                    self.condition_facts(child, holds, facts)?;
+                }
+            }
+            "unary_expression" => {
+                let (Some(operator), Some(operand)) = (
+                    condition.child_by_field_name("operator"),
+                    condition.named_child(1),
+                ) else {
+                    return Ok(());
+                };
+                let operator = self.node_text(operator)?;
+                let shows_set = match operator {
+                    "-n" => holds,
+                    "-z" => !holds,
+                    "-v" if holds => {
+                        facts.push(Fact::Set(self.node_text(operand)?.to_owned()));
+                        return Ok(());
+                    }
+                    _ => false,
+                };
+                if shows_set {
+                    if let Some(name) = self.expanded_name(operand)? {
+                        facts.push(Fact::Set(name));
+                    }
+                }
+            }
+            "binary_expression" => {
+                let (Some(left), Some(operator), Some(right)) = (
+                    condition.child_by_field_name("left"),
+                    condition.child(1),
+                    condition.child_by_field_name("right"),
+                ) else {
+                    return Ok(());
+                };
+                match operator.kind() {
+                    // Both sides hold when `a && b` succeeds, and neither does when `a || b` fails
+                    "&&" if holds => {
+                        self.condition_facts(left, holds, facts)?;
+                        self.condition_facts(right, holds, facts)?;
+                    }
+                    "||" if !holds => {
+                        self.condition_facts(left, holds, facts)?;
+                        self.condition_facts(right, holds, facts)?;
+                    }
+                    "=~" if holds => {
+                        let pattern = self.node_text(right)?;
+                        let unanchored = pattern
+                            .strip_prefix('^')
+                            .and_then(|pattern| pattern.strip_suffix('$'));
+                        if unanchored.is_some_and(|pattern| INTEGER_PATTERNS.contains(&pattern)) {
+                            if let Some(name) = self.expanded_name(left)? {
+                                facts.push(Fact::Set(name.clone()));
+                                facts.push(Fact::Integer(name));
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// The variable expanded by `$name`, `${name}` or `"$name"`
+    fn expanded_name(&self, node: Node) -> ParseResult<Option<String>> {
+        let node = match node.kind() {
+            "string" if node.named_child_count() == 1 => {
+                node.named_child(0).expect("child count to be one")
+            }
+            _ => node,
+        };
+        if !matches!(node.kind(), "simple_expansion" | "expansion") || node.named_child_count() != 1
+        {
+            return Ok(None);
+        }
+        let variable = node.named_child(0).expect("child count to be one");
+        if variable.kind() != "variable_name" {
+            return Ok(None);
+        }
+        let name = self.node_text(variable)?;
+        Ok(self.resolve_name(name).map(str::to_owned))
+    }
+
+    /// The names of the variables assigned anywhere in `node`
+    pub(crate) fn assigned_names(&self, node: Node) -> ParseResult<Vec<String>> {
+        let mut names = Vec::new();
+        let mut worklist = vec![node];
+        while let Some(node) = worklist.pop() {
+            let variable = match node.kind() {
+                "variable_assignment" => node.child_by_field_name("name"),
+                "for_statement" => node.child_by_field_name("variable"),
+                _ => None,
+            };
+            if let Some(variable) = variable.filter(|variable| variable.kind() == "variable_name") {
+                if let Some(name) = self.resolve_name(self.node_text(variable)?) {
+                    names.push(name.to_owned());
+                }
+            }
+            worklist.extend(node.named_children(&mut node.walk()));
+        }
+        Ok(names)
+    }
+}
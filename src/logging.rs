@@ -0,0 +1,59 @@
+//! Spans and events for the `tracing` feature, which show what the analysis decides and where it
+//! spends its time when run with `RUST_LOG=bashtyped=debug`. Without the feature the macros
+//! expand to nothing
+
+use crate::FileInfo;
+
+/// Enters a span until the end of the enclosing block, like
+/// `enter_span!(DEBUG, "rule", name = "ifs")`
+#[cfg(feature = "tracing")]
+macro_rules! enter_span {
+    ($level:ident, $($span:tt)+) => {
+        let _span = tracing::span!(tracing::Level::$level, $($span)+).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! enter_span {
+    ($($span:tt)+) => {};
+}
+
+/// Emits an event in the current span, like `log_event!(DEBUG, name, "Variable set")`
+#[cfg(feature = "tracing")]
+macro_rules! log_event {
+    ($level:ident, $($event:tt)+) => {
+        tracing::event!(tracing::Level::$level, $($event)+)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! log_event {
+    ($($event:tt)+) => {};
+}
+
+impl FileInfo<'_> {
+    /// Logs the diagnostics reported since there were `from` of them
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn log_diagnostics(&self, from: usize) {
+        #[cfg(feature = "tracing")]
+        for diagnostic in self.errors.get(from..).unwrap_or_default() {
+            log_event!(
+                DEBUG,
+                code = diagnostic.code.as_deref().unwrap_or_default(),
+                severity = ?diagnostic.severity,
+                offset = diagnostic.offset,
+                "{}",
+                diagnostic.message
+            );
+        }
+    }
+
+    /// Runs one of the checks made once the whole file is analyzed in a span named after it
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn run_rule(&mut self, name: &'static str, check: impl FnOnce(&mut Self)) {
+        enter_span!(DEBUG, "rule", name);
+        let reported = self.errors.len();
+        check(self);
+        self.log_diagnostics(reported);
+    }
+}
@@ -0,0 +1,64 @@
+use tree_sitter::Node;
+
+use crate::{BashType, Comment, FileInfo, Method, ParseResult, TypeDeclaration};
+
+impl FileInfo<'_> {
+    /// Types the variable of `for name in ...` and `select name in ...` from the words it takes
+    pub(crate) fn handle_loop(
+        &mut self,
+        node: Node,
+        annotation: Option<Comment>,
+    ) -> ParseResult<()> {
+        let Some(variable) = node.child_by_field_name("variable") else {
+            return Ok(());
+        };
+        let is_select = node
+            .child(0)
+            .is_some_and(|keyword| keyword.kind() == "select");
+
+        let mut inferred_type: Option<BashType> = None;
+        let mut has_values = false;
+        for value in node.children_by_field_name("value", &mut node.walk()) {
+            has_values = true;
+            let value_type = self.infer_type(value)?;
+            // `for item in "${items[@]}"` goes through the elements
+            let value_type = value_type.element_type().unwrap_or(value_type);
+            inferred_type = Some(match inferred_type {
+                Some(inferred_type) => inferred_type.union(value_type),
+                None => value_type,
+            });
+        }
+        // Without `in`, the loop goes through the positional parameters
+        let mut inferred_type = match inferred_type {
+            Some(inferred_type) => inferred_type,
+            None if has_values => BashType::Unknown,
+            None => BashType::String,
+        };
+        if is_select {
+            // Picking something that isn't in the menu sets the variable to the empty string
+            inferred_type = inferred_type.union(BashType::String);
+            let reply = TypeDeclaration {
+                bash_type: BashType::String,
+                range: node.start_byte()..variable.end_byte(),
+                method: Method::Inferred,
+                node_kind: node.kind().to_owned(),
+                annotation: None,
+                file: None,
+            };
+            self.set_variable("REPLY", reply, node);
+        }
+
+        let name = self.node_text(variable)?;
+        let Some(name) = self.resolve_name(name).map(str::to_owned) else {
+            return Ok(());
+        };
+        self.set_literal(&name, None);
+        self.declare_variable(
+            &name,
+            inferred_type,
+            variable.start_byte()..variable.end_byte(),
+            annotation,
+            node,
+        )
+    }
+}
@@ -1,20 +1,1032 @@
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::{self, ExitCode},
+};
+
 use ariadne::Source;
-use bashtyped::FileInfo;
+use bashtyped::{
+    baseline::{self, Baseline},
+    cache::{Cache, DEFAULT_CACHE_DIR, MAX_CACHE_SIZE},
+    codes,
+    config::{ConfigError, ConfigHierarchy, OutputFormat, CONFIG_FILE_NAME},
+    diff::ChangedLines,
+    encoding::DecodedSource,
+    files::{FileId, Files},
+    fix::{self, Applicability, FixKind},
+    init::Detected,
+    output::{self, FileReport},
+    scan::{self, IgnoreRules, IGNORE_FILE_NAME},
+    symbols::{Symbols, Workspace},
+    Config, Diagnostic, Dialect, FileInfo, Severity, SourceIndex,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+
+#[derive(Parser)]
+#[command(version, about, subcommand_negates_reqs = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Bash files to check, or directories to search for scripts that aren't ignored by the
+    /// `ignore` setting or `.bashtypedignore`
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Analyze every file again instead of reusing results from the cache
+    #[arg(long, global = true)]
+    no_cache: bool,
+    /// Configuration file to use instead of `bashtyped.toml` in the current directory
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// When to color the output. `auto` uses colors for terminals unless `NO_COLOR` is set
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+    /// How to print diagnostics. `short` prints one `path:line:column: message` line for each,
+    /// `checkstyle` and `junit` print a single XML report for all files, and `html` writes a
+    /// browsable report to the `--output` directory. Defaults to the `format` setting of the
+    /// config, or `full`
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+    /// Print how many assignments are typed in each file and how many diagnostics of each code
+    /// there are, instead of the diagnostics themselves
+    #[arg(long, conflicts_with_all = ["format", "output", "write_baseline"])]
+    stats: bool,
+    /// Directory to write the HTML report to
+    #[arg(long, required_if_eq("format", "html"))]
+    output: Option<PathBuf>,
+    /// Record every current diagnostic to this file instead of printing them, exiting successfully
+    /// once it is written
+    #[arg(long, value_name = "PATH", conflicts_with = "baseline")]
+    write_baseline: Option<PathBuf>,
+    /// Only report diagnostics that aren't recorded in this file from `--write-baseline`
+    #[arg(long, value_name = "PATH", global = true)]
+    baseline: Option<PathBuf>,
+    /// Only report diagnostics on lines changed since this git revision, or since the point
+    /// where the current branch split from it
+    #[arg(long, value_name = "REV", conflicts_with = "write_baseline")]
+    diff_base: Option<String>,
+    /// Only report diagnostics on lines with uncommitted changes, like `--diff-base HEAD`
+    #[arg(long, conflicts_with_all = ["diff_base", "write_baseline"])]
+    changed_only: bool,
+    /// Apply the safe fixes suggested for diagnostics to the files, then report what is left
+    #[arg(long, conflicts_with_all = ["write_baseline", "stats"])]
+    fix: bool,
+    /// Also apply fixes that may hide a real mistake, like widening an annotation
+    #[arg(long, requires = "fix")]
+    fix_unsafe: bool,
+    /// Only apply these kinds of fixes. `widen` turns an annotation that doesn't match the
+    /// assigned value into a union with its type, and `force` skips the checks of the statement
+    #[arg(long, value_enum, value_delimiter = ',', requires = "fix")]
+    fix_kind: Vec<FixKindChoice>,
+    /// Check every file as this language, including files in directories that don't look like
+    /// scripts
+    #[arg(long, value_enum, global = true)]
+    language: Option<Language>,
+    /// Report commands that aren't functions, builtins or programs on `PATH`, like the
+    /// `check_path` setting. Useful in CI images that may be missing tools
+    #[arg(long)]
+    check_path: bool,
+    /// Parse and analyze each file in chunks whose syntax trees take at most this many MiB, like
+    /// the `memory_limit` setting. Lets huge generated scripts be checked
+    #[arg(long, value_name = "MIB")]
+    memory_limit: Option<usize>,
+    /// Apply the `[profile.NAME]` tables of the config files, like `ci` or `editor`
+    #[arg(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+    /// Print each assignment of this variable, how its type was found and how it combined with
+    /// the type before, instead of the diagnostics
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["format", "stats", "output", "write_baseline", "fix"]
+    )]
+    explain_var: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Language {
+    Bash,
+    #[cfg(feature = "zsh")]
+    Zsh,
+}
+
+impl From<Language> for Dialect {
+    fn from(language: Language) -> Self {
+        match language {
+            Language::Bash => Self::Bash,
+            #[cfg(feature = "zsh")]
+            Language::Zsh => Self::Zsh,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum FixKindChoice {
+    Widen,
+    Force,
+    RemoveAnnotation,
+    Rename,
+    Quote,
+    Expansion,
+    Operator,
+}
+
+impl From<OutputFormat> for Format {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Full => Self::Full,
+            OutputFormat::Short => Self::Short,
+            OutputFormat::Checkstyle => Self::Checkstyle,
+            OutputFormat::Junit => Self::Junit,
+            OutputFormat::Html => Self::Html,
+        }
+    }
+}
+
+impl From<FixKindChoice> for FixKind {
+    fn from(kind: FixKindChoice) -> Self {
+        match kind {
+            FixKindChoice::Widen => Self::Widen,
+            FixKindChoice::Force => Self::Force,
+            FixKindChoice::RemoveAnnotation => Self::RemoveAnnotation,
+            FixKindChoice::Rename => Self::Rename,
+            FixKindChoice::Quote => Self::Quote,
+            FixKindChoice::Expansion => Self::Expansion,
+            FixKindChoice::Operator => Self::Operator,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check the files given by a pre-commit hook, printing a line for each diagnostic
+    Hook {
+        /// Bash files to check
+        files: Vec<PathBuf>,
+        /// Read this file from stdin instead of from disk, like to check its staged contents
+        #[arg(long, value_name = "PATH")]
+        stdin_filename: Option<PathBuf>,
+    },
+    /// List every variable and function in a file with its type
+    Symbols {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = SymbolsFormat::Text)]
+        format: SymbolsFormat,
+    },
+    /// Show which variables and functions each variable depends on
+    Graph {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+    },
+    /// Write a commented `bashtyped.toml` with settings detected from the scripts in the current
+    /// directory
+    Init {
+        /// Replace an existing config file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Rewrite the `#/` annotations of files in their canonical form, placed as the
+    /// `annotation_style` setting says
+    Annotate {
+        /// Bash files to rewrite, or directories to search for scripts
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Also annotate the first assignment of every variable with its inferred type
+        #[arg(long)]
+        all: bool,
+        /// List the files that would change instead of writing them, failing if there are any
+        #[arg(long)]
+        check: bool,
+    },
+    /// Describe a diagnostic code, with an example and how to fix it
+    Explain {
+        /// A code like `BT0003` or a rule name like `redefined-variable`
+        code: String,
+    },
+}
+
+/// The root of the git repository the current directory is in
+fn git_root() -> Result<String, String> {
+    let output = process::Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .map_err(|e| format!("Unable to run git: {e}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
+/// The lines of `files` changed since `base`, counting files git doesn't track as entirely new
+fn changed_lines(base: &str, files: &[PathBuf]) -> Result<ChangedLines, String> {
+    let git = |args: &[&str]| {
+        let output = process::Command::new("git")
+            .args(args)
+            .arg("--")
+            .args(files)
+            .output()
+            .map_err(|e| format!("Unable to run git: {e}"))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+        }
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    };
+    let mut changed = ChangedLines::from_diff(&git(&[
+        "diff",
+        "--unified=0",
+        "--no-color",
+        "--no-ext-diff",
+        "--merge-base",
+        base,
+    ])?)
+    .with_root(Path::new(git_root()?.trim_end()));
+    for path in git(&["ls-files", "--others", "--exclude-standard"])?.lines() {
+        changed.add_file(path.as_ref());
+    }
+    Ok(changed)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Full,
+    Short,
+    Checkstyle,
+    Junit,
+    Html,
+}
 
-fn main() {
-    let source_code = r#"# other thing
-val="lol" #/ bool | string
-val=1 #/ int
-echo "hi"
-"#;
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SymbolsFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GraphFormat {
+    /// A Graphviz graph
+    Dot,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+fn print_symbols(path: &Path, format: SymbolsFormat, config: Config) -> ExitCode {
+    let source_code = match DecodedSource::read(path) {
+        Ok(decoded) => decoded.source_code,
+        Err(e) => {
+            eprintln!("Unable to read {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut info = FileInfo::with_config(&source_code, config);
+    info.parse_code();
+    let symbols = Symbols::new(&info);
+    match format {
+        SymbolsFormat::Text => print!("{symbols}"),
+        SymbolsFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&symbols).expect("symbols to serialize")
+        ),
+    }
+    ExitCode::SUCCESS
+}
+
+fn print_graph(path: &Path, config: Config) -> ExitCode {
+    let source_code = match DecodedSource::read(path) {
+        Ok(decoded) => decoded.source_code,
+        Err(e) => {
+            eprintln!("Unable to read {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut info = FileInfo::with_config(&source_code, config);
+    info.parse_code();
+    print!("{}", info.dependency_graph().to_dot());
+    ExitCode::SUCCESS
+}
+
+/// Prints the diagnostics of a file in the full or short format, finding the lines of the file
+/// only once however many batches they come in
+struct Printer<'a> {
+    path: &'a str,
+    config: &'a Config,
+    index: SourceIndex<'a>,
+    /// The lines reports are printed from, only needed for the full format
+    source: Option<Source<&'a str>>,
+    stdout: io::BufWriter<io::StdoutLock<'static>>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(format: Format, path: &'a str, source_code: &'a str, config: &'a Config) -> Self {
+        Self {
+            path,
+            config,
+            index: SourceIndex::new(source_code),
+            source: (format == Format::Full).then(|| Source::from(source_code)),
+            stdout: io::BufWriter::new(io::stdout().lock()),
+        }
+    }
+
+    fn print(&mut self, diagnostics: &[Diagnostic]) {
+        for diagnostic in diagnostics {
+            match &mut self.source {
+                Some(source) => diagnostic
+                    .report(self.config, &self.index)
+                    .write_for_stdout(source, &mut self.stdout),
+                None => writeln!(
+                    self.stdout,
+                    "{}",
+                    diagnostic.short_indexed(self.path, &self.index)
+                ),
+            }
+            .expect("report printing to work");
+        }
+        self.stdout.flush().expect("report printing to work");
+    }
+}
+
+/// Prints the diagnostics of a file that was analyzed as a whole, reading it again unless its
+/// contents were kept
+fn print_file(
+    format: Format,
+    path: &str,
+    id: FileId,
+    diagnostics: &[Diagnostic],
+    configs: &mut ConfigHierarchy,
+    files: &mut Files,
+    workspace: &Workspace,
+) {
+    if diagnostics.is_empty() {
+        return;
+    }
+    let Some(file_path) = workspace.path(id) else {
+        return;
+    };
+    let config = match configs.config_for(file_path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let pointed_into = diagnostics
+        .iter()
+        .flat_map(|diagnostic| &diagnostic.labels)
+        .filter_map(|label| label.file);
+    read_again(files, workspace, pointed_into);
+    let source_code = match files.source_code(id) {
+        Some(source_code) => source_code.to_owned(),
+        None => match DecodedSource::read(file_path) {
+            Ok(decoded) => decoded.source_code,
+            Err(e) => {
+                eprintln!("Unable to read {}: {e}", file_path.display());
+                return;
+            }
+        },
+    };
+    let mut printer = Printer::new(format, path, &source_code, &config);
+    for diagnostic in diagnostics {
+        // Reports whose labels point into other files are printed with all of them
+        if format == Format::Full && diagnostic.labels.iter().any(|label| label.file.is_some()) {
+            read_again(files, workspace, [id]);
+            diagnostic
+                .report_in(id, &config, files)
+                .print(&mut *files)
+                .expect("report printing to work");
+        } else {
+            printer.print(std::slice::from_ref(diagnostic));
+        }
+    }
+}
 
-    let mut info = FileInfo::new(source_code);
+/// Reads the files with `ids` again when their contents weren't kept, for reports between files
+fn read_again(files: &mut Files, workspace: &Workspace, ids: impl IntoIterator<Item = FileId>) {
+    for id in ids {
+        if files.source_code(id).is_some() {
+            continue;
+        }
+        let Some(path) = workspace.path(id) else {
+            continue;
+        };
+        match DecodedSource::read(path) {
+            Ok(decoded) => files.insert(id, path.display().to_string(), decoded.source_code),
+            Err(e) => eprintln!("Unable to read {}: {e}", path.display()),
+        }
+    }
+}
 
+/// Applies the first fix of each diagnostic that is of one of the `kinds`, or of any kind if it is
+/// empty, writing the file if anything changed. Returns the new contents
+fn fix_file(
+    path: &Path,
+    decoded: DecodedSource,
+    config: Config,
+    kinds: &[FixKind],
+    allow_unsafe: bool,
+) -> io::Result<String> {
+    let mut info = FileInfo::with_config(&decoded.source_code, config);
     info.parse_code();
+    let fixes = info.errors.iter().filter_map(|error| {
+        error.fixes.iter().find(|fix| {
+            (kinds.is_empty() || kinds.contains(&fix.kind))
+                && (allow_unsafe || fix.applicability == Applicability::MachineApplicable)
+        })
+    });
+    let (fixed, applied) = fix::apply_fixes(&decoded.source_code, fixes);
+    if applied == 0 {
+        return Ok(decoded.source_code);
+    }
+    decoded.write(path, &fixed)?;
+    eprintln!("Fixed {applied} problems in {}", path.display());
+    Ok(fixed)
+}
+
+fn annotate_files(
+    files: &[PathBuf],
+    configs: &mut ConfigHierarchy,
+    language: Option<Language>,
+    all: bool,
+    check: bool,
+) -> ExitCode {
+    let mut failed = false;
+    for path in files {
+        let mut config = match configs.config_for(path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("{e}");
+                failed = true;
+                continue;
+            }
+        };
+        if let Some(language) = language {
+            config.dialect = language.into();
+        }
+        let decoded = match DecodedSource::read(path) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                eprintln!("Unable to read {}: {e}", path.display());
+                failed = true;
+                continue;
+            }
+        };
+        let mut info = FileInfo::with_config(&decoded.source_code, config);
+        info.parse_code();
+        let edits = info.organize_annotations(all);
+        if edits.is_empty() {
+            continue;
+        }
+        if check {
+            println!("{}", path.display());
+            failed = true;
+        } else if let Err(e) = decoded.write(path, &fix::apply_edits(&decoded.source_code, &edits))
+        {
+            eprintln!("Unable to write {}: {e}", path.display());
+            failed = true;
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Prints how `name` got its type in each of `files` that assigns it
+fn explain_variable(
+    files: &[PathBuf],
+    configs: &mut ConfigHierarchy,
+    language: Option<Language>,
+    name: &str,
+) -> ExitCode {
+    let mut failed = false;
+    let mut found = false;
+    for path in files {
+        let mut config = match configs.config_for(path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("{e}");
+                failed = true;
+                continue;
+            }
+        };
+        if let Some(language) = language {
+            config.dialect = language.into();
+        }
+        let source_code = match DecodedSource::read(path) {
+            Ok(decoded) => decoded.source_code,
+            Err(e) => {
+                eprintln!("Unable to read {}: {e}", path.display());
+                failed = true;
+                continue;
+            }
+        };
+        // Joins of paths aren't cached, so the file is always analyzed again
+        let mut info = FileInfo::with_config(&source_code, config);
+        info.parse_code();
+        if let Some(explanation) = info.explain(name) {
+            if files.len() > 1 {
+                println!("{}:", path.display());
+            }
+            print!("{explanation}");
+            found = true;
+        }
+    }
+    if !found && !failed {
+        eprintln!("{name} is never assigned");
+    }
+    if failed || !found {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// How `path` is recorded in the baseline being written or read
+fn baseline_entry_path(args: &Args, path: &Path) -> String {
+    match args.write_baseline.as_deref().or(args.baseline.as_deref()) {
+        Some(baseline) => baseline::entry_path(baseline, path),
+        None => path.display().to_string(),
+    }
+}
+
+fn init(path: Option<&Path>, force: bool) -> ExitCode {
+    let path = path.unwrap_or(Path::new(CONFIG_FILE_NAME));
+    if path.exists() && !force {
+        eprintln!(
+            "{} already exists, use --force to replace it",
+            path.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    let detected = match Detected::new(Path::new(".")) {
+        Ok(detected) => detected,
+        Err(e) => {
+            eprintln!("Unable to search for scripts: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = fs::write(path, detected.config_file()) {
+        eprintln!("Unable to write {}: {e}", path.display());
+        return ExitCode::FAILURE;
+    }
+    eprintln!(
+        "Wrote {} based on {} scripts",
+        path.display(),
+        detected.scripts
+    );
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    // `RUST_LOG=bashtyped=debug` shows what the analysis decides, and `trace` every node it visits
+    #[cfg(feature = "tracing")]
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(io::stderr)
+        .init();
+    let mut args = Args::parse();
+    let mut stdin_filename = None;
+    let mut symbols = None;
+    let mut graph = None;
+    let mut annotate = None;
+    match args.command.take() {
+        Some(Command::Hook {
+            files,
+            stdin_filename: stdin_path,
+        }) => {
+            args.files = files;
+            args.format = Some(Format::Short);
+            args.color = ColorChoice::Never;
+            if let Some(path) = &stdin_path {
+                if !args.files.contains(path) {
+                    args.files.push(path.clone());
+                }
+            }
+            stdin_filename = stdin_path;
+        }
+        Some(Command::Symbols { file, format }) => symbols = Some((file, format)),
+        Some(Command::Graph { file, format }) => graph = Some((file, format)),
+        Some(Command::Annotate { files, all, check }) => {
+            args.files = files;
+            annotate = Some((all, check));
+        }
+        Some(Command::Init { force }) => return init(args.config.as_deref(), force),
+        Some(Command::Explain { code }) => {
+            return match codes::rule(&code) {
+                Some(rule) => {
+                    print!("{rule}");
+                    ExitCode::SUCCESS
+                }
+                None => {
+                    eprintln!("Unknown diagnostic code {code}");
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        None => (),
+    }
+    let config_path = args.config.clone().or_else(|| {
+        let default = PathBuf::from(CONFIG_FILE_NAME);
+        default.exists().then_some(default)
+    });
+    let config = match (&config_path, &args.profile) {
+        (Some(path), profile) => Config::load_with_profile(path, profile.as_deref()),
+        (None, Some(profile)) => Err(ConfigError::UnknownProfile(profile.clone())),
+        (None, None) => Ok(Config::default()),
+    };
+    let mut config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    config.color = args.color.enabled();
+    // Config files in the directories below the root config can override it for their scripts
+    let root_dir = config_path
+        .as_deref()
+        .and_then(Path::parent)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut configs = ConfigHierarchy::new(root_dir, config.clone());
+    if let Some((path, format)) = symbols {
+        return match configs.config_for(&path) {
+            Ok(config) => print_symbols(&path, format, config),
+            Err(e) => {
+                eprintln!("{e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+    if let Some((path, GraphFormat::Dot)) = graph {
+        return match configs.config_for(&path) {
+            Ok(config) => print_graph(&path, config),
+            Err(e) => {
+                eprintln!("{e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let mut ignore = IgnoreRules::new(&config.ignore);
+    match fs::read_to_string(IGNORE_FILE_NAME) {
+        Ok(contents) => ignore.add_file(&contents),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+        Err(e) => {
+            eprintln!("Unable to read {IGNORE_FILE_NAME}: {e}");
+            return ExitCode::FAILURE;
+        }
+    }
+    args.files = match scan::find_scripts(&args.files, &ignore, args.language.is_some()) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("Unable to search for scripts: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some((all, check)) = annotate {
+        return annotate_files(&args.files, &mut configs, args.language, all, check);
+    }
+    if let Some(name) = &args.explain_var {
+        return explain_variable(&args.files, &mut configs, args.language, name);
+    }
+
+    let format = args
+        .format
+        .or(config.format.map(Format::from))
+        .unwrap_or(Format::Full);
+    if format == Format::Html && args.output.is_none() {
+        eprintln!("The html format needs a directory to write the report to with --output");
+        return ExitCode::FAILURE;
+    }
+    let cache = (!args.no_cache).then(|| Cache::new(DEFAULT_CACHE_DIR));
+    let baseline = match args.baseline.as_deref().map(Baseline::load).transpose() {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("Unable to read the baseline: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut new_baseline = Baseline::default();
+    let diff_base = args
+        .diff_base
+        .as_deref()
+        .or(args.changed_only.then_some("HEAD"));
+    let changed = match diff_base
+        .map(|base| changed_lines(base, &args.files))
+        .transpose()
+    {
+        Ok(changed) => changed,
+        Err(e) => {
+            eprintln!("Unable to find the changed lines: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let fix_kinds = args
+        .fix_kind
+        .iter()
+        .map(|&kind| kind.into())
+        .collect::<Vec<FixKind>>();
+    let mut workspace = Workspace::with_configs(configs.clone());
+    // The contents of every file, for reports that point into more than one of them
+    let mut files = Files::new();
+    let mut found_errors = false;
+    // Only kept for the XML and HTML formats and statistics, which need every file before anything
+    // is printed
+    let mut analyzed = Vec::new();
+    for path in &args.files {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("file", path = %path.display()).entered();
+        let decoded = if stdin_filename.as_ref() == Some(path) {
+            DecodedSource::from_reader(io::stdin())
+        } else {
+            DecodedSource::read(path)
+        };
+        let decoded = match decoded {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                eprintln!("Unable to read {}: {e}", path.display());
+                found_errors = true;
+                continue;
+            }
+        };
+
+        let file_config = match configs.config_for(path) {
+            Ok(file_config) => file_config,
+            Err(e) => {
+                eprintln!("{e}");
+                found_errors = true;
+                continue;
+            }
+        };
+        #[cfg(feature = "zsh")]
+        let mut file_config = {
+            let mut file_config = file_config;
+            if path.extension().is_some_and(|extension| extension == "zsh") {
+                file_config.dialect = Dialect::Zsh;
+            }
+            file_config
+        };
+        #[cfg(not(feature = "zsh"))]
+        let mut file_config = file_config;
+        if let Some(language) = args.language {
+            file_config.dialect = language.into();
+        }
+        file_config.check_path |= args.check_path;
+        file_config.memory_limit = args.memory_limit.or(file_config.memory_limit);
+
+        // Contents read from stdin have nowhere to be written back to
+        let source_code = if args.fix && stdin_filename.as_ref() != Some(path) && decoded.lossy {
+            eprintln!("Not fixing {}, since it isn't valid UTF-8", path.display());
+            decoded.source_code
+        } else if args.fix && stdin_filename.as_ref() != Some(path) {
+            match fix_file(
+                path,
+                decoded,
+                file_config.clone(),
+                &fix_kinds,
+                args.fix_unsafe,
+            ) {
+                Ok(source_code) => source_code,
+                Err(e) => {
+                    eprintln!("Unable to write {}: {e}", path.display());
+                    found_errors = true;
+                    continue;
+                }
+            }
+        } else {
+            decoded.source_code
+        };
+
+        let display_path = path.display().to_string();
+        // With a memory limit, diagnostics are printed as each chunk of the file is analyzed
+        // rather than once all of them are found
+        let streams = file_config.memory_limit.is_some()
+            && matches!(format, Format::Full | Format::Short)
+            && !args.stats
+            && args.write_baseline.is_none()
+            && baseline.is_none();
+        let report_config = file_config.clone();
+        if streams {
+            let mut info = FileInfo::with_config(&source_code, file_config);
+            let mut printer = Printer::new(format, &display_path, &source_code, &report_config);
+            info.parse_code_streaming(|mut errors| {
+                if let Some(changed) = &changed {
+                    errors = changed.changed_diagnostics(path, &source_code, errors);
+                }
+                found_errors |= errors.iter().any(|error| error.severity == Severity::Error);
+                printer.print(&errors);
+            });
+            workspace.update(path, &info);
+            if stdin_filename.as_ref() == Some(path) {
+                let id = workspace
+                    .file_id(path)
+                    .expect("the file to be in the workspace");
+                files.insert(id, display_path, source_code);
+            }
+            continue;
+        }
+
+        let mut info = FileInfo::with_config(&source_code, file_config);
+        match &cache {
+            Some(cache) => {
+                if let Err(e) = info.parse_code_cached(cache) {
+                    eprintln!("Unable to write to the cache: {e}");
+                }
+            }
+            None => info.parse_code(),
+        }
+        let id = workspace.update(path, &info);
+        if args.write_baseline.is_some() {
+            let entry_path = baseline_entry_path(&args, path);
+            new_baseline.add(&entry_path, info.source_code, &info.errors);
+        } else {
+            if let Some(baseline) = &baseline {
+                info.errors = baseline.new_diagnostics(
+                    &baseline_entry_path(&args, path),
+                    info.source_code,
+                    info.errors,
+                );
+            }
+            if let Some(changed) = &changed {
+                info.errors = changed.changed_diagnostics(path, info.source_code, info.errors);
+            }
+
+            found_errors |= info
+                .errors
+                .iter()
+                .any(|error| error.severity == Severity::Error);
+            // The full and short formats are printed with the problems between files once every
+            // file is analyzed, and don't need the declarations
+            let history = match format {
+                Format::Full | Format::Short if !args.stats => Default::default(),
+                _ => info.history,
+            };
+            analyzed.push((display_path.clone(), id, info.errors, history));
+        }
+        // Reports between files read the other files again, so only the contents that can't be
+        // read again and the ones the XML and HTML formats need are kept
+        if !matches!(format, Format::Full | Format::Short)
+            || args.stats
+            || stdin_filename.as_ref() == Some(path)
+        {
+            files.insert(id, display_path, source_code);
+        }
+    }
+
+    if let Some(cache) = &cache {
+        if let Err(e) = cache.prune(MAX_CACHE_SIZE) {
+            eprintln!("Unable to prune the cache: {e}");
+        }
+    }
+
+    // Problems between files that source each other, which need every file to be analyzed
+    for found in workspace.check() {
+        let pointed_into = found
+            .diagnostic
+            .labels
+            .iter()
+            .filter_map(|label| label.file);
+        read_again(
+            &mut files,
+            &workspace,
+            std::iter::once(found.file).chain(pointed_into),
+        );
+        let Some(source_code) = files.source_code(found.file) else {
+            continue;
+        };
+        let display_path = found.path.display().to_string();
+        let mut diagnostics = vec![found.diagnostic];
+        if args.write_baseline.is_some() {
+            new_baseline.add(
+                &baseline_entry_path(&args, &found.path),
+                source_code,
+                &diagnostics,
+            );
+            continue;
+        }
+        if let Some(baseline) = &baseline {
+            diagnostics = baseline.new_diagnostics(
+                &baseline_entry_path(&args, &found.path),
+                source_code,
+                diagnostics,
+            );
+        }
+        if let Some(changed) = &changed {
+            diagnostics = changed.changed_workspace_diagnostics(
+                &found.path,
+                source_code,
+                diagnostics,
+                &files,
+            );
+        }
+
+        found_errors |= diagnostics
+            .iter()
+            .any(|error| error.severity == Severity::Error);
+        let report = analyzed.iter_mut().find(|(_, id, ..)| *id == found.file);
+        if let Some((_, _, errors, _)) = report {
+            errors.extend(diagnostics);
+            continue;
+        }
+        // Files analyzed in chunks were printed while they were analyzed, so the problems between
+        // them and other files come after everything else
+        match format {
+            Format::Full => {
+                for diagnostic in &diagnostics {
+                    diagnostic
+                        .report_in(found.file, &config, &files)
+                        .print(&mut files)
+                        .expect("report printing to work");
+                }
+            }
+            Format::Short => {
+                for error in &diagnostics {
+                    println!("{}", error.short(&display_path, source_code));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(path) = &args.write_baseline {
+        if let Err(e) = new_baseline.save(path) {
+            eprintln!("Unable to write the baseline to {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+        eprintln!(
+            "Recorded {} diagnostics in {}",
+            new_baseline.len(),
+            path.display()
+        );
+        // The diagnostics were recorded rather than reported
+        return ExitCode::SUCCESS;
+    }
+
+    for (path, _, diagnostics, _) in &mut analyzed {
+        diagnostics.sort_by(|a, b| a.sort_key(&*path).cmp(&b.sort_key(&*path)));
+    }
+    analyzed.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+    if matches!(format, Format::Full | Format::Short) && !args.stats {
+        for (path, id, diagnostics, _) in &analyzed {
+            print_file(
+                format,
+                path,
+                *id,
+                diagnostics,
+                &mut configs,
+                &mut files,
+                &workspace,
+            );
+        }
+    }
+
+    let reports = analyzed
+        .iter()
+        .map(|(path, id, diagnostics, declarations)| FileReport {
+            path,
+            source_code: files.source_code(*id).unwrap_or_default(),
+            diagnostics,
+            declarations,
+        })
+        .collect::<Vec<_>>();
+    match format {
+        _ if args.stats => print!("{}", output::stats(&reports)),
+        Format::Checkstyle => print!("{}", output::checkstyle(&reports)),
+        Format::Junit => print!("{}", output::junit(&reports)),
+        Format::Html => {
+            let dir = args.output.expect("--output to be required for html");
+            if let Err(e) = output::html(&reports, &dir) {
+                eprintln!("Unable to write the report to {}: {e}", dir.display());
+                return ExitCode::FAILURE;
+            }
+        }
+        Format::Full | Format::Short => (),
+    }
 
-    for error in info.errors {
-        error
-            .print(Source::from(info.source_code))
-            .expect("comment printing to work");
+    if found_errors {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
     }
 }
@@ -0,0 +1,428 @@
+//! Reports for CI systems and for browsing the results of a whole codebase
+
+use std::{collections::BTreeMap, fmt::Write, fs, io, ops::Range, path::Path};
+
+use crate::{BashType, Diagnostic, Method, Severity, SourceIndex, TypeDeclaration};
+
+/// The results of analyzing one file
+pub struct FileReport<'a> {
+    pub path: &'a str,
+    pub source_code: &'a str,
+    pub diagnostics: &'a [Diagnostic],
+    /// Every declaration of each variable, as in [`crate::FileInfo::history`]
+    pub declarations: &'a BTreeMap<String, Vec<TypeDeclaration>>,
+}
+
+/// A Checkstyle XML report, as read by Jenkins' warnings plugin and most code quality tools
+pub fn checkstyle(files: &[FileReport]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<checkstyle version=\"4.3\">\n");
+    for file in files {
+        writeln!(xml, "  <file name=\"{}\">", escape(file.path)).unwrap();
+        let index = SourceIndex::new(file.source_code);
+        for diagnostic in file.diagnostics {
+            let (line, column) = index.line_column(diagnostic.offset);
+            let severity = match diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Advice => "info",
+            };
+            let source = match &diagnostic.code {
+                Some(code) => format!("bashtyped.{code}"),
+                None => String::from("bashtyped"),
+            };
+            writeln!(
+                xml,
+                "    <error line=\"{line}\" column=\"{column}\" severity=\"{severity}\" \
+                 message=\"{}\" source=\"{source}\"/>",
+                escape(&diagnostic.message),
+            )
+            .unwrap();
+        }
+        xml.push_str("  </file>\n");
+    }
+    xml.push_str("</checkstyle>\n");
+    xml
+}
+
+/// A JUnit XML report with a test suite for each file and a failing test case for each
+/// diagnostic. Files without diagnostics get a single passing test case
+pub fn junit(files: &[FileReport]) -> String {
+    let total = files
+        .iter()
+        .map(|file| file.diagnostics.len().max(1))
+        .sum::<usize>();
+    let failures = files
+        .iter()
+        .map(|file| file.diagnostics.len())
+        .sum::<usize>();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        xml,
+        "<testsuites name=\"bashtyped\" tests=\"{total}\" failures=\"{failures}\">"
+    )
+    .unwrap();
+    for file in files {
+        let path = escape(file.path);
+        writeln!(
+            xml,
+            "  <testsuite name=\"{path}\" tests=\"{}\" failures=\"{}\">",
+            file.diagnostics.len().max(1),
+            file.diagnostics.len(),
+        )
+        .unwrap();
+        if file.diagnostics.is_empty() {
+            writeln!(
+                xml,
+                "    <testcase name=\"{path}\" classname=\"bashtyped\"/>"
+            )
+            .unwrap();
+        }
+        let index = SourceIndex::new(file.source_code);
+        for diagnostic in file.diagnostics {
+            let (line, column) = index.line_column(diagnostic.offset);
+            let kind = diagnostic.code.as_deref().unwrap_or("bashtyped");
+            let message = escape(&diagnostic.message);
+            writeln!(
+                xml,
+                "    <testcase name=\"{path}:{line}:{column} {kind}\" classname=\"bashtyped\">"
+            )
+            .unwrap();
+            writeln!(
+                xml,
+                "      <failure type=\"{}\" message=\"{message}\">{path}:{line}:{column}: \
+                 {message}</failure>",
+                format!("{:?}", diagnostic.severity).to_lowercase(),
+            )
+            .unwrap();
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// How many assignments of a file have a known type
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TypeStats {
+    /// Assignments with a `#/` annotation or a typed declaration
+    pub declared: usize,
+    /// Assignments whose type was inferred from the value
+    pub inferred: usize,
+    /// Assignments whose type couldn't be inferred
+    pub unknown: usize,
+}
+
+impl TypeStats {
+    pub fn new(declarations: &BTreeMap<String, Vec<TypeDeclaration>>) -> Self {
+        let mut stats = Self::default();
+        for declaration in declarations.values().flatten() {
+            match declaration.method {
+                Method::Declared => stats.declared += 1,
+                Method::Inferred if declaration.bash_type == BashType::Unknown => {
+                    stats.unknown += 1
+                }
+                Method::Inferred => stats.inferred += 1,
+            }
+        }
+        stats
+    }
+
+    pub fn total(&self) -> usize {
+        self.declared + self.inferred + self.unknown
+    }
+
+    /// The percentage of assignments with a known type, or `None` without any assignments
+    pub fn coverage(&self) -> Option<f64> {
+        let total = self.total();
+        (total > 0).then(|| (self.declared + self.inferred) as f64 * 100.0 / total as f64)
+    }
+}
+
+impl std::ops::AddAssign for TypeStats {
+    fn add_assign(&mut self, other: Self) {
+        self.declared += other.declared;
+        self.inferred += other.inferred;
+        self.unknown += other.unknown;
+    }
+}
+
+/// A summary of how much of each file is typed, and how many diagnostics of each code were
+/// found, for tracking the adoption of annotations over time
+pub fn stats(files: &[FileReport]) -> String {
+    let width = files
+        .iter()
+        .map(|file| file.path.chars().count())
+        .chain([5])
+        .max()
+        .unwrap_or_default();
+    let mut text = format!(
+        "{:width$}  {:>8}  {:>8}  {:>7}  {:>8}\n",
+        "File", "Declared", "Inferred", "Unknown", "Coverage"
+    );
+    let row = |text: &mut String, name: &str, stats: TypeStats| {
+        let coverage = match stats.coverage() {
+            Some(coverage) => format!("{coverage:.1}%"),
+            None => String::from("-"),
+        };
+        writeln!(
+            text,
+            "{name:width$}  {:>8}  {:>8}  {:>7}  {coverage:>8}",
+            stats.declared, stats.inferred, stats.unknown
+        )
+        .unwrap();
+    };
+    let mut total = TypeStats::default();
+    for file in files {
+        let stats = TypeStats::new(file.declarations);
+        total += stats;
+        row(&mut text, file.path, stats);
+    }
+    row(&mut text, "Total", total);
+
+    let mut codes: BTreeMap<&str, usize> = BTreeMap::new();
+    for diagnostic in files.iter().flat_map(|file| file.diagnostics) {
+        *codes
+            .entry(diagnostic.code.as_deref().unwrap_or("-"))
+            .or_insert(0) += 1;
+    }
+    if !codes.is_empty() {
+        text.push_str("\nDiagnostics:\n");
+        for (code, count) in codes {
+            writeln!(text, "{code:<8}  {count:>5}").unwrap();
+        }
+    }
+    text
+}
+
+/// Writes a static HTML report to `dir`: `index.html` summarizes the diagnostics by code and
+/// severity, and links to a page for each file showing its source with the types of variables
+/// and the diagnostics available on hover
+pub fn html(files: &[FileReport], dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir.join("files"))?;
+    for (index, file) in files.iter().enumerate() {
+        fs::write(
+            dir.join("files").join(format!("{index}.html")),
+            html_file(file),
+        )?;
+    }
+    fs::write(dir.join("index.html"), html_index(files))
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+pre { margin: 0; line-height: 1.4; }
+.source { display: flex; font-family: monospace; }
+.lines { color: #888; text-align: right; padding-right: 1em; user-select: none; }
+.declared { background: #dbe9ff; }
+.inferred { background: #f3e1f7; }
+.error { text-decoration: underline wavy #d00; }
+.warning { text-decoration: underline wavy #c90; }
+.advice { text-decoration: underline dotted #06c; }
+";
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Advice => "advice",
+    }
+}
+
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>\n{STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+fn html_index(files: &[FileReport]) -> String {
+    let mut severities = BTreeMap::new();
+    let mut codes: BTreeMap<&str, (usize, &str)> = BTreeMap::new();
+    for diagnostic in files.iter().flat_map(|file| file.diagnostics) {
+        *severities
+            .entry(severity_name(diagnostic.severity))
+            .or_insert(0) += 1;
+        let code = diagnostic.code.as_deref().unwrap_or("-");
+        codes.entry(code).or_insert((0, &diagnostic.message)).0 += 1;
+    }
+
+    let mut body = String::from("<h1>bashtyped report</h1>\n<h2>Diagnostics</h2>\n<table>\n");
+    body.push_str("<tr><th>Severity</th><th>Count</th></tr>\n");
+    for (severity, count) in &severities {
+        writeln!(body, "<tr><td>{severity}</td><td>{count}</td></tr>").unwrap();
+    }
+    body.push_str("</table>\n<h2>Codes</h2>\n<table>\n");
+    body.push_str("<tr><th>Code</th><th>Count</th><th>Example</th></tr>\n");
+    for (code, (count, example)) in &codes {
+        writeln!(
+            body,
+            "<tr><td>{code}</td><td>{count}</td><td>{}</td></tr>",
+            escape(example)
+        )
+        .unwrap();
+    }
+    body.push_str("</table>\n<h2>Files</h2>\n<table>\n");
+    body.push_str(
+        "<tr><th>File</th><th>Errors</th><th>Warnings</th><th>Declared variables</th>\
+         <th>Inferred variables</th></tr>\n",
+    );
+    for (index, file) in files.iter().enumerate() {
+        let count = |severity| {
+            file.diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.severity == severity)
+                .count()
+        };
+        let declared = file
+            .declarations
+            .values()
+            .filter(|history| {
+                history
+                    .iter()
+                    .any(|declaration| declaration.method == Method::Declared)
+            })
+            .count();
+        writeln!(
+            body,
+            "<tr><td><a href=\"files/{index}.html\">{}</a></td><td>{}</td><td>{}</td>\
+             <td>{declared}</td><td>{}</td></tr>",
+            escape(file.path),
+            count(Severity::Error),
+            count(Severity::Warning),
+            file.declarations.len() - declared,
+        )
+        .unwrap();
+    }
+    body.push_str("</table>\n");
+    html_page("bashtyped report", &body)
+}
+
+/// A span of the source to highlight, with the text shown when hovering over it
+struct Mark {
+    range: Range<usize>,
+    class: &'static str,
+    title: String,
+}
+
+fn html_file(file: &FileReport) -> String {
+    let mut marks = Vec::new();
+    for (name, history) in file.declarations {
+        for declaration in history {
+            let (class, method) = match declaration.method {
+                Method::Declared => ("declared", "declared"),
+                Method::Inferred => ("inferred", "inferred"),
+            };
+            marks.push(Mark {
+                range: declaration.range.clone(),
+                class,
+                title: format!("{name}: {} ({method})", declaration.bash_type),
+            });
+        }
+    }
+    for diagnostic in file.diagnostics {
+        let message = match &diagnostic.code {
+            Some(code) => format!("{code}: {}", diagnostic.message),
+            None => diagnostic.message.clone(),
+        };
+        for label in &diagnostic.labels {
+            let label_text = match &label.bash_type {
+                Some(bash_type) => format!("{} {bash_type}", label.text),
+                None => label.text.clone(),
+            };
+            marks.push(Mark {
+                range: label.range.clone(),
+                class: severity_name(diagnostic.severity),
+                title: format!("{message} - {label_text}"),
+            });
+        }
+    }
+
+    let source = file.source_code;
+    marks.retain(|mark| {
+        mark.range.start < mark.range.end
+            && mark.range.end <= source.len()
+            && source.is_char_boundary(mark.range.start)
+            && source.is_char_boundary(mark.range.end)
+    });
+    let mut boundaries = vec![0, source.len()];
+    for mark in &marks {
+        boundaries.extend([mark.range.start, mark.range.end]);
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut code = String::new();
+    for segment in boundaries.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let text = escape(&source[start..end]);
+        let covering = marks
+            .iter()
+            .filter(|mark| mark.range.start <= start && end <= mark.range.end)
+            .collect::<Vec<_>>();
+        if covering.is_empty() {
+            code.push_str(&text);
+            continue;
+        }
+        let mut classes = covering.iter().map(|mark| mark.class).collect::<Vec<_>>();
+        classes.sort_unstable();
+        classes.dedup();
+        let title = covering
+            .iter()
+            .map(|mark| escape(&mark.title))
+            .collect::<Vec<_>>()
+            .join("&#10;");
+        write!(
+            code,
+            "<span class=\"{}\" title=\"{title}\">{text}</span>",
+            classes.join(" ")
+        )
+        .unwrap();
+    }
+    let lines = (1..=source.lines().count().max(1))
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut body = format!(
+        "<p><a href=\"../index.html\">Back to the summary</a></p>\n<h1>{}</h1>\n",
+        escape(file.path)
+    );
+    if !file.diagnostics.is_empty() {
+        body.push_str("<ul>\n");
+        let index = SourceIndex::new(source);
+        for diagnostic in file.diagnostics {
+            writeln!(
+                body,
+                "<li>{}</li>",
+                escape(&diagnostic.short_indexed(file.path, &index))
+            )
+            .unwrap();
+        }
+        body.push_str("</ul>\n");
+    }
+    writeln!(
+        body,
+        "<div class=\"source\"><pre class=\"lines\">{lines}</pre><pre>{code}</pre></div>"
+    )
+    .unwrap();
+    html_page(file.path, &body)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
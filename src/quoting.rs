@@ -0,0 +1,115 @@
+//! Unquoted expansions of strings, which bash splits into words and expands as globs
+
+use tree_sitter::Node;
+
+use crate::{
+    codes,
+    fix::{Applicability, Fix, FixKind, TextEdit},
+    BashType, Diagnostic, FileInfo, Label, LabelKind, ParseResult, Severity,
+};
+
+/// Characters that make an unquoted value split or glob with the default `IFS`
+const SPLIT_OR_GLOB: &[char] = &[' ', '\t', '\n', '*', '?', '['];
+
+impl FileInfo<'_> {
+    /// Warns about string variables expanded without quotes in the arguments of a command or in
+    /// `[ ... ]`. Integers can't be split, and `[[ ... ]]` and arithmetic don't split at all
+    pub(crate) fn check_unquoted(&mut self, node: Node) -> ParseResult<()> {
+        if self.force
+            || !self
+                .config
+                .rule_enabled(codes::UNQUOTED_EXPANSION, self.config.pedantic)
+        {
+            return Ok(());
+        }
+        let mut expansions = Vec::new();
+        match node.kind() {
+            "command" => {
+                for argument in node.children_by_field_name("argument", &mut node.walk()) {
+                    unquoted_expansions(argument, &mut expansions);
+                }
+            }
+            "test_command" if node.child(0).is_some_and(|bracket| bracket.kind() == "[") => {
+                for operand in node.named_children(&mut node.walk()) {
+                    unquoted_expansions(operand, &mut expansions);
+                }
+            }
+            _ => (),
+        }
+        for expansion in expansions {
+            self.check_unquoted_expansion(expansion)?;
+        }
+        Ok(())
+    }
+
+    fn check_unquoted_expansion(&mut self, expansion: Node) -> ParseResult<()> {
+        let Some(variable) = expansion.named_child(0).filter(|variable| {
+            expansion.named_child_count() == 1 && variable.kind() == "variable_name"
+        }) else {
+            return Ok(());
+        };
+        // Unknown variables are reported on their own
+        let Ok(bash_type) = self.variable_type(variable) else {
+            return Ok(());
+        };
+        if matches!(bash_type, BashType::Any | BashType::Unknown)
+            || !bash_type.can_contain(&BashType::String)
+        {
+            return Ok(());
+        }
+        let name = self.node_text(variable)?;
+        // A literal without spaces or glob characters is the same either way
+        let is_safe_literal = self
+            .resolve_name(name)
+            .and_then(|name| self.literal_values.get(name))
+            .is_some_and(|value| !value.is_empty() && !value.contains(SPLIT_OR_GLOB));
+        if is_safe_literal {
+            return Ok(());
+        }
+        let range = expansion.start_byte()..expansion.end_byte();
+        let text = self.node_text(expansion)?;
+        self.errors.push(
+            Diagnostic::new(format!("Unquoted expansion of {name}"), range.start)
+                .with_code(codes::UNQUOTED_EXPANSION)
+                .with_severity(Severity::Warning)
+                .with_label(
+                    Label::new(
+                        range.clone(),
+                        "Split into words and expanded as a glob, since it is",
+                        LabelKind::Inferred,
+                    )
+                    .with_type(bash_type),
+                )
+                .with_help(format!("Quote it as \"{text}\""))
+                .with_fix(Fix {
+                    kind: FixKind::Quote,
+                    applicability: Applicability::MaybeIncorrect,
+                    title: format!("Quote {text}"),
+                    edits: vec![
+                        TextEdit {
+                            range: range.start..range.start,
+                            new_text: String::from("\""),
+                        },
+                        TextEdit {
+                            range: range.end..range.end,
+                            new_text: String::from("\""),
+                        },
+                    ],
+                }),
+        );
+        Ok(())
+    }
+}
+
+/// The expansions in `node` that aren't inside quotes
+fn unquoted_expansions<'tree>(node: Node<'tree>, expansions: &mut Vec<Node<'tree>>) {
+    match node.kind() {
+        "simple_expansion" | "expansion" => expansions.push(node),
+        "concatenation" | "binary_expression" | "unary_expression" | "parenthesized_expression" => {
+            for child in node.named_children(&mut node.walk()) {
+                unquoted_expansions(child, expansions);
+            }
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,189 @@
+use tree_sitter::Node;
+
+use crate::{
+    codes, label_from_type_declaration, BashType, BashVersion, Diagnostic, FileInfo, Label,
+    LabelKind, Method, ParseResult, TypeDeclaration,
+};
+
+impl FileInfo<'_> {
+    /// Reports unknown variables in `<(cmd)` and `>(cmd)`, and in the targets of redirections,
+    /// where bash expands them before the command runs
+    pub(crate) fn check_redirect(&mut self, node: Node) -> ParseResult<()> {
+        // Everything nested in here is reported from the outermost region
+        if node.parent().is_some_and(|parent| in_redirect(parent)) {
+            return Ok(());
+        }
+        let targets: Vec<Node> = match node.kind() {
+            "file_redirect" => node
+                .children_by_field_name("destination", &mut node.walk())
+                .collect(),
+            "herestring_redirect" => node
+                .named_children(&mut node.walk())
+                .filter(|child| child.kind() != "file_descriptor")
+                .collect(),
+            _ => vec![node],
+        };
+        for target in targets {
+            let unknown = self.unknown_variables(target)?;
+            for variable in &unknown {
+                let error = self.unknown_variable_error(*variable)?;
+                self.push_parse_error(error, *variable);
+            }
+            if unknown.is_empty() && node.kind() == "file_redirect" {
+                self.check_redirect_target(target)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `> ${files[@]}` is an ambiguous redirect as soon as the array has more than one element
+    fn check_redirect_target(&mut self, target: Node) -> ParseResult<()> {
+        if target.kind() != "expansion" || self.force {
+            return Ok(());
+        }
+        let target_type = self.infer_type(target)?;
+        if target_type.element_type().is_some() {
+            self.errors.push(
+                Diagnostic::new("Redirection target is not a path", target.start_byte())
+                    .with_code(codes::REDIRECT_TARGET)
+                    .with_label(
+                        Label::new(
+                            target.start_byte()..target.end_byte(),
+                            "Type inferred to be",
+                            LabelKind::Inferred,
+                        )
+                        .with_type(target_type),
+                    ),
+            );
+        }
+        Ok(())
+    }
+
+    /// Handles `exec {fd}>file`, which opens a new file descriptor and assigns its number to
+    /// `fd`, and `exec {fd}>&-`, which closes the one `fd` holds. The grammar doesn't know this
+    /// form, leaving `{fd}` as a word just before the redirection
+    pub(crate) fn handle_descriptor_variables(&mut self, statement: Node) -> ParseResult<()> {
+        let redirects = statement
+            .children_by_field_name("redirect", &mut statement.walk())
+            .filter(|redirect| redirect.kind() == "file_redirect")
+            .collect::<Vec<_>>();
+        let mut words = redirects
+            .iter()
+            .flat_map(|redirect| {
+                redirect
+                    .children_by_field_name("destination", &mut redirect.walk())
+                    .last()
+            })
+            .collect::<Vec<_>>();
+        if let Some(argument) = statement.child_by_field_name("body").and_then(|body| {
+            body.children_by_field_name("argument", &mut body.walk())
+                .last()
+        }) {
+            words.push(argument);
+        }
+        for redirect in redirects {
+            let Some(variable) = words
+                .iter()
+                .find(|word| word.end_byte() == redirect.start_byte())
+                .map(|word| self.descriptor_variable(*word))
+                .transpose()?
+                .flatten()
+            else {
+                continue;
+            };
+            self.require_bash_version("{var} redirections", BashVersion::new(4, 1), redirect);
+            let closes = redirect.child_by_field_name("destination").is_none()
+                && self.node_text(redirect)?.ends_with('-');
+            self.handle_descriptor_variable(variable, redirect, closes)?;
+        }
+        Ok(())
+    }
+
+    /// The name in a word like `{fd}`
+    fn descriptor_variable<'tree>(&self, word: Node<'tree>) -> ParseResult<Option<Node<'tree>>> {
+        if word.kind() != "concatenation" || word.named_child_count() != 3 {
+            return Ok(None);
+        }
+        let parts = word
+            .named_children(&mut word.walk())
+            .map(|part| Ok((part, self.node_text(part)?)))
+            .collect::<ParseResult<Vec<_>>>()?;
+        Ok(match parts[..] {
+            [(_, "{"), (name, text), (_, "}")]
+                if name.kind() == "word"
+                    && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+            {
+                Some(name)
+            }
+            _ => None,
+        })
+    }
+
+    /// Declares the variable of `{fd}>file` as an int, or reads it for `{fd}>&-`, reporting it
+    /// if it already holds something else
+    fn handle_descriptor_variable(
+        &mut self,
+        variable: Node,
+        redirect: Node,
+        closes: bool,
+    ) -> ParseResult<()> {
+        let Some(name) = self
+            .resolve_name(self.node_text(variable)?)
+            .map(str::to_owned)
+        else {
+            return Ok(());
+        };
+        let range = variable.start_byte() - 1..variable.end_byte() + 1;
+        if let Some(declaration) = self.variables.get(&name).cloned() {
+            let bash_type = self.narrowed.get(&name).unwrap_or(&declaration.bash_type);
+            if !bash_type.can_contain(&BashType::Integer) {
+                if !self.force {
+                    self.errors.push(
+                        Diagnostic::new(
+                            format!("File descriptor variable {name} is not an int"),
+                            range.start,
+                        )
+                        .with_code(codes::REDIRECT_TARGET)
+                        .with_label(label_from_type_declaration(&declaration, false))
+                        .with_label(Label::new(
+                            range,
+                            "Used as a file descriptor here",
+                            LabelKind::Inferred,
+                        )),
+                    );
+                }
+                return Ok(());
+            }
+        }
+        if closes {
+            if let Err(error) = self.variable_type(variable) {
+                self.push_parse_error(error, variable);
+            }
+            return Ok(());
+        }
+        let declaration = TypeDeclaration {
+            bash_type: BashType::Integer,
+            range: range.start..redirect.end_byte(),
+            method: Method::Inferred,
+            node_kind: redirect.kind().to_owned(),
+            annotation: None,
+            file: None,
+        };
+        self.set_variable(&name, declaration, redirect);
+        Ok(())
+    }
+}
+
+fn in_redirect(node: Node) -> bool {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if matches!(
+            node.kind(),
+            "process_substitution" | "file_redirect" | "herestring_redirect"
+        ) {
+            return true;
+        }
+        current = node.parent();
+    }
+    false
+}
@@ -0,0 +1,169 @@
+//! Renaming a variable everywhere it is used in a file
+
+use std::{fmt::Display, ops::Range};
+
+use tree_sitter::Node;
+
+use crate::{arguments::NamedArgument, fix::TextEdit, FileInfo};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// There is no variable at the offset being renamed
+    NoVariable,
+    InvalidName(String),
+    /// The new name is already used by another variable
+    Collision(String),
+    /// The variable is also named somewhere its name can't be replaced, like `read li'ne'`
+    Unrewritable(String),
+}
+
+impl Display for RenameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoVariable => write!(f, "No variable to rename here"),
+            Self::InvalidName(name) => write!(f, "`{name}` isn't a valid variable name"),
+            Self::Collision(name) => write!(f, "A variable named `{name}` already exists"),
+            Self::Unrewritable(name) => {
+                write!(f, "`{name}` is also named somewhere that can't be renamed")
+            }
+        }
+    }
+}
+
+impl FileInfo<'_> {
+    /// The ranges of every assignment, declaration and expansion of `name`, along with the names
+    /// given in `#[set_var(...)]` annotations, in source order
+    pub fn occurrences(&mut self, name: &str) -> Vec<Range<usize>> {
+        self.named_ranges()
+            .into_iter()
+            .filter(|(text, _)| text == name)
+            .map(|(_, range)| range)
+            .collect()
+    }
+
+    /// The edits that rename the variable at `offset` to `new_name`. Fails when the new name
+    /// would merge it with another variable, including one only known from a sourced file, so
+    /// [`FileInfo::parse_code`] should have run first
+    pub fn rename(&mut self, offset: usize, new_name: &str) -> Result<Vec<TextEdit>, RenameError> {
+        let is_valid = new_name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && new_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !is_valid {
+            return Err(RenameError::InvalidName(new_name.to_owned()));
+        }
+        let named = self.named_arguments();
+        let old_name = named
+            .iter()
+            .find(|named| {
+                named
+                    .range
+                    .as_ref()
+                    .is_some_and(|range| range.contains(&offset) || range.end == offset)
+            })
+            .map(|named| named.name.clone())
+            .ok_or(RenameError::NoVariable)?;
+        if old_name == new_name {
+            return Ok(Vec::new());
+        }
+        if self.variables.contains_key(new_name) || named.iter().any(|named| named.name == new_name)
+        {
+            return Err(RenameError::Collision(new_name.to_owned()));
+        }
+        if named
+            .iter()
+            .any(|named| named.name == old_name && named.range.is_none())
+        {
+            return Err(RenameError::Unrewritable(old_name));
+        }
+        let names = named
+            .into_iter()
+            .filter_map(|named| Some((named.name, named.range?)))
+            .collect::<Vec<_>>();
+        Ok(names
+            .iter()
+            .filter(|(name, _)| *name == old_name)
+            .map(|(_, range)| TextEdit {
+                range: range.clone(),
+                new_text: new_name.to_owned(),
+            })
+            .collect())
+    }
+
+    /// Every variable name in the file with its range
+    pub(crate) fn named_ranges(&mut self) -> Vec<(String, Range<usize>)> {
+        self.named_arguments()
+            .into_iter()
+            .filter_map(|named| Some((named.name, named.range?)))
+            .collect()
+    }
+
+    /// Every variable name in the file, including the ones passed to builtins like `read` and
+    /// `printf -v` that may be written where they can't be renamed, in source order
+    fn named_arguments(&mut self) -> Vec<NamedArgument> {
+        let tree = self.parse_tree();
+        let mut names = Vec::new();
+        let mut worklist = vec![tree.root_node()];
+        while let Some(node) = worklist.pop() {
+            let mut children = node.named_children(&mut node.walk()).collect::<Vec<_>>();
+            match node.kind() {
+                "variable_name" => {
+                    if let Ok(text) = self.node_text(node) {
+                        names.push(NamedArgument {
+                            name: text.to_owned(),
+                            range: Some(node.start_byte()..node.end_byte()),
+                        });
+                    }
+                }
+                "comment" => {
+                    names.extend(self.set_var_name(node).map(|(name, range)| NamedArgument {
+                        name,
+                        range: Some(range),
+                    }))
+                }
+                "command" => names.extend(self.assigned_arguments(node).unwrap_or_default()),
+                "unset_command" | "declaration_command" => {
+                    let flags = self.declaration_flags(node).unwrap_or_default();
+                    // `unset -f name` and `export -f name` name functions
+                    if flags.contains('f') {
+                        children.retain(|child| child.kind() != "variable_name");
+                    }
+                    for child in &children {
+                        let value = match child.kind() {
+                            "variable_assignment" if flags.contains('n') => {
+                                child.child_by_field_name("value")
+                            }
+                            "word" | "raw_string" | "string" | "concatenation"
+                                if node.kind() == "unset_command" && !flags.contains('f') =>
+                            {
+                                Some(*child)
+                            }
+                            _ => None,
+                        };
+                        if let Some(value) = value {
+                            names.extend(self.argument_name(value).ok().flatten());
+                        }
+                    }
+                }
+                _ => (),
+            }
+            children.reverse();
+            worklist.extend(children);
+        }
+        names.sort_by_key(|named| named.range.as_ref().map(|range| range.start));
+        names
+    }
+
+    /// The variable named in a `#[set_var(name, type)]` comment
+    fn set_var_name(&self, node: Node) -> Option<(String, Range<usize>)> {
+        let comment = self.node_text(node).ok()?;
+        let after_marker = comment.strip_prefix("#[")?;
+        let arguments = after_marker.trim_start().strip_prefix("set_var(")?;
+        let (argument, _) = arguments.split_once(',')?;
+        let name = argument.trim();
+        let start = node.start_byte()
+            + (comment.len() - arguments.len())
+            + (argument.len() - argument.trim_start().len());
+        Some((name.to_owned(), start..start + name.len()))
+    }
+}
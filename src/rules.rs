@@ -0,0 +1,43 @@
+//! Applying the `[rules.BT0004]` tables of the config to the diagnostics of a file
+
+use crate::{scan::matches_glob, Config, Diagnostic, FileInfo};
+
+impl Config {
+    /// Removes the diagnostics of disabled rules and changes the severity of the others as
+    /// configured
+    pub(crate) fn apply_rules(&self, errors: &mut Vec<Diagnostic>) {
+        errors.retain(|error| {
+            error
+                .code
+                .as_deref()
+                .is_none_or(|code| self.rule_enabled(code, true))
+        });
+        for error in errors {
+            let severity = error
+                .code
+                .as_deref()
+                .and_then(|code| self.rules.get(code))
+                .and_then(|rule| rule.severity);
+            if let Some(severity) = severity {
+                error.severity = severity.into();
+            }
+        }
+    }
+
+    /// Whether `name` matches the `allow` patterns of the rule with `code`
+    pub(crate) fn is_allowed_variable(&self, code: &str, name: &str) -> bool {
+        self.rules
+            .get(code)
+            .is_some_and(|rule| rule.allow.iter().any(|pattern| matches_glob(pattern, name)))
+    }
+}
+
+impl FileInfo<'_> {
+    pub(crate) fn apply_rule_config(&mut self) {
+        self.config.apply_rules(&mut self.errors);
+    }
+
+    pub(crate) fn is_allowed_variable(&self, code: &str, name: &str) -> bool {
+        self.config.is_allowed_variable(code, name)
+    }
+}
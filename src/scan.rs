@@ -0,0 +1,258 @@
+//! Finding the scripts to check in directories, skipping the ones matched by ignore patterns
+
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+};
+
+/// A file of ignore patterns in the current directory, with the same syntax as `.gitignore`
+pub const IGNORE_FILE_NAME: &str = ".bashtypedignore";
+
+/// Patterns of paths to skip. Like in `.gitignore`, later patterns override earlier ones, `!`
+/// includes a path again, a trailing `/` only matches directories, and patterns containing a
+/// `/` are relative to the current directory while others match a name anywhere
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IgnoreRules {
+    patterns: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    segments: Vec<String>,
+    negated: bool,
+    directory_only: bool,
+    anchored: bool,
+}
+
+impl IgnoreRules {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        let mut rules = Self::default();
+        for pattern in patterns {
+            rules.add(pattern.as_ref());
+        }
+        rules
+    }
+
+    /// Adds every pattern of an ignore file. Blank lines and lines starting with `#` are skipped
+    pub fn add_file(&mut self, contents: &str) {
+        for line in contents.lines() {
+            self.add(line);
+        }
+    }
+
+    pub fn add(&mut self, pattern: &str) {
+        let pattern = pattern.trim_end();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            return;
+        }
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern.strip_prefix('\\').unwrap_or(pattern)),
+        };
+        let (directory_only, pattern) = match pattern.strip_suffix('/') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+        let anchored = pattern.contains('/');
+        self.patterns.push(Pattern {
+            segments: pattern
+                .trim_start_matches('/')
+                .split('/')
+                .map(str::to_owned)
+                .collect(),
+            negated,
+            directory_only,
+            anchored,
+        });
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let components = path
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let components = components.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+        let Some(name) = components.last() else {
+            return false;
+        };
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| {
+                (is_dir || !pattern.directory_only)
+                    && if pattern.anchored {
+                        matches_path(&pattern.segments, &components)
+                    } else {
+                        matches_glob(&pattern.segments[0], name)
+                    }
+            })
+            .is_some_and(|pattern| !pattern.negated)
+    }
+}
+
+/// The scripts to check for the paths given on the command line. Files are always included,
+/// while directories are searched for scripts that aren't ignored. With `all_files`, every text
+/// file in a directory counts as a script, whatever its extension or shebang
+pub fn find_scripts(
+    paths: &[PathBuf],
+    rules: &IgnoreRules,
+    all_files: bool,
+) -> io::Result<Vec<PathBuf>> {
+    let mut scripts = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            scan_directory(path, rules, all_files, &mut scripts)?;
+        } else {
+            scripts.push(path.clone());
+        }
+    }
+    Ok(scripts)
+}
+
+fn scan_directory(
+    dir: &Path,
+    rules: &IgnoreRules,
+    all_files: bool,
+    scripts: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        let is_dir = path.is_dir();
+        if path.file_name().is_some_and(|name| name == ".git") || rules.is_ignored(&path, is_dir) {
+            continue;
+        }
+        if is_dir {
+            scan_directory(&path, rules, all_files, scripts)?;
+        } else if is_script(&path) || (all_files && file_start(&path).is_some()) {
+            scripts.push(path);
+        }
+    }
+    Ok(())
+}
+
+const SHELLS: &[&str] = &[
+    "sh",
+    "bash",
+    "bats",
+    #[cfg(feature = "zsh")]
+    "zsh",
+];
+
+/// Whether a file is a shell script, going by its extension or else its shebang. Binary files are
+/// never scripts
+pub fn is_script(path: &Path) -> bool {
+    script_shell(path).is_some()
+}
+
+/// The shell a script is written for, like `bash` for `#!/usr/bin/env bash`. The shebang is what
+/// runs the file, so the extension is only used for files without one
+pub fn script_shell(path: &Path) -> Option<&'static str> {
+    let start = file_start(path)?;
+    let Some(shebang) = start
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("#!"))
+    else {
+        let extension = path.extension()?;
+        return SHELLS.iter().find(|shell| extension == **shell).copied();
+    };
+    let mut words = shebang.split_whitespace();
+    let mut interpreter = words.next()?;
+    if interpreter.ends_with("/env") {
+        interpreter = words.find(|word| !word.starts_with('-'))?;
+    }
+    let name = interpreter.rsplit('/').next()?;
+    SHELLS.iter().find(|shell| **shell == name).copied()
+}
+
+/// The first bytes of a text file, or `None` for binary files and files that can't be read
+fn file_start(path: &Path) -> Option<String> {
+    let mut start = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(1024)
+        .read_to_end(&mut start)
+        .ok()?;
+    if start.contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&start).into_owned())
+}
+
+/// Matches the segments of a pattern against the components of a path, where `**` matches any
+/// number of components
+fn matches_path(segments: &[String], components: &[&str]) -> bool {
+    match segments.split_first() {
+        None => components.is_empty(),
+        Some((segment, rest)) if segment == "**" => {
+            (0..=components.len()).any(|skipped| matches_path(rest, &components[skipped..]))
+        }
+        Some((segment, rest)) => components.split_first().is_some_and(|(component, others)| {
+            matches_glob(segment, component) && matches_path(rest, others)
+        }),
+    }
+}
+
+pub(crate) fn matches_glob(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    glob(&pattern, &text)
+}
+
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len()).any(|skipped| glob(&pattern[1..], &text[skipped..])),
+        Some('?') => !text.is_empty() && glob(&pattern[1..], &text[1..]),
+        Some('[') => match (class_end(pattern), text.first()) {
+            (Some(end), Some(c)) => {
+                matches_class(&pattern[1..end], *c) && glob(&pattern[end + 1..], &text[1..])
+            }
+            (Some(_), None) => false,
+            (None, _) => text.first() == Some(&'[') && glob(&pattern[1..], &text[1..]),
+        },
+        Some('\\') if pattern.len() > 1 => {
+            text.first() == Some(&pattern[1]) && glob(&pattern[2..], &text[1..])
+        }
+        Some(c) => text.first() == Some(c) && glob(&pattern[1..], &text[1..]),
+    }
+}
+
+/// The index of the `]` closing a character class that starts the pattern
+fn class_end(pattern: &[char]) -> Option<usize> {
+    let mut start = 1;
+    if matches!(pattern.get(start), Some('!' | '^')) {
+        start += 1;
+    }
+    // A `]` right at the start is part of the class
+    if pattern.get(start) == Some(&']') {
+        start += 1;
+    }
+    (start..pattern.len()).find(|&i| pattern[i] == ']')
+}
+
+fn matches_class(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.first() {
+        Some('!' | '^') => (true, &class[1..]),
+        _ => (false, class),
+    };
+    let mut matched = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            matched |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            matched |= class[i] == c;
+            i += 1;
+        }
+    }
+    matched != negated
+}
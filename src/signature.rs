@@ -0,0 +1,75 @@
+//! The parameters of a function while a call to it is being written
+
+use std::{fmt::Write, ops::Range};
+
+use serde::Serialize;
+
+use crate::FileInfo;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SignatureHelp {
+    /// The function written like `deploy(target: string, retries: int) -> bool`
+    pub label: String,
+    /// Where each parameter is in `label`
+    pub parameters: Vec<Range<usize>>,
+    /// The parameter the argument at the cursor is passed to, unless there are more arguments
+    /// than parameters
+    pub active_parameter: Option<usize>,
+}
+
+impl FileInfo<'_> {
+    /// The signature of the annotated function called at `offset`, using the functions found by
+    /// [`FileInfo::parse_code`]
+    pub fn signature_help(&mut self, offset: usize) -> Option<SignatureHelp> {
+        let tree = self.parse_tree();
+        let mut call = None;
+        let mut worklist = vec![tree.root_node()];
+        while let Some(node) = worklist.pop() {
+            // Arguments haven't been typed yet after a trailing space, which isn't part of the
+            // command
+            let reaches_offset = node.end_byte() >= offset
+                || self.source_code[node.end_byte()..offset]
+                    .chars()
+                    .all(|c| c == ' ' || c == '\t');
+            if node.kind() == "command" && node.start_byte() < offset && reaches_offset {
+                call = Some(node);
+            }
+            worklist.extend(node.named_children(&mut node.walk()));
+        }
+        let call = call?;
+        let name = call.child_by_field_name("name")?;
+        if offset <= name.end_byte() {
+            return None;
+        }
+        let function = self.functions.get(self.node_text(name).ok()?)?;
+        if function.params.is_empty() && function.returns.is_none() {
+            return None;
+        }
+
+        let mut label = format!("{}(", self.node_text(name).ok()?);
+        let mut parameters = Vec::new();
+        for (i, param) in function.params.iter().enumerate() {
+            if i > 0 {
+                label.push_str(", ");
+            }
+            let start = label.len();
+            write!(label, "{}: {}", param.name, param.bash_type).unwrap();
+            parameters.push(start..label.len());
+        }
+        label.push(')');
+        if let Some(returns) = &function.returns {
+            write!(label, " -> {returns}").unwrap();
+        }
+
+        // The argument at the cursor, or the one being started after the earlier ones
+        let active = call
+            .children_by_field_name("argument", &mut call.walk())
+            .filter(|argument| argument.end_byte() < offset)
+            .count();
+        Some(SignatureHelp {
+            label,
+            active_parameter: (active < parameters.len()).then_some(active),
+            parameters,
+        })
+    }
+}
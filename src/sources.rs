@@ -0,0 +1,55 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::{FileInfo, ParseResult};
+
+/// A file pulled in with `source` or `.`
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct SourceReference {
+    /// The file being sourced, if it could be determined without running the script
+    pub path: Option<String>,
+    pub range: Range<usize>,
+}
+
+impl FileInfo<'_> {
+    pub(crate) fn handle_source(&mut self, node: Node) -> ParseResult<()> {
+        let Some(argument) = node.child_by_field_name("argument") else {
+            return Ok(());
+        };
+        let path = match self.shellcheck_source_hint(node)? {
+            // shellcheck uses `source=/dev/null` to mean the file shouldn't be followed
+            Some(hint) if hint == "/dev/null" => return Ok(()),
+            Some(hint) => Some(hint),
+            None => self.static_text(argument)?,
+        };
+        self.sources.push(SourceReference {
+            path,
+            range: node.start_byte()..node.end_byte(),
+        });
+        Ok(())
+    }
+
+    /// Looks for a `# shellcheck source=path` directive in the comments directly above `node`
+    fn shellcheck_source_hint(&self, node: Node) -> ParseResult<Option<String>> {
+        let mut previous = node.prev_sibling();
+        while let Some(comment) = previous.filter(|previous| previous.kind() == "comment") {
+            let text = self.node_text(comment)?;
+            if let Some(directives) = text
+                .strip_prefix('#')
+                .map(str::trim_start)
+                .and_then(|text| text.strip_prefix("shellcheck "))
+            {
+                if let Some(path) = directives
+                    .split_whitespace()
+                    .find_map(|directive| directive.strip_prefix("source="))
+                {
+                    return Ok(Some(path.to_owned()));
+                }
+            }
+            previous = comment.prev_sibling();
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,127 @@
+use tree_sitter::Node;
+
+use crate::{
+    bash_integer, codes, BashType, Diagnostic, FileInfo, Label, LabelKind, ParseResult, Severity,
+};
+
+/// Nodes that run a command and so set `$?`
+const STATEMENTS: &[&str] = &[
+    "command",
+    "variable_assignment",
+    "declaration_command",
+    "unset_command",
+    "test_command",
+    "negated_command",
+    "redirected_statement",
+    "pipeline",
+    "list",
+    "subshell",
+    "compound_statement",
+    "if_statement",
+    "while_statement",
+    "for_statement",
+    "c_style_for_statement",
+    "case_statement",
+    "function_definition",
+];
+
+impl FileInfo<'_> {
+    /// Warns about `$?` being read before anything in the function or script has run
+    pub(crate) fn check_status_read(&mut self, expansion: Node) -> ParseResult<()> {
+        if !self.is_status(expansion) || self.force {
+            return Ok(());
+        }
+        let mut node = expansion;
+        while let Some(parent) = node.parent() {
+            let mut previous = node.prev_named_sibling();
+            while let Some(sibling) = previous {
+                if STATEMENTS.contains(&sibling.kind()) {
+                    return Ok(());
+                }
+                previous = sibling.prev_named_sibling();
+            }
+            if parent.kind() == "function_definition" {
+                break;
+            }
+            node = parent;
+        }
+        self.errors.push(
+            Diagnostic::new("$? read before any command ran", expansion.start_byte())
+                .with_code(codes::STATUS_MISUSE)
+                .with_severity(Severity::Warning)
+                .with_label(Label::new(
+                    expansion.start_byte()..expansion.end_byte(),
+                    "No command has run before this in the current block",
+                    LabelKind::Inferred,
+                )),
+        );
+        Ok(())
+    }
+
+    /// Reports comparisons like `[[ $? == "ok" ]]`, which can never be true
+    pub(crate) fn check_status_comparison(&mut self, comparison: Node) -> ParseResult<()> {
+        let (Some(left), Some(right)) = (
+            comparison.child_by_field_name("left"),
+            comparison.child_by_field_name("right"),
+        ) else {
+            return Ok(());
+        };
+        let (status, other) = if self.is_status(left) {
+            (left, right)
+        } else if self.is_status(right) {
+            (right, left)
+        } else {
+            return Ok(());
+        };
+        let Some(literal) = self.static_text(other)? else {
+            return Ok(());
+        };
+        if bash_integer(&literal).is_some() || self.force {
+            return Ok(());
+        }
+        self.errors.push(
+            Diagnostic::new(
+                "Exit status compared with a string",
+                comparison.start_byte(),
+            )
+            .with_code(codes::STATUS_MISUSE)
+            .with_label(
+                Label::new(
+                    status.start_byte()..status.end_byte(),
+                    "Type inferred to be",
+                    LabelKind::Inferred,
+                )
+                .with_type(BashType::Integer),
+            )
+            .with_label(
+                Label::new(
+                    other.start_byte()..other.end_byte(),
+                    "Type inferred to be",
+                    LabelKind::Inferred,
+                )
+                .with_type(BashType::String),
+            ),
+        );
+        Ok(())
+    }
+
+    /// Whether `node` is `$?`, `${?}` or `"$?"`
+    fn is_status(&self, node: Node) -> bool {
+        match node.kind() {
+            "simple_expansion" | "expansion" => {
+                node.named_child_count() == 1
+                    && node.named_child(0).is_some_and(|child| {
+                        child.kind() == "special_variable_name"
+                            && self.node_text(child).is_ok_and(|name| name == "?")
+                    })
+            }
+            "string" => {
+                node.named_child_count() == 1
+                    && node
+                        .named_child(0)
+                        .is_some_and(|child| self.is_status(child))
+            }
+            _ => false,
+        }
+    }
+}
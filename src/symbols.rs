@@ -0,0 +1,387 @@
+//! A listing of every variable and function in a file with the types bashtyped found for them
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    config::{ConfigError, ConfigHierarchy},
+    diagnostic::SourceIndex,
+    files::FileId,
+    Call, Config, FileInfo, Method, TypeDeclaration,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Symbols {
+    pub variables: Vec<VariableSymbol>,
+    pub functions: Vec<FunctionSymbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VariableSymbol {
+    pub name: String,
+    /// The type at the end of the file
+    #[serde(rename = "type")]
+    pub bash_type: String,
+    pub method: Method,
+    pub span: Span,
+    /// Every declaration and assignment, oldest first
+    pub declarations: Vec<DeclarationSymbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeclarationSymbol {
+    #[serde(rename = "type")]
+    pub bash_type: String,
+    pub method: Method,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionSymbol {
+    pub name: String,
+    /// The type given by a `#[returns ...]` annotation
+    pub returns: Option<String>,
+    pub span: Span,
+}
+
+/// A range of the source, with the line and column where it starts, both counted from 1
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Span {
+    /// The file of a [`Workspace`] the range is in, or `None` for the file the span was found in
+    pub file: Option<FileId>,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub(crate) fn new(index: &SourceIndex, range: &Range<usize>) -> Self {
+        let (line, column) = index.line_column(range.start);
+        Self {
+            file: None,
+            start: range.start,
+            end: range.end,
+            line,
+            column,
+        }
+    }
+
+    fn in_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+}
+
+impl Symbols {
+    /// Collects the symbols of a file that has been analyzed, sorted by name
+    pub fn new(file: &FileInfo) -> Self {
+        let declaration = |declaration: &TypeDeclaration| DeclarationSymbol {
+            bash_type: declaration.bash_type.to_string(),
+            method: declaration.method,
+            span: Span::new(file.source_index(), &declaration.range),
+        };
+        let mut variables = file
+            .variables
+            .iter()
+            .map(|(name, variable)| VariableSymbol {
+                name: name.clone(),
+                bash_type: variable.bash_type.to_string(),
+                method: variable.method,
+                span: Span::new(file.source_index(), &variable.range),
+                declarations: file
+                    .history
+                    .get(name)
+                    .into_iter()
+                    .flatten()
+                    .map(declaration)
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        variables.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut functions = file
+            .functions
+            .iter()
+            .map(|(name, function)| FunctionSymbol {
+                name: name.clone(),
+                returns: function.returns.as_ref().map(ToString::to_string),
+                span: Span::new(file.source_index(), &function.range),
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            variables,
+            functions,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SymbolKind {
+    Variable,
+    Function,
+}
+
+/// A variable or function as an editor shows it in an outline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DocumentSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The type of a variable or the return type of a function
+    pub detail: Option<String>,
+    pub span: Span,
+}
+
+impl Symbols {
+    /// The variables and functions in the order they appear in the file
+    pub fn document_symbols(&self) -> Vec<DocumentSymbol> {
+        let variables = self.variables.iter().map(|variable| DocumentSymbol {
+            name: variable.name.clone(),
+            kind: SymbolKind::Variable,
+            detail: Some(variable.bash_type.clone()),
+            span: variable.span.clone(),
+        });
+        let functions = self.functions.iter().map(|function| DocumentSymbol {
+            name: function.name.clone(),
+            kind: SymbolKind::Function,
+            detail: function.returns.clone(),
+            span: function.span.clone(),
+        });
+        let mut symbols = variables.chain(functions).collect::<Vec<_>>();
+        symbols.sort_by_key(|symbol| symbol.span.start);
+        symbols
+    }
+}
+
+/// A document symbol found by searching a [`Workspace`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkspaceSymbol {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub symbol: DocumentSymbol,
+}
+
+/// The symbols of every open or scanned file, so they can be searched together
+#[derive(Debug, Default, Clone)]
+pub struct Workspace {
+    pub(crate) files: BTreeMap<PathBuf, WorkspaceFile>,
+    /// The path of each file, by its [`FileId`]. Ids aren't reused after a file is removed
+    paths: Vec<PathBuf>,
+    configs: Option<ConfigHierarchy>,
+}
+
+/// What a [`Workspace`] keeps of each file
+#[derive(Debug, Clone)]
+pub(crate) struct WorkspaceFile {
+    pub(crate) id: FileId,
+    pub(crate) symbols: Vec<DocumentSymbol>,
+    /// The type of each variable at the end of the file, leaving out the ones only set with
+    /// `local`
+    pub(crate) globals: BTreeMap<String, TypeDeclaration>,
+    pub(crate) exports: BTreeMap<String, (FileId, Range<usize>)>,
+    /// The first declaration or assignment of each variable
+    pub(crate) definitions: BTreeMap<String, Span>,
+    /// The paths given to `source`, as written
+    pub(crate) sources: Vec<String>,
+    /// Where each function is defined
+    pub(crate) functions: BTreeMap<String, Span>,
+    pub(crate) calls: Vec<Call>,
+    pub(crate) references: BTreeSet<String>,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A workspace whose files are analyzed with the configs found by `configs`
+    pub fn with_configs(configs: ConfigHierarchy) -> Self {
+        Self {
+            configs: Some(configs),
+            ..Default::default()
+        }
+    }
+
+    /// The config to analyze the file at `path` with, which is the default one unless the
+    /// workspace was created with [`Workspace::with_configs`]
+    pub fn config_for(&mut self, path: &Path) -> Result<Config, ConfigError> {
+        match &mut self.configs {
+            Some(configs) => configs.config_for(path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// Adds the symbols of an analyzed file, replacing the ones found in it before. Returns the id
+    /// of the file, which stays the same when it is updated again
+    pub fn update(&mut self, path: impl Into<PathBuf>, file: &FileInfo) -> FileId {
+        let path = path.into();
+        let id = match self.files.get(&path) {
+            Some(existing) => existing.id,
+            None => {
+                self.paths.push(path.clone());
+                FileId(self.paths.len() - 1)
+            }
+        };
+        let globals = file
+            .variables
+            .iter()
+            .filter(|(name, _)| file.globals.contains(*name) || !file.history.contains_key(*name))
+            .map(|(name, variable)| {
+                let mut variable = variable.clone();
+                variable.file = Some(id);
+                (name.clone(), variable)
+            })
+            .collect();
+        let mut symbols = Symbols::new(file).document_symbols();
+        for symbol in &mut symbols {
+            symbol.span.file = Some(id);
+        }
+        let workspace_file = WorkspaceFile {
+            id,
+            symbols,
+            globals,
+            exports: file
+                .exports
+                .iter()
+                .map(|(name, range)| (name.clone(), (id, range.clone())))
+                .collect(),
+            definitions: file
+                .history
+                .iter()
+                .filter_map(|(name, history)| {
+                    let first = history.first()?;
+                    let span = Span::new(file.source_index(), &first.range).in_file(id);
+                    Some((name.clone(), span))
+                })
+                .collect(),
+            sources: file
+                .sources
+                .iter()
+                .filter_map(|source| source.path.clone())
+                .collect(),
+            functions: file
+                .functions
+                .iter()
+                .map(|(name, function)| {
+                    let span = Span::new(file.source_index(), &function.range).in_file(id);
+                    (name.clone(), span)
+                })
+                .collect(),
+            calls: file.calls.clone(),
+            references: file.references.clone(),
+        };
+        self.files.insert(path, workspace_file);
+        id
+    }
+
+    pub fn file_id(&self, path: &Path) -> Option<FileId> {
+        self.files.get(path).map(|file| file.id)
+    }
+
+    pub fn path(&self, id: FileId) -> Option<&Path> {
+        self.paths
+            .get(id.0)
+            .map(PathBuf::as_path)
+            .filter(|path| self.files.contains_key(*path))
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    pub fn document_symbols(&self, path: &Path) -> &[DocumentSymbol] {
+        self.files
+            .get(path)
+            .map_or(&[], |file| file.symbols.as_slice())
+    }
+
+    /// The symbols whose names contain `query`, ignoring case. Exact matches come first, then
+    /// names starting with the query, each sorted by name and path
+    pub fn search(&self, query: &str) -> Vec<WorkspaceSymbol> {
+        let query = query.to_lowercase();
+        let mut found = Vec::new();
+        for (path, file) in &self.files {
+            for symbol in &file.symbols {
+                let name = symbol.name.to_lowercase();
+                let rank = if name == query {
+                    0
+                } else if name.starts_with(&query) {
+                    1
+                } else if name.contains(&query) {
+                    2
+                } else {
+                    continue;
+                };
+                found.push((
+                    rank,
+                    WorkspaceSymbol {
+                        path: path.clone(),
+                        symbol: symbol.clone(),
+                    },
+                ));
+            }
+        }
+        found.sort_by(|(a_rank, a), (b_rank, b)| {
+            (a_rank, &a.symbol.name, &a.path, a.symbol.span.start).cmp(&(
+                b_rank,
+                &b.symbol.name,
+                &b.path,
+                b.symbol.span.start,
+            ))
+        });
+        found.into_iter().map(|(_, symbol)| symbol).collect()
+    }
+}
+
+fn method_name(method: Method) -> &'static str {
+    match method {
+        Method::Declared => "declared",
+        Method::Inferred => "inferred",
+    }
+}
+
+impl Display for Symbols {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Variables:")?;
+        for variable in &self.variables {
+            writeln!(
+                f,
+                "  {}: {} ({}) at {}:{}",
+                variable.name,
+                variable.bash_type,
+                method_name(variable.method),
+                variable.span.line,
+                variable.span.column
+            )?;
+            for declaration in &variable.declarations {
+                writeln!(
+                    f,
+                    "    {} {} at {}:{}",
+                    method_name(declaration.method),
+                    declaration.bash_type,
+                    declaration.span.line,
+                    declaration.span.column
+                )?;
+            }
+        }
+        writeln!(f, "Functions:")?;
+        for function in &self.functions {
+            write!(
+                f,
+                "  {} at {}:{}",
+                function.name, function.span.line, function.span.column
+            )?;
+            match &function.returns {
+                Some(returns) => writeln!(f, ", returns {returns}")?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
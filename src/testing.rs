@@ -0,0 +1,333 @@
+//! Helpers for testing rules against small scripts without spelling out every declaration and
+//! byte range
+//!
+//! ```
+//! use bashtyped::{assert_diagnostics, assert_types, BashType};
+//!
+//! assert_types!("count=1\nname=\"$(pwd)/x\"", {
+//!     "count" => BashType::Integer,
+//!     "name" => "string",
+//! });
+//! assert_diagnostics!("a=1 #/ bool", [BT0001 @ 7..11]);
+//! ```
+//!
+//! Scripts can also carry the diagnostics they expect as marker comments, which underline the
+//! range of the first label on the line above them and give its severity, code and message. A
+//! `#` directly followed by `^` is part of the underline, so ranges can start at column 0
+//!
+//! ```
+//! // A redefinition is labelled first at the declaration it conflicts with
+//! let source = "count=1\n#^^^^^^ error BT0003: Variable count defined with different type\n\
+//!               count=text";
+//! bashtyped::testing::check_markers(source).unwrap();
+//! ```
+
+use std::{fmt::Write, ops::Range};
+
+use crate::{BashType, Config, FileInfo, Severity};
+
+pub use crate::{assert_diagnostics, assert_types};
+
+/// A type a variable is expected to have, either a [`BashType`] or a type written as in an
+/// annotation, like `"array<int>"`
+pub trait ExpectedType {
+    fn expected_type(self) -> BashType;
+}
+
+impl ExpectedType for BashType {
+    fn expected_type(self) -> BashType {
+        self
+    }
+}
+
+impl ExpectedType for &str {
+    fn expected_type(self) -> BashType {
+        self.parse()
+            .unwrap_or_else(|e| panic!("Invalid expected type `{self}`: {e}"))
+    }
+}
+
+/// Analyzes `source` with the default config
+pub fn analyze(source: &str) -> FileInfo<'_> {
+    analyze_with_config(source, Config::default())
+}
+
+/// Analyzes `source` with `config`
+pub fn analyze_with_config(source: &str, config: Config) -> FileInfo<'_> {
+    let mut file = FileInfo::with_config(source, config);
+    file.parse_code();
+    file
+}
+
+/// Checks that each variable has the type it is paired with at the end of `file`, describing
+/// every difference otherwise. Variables that aren't listed aren't checked
+pub fn check_types(file: &FileInfo, expected: Vec<(&str, BashType)>) -> Result<(), String> {
+    let mut problems = String::new();
+    for (name, expected) in expected {
+        match file.variables.get(name) {
+            Some(variable) if variable.bash_type.normalized() == expected.normalized() => (),
+            Some(variable) => {
+                let _ = writeln!(
+                    problems,
+                    "{name} is {}, expected {expected}",
+                    variable.bash_type
+                );
+            }
+            None => {
+                let _ = writeln!(problems, "{name} is not set, expected {expected}");
+            }
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Checks that `file` has exactly the diagnostics with these codes, in order. A diagnostic with
+/// a range has to have it as the range of its first label
+pub fn check_diagnostics(
+    file: &FileInfo,
+    expected: Vec<(&str, Option<Range<usize>>)>,
+) -> Result<(), String> {
+    let found = file
+        .errors
+        .iter()
+        .map(|error| {
+            (
+                error.code.as_deref().unwrap_or_default(),
+                error.labels.first().map(|label| label.range.clone()),
+            )
+        })
+        .collect::<Vec<_>>();
+    let matches = found.len() == expected.len()
+        && found
+            .iter()
+            .zip(&expected)
+            .all(|((code, range), (expected_code, expected_range))| {
+                code == expected_code
+                    && expected_range
+                        .as_ref()
+                        .is_none_or(|expected_range| range.as_ref() == Some(expected_range))
+            });
+    if matches {
+        return Ok(());
+    }
+    let describe = |diagnostics: &mut dyn Iterator<Item = (&str, Option<&Range<usize>>)>| {
+        diagnostics
+            .map(|(code, range)| match range {
+                Some(range) => format!("{code} @ {range:?}"),
+                None => code.to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut problems = format!(
+        "expected [{}]\n   found [{}]\n",
+        describe(&mut expected.iter().map(|(code, range)| (*code, range.as_ref()))),
+        describe(&mut found.iter().map(|(code, range)| (*code, range.as_ref())))
+    );
+    for error in &file.errors {
+        let _ = writeln!(problems, "{}", error.short("<source>", file.source_code));
+    }
+    Err(problems)
+}
+
+/// Checks that every offset and range `file` reports, in its diagnostics, their labels and their
+/// fixes, is inside its source and on character boundaries, so reporting them can't panic
+pub fn check_ranges(file: &FileInfo) -> Result<(), String> {
+    let source = file.source_code;
+    let valid = |range: &Range<usize>| {
+        range.start <= range.end
+            && source.is_char_boundary(range.start)
+            && source.is_char_boundary(range.end)
+    };
+    let mut problems = String::new();
+    for error in &file.errors {
+        let code = error.code.as_deref().unwrap_or_default();
+        if !source.is_char_boundary(error.offset) {
+            let _ = writeln!(problems, "{code} is at offset {}", error.offset);
+        }
+        for label in error.labels.iter().filter(|label| label.file.is_none()) {
+            if !valid(&label.range) {
+                let _ = writeln!(problems, "{code} has a label at {:?}", label.range);
+            }
+        }
+        for edit in error.fixes.iter().flat_map(|fix| &fix.edits) {
+            if !valid(&edit.range) {
+                let _ = writeln!(problems, "{code} has a fix editing {:?}", edit.range);
+            }
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Ranges outside a source of {} bytes:\n{problems}",
+            source.len()
+        ))
+    }
+}
+
+/// A diagnostic expected by a marker comment, or one that was found, placed by the line and the
+/// columns of its range
+#[derive(Debug, PartialEq, Eq)]
+struct Marker {
+    line: usize,
+    columns: Range<usize>,
+    severity: String,
+    code: String,
+    message: String,
+}
+
+impl std::fmt::Display for Marker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, columns {:?}: {} {}: {}",
+            self.line + 1,
+            self.columns,
+            self.severity,
+            self.code,
+            self.message
+        )
+    }
+}
+
+/// Checks that analyzing `source` finds exactly the diagnostics its marker comments expect, like
+/// `# ^^^ error BT0001: Types do not match` under the line with the problem
+pub fn check_markers(source: &str) -> Result<(), String> {
+    let mut expected = parse_markers(source)?;
+    let file = analyze(source);
+    let mut unexpected = Vec::new();
+    for error in &file.errors {
+        let found = found_marker(source, error);
+        match expected.iter().position(|marker| *marker == found) {
+            Some(index) => {
+                expected.remove(index);
+            }
+            None => unexpected.push(found),
+        }
+    }
+    if expected.is_empty() && unexpected.is_empty() {
+        return Ok(());
+    }
+    let mut problems = String::new();
+    for marker in expected {
+        let _ = writeln!(problems, "missing    {marker}");
+    }
+    for marker in unexpected {
+        let _ = writeln!(problems, "unexpected {marker}");
+    }
+    Err(problems)
+}
+
+/// The markers of `source`, each for the closest line above it that isn't a marker
+fn parse_markers(source: &str) -> Result<Vec<Marker>, String> {
+    let mut markers = Vec::new();
+    let mut underlined = None;
+    for (number, line) in source.lines().enumerate() {
+        let text = line.trim_start();
+        let indent = line.len() - text.len();
+        let Some(after_hash) = text
+            .strip_prefix('#')
+            .filter(|after_hash| after_hash.trim_start().starts_with('^'))
+        else {
+            underlined = Some(number);
+            continue;
+        };
+        let invalid = |reason: &str| format!("Invalid marker on line {}: {reason}", number + 1);
+        let Some(underlined) = underlined else {
+            return Err(invalid("there is no line above it"));
+        };
+        let carets = after_hash.trim_start();
+        let width = carets.len() - carets.trim_start_matches('^').len();
+        let start = if after_hash.starts_with('^') {
+            indent
+        } else {
+            indent + 1 + after_hash.len() - carets.len()
+        };
+        let end = indent + 1 + after_hash.len() - carets.len() + width;
+        let description = carets[width..].trim();
+        let (severity, rest) = description
+            .split_once(' ')
+            .ok_or_else(|| invalid("expected a severity, code and message"))?;
+        let (code, message) = rest
+            .split_once(':')
+            .ok_or_else(|| invalid("expected `:` before the message"))?;
+        if !matches!(severity, "error" | "warning" | "advice") {
+            return Err(invalid("the severity should be error, warning or advice"));
+        }
+        markers.push(Marker {
+            line: underlined,
+            columns: start..end,
+            severity: severity.to_owned(),
+            code: code.trim().to_owned(),
+            message: message.trim().to_owned(),
+        });
+    }
+    Ok(markers)
+}
+
+/// Where a diagnostic is, as a marker would underline it. Ranges that go past the end of their
+/// first line are underlined to its end
+fn found_marker(source: &str, error: &crate::Diagnostic) -> Marker {
+    let range = error
+        .labels
+        .first()
+        .map_or(error.offset..error.offset + 1, |label| label.range.clone());
+    let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[range.start..]
+        .find('\n')
+        .map_or(source.len(), |i| range.start + i);
+    Marker {
+        line: source[..range.start].matches('\n').count(),
+        columns: range.start - line_start..range.end.min(line_end) - line_start,
+        severity: match error.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Advice => "advice",
+        }
+        .to_owned(),
+        code: error.code.clone().unwrap_or_default(),
+        message: error.message.clone(),
+    }
+}
+
+/// Asserts that a script gives variables these types, like
+/// `assert_types!(source, { "a" => BashType::Integer, "b" => "array<string>" })`. The source is
+/// analyzed with the default config, unless it is an analyzed [`FileInfo`] passed as
+/// `file: &file`
+#[macro_export]
+macro_rules! assert_types {
+    (file: $file:expr, { $($name:literal => $bash_type:expr),* $(,)? } $(,)?) => {
+        if let Err(problems) = $crate::testing::check_types(
+            $file,
+            vec![$(($name, $crate::testing::ExpectedType::expected_type($bash_type))),*],
+        ) {
+            panic!("Variables don't have the expected types:\n{problems}");
+        }
+    };
+    ($source:expr, { $($types:tt)* } $(,)?) => {
+        $crate::assert_types!(file: &$crate::testing::analyze($source), { $($types)* })
+    };
+}
+
+/// Asserts that a script has exactly these diagnostics in order, each a code optionally followed
+/// by the range of its first label, like `assert_diagnostics!(source, [BT0001 @ 7..11, BT0003])`.
+/// An empty list asserts that there are none
+#[macro_export]
+macro_rules! assert_diagnostics {
+    (file: $file:expr, [$($code:ident $(@ $range:expr)?),* $(,)?] $(,)?) => {
+        if let Err(problems) = $crate::testing::check_diagnostics(
+            $file,
+            vec![$((stringify!($code), None $(.or(Some($range)))?)),*],
+        ) {
+            panic!("The diagnostics aren't the expected ones:\n{problems}");
+        }
+    };
+    ($source:expr, [$($diagnostics:tt)*] $(,)?) => {
+        $crate::assert_diagnostics!(file: &$crate::testing::analyze($source), [$($diagnostics)*])
+    };
+}
@@ -0,0 +1,210 @@
+//! Classification of the parts of annotations, so editors can highlight them differently from
+//! ordinary comments
+
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::FileInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TokenKind {
+    /// The `#/`, `#[` and `#![` markers and the names of commands and directives
+    Keyword,
+    Type,
+    /// Punctuation inside types and directives, like `|`, `<` and `>=`
+    Operator,
+    /// Record keys and the variable named by `set_var`
+    Variable,
+    Number,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SemanticToken {
+    pub range: Range<usize>,
+    pub kind: TokenKind,
+}
+
+impl FileInfo<'_> {
+    /// The tokens of every annotation in the file, in source order. Doesn't need
+    /// [`FileInfo::parse_code`] to have run
+    pub fn semantic_tokens(&mut self) -> Vec<SemanticToken> {
+        let tree = self.parse_tree();
+        let mut tokens = Vec::new();
+        let mut worklist = vec![tree.root_node()];
+        while let Some(node) = worklist.pop() {
+            if node.kind() == "comment" {
+                if let Ok(text) = self.node_text(node) {
+                    tokens.extend(comment_tokens(text, node.start_byte()));
+                }
+            }
+            let mut children = node.named_children(&mut node.walk()).collect::<Vec<_>>();
+            children.reverse();
+            worklist.extend(children);
+        }
+        tokens
+    }
+}
+
+/// The tokens of a single comment starting at `offset`, which are empty for ordinary comments
+pub fn comment_tokens(comment: &str, offset: usize) -> Vec<SemanticToken> {
+    let keyword = |range| SemanticToken {
+        range,
+        kind: TokenKind::Keyword,
+    };
+    if let Some(annotation) = comment.strip_prefix("#/") {
+        let mut tokens = vec![keyword(offset..offset + 2)];
+        tokens.extend(type_tokens(&lex(annotation, offset + 2)));
+        return tokens;
+    }
+    let (marker, is_directive) = if comment.starts_with("#![") {
+        (3, true)
+    } else if comment.starts_with("#[") {
+        (2, false)
+    } else {
+        return Vec::new();
+    };
+    let trimmed = comment.trim_end();
+    if trimmed.len() <= marker || !trimmed.ends_with(']') {
+        return Vec::new();
+    }
+    let end = trimmed.len() - 1;
+    let mut tokens = vec![keyword(offset..offset + marker)];
+    let lexemes = lex(&comment[marker..end], offset + marker);
+    if let Some((name, rest)) = lexemes.split_first() {
+        tokens.push(keyword(name.range.clone()));
+        match (is_directive, name.text) {
+            // `#[set_var(name, type)]`
+            (false, "set_var") => {
+                for (i, lexeme) in rest.iter().enumerate() {
+                    if lexeme.text == "," {
+                        tokens.push(operator(lexeme.range.clone()));
+                        tokens.extend(type_tokens(&rest[i + 1..rest.len().saturating_sub(1)]));
+                        if let Some(close) = rest.last().filter(|_| rest.len() > i + 1) {
+                            tokens.push(operator(close.range.clone()));
+                        }
+                        break;
+                    }
+                    tokens.push(SemanticToken {
+                        range: lexeme.range.clone(),
+                        kind: if lexeme.is_word() {
+                            TokenKind::Variable
+                        } else {
+                            TokenKind::Operator
+                        },
+                    });
+                }
+            }
+            // `#[params path: string, retries: int]`, where the names are like record keys
+            (false, "params") => tokens.extend(type_tokens_in(rest, vec!["{"])),
+            // `#[returns int]` and other commands followed by a type
+            (false, _) => tokens.extend(type_tokens(rest)),
+            // `#![bash >= 4.2]`
+            _ => tokens.extend(rest.iter().map(|lexeme| SemanticToken {
+                range: lexeme.range.clone(),
+                kind: if lexeme.is_number() {
+                    TokenKind::Number
+                } else if lexeme.is_word() {
+                    TokenKind::Keyword
+                } else {
+                    TokenKind::Operator
+                },
+            })),
+        }
+    }
+    tokens.push(keyword(offset + end..offset + end + 1));
+    tokens
+}
+
+fn operator(range: Range<usize>) -> SemanticToken {
+    SemanticToken {
+        range,
+        kind: TokenKind::Operator,
+    }
+}
+
+/// Classifies the parts of a type like `{ host: string, ports: array<int> }`
+fn type_tokens(lexemes: &[Lexeme]) -> Vec<SemanticToken> {
+    type_tokens_in(lexemes, Vec::new())
+}
+
+/// Classifies the parts of a type that starts nested inside the `open` brackets
+fn type_tokens_in<'a>(lexemes: &[Lexeme<'a>], mut open: Vec<&'a str>) -> Vec<SemanticToken> {
+    let mut tokens = Vec::new();
+    for (i, lexeme) in lexemes.iter().enumerate() {
+        let kind = if lexeme.is_word() {
+            let is_key = open.last() == Some(&"{")
+                && lexemes.get(i + 1).is_some_and(|next| next.text == ":");
+            if is_key {
+                TokenKind::Variable
+            } else {
+                TokenKind::Type
+            }
+        } else if lexeme.is_number() {
+            TokenKind::Number
+        } else {
+            match lexeme.text {
+                "<" | "[" | "{" => open.push(lexeme.text),
+                ">" | "]" | "}" => {
+                    open.pop();
+                }
+                _ => (),
+            }
+            TokenKind::Operator
+        };
+        tokens.push(SemanticToken {
+            range: lexeme.range.clone(),
+            kind,
+        });
+    }
+    tokens
+}
+
+struct Lexeme<'a> {
+    text: &'a str,
+    range: Range<usize>,
+}
+
+impl Lexeme<'_> {
+    fn is_word(&self) -> bool {
+        self.text
+            .starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+    }
+
+    fn is_number(&self) -> bool {
+        self.text.starts_with(|c: char| c.is_ascii_digit())
+    }
+}
+
+/// Splits text into words, numbers like `4.2`, and punctuation, where `>=` is a single lexeme
+fn lex(text: &str, offset: usize) -> Vec<Lexeme<'_>> {
+    let mut lexemes = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        let continues: fn(char) -> bool = if c.is_ascii_alphabetic() || c == '_' {
+            |c| c.is_ascii_alphanumeric() || c == '_' || c == '-'
+        } else if c.is_ascii_digit() {
+            |c| c.is_ascii_digit() || c == '.'
+        } else if c == '>' || c == '<' {
+            |c| c == '='
+        } else {
+            |_| false
+        };
+        while let Some(&(i, next)) = chars.peek() {
+            if !continues(next) {
+                break;
+            }
+            end = i + next.len_utf8();
+            chars.next();
+        }
+        lexemes.push(Lexeme {
+            text: &text[start..end],
+            range: offset + start..offset + end,
+        });
+    }
+    lexemes
+}
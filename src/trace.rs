@@ -0,0 +1,243 @@
+//! The chain of evidence behind the type of a variable: each assignment, how its type was found,
+//! and how it combined with the type before it
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AnnotationPlacement, BashType, FileInfo, Method, TypeDeclaration};
+
+/// A change to the type of a variable made when the analysis joins paths through the script,
+/// rather than by an assignment
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeMerge {
+    pub name: String,
+    /// Where the paths join
+    pub offset: usize,
+    pub bash_type: BashType,
+    pub reason: MergeReason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MergeReason {
+    /// The right side of a `&&` or `||` list may not have run its assignment
+    ShortCircuit,
+    /// The assignment ran in another process, like a background job, so the type before it is
+    /// restored
+    Subshell,
+}
+
+/// How a step changed the type of the variable
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The first type of the variable
+    First,
+    /// The new type contains the old one, so it replaces it
+    Replaced,
+    /// An inferred type that fits the declared type, which is kept
+    KeptDeclaration,
+    /// An inferred type that fits the declared type, which is narrowed to it until the next
+    /// assignment
+    Narrowed,
+    /// A type the old one doesn't fit, reported as a redefinition unless the statement is forced
+    Conflict,
+    /// Paths through the script were joined
+    Merged(MergeReason),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceStep {
+    /// The line of the statement, counted from 1
+    pub line: usize,
+    /// The text of that line
+    pub source: String,
+    /// The type found by this step alone
+    pub bash_type: BashType,
+    /// How that type was found, like `inferred from the assigned value`
+    pub rule: String,
+    pub outcome: Outcome,
+    /// The type of the variable after this step
+    pub result: BashType,
+}
+
+/// Why a variable ended up with its type, as returned by [`FileInfo::explain`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    pub name: String,
+    pub steps: Vec<TraceStep>,
+    /// The type at the end of the file, or `None` if the variable was unset or only assigned in
+    /// a background job
+    pub final_type: Option<BashType>,
+}
+
+impl FileInfo<'_> {
+    /// Explains the type of `name` after [`FileInfo::parse_code`], or `None` if the file never
+    /// assigns it. Joins of paths are only known for files that were analyzed rather than loaded
+    /// from the cache
+    pub fn explain(&self, name: &str) -> Option<Explanation> {
+        let history = self.history.get(name)?;
+        let mut merges = self
+            .merges
+            .iter()
+            .filter(|merge| merge.name == name)
+            .peekable();
+        let mut steps = Vec::new();
+        let mut current: Option<TypeDeclaration> = None;
+        for declaration in history {
+            while let Some(merge) = merges.next_if(|merge| merge.offset <= declaration.range.start)
+            {
+                steps.push(self.merge_step(merge));
+                if let Some(current) = &mut current {
+                    current.bash_type = merge.bash_type.clone();
+                }
+            }
+            let (outcome, result) = match &current {
+                None => (Outcome::First, declaration.bash_type.clone()),
+                Some(previous) => self.combine(previous, declaration),
+            };
+            if !matches!(outcome, Outcome::KeptDeclaration | Outcome::Narrowed) {
+                current = Some(declaration.clone());
+            }
+            let (line, source) = self.line_text(self.statement_start(declaration));
+            steps.push(TraceStep {
+                line,
+                source,
+                bash_type: declaration.bash_type.clone(),
+                rule: rule(declaration),
+                outcome,
+                result,
+            });
+        }
+        steps.extend(merges.map(|merge| self.merge_step(merge)));
+        Some(Explanation {
+            name: name.to_owned(),
+            steps,
+            final_type: self
+                .variables
+                .get(name)
+                .map(|variable| variable.bash_type.clone()),
+        })
+    }
+
+    /// How `declaration` changes the type of a variable last set by `previous`, following the
+    /// rules of `set_variable`
+    fn combine(
+        &self,
+        previous: &TypeDeclaration,
+        declaration: &TypeDeclaration,
+    ) -> (Outcome, BashType) {
+        let keeps_declaration =
+            previous.method == Method::Declared && declaration.method == Method::Inferred;
+        if !keeps_declaration {
+            let outcome = if declaration.bash_type.can_contain(&previous.bash_type) {
+                Outcome::Replaced
+            } else {
+                Outcome::Conflict
+            };
+            return (outcome, declaration.bash_type.clone());
+        }
+        if !previous.bash_type.can_contain(&declaration.bash_type) {
+            return (Outcome::Conflict, declaration.bash_type.clone());
+        }
+        if self.config.narrow_on_assignment
+            && !matches!(declaration.bash_type, BashType::Any | BashType::Unknown)
+        {
+            (Outcome::Narrowed, declaration.bash_type.clone())
+        } else {
+            (Outcome::KeptDeclaration, previous.bash_type.clone())
+        }
+    }
+
+    fn merge_step(&self, merge: &TypeMerge) -> TraceStep {
+        let (line, source) = self.line_text(merge.offset.saturating_sub(1));
+        let rule = match merge.reason {
+            MergeReason::ShortCircuit => {
+                "the assignment may not run, so the type before it is kept"
+            }
+            MergeReason::Subshell => {
+                "the assignment ran in another process, so the type before it is restored"
+            }
+        };
+        TraceStep {
+            line,
+            source,
+            bash_type: merge.bash_type.clone(),
+            rule: rule.to_owned(),
+            outcome: Outcome::Merged(merge.reason),
+            result: merge.bash_type.clone(),
+        }
+    }
+
+    /// Where the statement of `declaration` starts, after an annotation on the line before it
+    fn statement_start(&self, declaration: &TypeDeclaration) -> usize {
+        let Some(annotation) = declaration
+            .annotation
+            .as_ref()
+            .filter(|annotation| annotation.placement == AnnotationPlacement::Preceding)
+        else {
+            return declaration.range.start;
+        };
+        self.source_code
+            .get(annotation.range.end..declaration.range.end)
+            .and_then(|after| after.find(|c: char| !c.is_whitespace()))
+            .map_or(declaration.range.start, |start| {
+                annotation.range.end + start
+            })
+    }
+
+    fn line_text(&self, offset: usize) -> (usize, String) {
+        let (line, _) = self.source_index().line_column(offset);
+        let text = self.source_code.lines().nth(line - 1).unwrap_or_default();
+        (line, text.trim().to_owned())
+    }
+}
+
+/// How the type of a declaration was found
+fn rule(declaration: &TypeDeclaration) -> String {
+    if let Some(annotation) = &declaration.annotation {
+        return match annotation.placement {
+            AnnotationPlacement::Inline => "declared by the annotation at the end of the line",
+            AnnotationPlacement::Preceding => "declared by the annotation on the line before",
+            AnnotationPlacement::Attribute => "declared by a flag of the declaration",
+            AnnotationPlacement::Command => "declared by `#[set_var]`",
+        }
+        .to_owned();
+    }
+    match (declaration.method, declaration.node_kind.as_str()) {
+        (Method::Declared, _) => "declared by a flag of the declaration".to_owned(),
+        (_, "variable_assignment") => "inferred from the assigned value".to_owned(),
+        (_, "for_statement") => "inferred from the values the loop goes through".to_owned(),
+        (_, "command") => "set by the command".to_owned(),
+        (_, "expansion") => "assigned a default by the expansion".to_owned(),
+        (_, "file_redirect") => "set to a file descriptor by the redirect".to_owned(),
+        (_, "") => "inferred".to_owned(),
+        (_, kind) => format!("inferred from a {}", kind.replace('_', " ")),
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::First => "first type",
+            Self::Replaced => "replaces the type before",
+            Self::KeptDeclaration => "fits the declared type, which is kept",
+            Self::Narrowed => "fits the declared type, narrowing it",
+            Self::Conflict => "conflicts with the type before",
+            Self::Merged(_) => "joined with the type before",
+        })
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "line {}: {}", step.line, step.source)?;
+            writeln!(f, "  {}, {}", step.bash_type, step.rule)?;
+            writeln!(f, "  {}, so {} is {}", step.outcome, self.name, step.result)?;
+        }
+        match &self.final_type {
+            Some(final_type) => writeln!(f, "{} is {final_type} at the end of the file", self.name),
+            None => writeln!(f, "{} is not set at the end of the file", self.name),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use tree_sitter::Node;
+
+use crate::{codes, Diagnostic, FileInfo, Label, LabelKind, ParseResult, Severity};
+
+/// Signals `trap` accepts besides numbers, without the `SIG` prefix
+const SIGNALS: &[&str] = &[
+    "EXIT", "ERR", "DEBUG", "RETURN", "HUP", "INT", "QUIT", "ILL", "TRAP", "ABRT", "IOT", "BUS",
+    "FPE", "KILL", "USR1", "SEGV", "USR2", "PIPE", "ALRM", "TERM", "STKFLT", "CHLD", "CONT",
+    "STOP", "TSTP", "TTIN", "TTOU", "URG", "XCPU", "XFSZ", "VTALRM", "PROF", "WINCH", "IO", "POLL",
+    "PWR", "SYS",
+];
+
+impl FileInfo<'_> {
+    /// Analyzes the handler of `trap 'cleanup "$tmpdir"' EXIT` as if it ran where the trap is
+    /// registered, and checks the signal names
+    pub(crate) fn handle_trap(&mut self, node: Node) -> ParseResult<()> {
+        let mut arguments = node
+            .children_by_field_name("argument", &mut node.walk())
+            .collect::<Vec<_>>();
+        let Some(first) = arguments.first() else {
+            return Ok(());
+        };
+        match self.static_text(*first)?.as_deref() {
+            // Listing signals or traps
+            Some("-l" | "-p") => return Ok(()),
+            Some("--") => {
+                arguments.remove(0);
+            }
+            _ => (),
+        }
+        // `trap cleanup` on its own resets the signal `cleanup`, which isn't worth checking
+        let [handler, signals @ ..] = arguments.as_slice() else {
+            return Ok(());
+        };
+        if signals.is_empty() {
+            return Ok(());
+        }
+        for signal in signals {
+            self.check_signal(*signal)?;
+        }
+
+        match self.static_text(*handler)? {
+            // `-` resets the signals and an empty handler ignores them
+            Some(text) if text == "-" || text.trim().is_empty() => (),
+            Some(text) => {
+                let offset = self.embedded_offset(*handler)?;
+                self.analyze_handler(&text, offset, *handler, node)?;
+            }
+            // Double quoted handlers are expanded when the trap is registered
+            None => {
+                for variable in self.unknown_variables(*handler)? {
+                    let error = self.unknown_variable_error(variable)?;
+                    self.push_parse_error(error, node);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Analyzes the code of a handler. Diagnostics point into it when it is written as it runs,
+    /// and at the whole handler when unescaping changed it
+    fn analyze_handler(
+        &mut self,
+        code: &str,
+        offset: Option<usize>,
+        handler: Node,
+        trap: Node,
+    ) -> ParseResult<()> {
+        let mut analysis = self.analyze_embedded(code)?;
+        self.merge_embedded_calls(&mut analysis, trap, offset);
+        let range = handler.start_byte()..handler.end_byte();
+        self.errors
+            .extend(analysis.errors.into_iter().map(|error| match offset {
+                Some(offset) => error.shifted(offset),
+                None => error.relocated(range.clone()),
+            }));
+        Ok(())
+    }
+
+    fn check_signal(&mut self, signal: Node) -> ParseResult<()> {
+        let Some(text) = self.static_text(signal)? else {
+            return Ok(());
+        };
+        let name = text.to_uppercase();
+        let name = name.strip_prefix("SIG").unwrap_or(&name);
+        let is_realtime = ["RTMIN", "RTMAX"]
+            .iter()
+            .any(|realtime| name.starts_with(realtime));
+        if name.parse::<u8>().is_ok_and(|number| number <= 64)
+            || is_realtime
+            || SIGNALS.contains(&name)
+        {
+            if matches!(name, "KILL" | "STOP" | "9" | "19") && !self.force {
+                self.errors.push(
+                    Diagnostic::new(format!("SIG{name} can't be trapped"), signal.start_byte())
+                        .with_code(codes::INVALID_SIGNAL)
+                        .with_severity(Severity::Warning)
+                        .with_label(Label::new(
+                            signal.start_byte()..signal.end_byte(),
+                            "The handler will never run for this signal",
+                            LabelKind::Inferred,
+                        )),
+                );
+            }
+            return Ok(());
+        }
+        if !self.force {
+            self.errors.push(
+                Diagnostic::new(format!("Unknown signal {text}"), signal.start_byte())
+                    .with_code(codes::INVALID_SIGNAL)
+                    .with_label(Label::new(
+                        signal.start_byte()..signal.end_byte(),
+                        "Not a signal bash knows about",
+                        LabelKind::ParseErr,
+                    )),
+            );
+        }
+        Ok(())
+    }
+}
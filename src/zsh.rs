@@ -0,0 +1,71 @@
+use tree_sitter::Node;
+
+use crate::{
+    literal_type, AnnotationPlacement, AnnotationSpan, BashType, Comment, CommentKind, FileInfo,
+    Method, ParseResult, TypeDeclaration,
+};
+
+impl FileInfo<'_> {
+    /// Handles zsh builtins that the bash grammar only sees as regular commands
+    pub(crate) fn handle_zsh_command(&mut self, node: Node) -> ParseResult<()> {
+        let command_name = node
+            .child_by_field_name("name")
+            .expect("command to have a name");
+        if self.node_text(command_name)? != "integer" {
+            return Ok(());
+        }
+
+        // `integer` is zsh's shorthand for `typeset -i`
+        let annotation = Comment {
+            text: String::from("int"),
+            range: command_name.start_byte()..command_name.end_byte(),
+            text_range: command_name.start_byte()..command_name.end_byte(),
+            kind: CommentKind::Type,
+        };
+        for argument in node.children_by_field_name("argument", &mut node.walk()) {
+            let location = argument.start_byte()..argument.end_byte();
+            let (name, inferred_type) = match argument.kind() {
+                "word" => {
+                    let text = self.node_text(argument)?;
+                    match text.split_once('=') {
+                        Some((name, value)) => (name, literal_type(value)),
+                        None => {
+                            let declaration = TypeDeclaration {
+                                bash_type: BashType::Integer,
+                                range: location,
+                                method: Method::Declared,
+                                node_kind: node.kind().to_owned(),
+                                annotation: Some(AnnotationSpan {
+                                    range: annotation.range.clone(),
+                                    type_range: annotation.text_range.clone(),
+                                    placement: AnnotationPlacement::Attribute,
+                                }),
+                                file: None,
+                            };
+                            self.set_variable(text, declaration, node);
+                            continue;
+                        }
+                    }
+                }
+                // `integer count=$other`
+                "concatenation" if argument.named_child_count() == 2 => {
+                    let name_node = argument.named_child(0).expect("child count to be two");
+                    let Some(name) = self.node_text(name_node)?.strip_suffix('=') else {
+                        continue;
+                    };
+                    let value = argument.named_child(1).expect("child count to be two");
+                    (name, self.infer_type(value)?)
+                }
+                _ => continue,
+            };
+            self.declare_variable(
+                name,
+                inferred_type,
+                location,
+                Some(annotation.clone()),
+                node,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use std::{fs, process::Command};
+
+use bashtyped::{fix::apply_edits, AnnotationStyle, Config, FileInfo};
+
+fn organized(source: &str, style: AnnotationStyle, insert_inferred: bool) -> String {
+    let mut config = Config::default();
+    config.annotation_style = style;
+    let mut file = FileInfo::with_config(source, config);
+    file.parse_code();
+    let edits = file.organize_annotations(insert_inferred);
+    apply_edits(source, &edits)
+}
+
+#[test]
+fn test_normalizes_spelling() {
+    assert_eq!(
+        organized("x=1 #/int|string", AnnotationStyle::Preserve, false),
+        "x=1 #/ string | int"
+    );
+    assert_eq!(
+        organized(
+            "#/ map< string,bool |int|int >\ndeclare -A m",
+            AnnotationStyle::Preserve,
+            false
+        ),
+        "#/ map<string, int | bool>\ndeclare -A m"
+    );
+    // Invalid annotations and ordinary comments are left for the checker to report
+    let source = "x=1 #/ integer\n# a note";
+    assert_eq!(organized(source, AnnotationStyle::Preserve, false), source);
+}
+
+#[test]
+fn test_moves_annotations_inline() {
+    assert_eq!(
+        organized(
+            "#/ int\ncount=1\nf() {\n  #/ string\n  local name=x\n}",
+            AnnotationStyle::Inline,
+            false
+        ),
+        "count=1 #/ int\nf() {\n  local name=x #/ string\n}"
+    );
+    // The statement already has a comment on its line
+    let source = "#/ int\ncount=1 # the count";
+    assert_eq!(organized(source, AnnotationStyle::Inline, false), source);
+}
+
+#[test]
+fn test_moves_annotations_above() {
+    assert_eq!(
+        organized(
+            "count=1 #/ int\nf() {\n  local name=x   #/string\n}",
+            AnnotationStyle::Above,
+            false
+        ),
+        "#/ int\ncount=1\nf() {\n  #/ string\n  local name=x\n}"
+    );
+    let source = "a=1; b=2 #/ int";
+    assert_eq!(organized(source, AnnotationStyle::Above, false), source);
+}
+
+#[test]
+fn test_inserts_inferred_annotations() {
+    let source = "count=1\ncount=2\nnames=(a b)\nlabel=$(date) #/ string\n#/ int\nsize=3\nlocal_value=$unknown\n";
+    assert_eq!(
+        organized(source, AnnotationStyle::Preserve, true),
+        "count=1 #/ int\ncount=2\nnames=(a b) #/ array<string>\nlabel=$(date) #/ string\n#/ int\nsize=3\nlocal_value=$unknown\n"
+    );
+    assert_eq!(
+        organized("f() {\n  local n=1\n}", AnnotationStyle::Above, true),
+        "f() {\n  #/ int\n  local n=1\n}"
+    );
+}
+
+#[test]
+fn test_organizing_is_stable() {
+    for style in [
+        AnnotationStyle::Preserve,
+        AnnotationStyle::Inline,
+        AnnotationStyle::Above,
+    ] {
+        let once = organized("#/ int|string\nx=1\ny=text #/ string\nz=(1 2)", style, true);
+        assert_eq!(organized(&once, style, true), once);
+    }
+}
+
+#[test]
+fn test_annotate_command() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-annotate-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("bashtyped.toml"),
+        "annotation_style = \"inline\"\n",
+    )
+    .unwrap();
+    fs::write(dir.join("script.sh"), "#/ int\ncount=1\nname=x\n").unwrap();
+    let annotate = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+            .arg("annotate")
+            .args(args)
+            .arg("script.sh")
+            .current_dir(&dir)
+            .output()
+            .unwrap()
+    };
+
+    let output = annotate(&["--check"]);
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "script.sh\n");
+
+    assert!(annotate(&["--all"]).status.success());
+    assert_eq!(
+        fs::read_to_string(dir.join("script.sh")).unwrap(),
+        "count=1 #/ int\nname=x #/ string\n"
+    );
+    assert!(annotate(&["--all", "--check"]).status.success());
+}
@@ -0,0 +1,45 @@
+use bashtyped::{testing::analyze, AnnotationPlacement, AnnotationSpan};
+
+#[test]
+fn test_annotation_placements() {
+    let file = analyze("a=1 #/ int\n#/ string\nb=text");
+    assert_eq!(
+        file.variables["a"].annotation,
+        Some(AnnotationSpan {
+            range: 4..10,
+            type_range: 7..10,
+            placement: AnnotationPlacement::Inline,
+        })
+    );
+    assert_eq!(
+        file.variables["b"].annotation,
+        Some(AnnotationSpan {
+            range: 11..20,
+            type_range: 14..20,
+            placement: AnnotationPlacement::Preceding,
+        })
+    );
+}
+
+#[test]
+fn test_attribute_annotation() {
+    let file = analyze("declare -i count=3");
+    let count = &file.variables["count"];
+    assert_eq!(count.node_kind, "variable_assignment");
+    assert_eq!(
+        count.annotation,
+        Some(AnnotationSpan {
+            range: 8..10,
+            type_range: 8..10,
+            placement: AnnotationPlacement::Attribute,
+        })
+    );
+}
+
+#[test]
+fn test_node_kind() {
+    let file = analyze("for item in a b; do\n  echo \"$item\"\ndone\nname=text");
+    assert_eq!(file.variables["item"].node_kind, "for_statement");
+    assert_eq!(file.variables["item"].annotation, None);
+    assert_eq!(file.variables["name"].node_kind, "variable_assignment");
+}
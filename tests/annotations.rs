@@ -0,0 +1,59 @@
+use bashtyped::{
+    annotations::{AnnotationKind, AnnotationPayload},
+    BashType, BashVersion, FileInfo, Parameter,
+};
+
+#[test]
+fn test_every_annotation() {
+    let mut file = FileInfo::new(
+        "#![bash >= 4.4]\n#/ int\n# plain comment\necho hi\n#[set_var(name, string)]\n\
+         #[params path: string]\nf() {\n  :\n}",
+    );
+    let annotations = file.annotations();
+    assert_eq!(annotations.len(), 4);
+    assert_eq!(annotations[0].kind, AnnotationKind::Directive);
+    assert_eq!(
+        annotations[0].payload,
+        AnnotationPayload::BashVersion(BashVersion::new(4, 4))
+    );
+    // Not followed by an assignment, so it doesn't describe any variable
+    assert_eq!(annotations[1].kind, AnnotationKind::Type);
+    assert_eq!(annotations[1].range, 16..22);
+    assert_eq!(annotations[1].text_range, 19..22);
+    assert_eq!(
+        annotations[1].payload,
+        AnnotationPayload::Type(BashType::Integer)
+    );
+    assert_eq!(
+        annotations[2].payload,
+        AnnotationPayload::SetVar {
+            name: String::from("name"),
+            bash_type: BashType::String,
+        }
+    );
+    assert_eq!(
+        annotations[3].payload,
+        AnnotationPayload::Params(vec![Parameter {
+            name: String::from("path"),
+            bash_type: BashType::String,
+        }])
+    );
+}
+
+#[test]
+fn test_invalid_annotations() {
+    let mut file = FileInfo::new("a=1 #/ integer\n#[returns]\n#[retry]");
+    let payloads = file
+        .annotations()
+        .into_iter()
+        .map(|annotation| annotation.payload)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        payloads,
+        vec![
+            AnnotationPayload::Invalid(String::from("Unknown type integer")),
+            AnnotationPayload::Invalid(String::from("Unknown command returns")),
+            AnnotationPayload::Invalid(String::from("Unknown command retry")),
+        ]
+    );
+}
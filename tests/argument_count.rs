@@ -0,0 +1,76 @@
+use bashtyped::{codes, testing::analyze, Severity};
+
+const GREET: &str = "#[params name: string, greeting: string]\ngreet() {\n    echo \"$2 $1\"\n}\n";
+
+#[test]
+fn test_too_few_arguments() {
+    let source = format!("{GREET}greet world");
+    let file = analyze(&source);
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "Too few arguments to greet");
+    assert_eq!(error.code.as_deref(), Some(codes::ARGUMENT_COUNT));
+    assert_eq!(error.severity, Severity::Warning);
+    assert_eq!(error.labels[0].text, "Called with 1 argument");
+    assert_eq!(error.labels[0].range, 70..81);
+    assert_eq!(error.labels[1].text, "greet takes 2 arguments");
+    assert_eq!(error.labels[1].range, 41..69);
+}
+
+#[test]
+fn test_too_many_arguments() {
+    let source = format!("{GREET}greet world hello again");
+    let file = analyze(&source);
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Too many arguments to greet");
+    assert_eq!(file.errors[0].labels[0].text, "Called with 3 arguments");
+}
+
+#[test]
+fn test_matching_arguments() {
+    let source = format!("{GREET}greet world hello\ngreet \"$name\" 'good morning'");
+    assert!(analyze(&source).errors.is_empty());
+}
+
+#[test]
+fn test_variadic_parameters() {
+    let file = analyze(
+        "#[params $1: string, ...: int]\nsum() {\n    :\n}\nsum total 1 2 3\nsum total\nsum",
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Too few arguments to sum");
+    assert_eq!(
+        file.errors[0].labels[1].text,
+        "sum takes at least 1 argument"
+    );
+}
+
+#[test]
+fn test_spread_arguments_are_not_counted() {
+    let source = format!(
+        "{GREET}greet \"$@\"\nnames=(a b)\ngreet \"${{names[@]}}\"\ngreet *.txt\ngreet {{a,b}}"
+    );
+    assert!(analyze(&source).errors.is_empty());
+}
+
+#[test]
+fn test_call_before_definition() {
+    let file = analyze(
+        "main() {\n    greet world\n}\n#[params name: string, greeting: string]\ngreet() {\n    :\n}",
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Too few arguments to greet");
+}
+
+#[test]
+fn test_functions_without_params() {
+    assert!(analyze("greet() {\n    :\n}\ngreet a b c")
+        .errors
+        .is_empty());
+}
+
+#[test]
+fn test_forced_call() {
+    let source = format!("{GREET}#[force]\ngreet");
+    assert!(analyze(&source).errors.is_empty());
+}
@@ -0,0 +1,63 @@
+use bashtyped::{codes, testing::analyze, BashType, Severity};
+
+#[test]
+fn test_arithmetic_expansion_is_int() {
+    let file = analyze("count=3\ntotal=$((count + 1))\nlabel=\"$((total * 2))\"");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["total"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["label"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_string_flag_in_arithmetic_condition() {
+    let file = analyze("verbose=yes\nif (( verbose )); then\n    echo debugging\nfi");
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "String verbose used in arithmetic");
+    assert_eq!(error.code.as_deref(), Some(codes::ARITHMETIC_STRING));
+    assert_eq!(error.severity, Severity::Warning);
+    assert_eq!(error.labels[0].range, 18..25);
+    assert_eq!(error.labels[0].bash_type, Some(BashType::String));
+    assert!(error.help.as_deref().unwrap().contains("[[ -n $verbose ]]"));
+}
+
+#[test]
+fn test_string_operands() {
+    let file = analyze(
+        r#"name="$(pwd)/app"
+(( name > 5 ))
+half=$(( ${name} / 2 ))
+(( name = 3 ))"#,
+    );
+    assert_eq!(file.errors.len(), 2);
+    for error in &file.errors {
+        assert_eq!(error.message, "String name used in arithmetic");
+    }
+}
+
+#[test]
+fn test_numeric_operands() {
+    let file = analyze(
+        r#"count=3
+quoted="5"
+empty=
+if (( count > 5 && quoted + empty )); then :; fi
+(( $1 > count ))
+(( undeclared++ ))"#,
+    );
+    assert!(file
+        .errors
+        .iter()
+        .all(|error| error.code.as_deref() != Some(codes::ARITHMETIC_STRING)));
+}
+
+#[test]
+fn test_arithmetic_command_is_bool_status() {
+    let file = analyze(
+        r#"#[returns bool]
+is_big() {
+    (( $1 > 5 ))
+}"#,
+    );
+    assert!(file.errors.is_empty());
+}
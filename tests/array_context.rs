@@ -0,0 +1,77 @@
+use bashtyped::{codes, fix::apply_fixes, testing::analyze, Severity};
+
+#[test]
+fn test_array_as_scalar() {
+    let source = "files=(a.txt b.txt)\nrm $files";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(
+        error.message,
+        "files is an array, but only its first element is used"
+    );
+    assert_eq!(error.code.as_deref(), Some(codes::ARRAY_CONTEXT));
+    assert_eq!(error.severity, Severity::Warning);
+    assert_eq!(error.labels[0].range, 23..29);
+    assert_eq!(
+        apply_fixes(source, [&error.fixes[0]]).0,
+        "files=(a.txt b.txt)\nrm \"${files[@]}\""
+    );
+    assert_eq!(
+        apply_fixes(source, [&error.fixes[1]]).0,
+        "files=(a.txt b.txt)\nrm ${files[0]}"
+    );
+}
+
+#[test]
+fn test_quoted_array_as_scalar() {
+    let source = "declare -A ports=([web]=80)\necho \"${ports}\"";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    // Maps have no first element to suggest
+    assert_eq!(file.errors[0].fixes.len(), 1);
+    assert_eq!(
+        apply_fixes(source, [&file.errors[0].fixes[0]]).0,
+        "declare -A ports=([web]=80)\necho \"${ports[@]}\""
+    );
+}
+
+#[test]
+fn test_array_length() {
+    let source = "files=(a b)\ncount=${#files}";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "files is an array, but this is the length of its first element"
+    );
+    assert_eq!(
+        apply_fixes(source, [&file.errors[0].fixes[0]]).0,
+        "files=(a b)\ncount=${#files[@]}"
+    );
+}
+
+#[test]
+fn test_scalar_as_array() {
+    let source = "name=\"$(pwd)/x\"\nfor part in \"${name[@]}\"; do :; done\necho ${#name[*]}";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 2);
+    assert_eq!(file.errors[0].message, "name is not an array");
+    assert_eq!(
+        file.errors[1].message,
+        "name is not an array, so this is always 1"
+    );
+    assert_eq!(
+        apply_fixes(source, [&file.errors[0].fixes[0]]).0,
+        "name=\"$(pwd)/x\"\nfor part in \"${name}\"; do :; done\necho ${#name[*]}"
+    );
+}
+
+#[test]
+fn test_correct_expansions() {
+    let file = analyze(
+        "files=(a b)\nname=x\nrm \"${files[@]}\"\necho \"${files[0]}\" ${#files[@]} $name \
+         ${name[0]} ${files:-none}",
+    );
+    assert!(file.errors.is_empty());
+}
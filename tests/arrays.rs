@@ -0,0 +1,331 @@
+use bashtyped::{testing::analyze, BashType, Method};
+
+fn array(element: BashType) -> BashType {
+    BashType::Array(Box::new(element))
+}
+
+#[test]
+fn test_array_literal() {
+    let file = analyze("numbers=(1 2 3)\nmixed=(1 text)");
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["numbers"].bash_type,
+        array(BashType::Integer)
+    );
+    assert_eq!(
+        file.variables["mixed"].bash_type,
+        array(BashType::Or(
+            Box::new(BashType::Integer),
+            Box::new(BashType::String)
+        ))
+    );
+}
+
+#[test]
+fn test_associative_array_literal() {
+    let file = analyze(
+        r#"age=31
+declare -A ages=([alice]=30 [bob]="$age")"#,
+    );
+    assert_eq!(
+        file.variables["ages"].bash_type,
+        BashType::Map(Box::new(BashType::String), Box::new(BashType::Integer))
+    );
+}
+
+#[test]
+fn test_element_assignment() {
+    let file = analyze("numbers=(1 2 3)\nnumbers[3]=4\nnumbers[i+1]=5");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_element_type_mismatch() {
+    let file = analyze("numbers=(1 2 3)\nnumbers[0]=text");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Element type does not match");
+}
+
+#[test]
+fn test_string_index() {
+    let file = analyze("name=text\nnumbers=(1 2 3)\nnumbers[name]=4");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Array index must be an int");
+}
+
+#[test]
+fn test_associative_array_accepts_any_key() {
+    let file = analyze("declare -A ages\nages[alice]=30\nages[bob]=31");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["ages"].method, Method::Declared);
+}
+
+#[test]
+fn test_scalar_used_as_array() {
+    let file = analyze("count=1\ncount[1]=2");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Variable count is not an array");
+}
+
+#[test]
+fn test_element_assignment_creates_array() {
+    let file = analyze("numbers[0]=1");
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["numbers"].bash_type,
+        array(BashType::Integer)
+    );
+}
+
+#[test]
+fn test_element_reads() {
+    let file = analyze(
+        r#"numbers=(1 2 3)
+first="${numbers[0]}"
+all=("${numbers[@]}")
+count=${#numbers[@]}"#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["first"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["all"].bash_type, array(BashType::Integer));
+    assert_eq!(file.variables["count"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_array_annotation() {
+    let file = analyze("#/ array\nnames=(a b)");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["names"].method, Method::Declared);
+}
+
+#[test]
+fn test_slices() {
+    let file = analyze(
+        r#"numbers=(1 2 3)
+count=10
+some=("${numbers[@]:1:2}")
+text="${count:0:1}"
+last=${count: -1}"#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["some"].bash_type, array(BashType::Integer));
+    assert_eq!(file.variables["text"].bash_type, BashType::String);
+    assert_eq!(file.variables["last"].bash_type, BashType::String);
+}
+
+#[test]
+fn test_lengths() {
+    let file = analyze(
+        r#"numbers=(1 2 3)
+name=text
+elements=${#numbers[@]}
+characters=${#name}"#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["elements"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["characters"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_parameterized_annotations() {
+    let file = analyze(
+        r#"#/ array<int | string>
+mixed=(1 text)
+#/ map<string, array<int>>
+declare -A groups
+#/ array<int>
+numbers=(1 text)"#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Types do not match");
+    assert_eq!(
+        file.variables["groups"].bash_type,
+        BashType::Map(
+            Box::new(BashType::String),
+            Box::new(array(BashType::Integer))
+        )
+    );
+}
+
+#[test]
+fn test_parse_types() {
+    assert_eq!(
+        "map<string, int | bool>".parse(),
+        Ok(BashType::Map(
+            Box::new(BashType::String),
+            Box::new(BashType::Or(
+                Box::new(BashType::Integer),
+                Box::new(BashType::Bool)
+            ))
+        ))
+    );
+    assert!("array<int".parse::<BashType>().is_err());
+    assert!("map<int>".parse::<BashType>().is_err());
+    assert!("number".parse::<BashType>().is_err());
+}
+
+#[test]
+fn test_unknown_annotation_type() {
+    let file = analyze("count=1 #/ number");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Invalid type");
+}
+
+#[test]
+fn test_append() {
+    let file = analyze(
+        r#"#/ array<int>
+numbers=(1 2)
+numbers+=(3 4)
+numbers+=(five)"#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Element type does not match");
+}
+
+#[test]
+fn test_integer_array_attribute() {
+    let file = analyze("declare -ai numbers=(1 2)\nnumbers[2]=text");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Element type does not match");
+    assert_eq!(
+        file.variables["numbers"].bash_type,
+        array(BashType::Integer)
+    );
+}
+
+fn tuple(elements: &[BashType]) -> BashType {
+    BashType::Tuple(elements.to_vec())
+}
+
+#[test]
+fn test_tuple_annotation() {
+    let file = analyze(
+        r#"#/ [int, string, string]
+row=(1 text more)
+id="${row[0]}"
+label="${row[1]}"
+everything=("${row[@]}")"#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["row"].bash_type,
+        tuple(&[BashType::Integer, BashType::String, BashType::String])
+    );
+    assert_eq!(file.variables["id"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["label"].bash_type, BashType::String);
+    assert_eq!(
+        file.variables["everything"].bash_type,
+        array(BashType::Or(
+            Box::new(BashType::Integer),
+            Box::new(BashType::String)
+        ))
+    );
+}
+
+#[test]
+fn test_tuple_length_mismatch() {
+    let file = analyze("row=(1 text) #/ [int, string, string]");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Types do not match");
+}
+
+#[test]
+fn test_tuple_element_assignment() {
+    let file = analyze(
+        r#"row=(1 text) #/ [int, string]
+row[1]=other
+row[0]=text"#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Element type does not match");
+}
+
+#[test]
+fn test_tuple_index_out_of_bounds() {
+    let file = analyze(
+        r#"row=(1 text) #/ [int, string]
+missing="${row[2]}""#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Tuple index out of bounds");
+}
+
+#[test]
+fn test_parse_tuple() {
+    assert_eq!(
+        "[int, array<string>]".parse(),
+        Ok(tuple(&[BashType::Integer, array(BashType::String)]))
+    );
+    assert_eq!(
+        tuple(&[BashType::Integer, BashType::Bool]).to_string(),
+        "[int, bool]"
+    );
+}
+
+fn record(fields: &[(&str, BashType)]) -> BashType {
+    BashType::Record(
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_record_annotation() {
+    let file = analyze(
+        r#"#/ { host: string, port: int }
+declare -A cfg=([host]=localhost [port]=8080)
+cfg[port]=443
+host="${cfg[host]}"
+port="${cfg[port]}""#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["cfg"].bash_type,
+        record(&[("host", BashType::String), ("port", BashType::Integer)])
+    );
+    assert_eq!(file.variables["host"].bash_type, BashType::String);
+    assert_eq!(file.variables["port"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_record_field_mismatch() {
+    let file = analyze(
+        r#"#/ { host: string, port: int }
+declare -A cfg
+cfg[port]=https"#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Element type does not match");
+}
+
+#[test]
+fn test_record_undeclared_key() {
+    let file = analyze(
+        r#"declare -A cfg=([host]=localhost) #/ { host: string }
+cfg[prot]=80
+cfg+=([user]=root)"#,
+    );
+    assert_eq!(file.errors.len(), 2);
+    assert_eq!(file.errors[0].message, "Key prot is not declared for cfg");
+    assert_eq!(file.errors[1].message, "Element type does not match");
+}
+
+#[test]
+fn test_parse_record() {
+    let parsed_type = "{ port: int, hosts: array<string>, }".parse();
+    assert_eq!(
+        parsed_type,
+        Ok(record(&[
+            ("port", BashType::Integer),
+            ("hosts", array(BashType::String))
+        ]))
+    );
+    assert_eq!(
+        parsed_type.unwrap().to_string(),
+        "{ hosts: array<string>, port: int }"
+    );
+    assert!("{ port }".parse::<BashType>().is_err());
+}
@@ -0,0 +1,44 @@
+use bashtyped::{codes, testing::analyze, BashType, Severity};
+
+#[test]
+fn test_brace_group_assignments_are_kept() {
+    let file =
+        analyze("{ count=1; next=$count; }\ntotal=$next #/ int\n{ name=app; } > log\ncopy=$name");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["total"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["copy"].bash_type, BashType::String);
+}
+
+#[test]
+fn test_background_assignment_read_afterwards() {
+    let file = analyze("{ result=\"$(pwd)/out\"; } &\nwait\ncopy=$result");
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "Variable set in a background job");
+    assert_eq!(error.code.as_deref(), Some(codes::BACKGROUND_ASSIGNMENT));
+    assert_eq!(error.severity, Severity::Warning);
+    assert_eq!(error.labels[0].range, 38..44);
+    assert!(!file.variables.contains_key("result"));
+}
+
+#[test]
+fn test_background_assignment_keeps_old_type() {
+    let file = analyze("count=1\ncount=3 &\ncount=2\ntotal=$count");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["count"].bash_type, BashType::Integer);
+
+    let file = analyze("count=1\ncount=2 &\ncopy=$count");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].code.as_deref(),
+        Some(codes::BACKGROUND_ASSIGNMENT)
+    );
+}
+
+#[test]
+fn test_assignments_read_inside_the_job() {
+    let file = analyze("{ first=1; second=$first; } &\nserver & pid=$!");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["pid"].bash_type, BashType::Integer);
+    assert!(!file.variables.contains_key("first"));
+}
@@ -0,0 +1,109 @@
+use std::{fs, process::Command};
+
+use bashtyped::{
+    baseline::{entry_path, Baseline},
+    Diagnostic, FileInfo,
+};
+
+fn analyzed(source: &str) -> Vec<Diagnostic> {
+    let mut file = FileInfo::new(source);
+    file.parse_code();
+    file.errors
+}
+
+const OLD: &str = "a=1 #/ bool\nb=$missing\n";
+
+fn baseline() -> Baseline {
+    let mut baseline = Baseline::default();
+    baseline.add("script.sh", OLD, &analyzed(OLD));
+    baseline
+}
+
+#[test]
+fn test_known_diagnostics_are_ignored() {
+    let baseline = baseline();
+    assert_eq!(baseline.len(), 2);
+    assert!(baseline
+        .new_diagnostics("script.sh", OLD, analyzed(OLD))
+        .is_empty());
+}
+
+#[test]
+fn test_moved_diagnostics_are_ignored() {
+    let source = "# a new comment\n\nb=$missing\n  a=1   #/ bool\n";
+    assert!(baseline()
+        .new_diagnostics("script.sh", source, analyzed(source))
+        .is_empty());
+}
+
+#[test]
+fn test_new_diagnostics_are_reported() {
+    let source = "a=1 #/ bool\nb=$missing\nc=text #/ int\n";
+    let new = baseline().new_diagnostics("script.sh", source, analyzed(source));
+    assert_eq!(new.len(), 1);
+    assert_eq!(new[0].code.as_deref(), Some("BT0001"));
+    assert_eq!(new[0].offset, 23);
+
+    let other = baseline().new_diagnostics("other.sh", OLD, analyzed(OLD));
+    assert_eq!(other.len(), 2);
+}
+
+#[test]
+fn test_repeated_diagnostics_are_counted() {
+    let source = "a=1 #/ bool\nb=$missing\na=1 #/ bool\n";
+    assert_eq!(
+        baseline()
+            .new_diagnostics("script.sh", source, analyzed(source))
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_baseline_round_trip() {
+    let path = std::env::temp_dir().join(format!("bashtyped-baseline-{}.json", std::process::id()));
+    let baseline = baseline();
+    baseline.save(&path).unwrap();
+    assert_eq!(Baseline::load(&path).unwrap(), baseline);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_entry_paths_are_relative_to_the_baseline() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-entries-{}", std::process::id()));
+    assert_eq!(
+        entry_path(&dir.join("baseline.json"), &dir.join("bin/deploy.sh")),
+        "bin/deploy.sh"
+    );
+    assert_eq!(
+        entry_path(&dir.join("ci/baseline.json"), &dir.join("bin/./deploy.sh")),
+        "../bin/deploy.sh"
+    );
+}
+
+#[test]
+fn test_cli_baseline_from_another_directory() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-cli-baseline-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("bin")).unwrap();
+    fs::write(dir.join("bin/deploy.sh"), OLD).unwrap();
+    let run = |current_dir: &str, args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+            .current_dir(dir.join(current_dir))
+            .args(["--no-cache", "--color", "never", "--format", "short"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let written = run(".", &["--write-baseline", "baseline.json", "bin/deploy.sh"]);
+    assert!(written.status.success());
+    assert!(fs::read_to_string(dir.join("baseline.json"))
+        .unwrap()
+        .contains("\"bin/deploy.sh\""));
+
+    let checked = run("bin", &["--baseline", "../baseline.json", "deploy.sh"]);
+    assert!(checked.status.success());
+    assert!(checked.stdout.is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
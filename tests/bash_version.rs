@@ -0,0 +1,53 @@
+use bashtyped::{BashVersion, Config, FileInfo};
+
+fn check(source: &str, config: Config) -> Vec<String> {
+    let mut file = FileInfo::with_config(source, config);
+    file.parse_code();
+    file.errors.into_iter().map(|error| error.message).collect()
+}
+
+#[test]
+fn test_no_minimum_version() {
+    assert!(check("declare -A map\nlocal -n ref=target", Config::default()).is_empty());
+}
+
+#[test]
+fn test_directive_sets_minimum_version() {
+    assert_eq!(
+        check(
+            r#"#![bash >= 4.2]
+declare -A map
+declare -n ref=map
+echo "${map@Q}""#,
+            Config::default()
+        ),
+        vec![
+            "Using namerefs requires bash 4.3",
+            "Using the @Q transformation requires bash 4.4",
+        ]
+    );
+}
+
+#[test]
+fn test_config_sets_minimum_version() {
+    let config = Config::from_toml(r#"bash_version = "3.2""#).unwrap();
+    assert_eq!(config.bash_version, Some(BashVersion::new(3, 2)));
+    assert_eq!(
+        check("declare -gA map", config),
+        vec!["Using associative arrays requires bash 4.0"]
+    );
+}
+
+#[test]
+fn test_directive_overrides_config() {
+    let config = Config::from_toml(r#"bash_version = "3.2""#).unwrap();
+    assert!(check("#![bash >= 5.0]\ndeclare -A map", config).is_empty());
+}
+
+#[test]
+fn test_invalid_directive() {
+    assert_eq!(
+        check("#![bash newest]", Config::default()),
+        vec!["Error while parsing comment"]
+    );
+}
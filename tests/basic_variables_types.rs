@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-use bashtyped::{BashType, Method, TypeDeclaration};
+use bashtyped::{AnnotationPlacement, AnnotationSpan, BashType, Method, TypeDeclaration};
 
 #[test]
 fn test_creating_str_var() {
@@ -9,12 +9,19 @@ fn test_creating_str_var() {
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([(
+        BTreeMap::from([(
             String::from("a"),
             TypeDeclaration {
                 bash_type: BashType::String,
                 method: Method::Declared,
                 range: 0..17,
+                node_kind: String::from("variable_assignment"),
+                annotation: Some(AnnotationSpan {
+                    range: 8..17,
+                    type_range: 11..17,
+                    placement: AnnotationPlacement::Inline,
+                }),
+                file: None,
             },
         )])
     );
@@ -27,12 +34,15 @@ fn test_creating_inferred_str_var() {
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([(
+        BTreeMap::from([(
             String::from("a"),
             TypeDeclaration {
                 bash_type: BashType::String,
                 method: Method::Inferred,
                 range: 0..7,
+                node_kind: String::from("variable_assignment"),
+                annotation: None,
+                file: None,
             },
         )])
     );
@@ -45,12 +55,19 @@ fn test_creating_int_var() {
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([(
+        BTreeMap::from([(
             String::from("a"),
             TypeDeclaration {
                 bash_type: BashType::Integer,
                 method: Method::Declared,
                 range: 0..10,
+                node_kind: String::from("variable_assignment"),
+                annotation: Some(AnnotationSpan {
+                    range: 4..10,
+                    type_range: 7..10,
+                    placement: AnnotationPlacement::Inline,
+                }),
+                file: None,
             },
         )])
     );
@@ -67,12 +84,19 @@ a=1"#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([(
+        BTreeMap::from([(
             String::from("a"),
             TypeDeclaration {
                 bash_type: BashType::Integer,
                 method: Method::Declared,
                 range: 1..11,
+                node_kind: String::from("variable_assignment"),
+                annotation: Some(AnnotationSpan {
+                    range: 1..7,
+                    type_range: 4..7,
+                    placement: AnnotationPlacement::Preceding,
+                }),
+                file: None,
             },
         )])
     );
@@ -88,13 +112,20 @@ b=3 #/ int"#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([
+        BTreeMap::from([
             (
                 String::from("a"),
                 TypeDeclaration {
                     bash_type: BashType::Integer,
                     method: Method::Declared,
                     range: 0..10,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 4..10,
+                        type_range: 7..10,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             ),
             (
@@ -103,6 +134,13 @@ b=3 #/ int"#,
                     bash_type: BashType::Integer,
                     method: Method::Declared,
                     range: 11..21,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 15..21,
+                        type_range: 18..21,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             )
         ])
@@ -119,13 +157,16 @@ b=3 #/ int"#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([
+        BTreeMap::from([
             (
                 String::from("a"),
                 TypeDeclaration {
                     bash_type: BashType::Integer,
                     method: Method::Inferred,
                     range: 0..3,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: None,
+                    file: None,
                 },
             ),
             (
@@ -134,6 +175,13 @@ b=3 #/ int"#,
                     bash_type: BashType::Integer,
                     method: Method::Declared,
                     range: 4..14,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 8..14,
+                        type_range: 11..14,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             )
         ])
@@ -151,13 +199,16 @@ c=3"#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([
+        BTreeMap::from([
             (
                 String::from("a"),
                 TypeDeclaration {
                     bash_type: BashType::Integer,
                     method: Method::Inferred,
                     range: 0..3,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: None,
+                    file: None,
                 },
             ),
             (
@@ -166,6 +217,9 @@ c=3"#,
                     bash_type: BashType::Integer,
                     method: Method::Inferred,
                     range: 4..7,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: None,
+                    file: None,
                 },
             ),
             (
@@ -174,6 +228,9 @@ c=3"#,
                     bash_type: BashType::Integer,
                     method: Method::Inferred,
                     range: 8..11,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: None,
+                    file: None,
                 },
             )
         ])
@@ -192,12 +249,15 @@ whoa="Silvester Belt""#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([(
+        BTreeMap::from([(
             String::from("whoa"),
             TypeDeclaration {
                 bash_type: BashType::String,
                 method: Method::Inferred,
                 range: 39..60,
+                node_kind: String::from("variable_assignment"),
+                annotation: None,
+                file: None,
             },
         ),])
     );
@@ -213,13 +273,20 @@ b="$a""#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([
+        BTreeMap::from([
             (
                 String::from("a"),
                 TypeDeclaration {
                     bash_type: BashType::String,
                     method: Method::Declared,
                     range: 0..17,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 8..17,
+                        type_range: 11..17,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             ),
             (
@@ -228,6 +295,9 @@ b="$a""#,
                     bash_type: BashType::String,
                     method: Method::Inferred,
                     range: 18..24,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: None,
+                    file: None,
                 },
             )
         ])
@@ -244,13 +314,20 @@ b="$a""#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([
+        BTreeMap::from([
             (
                 String::from("a"),
                 TypeDeclaration {
                     bash_type: BashType::Integer,
                     method: Method::Declared,
                     range: 0..10,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 4..10,
+                        type_range: 7..10,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             ),
             (
@@ -259,6 +336,9 @@ b="$a""#,
                     bash_type: BashType::Integer,
                     method: Method::Inferred,
                     range: 11..17,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: None,
+                    file: None,
                 },
             )
         ])
@@ -275,13 +355,16 @@ b="I love $b""#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([
+        BTreeMap::from([
             (
                 String::from("a"),
                 TypeDeclaration {
                     bash_type: BashType::Integer,
                     method: Method::Inferred,
                     range: 0..3,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: None,
+                    file: None,
                 },
             ),
             (
@@ -290,6 +373,9 @@ b="I love $b""#,
                     bash_type: BashType::String,
                     method: Method::Inferred,
                     range: 4..17,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: None,
+                    file: None,
                 },
             ),
         ])
@@ -0,0 +1,82 @@
+use bashtyped::{codes, testing::analyze_with_config, BashType, Config};
+
+fn checking_path() -> Config {
+    let mut config = Config::default();
+    config.check_path = true;
+    config
+}
+
+const TESTS: &str = r#"#!/usr/bin/env bats
+
+setup() {
+    workdir="$BATS_TEST_TMPDIR/work"
+}
+
+@test "adds numbers" {
+    run add 1 2
+    [ "$status" -eq 0 ]
+    result=$output
+    first=${lines[0]}
+    code=$status
+    count=3
+}
+
+@test "rejects words" {
+    count=none
+    run add x
+    [ "$status" -ne 0 ]
+}
+"#;
+
+#[test]
+fn test_bats_variables() {
+    let file = analyze_with_config(TESTS, checking_path());
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["workdir"].bash_type, BashType::String);
+    assert_eq!(file.history["code"][0].bash_type, BashType::Integer);
+    assert_eq!(file.history["first"][0].bash_type, BashType::String);
+}
+
+#[test]
+fn test_tests_dont_share_variables() {
+    let file = analyze_with_config(
+        r#"@test "first" {
+    result=done
+}
+@test "second" {
+    copy=$result
+}"#,
+        checking_path(),
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].code.as_deref(),
+        Some(codes::UNKNOWN_VARIABLE)
+    );
+    assert!(!file.variables.contains_key("result"));
+}
+
+#[test]
+fn test_load_sources_helper() {
+    let file = analyze_with_config(
+        "load test_helper\nload 'helpers/assert.sh'\n@test \"runs\" {\n    helper\n}",
+        checking_path(),
+    );
+    assert!(file.errors.is_empty());
+    let paths = file
+        .sources
+        .iter()
+        .map(|source| source.path.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, [Some("test_helper.bash"), Some("helpers/assert.sh")]);
+}
+
+#[test]
+fn test_bats_variables_only_in_test_files() {
+    let file = analyze_with_config("code=$status", checking_path());
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].code.as_deref(),
+        Some(codes::UNKNOWN_VARIABLE)
+    );
+}
@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-use bashtyped::{BashType, Method, TypeDeclaration};
+use bashtyped::{AnnotationPlacement, AnnotationSpan, BashType, Method, TypeDeclaration};
 
 #[test]
 fn test_broadening_type_def_from_other_var() {
@@ -12,13 +12,16 @@ b="$a" #/ int | string "#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([
+        BTreeMap::from([
             (
                 String::from("a"),
                 TypeDeclaration {
                     bash_type: BashType::Integer,
                     method: Method::Inferred,
                     range: 0..3,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: None,
+                    file: None,
                 },
             ),
             (
@@ -30,6 +33,13 @@ b="$a" #/ int | string "#,
                     ),
                     method: Method::Declared,
                     range: 4..27,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 11..27,
+                        type_range: 14..26,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             ),
         ])
@@ -46,13 +56,20 @@ b="$a" #/ int | string | bool "#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([
+        BTreeMap::from([
             (
                 String::from("a"),
                 TypeDeclaration {
                     bash_type: BashType::Or(Box::new(BashType::Integer), Box::new(BashType::Bool)),
                     method: Method::Declared,
                     range: 0..17,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 4..17,
+                        type_range: 7..17,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             ),
             (
@@ -67,6 +84,13 @@ b="$a" #/ int | string | bool "#,
                     ),
                     method: Method::Declared,
                     range: 18..48,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 25..48,
+                        type_range: 28..47,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             ),
         ])
@@ -103,13 +127,20 @@ b="$a" #/ any "#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([
+        BTreeMap::from([
             (
                 String::from("a"),
                 TypeDeclaration {
                     bash_type: BashType::Integer,
                     method: Method::Declared,
                     range: 0..10,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 4..10,
+                        type_range: 7..10,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             ),
             (
@@ -118,6 +149,13 @@ b="$a" #/ any "#,
                     bash_type: BashType::Any,
                     method: Method::Declared,
                     range: 11..25,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 18..25,
+                        type_range: 21..24,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             ),
         ])
@@ -144,7 +182,7 @@ b="$a" #/ string | int "#,
     assert!(file.errors.is_empty());
     assert_eq!(
         file.variables,
-        HashMap::from([
+        BTreeMap::from([
             (
                 String::from("a"),
                 TypeDeclaration {
@@ -154,6 +192,13 @@ b="$a" #/ string | int "#,
                     ),
                     method: Method::Declared,
                     range: 0..19,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 4..19,
+                        type_range: 7..19,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             ),
             (
@@ -165,6 +210,13 @@ b="$a" #/ string | int "#,
                     ),
                     method: Method::Declared,
                     range: 20..43,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 27..43,
+                        type_range: 30..42,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
                 },
             ),
         ])
@@ -0,0 +1,167 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs, thread,
+    time::Duration,
+};
+
+use bashtyped::{
+    cache::{Cache, CachedAnalysis},
+    AnnotationPlacement, AnnotationSpan, BashType, Config, FileInfo, Method, TypeDeclaration,
+};
+
+fn temp_cache(name: &str) -> Cache {
+    let dir = std::env::temp_dir().join(format!("bashtyped-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    Cache::new(dir)
+}
+
+#[test]
+fn test_cache_round_trip() {
+    let cache = temp_cache("round-trip");
+    let source = r#"a=1
+a="text" #/ string"#;
+
+    let mut first = FileInfo::new(source);
+    first.parse_code_cached(&cache).unwrap();
+    assert_eq!(first.errors.len(), 1);
+
+    assert_eq!(
+        cache.load(source, &Config::default()),
+        Some(CachedAnalysis {
+            errors: first.errors.clone(),
+            variables: BTreeMap::from([(
+                String::from("a"),
+                TypeDeclaration {
+                    bash_type: BashType::String,
+                    method: Method::Declared,
+                    range: 4..22,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 13..22,
+                        type_range: 16..22,
+                        placement: AnnotationPlacement::Inline,
+                    }),
+                    file: None,
+                },
+            )]),
+            history: first.history.clone(),
+            sources: Vec::new(),
+            exports: HashMap::new(),
+            functions: HashMap::new(),
+            calls: Vec::new(),
+            references: BTreeSet::new(),
+            globals: BTreeSet::from([String::from("a")]),
+            heredocs: Vec::new(),
+            merges: Vec::new(),
+        })
+    );
+}
+
+#[test]
+fn test_cache_hit_skips_analysis() {
+    let cache = temp_cache("hit");
+    let source = r#"a=1 #/ bool"#;
+    cache
+        .store(
+            source,
+            &Config::default(),
+            &CachedAnalysis {
+                errors: Vec::new(),
+                variables: BTreeMap::new(),
+                history: BTreeMap::new(),
+                sources: Vec::new(),
+                exports: HashMap::new(),
+                functions: HashMap::new(),
+                calls: Vec::new(),
+                references: BTreeSet::new(),
+                globals: BTreeSet::new(),
+                heredocs: Vec::new(),
+                merges: Vec::new(),
+            },
+        )
+        .unwrap();
+
+    let mut file = FileInfo::new(source);
+    file.parse_code_cached(&cache).unwrap();
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_cache_key_depends_on_contents() {
+    let config = Config::default();
+    assert_eq!(Cache::key("a=1", &config), Cache::key("a=1", &config));
+    assert_ne!(Cache::key("a=1", &config), Cache::key("a=2", &config));
+    assert_ne!(
+        Cache::key("a=1", &config),
+        Cache::key(
+            "a=1",
+            &Config::from_toml(r#"bash_version = "4.2""#).unwrap()
+        )
+    );
+}
+
+#[test]
+fn test_cached_analysis_matches_fresh() {
+    let cache = temp_cache("fresh");
+    let source = r#"source ./lib.sh
+export name=text
+count=1
+[[ -n $name ]] && count=text
+#[returns int]
+check() {
+    local flag=1
+    return 0
+}
+check
+#/ heredoc(json)
+cat <<EOF
+{"count": $count}
+EOF"#;
+    let mut fresh = FileInfo::new(source);
+    fresh.parse_code();
+    FileInfo::new(source).parse_code_cached(&cache).unwrap();
+    let mut cached = FileInfo::new(source);
+    cached.parse_code_cached(&cache).unwrap();
+
+    assert!(!fresh.heredocs.is_empty());
+    assert_eq!(cached.errors, fresh.errors);
+    assert_eq!(cached.variables, fresh.variables);
+    assert_eq!(cached.history, fresh.history);
+    assert_eq!(cached.sources, fresh.sources);
+    assert_eq!(cached.exports, fresh.exports);
+    assert_eq!(cached.functions, fresh.functions);
+    assert_eq!(cached.calls, fresh.calls);
+    assert_eq!(cached.references, fresh.references);
+    assert_eq!(cached.globals, fresh.globals);
+    assert_eq!(cached.heredocs, fresh.heredocs);
+    // The type `count` gets where the paths join is only known from the merges
+    assert_eq!(cached.explain("count"), fresh.explain("count"));
+    assert!(fresh.explain("count").unwrap().steps.len() > 2);
+}
+
+#[test]
+fn test_prune_removes_least_recently_used() {
+    let cache = temp_cache("prune");
+    let dir = std::env::temp_dir().join(format!("bashtyped-prune-{}", std::process::id()));
+    let config = Config::default();
+    for source in ["a=1", "b=1", "c=1"] {
+        FileInfo::new(source).parse_code_cached(&cache).unwrap();
+        // Modification times can be coarse
+        thread::sleep(Duration::from_millis(20));
+    }
+    // Loading a result counts as using it
+    for source in ["b=1", "a=1"] {
+        assert!(cache.load(source, &config).is_some());
+        thread::sleep(Duration::from_millis(20));
+    }
+    let size = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().metadata().unwrap().len())
+        .sum::<u64>();
+    cache.prune(size - 1).unwrap();
+    assert!(cache.load("a=1", &config).is_some());
+    assert!(cache.load("b=1", &config).is_some());
+    assert!(cache.load("c=1", &config).is_none());
+    cache.prune(0).unwrap();
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+}
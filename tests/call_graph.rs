@@ -0,0 +1,153 @@
+use std::path::PathBuf;
+
+use bashtyped::{
+    call_graph::{CallGraph, Caller, FunctionId},
+    codes,
+    collisions::WorkspaceDiagnostic,
+    symbols::Workspace,
+    FileInfo, Severity,
+};
+
+fn workspace(files: &[(&str, &str)]) -> Workspace {
+    let mut workspace = Workspace::new();
+    for (path, source) in files {
+        let mut file = FileInfo::new(source);
+        file.parse_code();
+        workspace.update(path, &file);
+    }
+    workspace
+}
+
+fn unused(files: &[(&str, &str)]) -> Vec<WorkspaceDiagnostic> {
+    workspace(files)
+        .check()
+        .into_iter()
+        .filter(|found| found.diagnostic.code.as_deref() == Some(codes::UNUSED_FUNCTION))
+        .collect()
+}
+
+fn function(path: &str, name: &str) -> FunctionId {
+    FunctionId {
+        path: PathBuf::from(path),
+        name: String::from(name),
+    }
+}
+
+fn calls(graph: &CallGraph) -> Vec<(Caller, FunctionId)> {
+    graph.calls.iter().cloned().collect()
+}
+
+#[test]
+fn test_calls_between_files() {
+    let graph = workspace(&[
+        (
+            "lib.sh",
+            "log() { echo \"$1\"; }\nwarn() { log \"warning: $1\"; }",
+        ),
+        ("main.sh", "source lib.sh\nwarn disk\nlog done"),
+    ])
+    .call_graph();
+    assert_eq!(graph.functions.len(), 2);
+    assert_eq!(graph.functions[&function("lib.sh", "warn")].line, 2);
+    assert_eq!(
+        calls(&graph),
+        vec![
+            (
+                Caller::File(PathBuf::from("main.sh")),
+                function("lib.sh", "log")
+            ),
+            (
+                Caller::File(PathBuf::from("main.sh")),
+                function("lib.sh", "warn")
+            ),
+            (
+                Caller::Function(function("lib.sh", "warn")),
+                function("lib.sh", "log")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_calls_prefer_local_definitions() {
+    let graph = workspace(&[
+        ("lib.sh", "log() { echo \"$1\"; }"),
+        ("other.sh", "log() { echo \"other: $1\"; }"),
+        ("main.sh", "source lib.sh\nlog done"),
+    ])
+    .call_graph();
+    assert_eq!(
+        calls(&graph),
+        vec![(
+            Caller::File(PathBuf::from("main.sh")),
+            function("lib.sh", "log")
+        )]
+    );
+}
+
+#[test]
+fn test_calls_from_traps() {
+    let graph = workspace(&[(
+        "main.sh",
+        "cleanup() { rm -f lock; }\nsetup() { trap 'cleanup' EXIT; }\nsetup",
+    )])
+    .call_graph();
+    assert!(graph.calls.contains(&(
+        Caller::Function(function("main.sh", "setup")),
+        function("main.sh", "cleanup")
+    )));
+}
+
+#[test]
+fn test_unused_function() {
+    let found = unused(&[(
+        "main.sh",
+        "build() { make; }\ncleanup() { rm -f lock; }\nbuild",
+    )]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, PathBuf::from("main.sh"));
+    assert_eq!(
+        found[0].diagnostic.message,
+        "Function cleanup is never called"
+    );
+    assert_eq!(found[0].diagnostic.severity, Severity::Warning);
+    assert_eq!(found[0].diagnostic.labels[0].range, 18..43);
+}
+
+#[test]
+fn test_unused_recursive_functions() {
+    let found = unused(&[("main.sh", "ping() { pong; }\npong() { ping; }\necho done")]);
+    assert_eq!(found.len(), 2);
+}
+
+#[test]
+fn test_functions_used_indirectly() {
+    let found = unused(&[(
+        "main.sh",
+        "on_exit() { rm -f lock; }\nhelper() { :; }\nreport() { :; }\n\
+         trap on_exit EXIT\nexport -f helper\nhandler=report\n\"$handler\"",
+    )]);
+    assert!(found.is_empty());
+}
+
+#[test]
+fn test_library_functions_are_used() {
+    let found = unused(&[
+        ("lib.sh", "log() { echo \"$1\"; }\nwarn() { log \"$1\"; }"),
+        ("main.sh", "source lib.sh\necho starting"),
+    ]);
+    assert!(found.is_empty());
+}
+
+#[test]
+fn test_unused_function_reached_from_main() {
+    let found = unused(&[(
+        "main.sh",
+        "main() { step; }\nstep() { :; }\nunused() { :; }\nmain \"$@\"",
+    )]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].diagnostic.message,
+        "Function unused is never called"
+    );
+}
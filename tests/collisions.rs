@@ -0,0 +1,176 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use bashtyped::{
+    codes, collisions::WorkspaceDiagnostic, files::FileId, symbols::Workspace, FileInfo, LabelKind,
+    Severity,
+};
+
+/// Checks a workspace of `(path, source)` files
+fn checked(files: &[(&str, &str)]) -> Vec<WorkspaceDiagnostic> {
+    let mut workspace = Workspace::new();
+    for (path, source) in files {
+        let mut file = FileInfo::new(source);
+        file.parse_code();
+        workspace.update(path, &file);
+    }
+    workspace.check()
+}
+
+#[test]
+fn test_global_collision() {
+    let found = checked(&[
+        ("scripts/lib.sh", "retries=3"),
+        ("scripts/main.sh", "source lib.sh\nretries=(1 2)"),
+    ]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, PathBuf::from("scripts/main.sh"));
+    let diagnostic = &found[0].diagnostic;
+    assert_eq!(diagnostic.code.as_deref(), Some(codes::GLOBAL_COLLISION));
+    assert_eq!(
+        diagnostic.message,
+        "retries is also a global in scripts/lib.sh with an incompatible type"
+    );
+    assert_eq!(found[0].file, FileId(1));
+    assert_eq!(diagnostic.labels[0].file, None);
+    assert_eq!(diagnostic.labels[1].file, Some(FileId(0)));
+    assert_eq!(diagnostic.labels[1].range, 0..9);
+    assert_eq!(diagnostic.labels[1].kind, LabelKind::Inferred);
+}
+
+#[test]
+fn test_files_sourced_together() {
+    // Neither library sources the other, but both end up in the same script
+    let found = checked(&[
+        ("a.sh", "name=text"),
+        ("b.sh", "name=1 #/ int"),
+        ("main.sh", ". ./a.sh\nsource b.sh"),
+    ]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, PathBuf::from("b.sh"));
+    assert_eq!(found[0].diagnostic.labels[0].kind, LabelKind::Specified);
+}
+
+#[test]
+fn test_compatible_globals() {
+    assert!(checked(&[
+        ("lib.sh", "count=1 #/ int | string"),
+        (
+            "main.sh",
+            "source lib.sh\ncount=2\nf() {\n  local name=1\n}"
+        ),
+        ("other.sh", "name=text"),
+    ])
+    .is_empty());
+    // Files that aren't sourced together can use the same names freely
+    assert!(checked(&[("a.sh", "name=text"), ("b.sh", "name=(1 2)")]).is_empty());
+}
+
+#[test]
+fn test_function_locals() {
+    let lib = "count=1\nname=text\nflag=true\nmode=fast\nlevel=low";
+    let main = "source lib.sh
+f() {
+\tlocal\tcount=(1 2)
+\tdeclare name=(1 2)
+\ttypeset -i flag=3
+\tlocal -r mode=(1 2)
+\tmode=(3 4)
+}
+g() {
+\tdeclare -g level=(1 2)
+}";
+    let found = checked(&[("lib.sh", lib), ("main.sh", main)]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].diagnostic.message,
+        "level is also a global in lib.sh with an incompatible type"
+    );
+}
+
+#[test]
+fn test_shadowed_export() {
+    let found = checked(&[
+        ("env.sh", "export PREFIX=/usr"),
+        ("main.sh", "source env.sh\nPREFIX=build"),
+    ]);
+    assert_eq!(found.len(), 1);
+    let diagnostic = &found[0].diagnostic;
+    assert_eq!(diagnostic.code.as_deref(), Some(codes::SHADOWED_EXPORT));
+    assert_eq!(diagnostic.severity, Severity::Warning);
+    assert_eq!(
+        diagnostic.message,
+        "PREFIX overwrites the variable exported by env.sh"
+    );
+    assert_eq!(diagnostic.labels[1].file, Some(FileId(0)));
+
+    assert!(checked(&[
+        ("env.sh", "PREFIX=/usr\nexport -n HOME"),
+        ("main.sh", "source env.sh\nHOME=/root"),
+    ])
+    .is_empty());
+}
+
+#[test]
+fn test_source_cycle() {
+    let found = checked(&[
+        ("a.sh", "source b.sh\nvalue=1"),
+        ("b.sh", "source a.sh\nvalue=(x)"),
+    ]);
+    assert_eq!(found.len(), 2);
+}
+
+#[test]
+fn test_cli_reports_collisions() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-collisions-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("lib.sh"), "retries=3\n").unwrap();
+    fs::write(dir.join("main.sh"), "source lib.sh\nretries=(1 2)\n").unwrap();
+    let run = |format: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+            .current_dir(&dir)
+            .args(["--no-cache", "--color", "never", "--format", format])
+            .args(["lib.sh", "main.sh"])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(
+        run("short"),
+        "main.sh:2:1: error[BT0019]: retries is also a global in lib.sh with an incompatible \
+         type\n"
+    );
+    let full = run("full");
+    assert!(full.contains("main.sh:2:1"));
+    assert!(full.contains("lib.sh:1:1"));
+}
+
+#[test]
+fn test_cli_sorts_collisions_with_file_diagnostics() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-sorted-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("lib.sh"),
+        "name=a\nname=(x) #/ string\nretries=3\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("main.sh"),
+        "count=1 #/ int\nsource lib.sh\nretries=(1 2)\ncount=text\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+        .current_dir(&dir)
+        .args(["--no-cache", "--color", "never", "--format", "short"])
+        .args(["main.sh", "lib.sh"])
+        .output()
+        .unwrap();
+    let lines = String::from_utf8(output.stdout).unwrap();
+    let locations = lines
+        .lines()
+        .map(|line| line.split(": ").next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(locations, ["lib.sh:2:1", "main.sh:3:1", "main.sh:4:1"]);
+}
@@ -0,0 +1,64 @@
+use ariadne::Source;
+use bashtyped::{Config, FileInfo};
+
+fn rendered(source: &str, config: Config) -> String {
+    let mut file = FileInfo::with_config(source, config);
+    file.parse_code();
+    let mut output = Vec::new();
+    for report in file.reports() {
+        report.write(Source::from(source), &mut output).unwrap();
+    }
+    String::from_utf8(output).unwrap()
+}
+
+const SOURCE: &str = "count=1 #/ int\ncount=text";
+
+#[test]
+fn test_colors_disabled() {
+    let mut config = Config::default();
+    config.color = false;
+    let output = rendered(SOURCE, config);
+    assert!(output.starts_with("[BT0003] Error: Variable count defined with different type"));
+    assert!(!output.contains('\u{1b}'));
+}
+
+#[test]
+fn test_colors_enabled() {
+    let output = rendered(SOURCE, Config::default());
+    assert!(output.contains('\u{1b}'));
+}
+
+#[test]
+fn test_theme_and_custom_colors() {
+    let config = Config::from_toml(
+        r##"theme = "colorblind"
+[colors]
+error = "#ff00ff"
+specified = "bright-cyan"
+inferred = "208""##,
+    )
+    .unwrap();
+    let output = rendered(SOURCE, config);
+    assert!(output.contains("\u{1b}[38;2;255;0;255m[BT0003] Error:"));
+    assert!(output.contains("\u{1b}[38;5;208m"));
+    assert!(output.contains("\u{1b}[38;5;14m"));
+}
+
+#[test]
+fn test_colorblind_theme_differs() {
+    let colorblind = Config::from_toml(r#"theme = "colorblind""#).unwrap();
+    assert_ne!(
+        rendered(SOURCE, colorblind),
+        rendered(SOURCE, Config::default())
+    );
+}
+
+#[test]
+fn test_invalid_colors() {
+    for color in [r#""purple""#, r##""#12345""##, r##""#gg0000""##, r#""300""#] {
+        let config = Config::from_toml(&format!("[colors]\nerror = {color}"));
+        assert!(config.is_err(), "{color} should be rejected");
+    }
+    assert!(Config::from_toml(r#"theme = "neon""#).is_err());
+    assert!(Config::from_toml("[colors]\nbackground = \"red\"").is_err());
+}
@@ -0,0 +1,72 @@
+use bashtyped::{commands::CommandRegistry, testing::analyze, BashType};
+
+#[test]
+fn test_extraction_outputs() {
+    let file = analyze(
+        r#"count=$(jq '.items | length' items.json)
+name=$(jq -r '.name' package.json)
+quoted="$(jq -r '.name' package.json)"
+first=$(awk '{print $1}' hosts.txt)
+user=$(cut -d: -f1 /etc/passwd)
+lines=$(grep -c error log.txt | wc -l)
+started=$(date +%s)
+today=$(date +%F)
+cores=$(nproc)
+uid=$(id -u)"#,
+    );
+    assert!(file.errors.is_empty());
+    for (name, bash_type) in [
+        ("count", BashType::Integer),
+        ("name", BashType::String),
+        ("quoted", BashType::String),
+        ("first", BashType::String),
+        ("user", BashType::String),
+        ("lines", BashType::Integer),
+        ("started", BashType::Integer),
+        ("today", BashType::String),
+        ("cores", BashType::Integer),
+        ("uid", BashType::Integer),
+    ] {
+        assert_eq!(file.variables[name].bash_type, bash_type, "{name}");
+    }
+}
+
+#[test]
+fn test_unmatched_output_is_unknown() {
+    let file = analyze(
+        r#"items=$(jq '.items[]' items.json)
+counted=$(wc -l items.json)"#,
+    );
+    assert_eq!(file.variables["items"].bash_type, BashType::Unknown);
+    assert_eq!(file.variables["counted"].bash_type, BashType::Unknown);
+}
+
+#[test]
+fn test_function_output_from_signature() {
+    let file = analyze(
+        r#"count_items() {
+    jq '.items | length' "$1"
+}
+total=$(count_items items.json)"#,
+    );
+    assert_eq!(file.variables["total"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_custom_output_rules() {
+    let registry = CommandRegistry::from_toml(
+        r#"[kubectl]
+outputs = [
+    { args = "get * -o name", type = "string" },
+    { args = "version*", type = "string" },
+]"#,
+    )
+    .unwrap();
+    let signature = registry.get("kubectl").unwrap();
+    assert_eq!(
+        signature.output_type("get pods -o name"),
+        Some(&BashType::String)
+    );
+    assert_eq!(signature.output_type("get pods -o json"), None);
+    assert!(CommandRegistry::from_toml("[tool]\noutputs = [{ args = \"*\" }]").is_err());
+}
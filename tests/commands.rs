@@ -0,0 +1,60 @@
+use bashtyped::{commands::CommandRegistry, testing::analyze, BashType, Severity};
+
+#[test]
+fn test_string_passed_as_int() {
+    let file = analyze("name=text\nsleep \"$name\"");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Argument to sleep has the wrong type"
+    );
+    assert_eq!(file.errors[0].severity, Severity::Warning);
+}
+
+#[test]
+fn test_flags_are_skipped() {
+    let file = analyze("pid=123\nsignal=TERM\nkill -9 \"$pid\"\nkill -s \"$signal\" \"$pid\"");
+    assert!(file.errors.is_empty());
+    let file = analyze("name=text\nkill -9 \"$name\"");
+    assert_eq!(file.errors.len(), 1);
+}
+
+#[test]
+fn test_option_values_are_checked() {
+    let file = analyze("lines=many\nhead -n \"$lines\" file.txt");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Argument to head has the wrong type"
+    );
+}
+
+#[test]
+fn test_literals_and_unknowns_are_not_checked() {
+    let file = analyze("sleep 5m\nkill %1\nsleep \"$1\"\nchmod u+x script.sh");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_union_argument() {
+    let file =
+        analyze("mode=0755\nchmod \"$mode\" script.sh\nmode_text=u+x\nchmod \"$mode_text\" a");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_custom_signatures() {
+    let registry = CommandRegistry::from_toml(
+        r#"[deploy]
+options = { "--replicas" = "int" }
+args = ["string", "array<int>"]"#,
+    )
+    .unwrap();
+    let signature = registry.get("deploy").unwrap();
+    assert_eq!(signature.options["--replicas"], BashType::Integer);
+    assert_eq!(
+        signature.argument_type(5),
+        Some(&BashType::Array(Box::new(BashType::Integer)))
+    );
+    assert!(CommandRegistry::from_toml("[deploy]\nargs = [\"number\"]").is_err());
+}
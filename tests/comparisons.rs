@@ -0,0 +1,62 @@
+use bashtyped::{codes, fix::apply_fixes, testing::analyze, BashType, Severity};
+
+#[test]
+fn test_ints_compared_as_strings() {
+    let source = "count=10\nlimit=9\nif [[ $count > $limit ]]; then\n    echo over\nfi";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "Integers compared as strings with >");
+    assert_eq!(error.code.as_deref(), Some(codes::COMPARISON_OPERATOR));
+    assert_eq!(error.severity, Severity::Warning);
+    assert_eq!(error.labels.len(), 2);
+    assert_eq!(error.labels[0].bash_type, Some(BashType::Integer));
+    let (fixed, applied) = apply_fixes(source, &error.fixes);
+    assert_eq!(applied, 1);
+    assert!(fixed.contains("[[ $count -gt $limit ]]"));
+}
+
+#[test]
+fn test_strings_compared_as_ints() {
+    let source = r#"name="$(pwd)/x"
+count=3
+[[ $name -eq $count ]]
+[ "$name" -lt 3 ]
+[[ $name -ge 3 ]]"#;
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 3);
+    assert_eq!(
+        file.errors[0].message,
+        "Strings compared as integers with -eq"
+    );
+    // Only the string operand is labelled
+    assert_eq!(file.errors[0].labels.len(), 1);
+    assert_eq!(file.errors[0].labels[0].range, 27..32);
+    let fixes = file
+        .errors
+        .iter()
+        .flat_map(|error| &error.fixes)
+        .collect::<Vec<_>>();
+    // `-ge` has no string equivalent
+    assert_eq!(fixes.len(), 2);
+    let (fixed, _) = apply_fixes(source, fixes);
+    assert!(fixed.contains("[[ $name == $count ]]"));
+    assert!(fixed.contains(r#"[ "$name" \< 3 ]"#));
+}
+
+#[test]
+fn test_matching_comparisons() {
+    let file = analyze(
+        r#"count=10
+limit=9
+version="$(pwd)"
+seconds="30"
+[[ $count -gt $limit ]]
+[[ $version > "1.10" ]]
+[[ $seconds -ge 5 ]]
+(( count > limit ))
+[[ $count == "$limit" ]]
+[[ $1 > $2 ]]"#,
+    );
+    assert!(file.errors.is_empty());
+}
@@ -0,0 +1,71 @@
+use bashtyped::{
+    completion::{complete, CompletionKind},
+    BashType, ANNOTATION_COMMANDS, TYPE_NAMES,
+};
+
+/// Completes at the `$` in `source`
+fn labels(source: &str) -> Vec<String> {
+    let offset = source.find('$').unwrap();
+    let source = source.replacen('$', "", 1);
+    complete(&source, offset)
+        .into_iter()
+        .map(|completion| completion.label)
+        .collect()
+}
+
+#[test]
+fn test_type_names() {
+    let all = all_types();
+    assert_eq!(labels("count=1 #/ $"), all);
+    assert_eq!(labels("count=1 #/ i$"), ["int"]);
+    assert_eq!(labels("#/ a$\nnames=()"), ["any", "array"]);
+    assert_eq!(labels("count=1 #/ int | st$"), ["string"]);
+    assert_eq!(labels("#/ map<string, $"), all);
+    assert_eq!(labels("#/ [int, b$"), ["bool"]);
+    assert_eq!(labels("#/ { host: s$"), ["string"]);
+    assert!(labels("#/ { ho$").is_empty());
+    assert!(labels("count=1 # int$").is_empty());
+}
+
+#[test]
+fn test_union_continuation() {
+    let completions = complete("count=1 #/ int ", 15);
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].label, "|");
+    assert_eq!(completions[0].kind, CompletionKind::Operator);
+    assert_eq!(labels("count=1 #/ int$"), ["int"]);
+}
+
+#[test]
+fn test_commands() {
+    let all = ANNOTATION_COMMANDS
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(labels("#[$"), all);
+    assert_eq!(labels("#[re$"), ["returns"]);
+    assert_eq!(labels("#[returns b$"), ["bool"]);
+    assert_eq!(labels("#[set_var(name, $"), all_types());
+    assert!(labels("#[force] $").is_empty());
+}
+
+#[test]
+fn test_replaced_range() {
+    let completions = complete("a=1 #/ in", 9);
+    assert_eq!(completions[0].range, 7..9);
+    assert_eq!(completions[0].kind, CompletionKind::Type);
+}
+
+#[test]
+fn test_type_names_parse() {
+    for (name, _) in TYPE_NAMES {
+        assert!(name.parse::<BashType>().is_ok(), "{name}");
+    }
+}
+
+fn all_types() -> Vec<String> {
+    TYPE_NAMES
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
@@ -0,0 +1,54 @@
+use bashtyped::{codes, testing::analyze, Severity};
+
+#[test]
+fn test_output_used_as_test() {
+    let file = analyze("if [ $(grep -q error log) ]; then\n  echo failed\nfi");
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "Output of a command used as a test");
+    assert_eq!(error.code.as_deref(), Some(codes::OUTPUT_CONDITION));
+    assert_eq!(error.severity, Severity::Warning);
+    assert_eq!(error.labels[0].range, 5..25);
+}
+
+#[test]
+fn test_quoted_output_in_other_conditions() {
+    let file = analyze(
+        "if true; then :\nelif [[ \"$(ls)\" ]]; then :\nfi\nwhile ! test $(jobs -p); do :; done\n\
+         until [ $(cat flag) ] && true; do :; done",
+    );
+    assert_eq!(file.errors.len(), 3);
+    assert_eq!(file.errors[0].labels[0].range, 24..31);
+}
+
+#[test]
+fn test_function_with_bool_status() {
+    let file = analyze(
+        "#[returns bool]\nis_ready() { [[ -f ready ]]; }\nif [[ $(is_ready) ]]; then :; fi",
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].help.as_deref(),
+        Some("is_ready returns a bool status, so use `if is_ready; then`")
+    );
+}
+
+#[test]
+fn test_output_run_as_condition() {
+    let file = analyze("if $(command -v git); then :; fi");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Output of a command run as the condition"
+    );
+    assert_eq!(file.errors[0].labels[0].range, 3..20);
+}
+
+#[test]
+fn test_commands_and_tested_output() {
+    let file = analyze(
+        "if grep -q error log; then :; fi\nif [[ -n $(ls) ]]; then :; fi\n\
+         if [ \"$(id -u)\" = 0 ]; then :; fi\nif [ \"x$(ls)\" ]; then :; fi",
+    );
+    assert!(file.errors.is_empty());
+}
@@ -0,0 +1,138 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use bashtyped::{
+    codes,
+    config::{ConfigError, ConfigHierarchy, RuleSeverity},
+    symbols::Workspace,
+    Config, FileInfo, Severity,
+};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("bashtyped-hierarchy-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+fn hierarchy(dir: &Path) -> ConfigHierarchy {
+    let root = Config::load(&dir.join("bashtyped.toml")).unwrap();
+    ConfigHierarchy::new(dir, root)
+}
+
+#[test]
+fn test_nested_config_overrides_root() {
+    let dir = temp_dir("override");
+    write(
+        &dir.join("bashtyped.toml"),
+        "pedantic = true\nnarrow_on_assignment = true\n[rules.BT0002]\nallow = [\"CI_*\"]",
+    );
+    write(
+        &dir.join("scripts/legacy/bashtyped.toml"),
+        "pedantic = false\n[rules.unknown-variable]\nseverity = \"advice\"\nallow = [\"LEGACY_*\"]",
+    );
+    let mut configs = hierarchy(&dir);
+
+    let root = configs.config_for(&dir.join("build.sh")).unwrap();
+    assert!(root.pedantic);
+    let scripts = configs.config_for(&dir.join("scripts/run.sh")).unwrap();
+    assert!(scripts.pedantic);
+
+    let legacy = configs
+        .config_for(&dir.join("scripts/legacy/old/deploy.sh"))
+        .unwrap();
+    assert!(!legacy.pedantic);
+    // Settings the nested config doesn't mention are inherited
+    assert!(legacy.narrow_on_assignment);
+    let rule = &legacy.rules[codes::UNKNOWN_VARIABLE];
+    assert_eq!(rule.severity, Some(RuleSeverity::Advice));
+    assert_eq!(rule.allow, ["CI_*", "LEGACY_*"]);
+
+    let source = "token=$CI_TOKEN\nhome=$LEGACY_HOME\nother=$MISSING";
+    let mut file = FileInfo::with_config(source, legacy);
+    file.parse_code();
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].severity, Severity::Advice);
+}
+
+#[test]
+fn test_root_stops_inheritance() {
+    let dir = temp_dir("root");
+    write(&dir.join("bashtyped.toml"), "pedantic = true");
+    write(
+        &dir.join("vendor/bashtyped.toml"),
+        "root = true\nnarrow_on_assignment = true",
+    );
+    let mut configs = hierarchy(&dir);
+    let vendor = configs.config_for(&dir.join("vendor/lib.sh")).unwrap();
+    assert!(!vendor.pedantic);
+    assert!(vendor.narrow_on_assignment);
+}
+
+#[test]
+fn test_nested_config_errors() {
+    let dir = temp_dir("errors");
+    write(&dir.join("bashtyped.toml"), "");
+    write(&dir.join("ignored/bashtyped.toml"), "ignore = [\"*.sh\"]");
+    write(&dir.join("unknown/bashtyped.toml"), "[rules.BT9999]");
+    let mut configs = hierarchy(&dir);
+    assert!(matches!(
+        configs.config_for(&dir.join("ignored/a.sh")),
+        Err(ConfigError::NestedIgnore(_))
+    ));
+    assert!(matches!(
+        configs.config_for(&dir.join("unknown/a.sh")),
+        Err(ConfigError::UnknownRule(_))
+    ));
+}
+
+#[test]
+fn test_workspace_config() {
+    let dir = temp_dir("workspace");
+    write(&dir.join("bashtyped.toml"), "");
+    write(&dir.join("legacy/bashtyped.toml"), "pedantic = true");
+    let mut workspace = Workspace::with_configs(hierarchy(&dir));
+    assert!(
+        workspace
+            .config_for(&dir.join("legacy/a.sh"))
+            .unwrap()
+            .pedantic
+    );
+    assert!(!workspace.config_for(&dir.join("a.sh")).unwrap().pedantic);
+    assert!(
+        !Workspace::new()
+            .config_for(&dir.join("legacy/a.sh"))
+            .unwrap()
+            .pedantic
+    );
+}
+
+#[test]
+fn test_cli_nested_config() {
+    let dir = temp_dir("cli");
+    write(&dir.join("bashtyped.toml"), "");
+    write(
+        &dir.join("legacy/bashtyped.toml"),
+        "[rules.BT0003]\nenabled = false",
+    );
+    let source = "count=1\ncount=text\n";
+    write(&dir.join("main.sh"), source);
+    write(&dir.join("legacy/old.sh"), source);
+    let output = Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+        .current_dir(&dir)
+        .args(["--no-cache", "--format", "short", "."])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("main.sh"));
+    assert!(!stdout.contains("old.sh"));
+}
@@ -0,0 +1,28 @@
+use std::fs;
+
+use bashtyped::testing::{analyze, check_markers, check_ranges};
+
+/// Checks every script in `tests/corpus` against the marker comments it contains, and that the
+/// ranges of its diagnostics are inside it
+#[test]
+fn test_corpus() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/corpus");
+    let mut paths = fs::read_dir(dir)
+        .expect("corpus directory to exist")
+        .map(|entry| entry.expect("corpus entry to be readable").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "sh"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty());
+    let failures = paths
+        .iter()
+        .filter_map(|path| {
+            let source = fs::read_to_string(path).expect("corpus script to be readable");
+            check_markers(&source)
+                .and_then(|()| check_ranges(&analyze(&source)))
+                .err()
+                .map(|problems| format!("{}:\n{problems}", path.display()))
+        })
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
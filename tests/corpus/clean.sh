@@ -0,0 +1,7 @@
+#!/bin/bash
+#/ array<string>
+targets=(build test)
+for target in "${targets[@]}"; do
+    echo "$target"
+done
+total=$(( ${#targets[@]} + 1 ))
@@ -0,0 +1,12 @@
+#!/bin/bash
+count=10
+limit=9
+if [[ $count > $limit ]]; then
+#     ^^^^^^ warning BT0035: Integers compared as strings with >
+    echo over
+fi
+verbose=yes
+if (( verbose )); then
+#     ^^^^^^^ warning BT0036: String verbose used in arithmetic
+    echo debugging
+fi
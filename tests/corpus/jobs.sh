@@ -0,0 +1,7 @@
+#!/bin/bash
+{ result="$(pwd)/out"; } &
+wait
+copy=$result
+#     ^^^^^^ warning BT0037: Variable set in a background job
+coproc worker { cat; }
+pid=$worker_PID #/ int
@@ -0,0 +1,7 @@
+#!/bin/bash
+count=1 #/ bool
+#          ^^^^ error BT0001: Types do not match
+# The first label of a redefinition is the declaration it conflicts with
+name="$(pwd)/out"
+#^^^^^^^^^^^^^^^^ error BT0003: Variable name defined with different type
+name=3
@@ -0,0 +1,44 @@
+use bashtyped::{BashType, FileInfo, Method};
+
+#[test]
+fn test_integer_attribute() {
+    let mut file = FileInfo::new("declare -i count=3\ntypeset -i total");
+    file.parse_code();
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["count"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["count"].method, Method::Declared);
+    assert_eq!(file.variables["total"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_integer_attribute_mismatch() {
+    let mut file = FileInfo::new(r#"local -i count="many""#);
+    file.parse_code();
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].labels[0].range, 6..8);
+}
+
+#[test]
+fn test_annotations_on_local() {
+    let mut file = FileInfo::new(
+        r#"local a=1 #/ int | string
+#/ bool | string
+local b="yes""#,
+    );
+    file.parse_code();
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["a"].method, Method::Declared);
+    assert_eq!(file.variables["b"].method, Method::Declared);
+}
+
+#[test]
+fn test_force_applies_to_next_statement() {
+    let mut file = FileInfo::new(
+        r#"#[force]
+local a="text" #/ int
+b="text" #/ int"#,
+    );
+    file.parse_code();
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.variables["a"].bash_type, BashType::Integer);
+}
@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use bashtyped::{symbols::Workspace, testing::analyze, FileInfo};
+
+fn workspace(files: &[(&str, &str)]) -> Workspace {
+    let mut workspace = Workspace::new();
+    for (path, source) in files {
+        workspace.update(path, &analyze(source));
+    }
+    workspace
+}
+
+#[test]
+fn test_definition_in_file() {
+    let source = "count=1\ncount=2\necho $count";
+    let mut file = analyze(source);
+    assert_eq!(file.definition(source.rfind("count").unwrap()), Some(0..7));
+    assert_eq!(
+        file.variable_at(source.rfind("count").unwrap() + 2)
+            .as_deref(),
+        Some("count")
+    );
+    assert_eq!(file.definition(source.find("echo").unwrap()), None);
+}
+
+#[test]
+fn test_definition_through_source() {
+    let paths = "#!/bin/bash\nCONFIG_DIR=/etc/app";
+    let deploy = "source ../lib/paths.sh\necho \"$CONFIG_DIR\"";
+    let workspace = workspace(&[("lib/paths.sh", paths), ("bin/deploy.sh", deploy)]);
+    let mut file = analyze(deploy);
+    let location = workspace
+        .definition(
+            Path::new("bin/deploy.sh"),
+            &mut file,
+            deploy.find("CONFIG_DIR").unwrap(),
+        )
+        .unwrap();
+    assert_eq!(location.path, PathBuf::from("lib/paths.sh"));
+    assert_eq!(
+        location.span.file,
+        workspace.file_id(Path::new("lib/paths.sh"))
+    );
+    assert_eq!(location.span.start, paths.find("CONFIG").unwrap());
+    assert_eq!((location.span.line, location.span.column), (2, 1));
+}
+
+#[test]
+fn test_definition_prefers_last_sourced() {
+    let main = "source a.sh\nsource b.sh\necho $name";
+    let workspace = workspace(&[
+        ("a.sh", "name=first"),
+        ("b.sh", "source c.sh\nname=second"),
+        ("c.sh", "name=third\nother=1"),
+        ("main.sh", main),
+    ]);
+    let mut file = analyze(main);
+    let definition = |file: &mut FileInfo, name: &str| {
+        workspace
+            .definition(Path::new("main.sh"), file, main.rfind(name).unwrap())
+            .map(|location| location.path)
+    };
+    assert_eq!(definition(&mut file, "name"), Some(PathBuf::from("b.sh")));
+    // Files sourced by sourced files are followed too
+    let main = "source a.sh\nsource b.sh\necho $other";
+    let mut file = analyze(main);
+    assert_eq!(
+        workspace
+            .definition(
+                Path::new("main.sh"),
+                &mut file,
+                main.rfind("other").unwrap()
+            )
+            .map(|location| location.path),
+        Some(PathBuf::from("c.sh"))
+    );
+}
+
+#[test]
+fn test_definition_defined_locally_first() {
+    let main = "source lib.sh\nname=mine\necho $name";
+    let workspace = workspace(&[("lib.sh", "name=theirs"), ("main.sh", main)]);
+    let mut file = analyze(main);
+    let location = workspace
+        .definition(Path::new("main.sh"), &mut file, main.rfind("name").unwrap())
+        .unwrap();
+    assert_eq!(location.path, PathBuf::from("main.sh"));
+    assert_eq!(location.span.start, main.find("name=").unwrap());
+}
@@ -0,0 +1,159 @@
+use bashtyped::{
+    fix::{Applicability, Fix, FixKind, TextEdit},
+    BashType, Diagnostic, Label, LabelKind,
+};
+
+#[test]
+fn test_mismatch_is_structured() {
+    let mut file = bashtyped::FileInfo::new(r#"a=1 #/ bool"#);
+    file.parse_code();
+    assert_eq!(
+        file.errors,
+        vec![Diagnostic::new("Types do not match", 0)
+            .with_code("BT0001")
+            .with_label(
+                Label::new(7..11, "Type specified as", LabelKind::Specified)
+                    .with_type(BashType::Bool)
+            )
+            .with_label(
+                Label::new(0..3, "Type inferred to be", LabelKind::Inferred)
+                    .with_type(BashType::Integer)
+            )
+            .with_fix(Fix {
+                kind: FixKind::Widen,
+                applicability: Applicability::MaybeIncorrect,
+                title: String::from("Change the annotation to bool | int"),
+                edits: vec![TextEdit {
+                    range: 4..11,
+                    new_text: String::from("#/ bool | int"),
+                }],
+            })
+            .with_fix(Fix {
+                kind: FixKind::Force,
+                applicability: Applicability::MaybeIncorrect,
+                title: String::from("Skip the checks of this statement with #[force]"),
+                edits: vec![TextEdit {
+                    range: 0..0,
+                    new_text: String::from("#[force]\n"),
+                }],
+            })]
+    );
+}
+
+#[test]
+fn test_reports_rendered_on_request() {
+    let mut file = bashtyped::FileInfo::new(
+        r#"a=1
+a="text" #/ string"#,
+    );
+    file.parse_code();
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.reports().count(), 1);
+}
+
+#[test]
+fn test_unknown_variable_reported_once() {
+    let source = "a=$missing\nb=$missing\necho done > \"$missing\"";
+    let mut file = bashtyped::FileInfo::new(source);
+    file.parse_code();
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Unknown variable");
+    let ranges = file.errors[0]
+        .labels
+        .iter()
+        .map(|label| &source[label.range.clone()])
+        .collect::<Vec<_>>();
+    assert_eq!(ranges, ["missing", "missing", "missing"]);
+    assert_eq!(file.errors[0].labels[1].text, "Also used here");
+}
+
+#[test]
+fn test_different_unknown_variables() {
+    let mut file = bashtyped::FileInfo::new("a=$first\nb=$second");
+    file.parse_code();
+    assert_eq!(file.errors.len(), 2);
+}
+
+#[test]
+fn test_uses_after_mismatch_are_grouped() {
+    let source = "count=text #/ int\ntotal=$count\nother=$count";
+    let mut file = bashtyped::FileInfo::new(source);
+    file.parse_code();
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Types do not match");
+    assert_eq!(file.errors[0].labels.len(), 4);
+    let range = file.errors[0].labels[2].range.clone();
+    assert_eq!(range.start, source.find("$count").unwrap() + 1);
+}
+
+#[test]
+fn test_conflict_notes() {
+    let mut file = bashtyped::FileInfo::new("echo start\ncount=1 #/ int\ncount=text");
+    file.parse_code();
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].note.as_deref(),
+        Some("count was declared on line 2: count=1 #/ int")
+    );
+    assert_eq!(
+        file.errors[0].help.as_deref(),
+        Some(
+            "Annotate the earlier declaration with `#/ int | string` to allow both, or put \
+             `#[force]` before this one to skip the check"
+        )
+    );
+}
+
+#[test]
+fn test_short_format() {
+    let source = "echo start\n  flag=2 #/ bool\ncount=$missing";
+    let mut file = bashtyped::FileInfo::new(source);
+    file.parse_code();
+    let lines = file
+        .errors
+        .iter()
+        .map(|error| error.short("script.sh", source))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "script.sh:2:3: error[BT0001]: Types do not match",
+            "script.sh:3:1: error[BT0002]: Unknown variable",
+        ]
+    );
+}
+
+#[test]
+fn test_short_format_without_code() {
+    let diagnostic =
+        Diagnostic::new("Something odd", 4).with_severity(bashtyped::Severity::Warning);
+    assert_eq!(
+        diagnostic.short("a.sh", "é=1\nb=2"),
+        "a.sh:1:4: warning: Something odd"
+    );
+}
+
+#[test]
+fn test_diagnostics_are_in_source_order() {
+    // Calls are checked once every function is known, after the redefinition is found
+    let mut file = bashtyped::FileInfo::new(
+        "main() {\n    greet world\n}\ncount=1\ncount=text\n#[params name: string, greeting: string]\ngreet() {\n    :\n}",
+    );
+    file.parse_code();
+    let codes = file
+        .errors
+        .iter()
+        .map(|error| error.code.as_deref().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        codes,
+        [
+            bashtyped::codes::ARGUMENT_COUNT,
+            bashtyped::codes::REDEFINED_VARIABLE
+        ]
+    );
+    assert!(file
+        .errors
+        .windows(2)
+        .all(|pair| pair[0].sort_key(()) <= pair[1].sort_key(())));
+}
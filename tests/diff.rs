@@ -0,0 +1,157 @@
+use std::{fs, path::Path, process::Command};
+
+use bashtyped::{
+    diff::ChangedLines,
+    files::{FileId, Files},
+    Diagnostic, FileInfo, Label, LabelKind,
+};
+
+fn analyzed(source: &str) -> Vec<Diagnostic> {
+    let mut file = FileInfo::new(source);
+    file.parse_code();
+    file.errors
+}
+
+const DIFF: &str = "diff --git a/scripts/build.sh b/scripts/build.sh
+index 1234567..89abcde 100644
+--- a/scripts/build.sh
++++ b/scripts/build.sh
+@@ -2,0 +3,2 @@ set -e
++version=2
++name=build
+@@ -10 +12 @@ fi
+-echo old
++echo new
+@@ -20,3 +21,0 @@ done
+-echo removed
+diff --git a/old.sh b/old.sh
+deleted file mode 100644
+--- a/old.sh
++++ /dev/null
+@@ -1 +0,0 @@
+-echo gone
+";
+
+#[test]
+fn test_parse_diff() {
+    let changed = ChangedLines::from_diff(DIFF);
+    let path = Path::new("scripts/build.sh");
+    assert!(!changed.contains(path, 2));
+    assert!(changed.contains(path, 3));
+    assert!(changed.contains(path, 4));
+    assert!(!changed.contains(path, 5));
+    assert!(changed.contains(path, 12));
+    assert!(!changed.contains(path, 21));
+    assert!(changed.contains(Path::new("./scripts/build.sh"), 3));
+    assert!(changed.contains(Path::new("scripts/./lib/../build.sh"), 3));
+    assert!(!changed.contains(Path::new("old.sh"), 1));
+    assert!(!changed.contains(Path::new("other.sh"), 3));
+}
+
+#[test]
+fn test_untracked_files_are_changed() {
+    let mut changed = ChangedLines::default();
+    changed.add_file(Path::new("new.sh"));
+    assert!(changed.contains(Path::new("new.sh"), 1));
+    assert!(changed.contains(Path::new("./new.sh"), 500));
+}
+
+#[test]
+fn test_only_changed_diagnostics() {
+    let source = "a=1 #/ bool\nb=$missing\nc=text #/ int\n";
+    let changed = ChangedLines::from_diff("+++ b/script.sh\n@@ -2,0 +3 @@\n+c=text #/ int\n");
+    let diagnostics = changed.changed_diagnostics(Path::new("script.sh"), source, analyzed(source));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].offset, 23);
+}
+
+#[test]
+fn test_changed_declaration_reports_old_uses() {
+    // The conflict is reported on the old line, but points at the changed annotation
+    let source = "count=1 #/ int\ncount=text\n";
+    let changed = ChangedLines::from_diff("+++ b/script.sh\n@@ -1 +1 @@\n+count=1 #/ int\n");
+    let diagnostics = changed.changed_diagnostics(Path::new("script.sh"), source, analyzed(source));
+    assert!(diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.message == "Variable count defined with different type"));
+}
+
+#[test]
+fn test_paths_from_the_repository_root() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-diff-root-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("scripts")).unwrap();
+    fs::write(dir.join("scripts/build.sh"), "echo hi\n").unwrap();
+    let changed = ChangedLines::from_diff(DIFF).with_root(&dir);
+    assert!(changed.contains(&dir.join("scripts/build.sh"), 3));
+    assert!(changed.contains(&dir.join("scripts/../scripts/build.sh"), 3));
+    assert!(!changed.contains(&dir.join("build.sh"), 3));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_labels_in_other_files() {
+    let mut files = Files::new();
+    files.insert(FileId(1), "lib.sh", "retries=3\ncount=1\n");
+    let diagnostic = Diagnostic::new("count is also a global in lib.sh", 0)
+        .with_label(Label::new(10..17, "Declared here", LabelKind::Inferred).with_file(FileId(1)));
+    let source = "count=(1 2)\n";
+
+    let changed = ChangedLines::from_diff("+++ b/lib.sh\n@@ -2 +2 @@\n+count=1\n");
+    let kept = changed.changed_workspace_diagnostics(
+        Path::new("main.sh"),
+        source,
+        vec![diagnostic.clone()],
+        &files,
+    );
+    assert_eq!(kept.len(), 1);
+
+    // The label's range is in lib.sh, so a change on the same line of main.sh doesn't count
+    let changed = ChangedLines::from_diff("+++ b/lib.sh\n@@ -1 +1 @@\n+retries=3\n");
+    let kept = changed.changed_workspace_diagnostics(
+        Path::new("main.sh"),
+        source,
+        vec![diagnostic],
+        &files,
+    );
+    assert!(kept.is_empty());
+}
+
+#[test]
+fn test_cli_changed_only_from_a_subdirectory() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-diff-cli-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("bin")).unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .current_dir(&dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?}");
+    };
+    git(&["init", "--quiet"]);
+    fs::write(dir.join("lib.sh"), "retries=3\n").unwrap();
+    fs::write(
+        dir.join("bin/main.sh"),
+        "a=1 #/ bool\nsource ../lib.sh\nretries=(1 2)\n",
+    )
+    .unwrap();
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "-m", "init"]);
+    fs::write(dir.join("lib.sh"), "retries=4\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+        .current_dir(dir.join("bin"))
+        .args(["--no-cache", "--color", "never", "--format", "short"])
+        .args(["--changed-only", "./main.sh", "../lib.sh"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // Only the conflict points at the changed line of lib.sh
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    assert!(stdout.contains("BT0019"), "{stdout}");
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,152 @@
+use bashtyped::{
+    codes,
+    testing::{analyze, analyze_with_config},
+    Config, Diagnostic, FileInfo, Severity,
+};
+
+fn pedantic() -> Config {
+    let mut config = Config::default();
+    config.pedantic = true;
+    config
+}
+
+fn division_errors<'a>(file: &'a FileInfo) -> Vec<&'a Diagnostic> {
+    file.errors
+        .iter()
+        .filter(|error| error.code.as_deref() == Some(codes::DIVISION_BY_ZERO))
+        .collect()
+}
+
+#[test]
+fn test_division_by_literal_zero() {
+    let file = analyze("total=10\nhalf=$((total / 0))");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Division by zero");
+    assert_eq!(
+        file.errors[0].code.as_deref(),
+        Some(codes::DIVISION_BY_ZERO)
+    );
+    assert_eq!(file.errors[0].severity, Severity::Error);
+    assert_eq!(file.errors[0].labels[0].range, 25..26);
+}
+
+#[test]
+fn test_modulo_by_constant_zero() {
+    let file = analyze("count=0\n((rest = 10 % count))\n((rest %= (count)))");
+    assert_eq!(file.errors.len(), 2);
+    assert!(file
+        .errors
+        .iter()
+        .all(|error| error.message == "Division by zero"));
+}
+
+#[test]
+fn test_division_by_empty_variable() {
+    let file = analyze("count=\naverage=$((10 / $count))");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Division by zero");
+}
+
+#[test]
+fn test_division_by_nonzero() {
+    let file = analyze_with_config(
+        "count=4\nquarter=$((10 / count))\nhex=$((10 / 0x10))",
+        pedantic(),
+    );
+    assert!(division_errors(&file).is_empty());
+}
+
+#[test]
+fn test_division_by_reassigned_variable() {
+    let file = analyze_with_config(
+        "count=0\ncount=$((count + 1))\naverage=$((10 / count))",
+        pedantic(),
+    );
+    assert!(division_errors(&file).is_empty());
+}
+
+#[test]
+fn test_division_by_possibly_unset_variable_pedantic() {
+    let source = "[[ -n $1 ]] && count=2\naverage=$((10 / count))";
+    assert!(analyze(source).errors.is_empty());
+    let file = analyze_with_config(source, pedantic());
+    let errors = division_errors(&file);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Divisor may be zero");
+    assert_eq!(errors[0].severity, Severity::Warning);
+    assert_eq!(
+        errors[0].labels[0].text,
+        "count is 0 when it is unset or empty"
+    );
+}
+
+#[test]
+fn test_division_by_possibly_empty_string_pedantic() {
+    let file = analyze_with_config(
+        "count=\"$(wc -l < file)\" #/ string\naverage=$((10 / count))",
+        pedantic(),
+    );
+    let errors = division_errors(&file);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Divisor may be zero");
+}
+
+#[test]
+fn test_division_outside_arithmetic() {
+    let file = analyze("[[ 10 / 0 ]]");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_division_after_branches_with_different_values() {
+    let file =
+        analyze("count=0\nif [[ -n $1 ]]; then count=0; else count=5; fi\necho $((10 / count))");
+    assert!(division_errors(&file).is_empty());
+    let file = analyze("count=0\ncase $1 in\n  a) count=2 ;;\nesac\necho $((10 / count))");
+    assert!(division_errors(&file).is_empty());
+    let file = analyze("count=0\nfor i in 1 2; do count=$i; done\necho $((10 / count))");
+    assert!(division_errors(&file).is_empty());
+}
+
+#[test]
+fn test_division_after_branches_with_the_same_value() {
+    let file = analyze("if [[ -n $1 ]]; then count=0; else count=0; fi\necho $((10 / count))");
+    assert_eq!(division_errors(&file).len(), 1);
+    let file = analyze("count=0\n( count=3 )\necho $((10 / count))");
+    assert_eq!(division_errors(&file).len(), 1);
+}
+
+#[test]
+fn test_division_after_builtins_assign_divisor() {
+    for assignment in [
+        "read count",
+        "read -r -p 'count: ' count",
+        "printf -v count %d 3",
+        "wait -p count",
+        "mapfile count",
+        "let count=3",
+        "let 'count += 3'",
+    ] {
+        let source = format!("count=0\n{assignment}\necho $((10 / count))");
+        let file = analyze(&source);
+        assert!(division_errors(&file).is_empty(), "{assignment}");
+    }
+}
+
+#[test]
+fn test_division_after_arithmetic_assigns_divisor() {
+    for assignment in ["(( count++ ))", ": $((count = 4))", "(( count += 2 ))"] {
+        let source = format!("count=0\n{assignment}\necho $((10 / count))");
+        let file = analyze(&source);
+        assert!(division_errors(&file).is_empty(), "{assignment}");
+    }
+}
+
+#[test]
+fn test_division_after_function_assigns_divisor() {
+    let file = analyze("count=0\nset_count() {\n  count=3\n}\nset_count\necho $((10 / count))");
+    assert!(division_errors(&file).is_empty());
+    let file =
+        analyze("count=0\nuse_count() {\n  local count=3\n}\nuse_count\necho $((10 / count))");
+    assert_eq!(division_errors(&file).len(), 1);
+}
@@ -0,0 +1,123 @@
+use std::fs;
+
+use ariadne::Source;
+use bashtyped::{
+    codes,
+    encoding::DecodedSource,
+    fix::apply_fixes,
+    testing::{analyze, analyze_with_config},
+    Config, Severity,
+};
+
+#[test]
+fn test_decode_byte_order_mark() {
+    let decoded = DecodedSource::new(b"\xef\xbb\xbfcount=1\n");
+    assert_eq!(decoded.source_code, "count=1\n");
+    assert!(decoded.byte_order_mark);
+    assert!(!decoded.lossy);
+
+    let decoded = DecodedSource::new(b"count=1\n");
+    assert!(!decoded.byte_order_mark);
+}
+
+#[test]
+fn test_invalid_bytes_are_reported_once() {
+    let decoded = DecodedSource::new(b"name=\"caf\xe9\"\nother=\"\xff\"\ncount=1 #/ bool");
+    assert!(decoded.lossy);
+    assert_eq!(decoded.source_code.matches('\u{fffd}').count(), 2);
+    let file = analyze(&decoded.source_code);
+    let warnings = file
+        .errors
+        .iter()
+        .filter(|error| error.code.as_deref() == Some(codes::INVALID_UTF8))
+        .collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, Severity::Warning);
+    assert_eq!(warnings[0].labels[0].range, 9..12);
+    assert_eq!(
+        warnings[0].note.as_deref(),
+        Some("1 more invalid sequence was replaced later in the file")
+    );
+    // The rest of the file is still analyzed
+    assert!(file
+        .errors
+        .iter()
+        .any(|error| error.code.as_deref() == Some(codes::TYPE_MISMATCH)));
+    assert!(analyze("name=café").errors.is_empty());
+}
+
+#[test]
+fn test_write_keeps_byte_order_mark() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-encoding-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("bom.sh");
+    fs::write(&path, b"\xef\xbb\xbfcount=1\n").unwrap();
+    let decoded = DecodedSource::read(&path).unwrap();
+    decoded.write(&path, "count=2\n").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"\xef\xbb\xbfcount=2\n");
+
+    // The original bytes of a lossy file can't be written back
+    fs::write(&path, b"name=caf\xe9\n").unwrap();
+    let decoded = DecodedSource::read(&path).unwrap();
+    assert!(decoded.write(&path, &decoded.source_code).is_err());
+    assert_eq!(fs::read(&path).unwrap(), b"name=caf\xe9\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_byte_order_mark_isnt_a_column() {
+    let source = "\u{feff}ready=1 #/ bool";
+    let file = analyze(source);
+    assert_eq!(
+        file.errors[0].short("bom.sh", source),
+        "bom.sh:1:1: error[BT0001]: Types do not match"
+    );
+}
+
+#[test]
+fn test_crlf_line_endings() {
+    let source = "count=1 #/ bool\r\nname=text\r\n";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    // The annotation ends before the carriage return
+    assert_eq!(file.errors[0].labels[0].range, 11..15);
+    assert_eq!(
+        file.errors[0].short("crlf.sh", source),
+        "crlf.sh:1:1: error[BT0001]: Types do not match"
+    );
+
+    let force = &file.errors[0].fixes[1];
+    let (fixed, _) = apply_fixes(source, [force]);
+    assert_eq!(fixed, "#[force]\r\ncount=1 #/ bool\r\nname=text\r\n");
+}
+
+#[test]
+fn test_crlf_annotation_removal() {
+    let mut config = Config::default();
+    config.pedantic = true;
+    let source = "#/ int\r\nretries=3\r\necho done\r\n";
+    let file = analyze_with_config(source, config);
+    let redundant = file
+        .errors
+        .iter()
+        .find(|error| error.code.as_deref() == Some(codes::REDUNDANT_ANNOTATION))
+        .unwrap();
+    let (fixed, _) = apply_fixes(source, &redundant.fixes);
+    assert_eq!(fixed, "retries=3\r\necho done\r\n");
+}
+
+#[test]
+fn test_reports_count_characters() {
+    let source = "name=\"café\"\nready=$name #/ bool";
+    let mut config = Config::default();
+    config.color = false;
+    let file = analyze_with_config(source, config);
+    let mut output = Vec::new();
+    file.reports()
+        .next()
+        .unwrap()
+        .write(Source::from(source), &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(":2:1]"), "{output}");
+}
@@ -0,0 +1,62 @@
+use bashtyped::{testing::analyze, BashType, Severity};
+
+#[test]
+fn test_eval_is_flagged() {
+    let file = analyze("eval \"$(generate_code)\"");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Use of eval");
+    assert_eq!(file.errors[0].severity, Severity::Warning);
+    assert_eq!(file.errors[0].code.as_deref(), Some("BT0010"));
+}
+
+#[test]
+fn test_eval_literal_is_analyzed() {
+    let source = "count=1\neval 'count=text'";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 2);
+    assert_eq!(
+        file.errors[1].message,
+        "Variable count defined with different type"
+    );
+    assert_eq!(
+        &source[file.errors[1].labels[1].range.clone()],
+        "count=text"
+    );
+}
+
+#[test]
+fn test_eval_substitutes_literals() {
+    let file = analyze("var_name=count\neval \"$var_name=5\"\nnext=\"$count\"");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.variables["count"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["next"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_eval_unknown_variable() {
+    let file = analyze("eval 'echo \"$missing\"'");
+    assert_eq!(file.errors.len(), 2);
+    assert_eq!(file.errors[1].message, "Unknown variable");
+}
+
+#[test]
+fn test_eval_unescapes_double_quotes() {
+    let file = analyze("eval \"a=1; b=\\$a\"");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.variables["b"].bash_type, BashType::Integer);
+
+    let file = analyze("eval \"greeting=\\\"hi\\\"\"");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.variables["greeting"].bash_type, BashType::String);
+}
+
+#[test]
+fn test_eval_updates_existing_variables() {
+    let source = "x=1\neval \"x=foo\"\ny=$x";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 2);
+    assert_eq!(file.variables["x"].bash_type, BashType::String);
+    assert_eq!(&source[file.variables["x"].range.clone()], "x=foo");
+    assert_eq!(file.history["x"].len(), 2);
+    assert_eq!(file.variables["y"].bash_type, BashType::String);
+}
@@ -0,0 +1,151 @@
+use bashtyped::{
+    testing::{analyze, analyze_with_config},
+    BashType, Config, Severity,
+};
+
+fn pedantic() -> Config {
+    let mut config = Config::default();
+    config.pedantic = true;
+    config
+}
+
+#[test]
+fn test_case_modification() {
+    let file = analyze_with_config(
+        r#"name=world
+upper=${name^^}
+lower="${name,,}"
+first=${name^}"#,
+        pedantic(),
+    );
+    assert!(file.errors.is_empty());
+    for variable in ["upper", "lower", "first"] {
+        assert_eq!(file.variables[variable].bash_type, BashType::String);
+    }
+}
+
+#[test]
+fn test_substitution() {
+    let file = analyze_with_config(
+        r#"path=/usr/local/bin
+dashed=${path//\//-}
+trimmed=${path/#\/usr/}
+renamed=${path/%bin/lib}"#,
+        pedantic(),
+    );
+    assert!(file.errors.is_empty());
+    for variable in ["dashed", "trimmed", "renamed"] {
+        assert_eq!(file.variables[variable].bash_type, BashType::String);
+    }
+}
+
+#[test]
+fn test_substitution_of_integer_gives_string() {
+    let file = analyze("port=8080\nshort=${port/80/}\nother=$short #/ int");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Types do not match");
+}
+
+#[test]
+fn test_string_operation_on_unknown_variable() {
+    let file = analyze("upper=${missing^^}");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Unknown variable");
+}
+
+#[test]
+fn test_string_operation_on_array_elements() {
+    let file = analyze("names=(a b)\nupper=(\"${names[@]^^}\")");
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["upper"].bash_type,
+        BashType::Array(Box::new(BashType::String))
+    );
+}
+
+#[test]
+fn test_string_operation_on_integer_pedantic() {
+    let source = "count=3\nshown=${count^^}";
+    let file = analyze_with_config(source, pedantic());
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "String operation on a value that isn't a string"
+    );
+    assert_eq!(file.errors[0].severity, Severity::Warning);
+
+    let file = analyze(source);
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_transformations() {
+    let file = analyze_with_config(
+        r#"count=1
+quoted=${count@Q}
+escaped="${count@E}"
+prompt=${count@P}
+assignment=${count@A}
+attributes=${count@a}
+items=(1 2)
+all=("${items[@]@Q}")"#,
+        pedantic(),
+    );
+    assert!(file.errors.is_empty());
+    for variable in ["quoted", "escaped", "prompt", "assignment", "attributes"] {
+        assert_eq!(file.variables[variable].bash_type, BashType::String);
+    }
+    assert_eq!(
+        file.variables["all"].bash_type,
+        BashType::Array(Box::new(BashType::String))
+    );
+    assert!(!analyze("quoted=${missing@Q}").errors.is_empty());
+}
+
+#[test]
+fn test_default_value() {
+    let file = analyze("count=${1:-5}\nname=${NAME:-}\nport=8080\nother=${port:-1}");
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["count"].bash_type,
+        BashType::Or(Box::new(BashType::String), Box::new(BashType::Integer))
+    );
+    assert_eq!(file.variables["name"].bash_type, BashType::String);
+    assert_eq!(file.variables["other"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_alternate_value() {
+    let file = analyze("flag=${DEBUG:+--verbose}\nport=8080\nset=${port:+1}");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["flag"].bash_type, BashType::String);
+    assert_eq!(
+        file.variables["set"].bash_type,
+        BashType::Or(Box::new(BashType::Integer), Box::new(BashType::String))
+    );
+}
+
+#[test]
+fn test_error_if_unset_defines_variable() {
+    let file = analyze("dir=${TARGET_DIR:?needs a directory}\ncopy=$TARGET_DIR");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["dir"].bash_type, BashType::String);
+    assert_eq!(file.variables["TARGET_DIR"].bash_type, BashType::String);
+}
+
+#[test]
+fn test_error_if_unset_guards_conditional_variable() {
+    let file = analyze("[[ -n $1 ]] && port=8080\nchecked=${port:?}\nagain=$port");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["checked"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_assign_default_defines_variable() {
+    let file = analyze(": ${retries:=3}\ncopy=$retries");
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["retries"].bash_type,
+        BashType::Or(Box::new(BashType::String), Box::new(BashType::Integer))
+    );
+}
@@ -0,0 +1,57 @@
+use bashtyped::{
+    codes::{self, RULES},
+    Config, FileInfo,
+};
+
+#[test]
+fn test_examples_trigger_their_rule() {
+    for rule in RULES {
+        if codes::WORKSPACE_CODES.contains(&rule.code) {
+            continue;
+        }
+        let mut config = Config::default();
+        config.pedantic = true;
+        // Nothing fits in a memory limit of 0
+        if rule.code == codes::TOO_LARGE {
+            config.memory_limit = Some(0);
+        }
+        let mut file = FileInfo::with_config(rule.example, config);
+        file.parse_code();
+        assert!(
+            file.errors
+                .iter()
+                .any(|error| error.code.as_deref() == Some(rule.code)),
+            "the example for {} gave {:?}",
+            rule.code,
+            file.errors
+        );
+    }
+}
+
+#[test]
+fn test_rules_are_in_order() {
+    for (i, rule) in RULES.iter().enumerate() {
+        assert_eq!(rule.code, format!("BT{:04}", i + 1));
+    }
+}
+
+#[test]
+fn test_find_rule() {
+    assert_eq!(codes::rule("BT0003").unwrap().name, "redefined-variable");
+    assert_eq!(
+        codes::rule("bt0003").unwrap().code,
+        codes::REDEFINED_VARIABLE
+    );
+    assert_eq!(codes::rule("eval").unwrap().code, codes::EVAL);
+    assert!(codes::rule("BT9999").is_none());
+}
+
+#[test]
+fn test_explanation() {
+    let text = codes::rule(codes::REDEFINED_VARIABLE).unwrap().to_string();
+    assert!(text.starts_with(
+        "BT0003 (redefined-variable): A variable given a type that doesn't fit an earlier \
+         declaration\n\n"
+    ));
+    assert!(text.contains("Example:\n\n    count=1 #/ int\n    count=many\n\nFix: "));
+}
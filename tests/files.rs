@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use ariadne::Cache;
+use bashtyped::{
+    files::{FileId, Files},
+    symbols::Workspace,
+    Config, Diagnostic, FileInfo, Label, LabelKind,
+};
+
+#[test]
+fn test_workspace_file_ids() {
+    let mut workspace = Workspace::new();
+    let file = FileInfo::new("a=1");
+    let first = workspace.update("a.sh", &file);
+    let second = workspace.update("b.sh", &file);
+    assert_ne!(first, second);
+    // Updating a file keeps its id
+    assert_eq!(workspace.update("a.sh", &file), first);
+    assert_eq!(workspace.file_id(Path::new("b.sh")), Some(second));
+    assert_eq!(workspace.path(first), Some(Path::new("a.sh")));
+
+    workspace.remove(Path::new("a.sh"));
+    assert_eq!(workspace.path(first), None);
+    assert_ne!(workspace.update("a.sh", &file), second);
+}
+
+#[test]
+fn test_files_cache() {
+    let mut files = Files::new();
+    files.insert(FileId(3), "lib.sh", "retries=3\n");
+    assert_eq!(files.path(FileId(3)), Some("lib.sh"));
+    assert_eq!(files.source_code(FileId(3)), Some("retries=3\n"));
+    assert!(files.fetch(&FileId(3)).is_ok());
+    assert!(files.fetch(&FileId(4)).is_err());
+    assert_eq!(
+        files.display(&FileId(3)).map(|path| path.to_string()),
+        Some(String::from("lib.sh"))
+    );
+}
+
+#[test]
+fn test_report_spanning_files() {
+    let mut files = Files::new();
+    files.insert(FileId(0), "lib.sh", "retries=3\n");
+    files.insert(FileId(1), "main.sh", "source lib.sh\nretries=(1 2)\n");
+    let diagnostic = Diagnostic::new("retries is also a global in lib.sh", 14)
+        .with_label(Label::new(14..27, "Defined here", LabelKind::Inferred))
+        .with_label(Label::new(0..9, "Defined here", LabelKind::Inferred).with_file(FileId(0)));
+    let mut config = Config::default();
+    config.color = false;
+    let mut output = Vec::new();
+    diagnostic
+        .report_in(FileId(1), &config, &files)
+        .write(&mut files, &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("main.sh:2:1"));
+    assert!(output.contains("lib.sh:1:1"));
+    assert!(output.contains("retries=(1 2)"));
+    assert!(output.contains("retries=3"));
+}
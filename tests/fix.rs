@@ -0,0 +1,114 @@
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+use bashtyped::{
+    codes,
+    fix::{apply_fixes, Applicability, Fix, FixKind, TextEdit},
+    testing::analyze,
+};
+
+/// Applies the first fix of each diagnostic
+fn fixed(source: &str) -> String {
+    let file = analyze(source);
+    let (fixed, _) = apply_fixes(
+        source,
+        file.errors.iter().flat_map(|error| error.fixes.first()),
+    );
+    fixed
+}
+
+#[test]
+fn test_widen_mismatched_annotation() {
+    let file = analyze("ready=1 #/ bool");
+    assert_eq!(file.errors[0].code.as_deref(), Some(codes::TYPE_MISMATCH));
+    let fix = &file.errors[0].fixes[0];
+    assert_eq!(fix.kind, FixKind::Widen);
+    assert_eq!(fix.applicability, Applicability::MaybeIncorrect);
+    assert_eq!(fix.title, "Change the annotation to bool | int");
+
+    assert_eq!(fixed("ready=1 #/ bool"), "ready=1 #/ bool | int");
+    assert_eq!(
+        fixed("#/ array<int>\nnames=(a b)\necho done"),
+        "#/ array<int> | array<string>\nnames=(a b)\necho done"
+    );
+    assert!(analyze(&fixed("ready=1 #/ bool")).errors.is_empty());
+}
+
+#[test]
+fn test_force_mismatch() {
+    let source = "f() {\n  local ready=1 #/ bool\n}";
+    let file = analyze(source);
+    let force = &file.errors[0].fixes[1];
+    assert_eq!(force.kind, FixKind::Force);
+    let (fixed, _) = apply_fixes(source, [force]);
+    assert_eq!(fixed, "f() {\n  #[force]\n  local ready=1 #/ bool\n}");
+    assert!(analyze(&fixed).errors.is_empty());
+}
+
+#[test]
+fn test_no_widen_without_comment() {
+    let file = analyze("declare -i count=text");
+    assert!(file
+        .errors
+        .iter()
+        .flat_map(|error| &error.fixes)
+        .all(|fix| fix.kind != FixKind::Widen));
+}
+
+#[test]
+fn test_overlapping_fixes() {
+    let fix = |range, new_text: &str| Fix {
+        kind: FixKind::Widen,
+        applicability: Applicability::MachineApplicable,
+        title: String::new(),
+        edits: vec![TextEdit {
+            range,
+            new_text: new_text.to_owned(),
+        }],
+    };
+    let fixes = [fix(4..6, "XY"), fix(0..2, "ab"), fix(1..5, "skipped")];
+    assert_eq!(apply_fixes("0123456", &fixes), (String::from("ab23XY6"), 2));
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bashtyped-fix-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn fix(script: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+        .args(["--no-cache", "--format", "short", "--fix"])
+        .args(args)
+        .arg(script)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_fix_flag() {
+    let dir = temp_dir("flag");
+    let script = dir.join("script.sh");
+    let source = "ready=1 #/ bool\ncount=1 #/ int\ncount=text\n";
+    fs::write(&script, source).unwrap();
+    // Widening is only applied when unsafe fixes are allowed
+    let output = fix(&script, &[]);
+    assert_eq!(fs::read_to_string(&script).unwrap(), source);
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 2);
+
+    let output = fix(&script, &["--fix-unsafe", "--fix-kind", "widen"]);
+    assert_eq!(
+        fs::read_to_string(&script).unwrap(),
+        "ready=1 #/ bool | int\ncount=1 #/ int\ncount=text\n"
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Fixed 1 problems"));
+    // The redefinition has no fix and is still reported
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(stdout.contains("BT0003"));
+}
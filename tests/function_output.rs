@@ -0,0 +1,125 @@
+use bashtyped::{codes, testing::analyze, BashType, Severity};
+
+#[test]
+fn test_echoed_number() {
+    let file = analyze("get_port() {\n  echo 8080\n}\nport=$(get_port)");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["port"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_printf_formats() {
+    let file = analyze(
+        "count() { printf '%d\\n' \"$1\"; }\nname() { printf '%s' \"$HOME/x\"; }\n\
+         total=$(count 3)\nlabel=$(name)",
+    );
+    assert_eq!(file.variables["total"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["label"].bash_type, BashType::String);
+}
+
+#[test]
+fn test_echoed_variables_and_arithmetic() {
+    let file = analyze(
+        "next_id() {\n  local id=4\n  echo \"$id\"\n}\ndouble() { echo \"$(( $1 * 2 ))\"; }\n\
+         id=$(next_id)\ntwice=$(double 2)",
+    );
+    assert_eq!(file.variables["id"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["twice"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_outputs_are_combined() {
+    let file = analyze(
+        "get_port() {\n  if [[ -n $1 ]]; then\n    echo default\n  else\n    echo 8080\n  fi\n}\n\
+         port=$(get_port)",
+    );
+    assert_eq!(
+        file.variables["port"].bash_type,
+        BashType::Or(Box::new(BashType::String), Box::new(BashType::Integer))
+    );
+}
+
+#[test]
+fn test_failure_and_redirected_output_is_ignored() {
+    let file = analyze(
+        "get_port() {\n  if [[ -z $PORT_FILE ]]; then\n    echo \"no port file\"\n    return 1\n  \
+         fi\n  echo \"reading\" >&2\n  echo 8080 > /dev/null\n  echo 80 | cat > /dev/null\n  \
+         local seen=$(echo text)\n  echo 8080\n}\nport=$(get_port)",
+    );
+    assert_eq!(file.variables["port"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_output_through_other_functions() {
+    let file = analyze("base() { echo 1; }\nnext() {\n  base\n  return 0\n}\nvalue=$(next)");
+    assert_eq!(file.variables["value"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_unknown_output() {
+    let file = analyze(
+        "get_port() {\n  cat port.txt\n}\nlines() { echo 1; grep -c x file; }\n\
+         port=$(get_port)\ncount=$(lines)\nother=$(hostname)",
+    );
+    assert_eq!(file.variables["port"].bash_type, BashType::Unknown);
+    assert_eq!(file.variables["count"].bash_type, BashType::Unknown);
+    assert_eq!(file.variables["other"].bash_type, BashType::Unknown);
+}
+
+#[test]
+fn test_annotation_checked_against_output() {
+    let file = analyze("get_port() { echo 8080; }\nport=$(get_port) #/ bool");
+    assert_eq!(file.errors.len(), 1);
+}
+
+#[test]
+fn test_declared_stdout() {
+    let file = analyze(
+        "#[returns status: bool, stdout: int]\nget_port() {\n  echo \"$PORT\"\n  return 0\n}\n\
+         port=$(get_port)\nif get_port; then :; fi",
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["port"].bash_type, BashType::Integer);
+    assert_eq!(file.functions["get_port"].returns, Some(BashType::Bool));
+    assert_eq!(file.functions["get_port"].stdout, Some(BashType::Integer));
+}
+
+#[test]
+fn test_declared_stdout_only() {
+    let file = analyze("#[returns stdout: string]\nname() { echo \"$USER\"; }");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.functions["name"].returns, None);
+    assert_eq!(file.functions["name"].stdout, Some(BashType::String));
+}
+
+#[test]
+fn test_printed_value_does_not_match() {
+    let file = analyze("#[returns stdout: int]\nget_port() {\n  echo none\n}");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Output does not match");
+    assert_eq!(file.errors[0].code.as_deref(), Some(codes::RETURN_MISMATCH));
+    assert_eq!(file.errors[0].labels[0].range, 0..22);
+    assert_eq!(file.errors[0].labels[1].range, 38..47);
+}
+
+#[test]
+fn test_capturing_a_status() {
+    let file = analyze("#[returns bool]\nis_ready() {\n  [[ -f ready ]]\n}\nready=$(is_ready)");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "is_ready returns a status and doesn't print anything"
+    );
+    assert_eq!(file.errors[0].severity, Severity::Warning);
+    assert_eq!(file.errors[0].labels[0].range, 54..65);
+}
+
+#[test]
+fn test_invalid_channel() {
+    let file = analyze("#[returns status: bool, stderr: string]\nf() { :; }");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].labels[0].text,
+        "Unknown channel `stderr`, expected `status` or `stdout`"
+    );
+}
@@ -0,0 +1,120 @@
+use bashtyped::{testing::analyze, FileInfo, Severity};
+
+fn messages<'a>(file: &'a FileInfo) -> Vec<&'a str> {
+    file.errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect()
+}
+
+#[test]
+fn test_exit_status_types() {
+    let file = analyze("code=1\nexit \"$code\"\nreason=failed\nexit \"$reason\"");
+    assert_eq!(messages(&file), ["Argument to exit has the wrong type"]);
+}
+
+#[test]
+fn test_exit_status_range() {
+    let file = analyze("exit 255\nexit 300\nreturn -1\nexit failed");
+    assert_eq!(file.errors.len(), 3);
+    assert!(file
+        .errors
+        .iter()
+        .all(|error| error.message == "Exit status out of range"
+            && error.severity == Severity::Warning));
+}
+
+#[test]
+fn test_returns_int() {
+    let file = analyze(
+        r#"#[returns int]
+check() {
+    if [[ -n $1 ]]; then
+        name=text
+        return "$name"
+    fi
+    return 3
+}"#,
+    );
+    assert_eq!(
+        messages(&file),
+        [
+            "Argument to return has the wrong type",
+            "Return status does not match"
+        ]
+    );
+}
+
+#[test]
+fn test_returns_bool() {
+    let file = analyze(
+        r#"#[returns bool]
+is_ready() {
+    if [[ -f ready ]]; then
+        return 0
+    elif [[ -f failed ]]; then
+        return 2
+    fi
+    case $1 in
+        now) true ;;
+        *) echo later ;;
+    esac
+}"#,
+    );
+    assert_eq!(
+        messages(&file),
+        [
+            "Return status does not match",
+            "Function may end with a status that isn't a bool"
+        ]
+    );
+}
+
+#[test]
+fn test_returns_ignores_nested_functions() {
+    let file = analyze(
+        r#"#[returns bool]
+outer() {
+    inner() {
+        return 5
+    }
+    [[ -n $1 ]]
+}"#,
+    );
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_misplaced_returns() {
+    let file = analyze("#[returns int]\ncount=1");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Error while parsing comment");
+}
+
+#[test]
+fn test_params() {
+    let file = analyze("#[params path: string, counts: map<string, int>]\ncheck() { :; }");
+    assert!(file.errors.is_empty());
+    let params = file.functions["check"]
+        .params
+        .iter()
+        .map(|param| (param.name.as_str(), param.bash_type.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        params,
+        [
+            ("path", String::from("string")),
+            ("counts", String::from("map<string, int>"))
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_params() {
+    let file = analyze("#[params path]\ncheck() { :; }");
+    assert_eq!(file.errors[0].labels[0].text, "Expected a type for path");
+    let file = analyze("#[params path: str]\ncheck() { :; }");
+    assert_eq!(file.errors[0].message, "Invalid type");
+    let file = analyze("#[params path: string]\ncount=1");
+    assert_eq!(file.errors[0].message, "Error while parsing comment");
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "arbitrary")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use bashtyped::{
+    fuzzing::Script,
+    testing::{analyze, check_ranges},
+};
+use tree_sitter::Parser;
+
+/// Bytes for seeding scripts, the same on every run
+fn seeded_bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn test_generated_scripts_are_analyzed() {
+    let mut parser = Parser::new();
+    parser.set_language(tree_sitter_bash::language()).unwrap();
+    let mut parsed_cleanly = 0;
+    for seed in 0..300 {
+        let bytes = seeded_bytes(seed, 512);
+        let Script(source) = Script::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+        if !tree.root_node().has_error() {
+            parsed_cleanly += 1;
+        }
+        let file = analyze(&source);
+        if let Err(problems) = check_ranges(&file) {
+            panic!("{problems}in\n{source}");
+        }
+    }
+    assert!(parsed_cleanly > 290, "only {parsed_cleanly} scripts parsed");
+}
+
+#[test]
+fn test_arbitrary_text_is_analyzed() {
+    // Bash punctuation makes it more likely that the bytes form partial constructs
+    const ALPHABET: &[u8] = b"ab1 \n$(){}[]\"'`#/<>|&;=-+:^*@!~\\";
+    for seed in 0..300 {
+        let bytes = seeded_bytes(seed, 200);
+        let source = if seed % 2 == 0 {
+            bytes
+                .iter()
+                .map(|byte| ALPHABET[*byte as usize % ALPHABET.len()] as char)
+                .collect::<String>()
+        } else {
+            String::from_utf8_lossy(&bytes).into_owned()
+        };
+        let file = analyze(&source);
+        if let Err(problems) = check_ranges(&file) {
+            panic!("{problems}in\n{source:?}");
+        }
+    }
+}
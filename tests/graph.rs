@@ -0,0 +1,86 @@
+use bashtyped::{
+    graph::{DependencyGraph, Symbol},
+    FileInfo,
+};
+
+fn graph(source: &str) -> DependencyGraph {
+    let mut file = FileInfo::new(source);
+    file.parse_code();
+    file.dependency_graph()
+}
+
+fn variable(name: &str) -> Symbol {
+    Symbol::Variable(String::from(name))
+}
+
+fn function(name: &str) -> Symbol {
+    Symbol::Function(String::from(name))
+}
+
+#[test]
+fn test_variable_dependencies() {
+    let graph = graph(
+        "base=/opt\ncount=2\npath=\"$base/bin\"\ntotal=$((count + 1))\nlist=(${path} \"$extra\")\nPATH=$PATH:$path",
+    );
+    assert_eq!(
+        graph.edges,
+        [
+            (variable("PATH"), variable("path")),
+            (variable("list"), variable("extra")),
+            (variable("list"), variable("path")),
+            (variable("path"), variable("base")),
+            (variable("total"), variable("count")),
+        ]
+        .into()
+    );
+    assert_eq!(graph.nodes[&variable("count")], "count: int");
+    assert_eq!(graph.nodes[&variable("extra")], "extra");
+}
+
+#[test]
+fn test_loops_and_subscripts() {
+    let graph = graph("for file in \"${files[@]}\"; do name=$file; done\nmap[$key]=${value:-x}");
+    assert_eq!(
+        graph.edges,
+        [
+            (variable("file"), variable("files")),
+            (variable("map"), variable("value")),
+            (variable("name"), variable("file")),
+        ]
+        .into()
+    );
+}
+
+#[test]
+fn test_function_dependencies() {
+    let graph = graph(
+        "prefix=app\nname() {\n  local id=1\n  echo \"$prefix-$id\"\n  helper\n}\nhelper() { :; }\nlabel=$(name \"$suffix\")\ntoday=$(date)",
+    );
+    assert_eq!(
+        graph.edges,
+        [
+            (variable("label"), variable("suffix")),
+            (variable("label"), function("name")),
+            (function("name"), variable("id")),
+            (function("name"), variable("prefix")),
+            (function("name"), function("helper")),
+        ]
+        .into()
+    );
+    assert_eq!(graph.nodes[&function("helper")], "helper()");
+}
+
+#[test]
+fn test_dot() {
+    assert_eq!(
+        graph("a=1\nb=$a\nf() { echo \"$b\"; }").to_dot(),
+        r#"digraph bashtyped {
+  "a" [shape=ellipse, label="a: int"];
+  "b" [shape=ellipse, label="b: int"];
+  "f()" [shape=box, label="f()"];
+  "b" -> "a";
+  "f()" -> "b";
+}
+"#
+    );
+}
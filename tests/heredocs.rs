@@ -0,0 +1,85 @@
+use bashtyped::{codes, testing::analyze, BashType, HeredocLanguage, Severity};
+
+#[test]
+fn test_array_in_json_heredoc() {
+    let source = r#"ports=(80 443)
+name="$(pwd)/app"
+#/ heredoc(json)
+cat <<EOF > config.json
+{"name": "$name", "ports": ${ports[@]}, "first": ${ports[0]}, "count": ${#ports[@]}}
+EOF"#;
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(
+        error.message,
+        "${ports[@]} interpolated into a json heredoc"
+    );
+    assert_eq!(error.code.as_deref(), Some(codes::HEREDOC_INTERPOLATION));
+    assert_eq!(error.severity, Severity::Warning);
+    assert_eq!(
+        error.labels[0].bash_type,
+        Some(BashType::Array(Box::new(BashType::Integer)))
+    );
+    assert_eq!(file.heredocs.len(), 1);
+    let heredoc = &file.heredocs[0];
+    assert_eq!(heredoc.language, Some(HeredocLanguage::Json));
+    assert!(heredoc.expands);
+    assert!(source[heredoc.range.clone()].starts_with("{\"name\""));
+}
+
+#[test]
+fn test_inline_heredoc_annotation() {
+    let file = analyze(
+        r#"declare -A labels=([app]=web)
+kubectl apply -f - <<EOF #/ heredoc(yaml)
+labels: ${labels[@]}
+EOF"#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "${labels[@]} interpolated into a yaml heredoc"
+    );
+    assert_eq!(file.heredocs[0].language, Some(HeredocLanguage::Yaml));
+}
+
+#[test]
+fn test_unannotated_heredoc() {
+    let source = "ports=(80 443)\ncat <<EOF\n${ports[@]}\nEOF\ncat <<'EOF'\n$HOME\nEOF";
+    let file = analyze(source);
+    assert!(file.errors.is_empty());
+    assert_eq!(file.heredocs.len(), 2);
+    assert_eq!(file.heredocs[0].language, None);
+    assert!(file.heredocs[0].expands);
+    assert!(!file.heredocs[1].expands);
+    assert_eq!(&source[file.heredocs[1].range.clone()], "$HOME\n");
+}
+
+#[test]
+fn test_quoted_heredoc_isnt_checked() {
+    let file = analyze(
+        r#"ports=(80 443)
+#/ heredoc(sql)
+psql <<'SQL'
+SELECT ${ports[@]};
+SQL"#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.heredocs[0].language, Some(HeredocLanguage::Sql));
+}
+
+#[test]
+fn test_unknown_heredoc_language() {
+    let file = analyze("#/ heredoc(toml)\ncat <<EOF\nkey = 1\nEOF");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Unknown heredoc language toml, expected json, yaml or sql"
+    );
+    assert_eq!(
+        file.errors[0].code.as_deref(),
+        Some(codes::INVALID_ANNOTATION)
+    );
+    assert_eq!(file.heredocs[0].language, None);
+}
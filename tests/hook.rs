@@ -0,0 +1,71 @@
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bashtyped-hook-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("good.sh"), "count=1 #/ int\n").unwrap();
+    fs::write(dir.join("bad.sh"), "count=1 #/ int\ncount=text\n").unwrap();
+    dir
+}
+
+fn hook(dir: &PathBuf, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+        .arg("hook")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_hook_is_terse() {
+    let dir = temp_dir("terse");
+    let output = hook(&dir, &["good.sh", "bad.sh"], "");
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "bad.sh:2:1: error[BT0003]: Variable count defined with different type\n"
+    );
+
+    let output = hook(&dir, &["good.sh"], "");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    // The results were cached in the directory the hook ran in
+    assert!(dir.join(".bashtyped").join("cache").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_hook_reads_stdin() {
+    let dir = temp_dir("stdin");
+    let output = hook(&dir, &["--stdin-filename", "good.sh"], "count=1 #/ bool\n");
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "good.sh:1:1: error[BT0001]: Types do not match\n"
+    );
+
+    let output = hook(
+        &dir,
+        &["--no-cache", "--stdin-filename", "bad.sh", "bad.sh"],
+        "count=2 #/ int\n",
+    );
+    assert!(output.status.success());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,129 @@
+use bashtyped::{
+    codes,
+    testing::{analyze, analyze_with_config},
+    BashType, Config, Severity,
+};
+
+fn pedantic() -> Config {
+    let mut config = Config::default();
+    config.pedantic = true;
+    config
+}
+
+#[test]
+fn test_prefix_assignment_is_scoped() {
+    let file = analyze(
+        r#"count=3
+count="$(pwd)" ls
+LC_ALL=C sort
+total=$((count + 1))"#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["count"].bash_type, BashType::Integer);
+    assert!(!file.variables.contains_key("LC_ALL"));
+}
+
+#[test]
+fn test_prefix_assignment_value_is_checked() {
+    let file = analyze("IFS=$separator read -r line");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].code.as_deref(),
+        Some(codes::UNKNOWN_VARIABLE)
+    );
+}
+
+#[test]
+fn test_read_declares_variables() {
+    let file = analyze(
+        r#"IFS= read -r line
+while IFS=: read -r user _ uid rest; do
+    :
+done < /etc/passwd
+IFS=, read -ra fields
+read -d '' -p "Name: " name
+read
+copy=($line $user $uid $rest $name $REPLY)
+all=("${fields[@]}")"#,
+    );
+    assert!(file.errors.is_empty());
+    for name in ["line", "user", "uid", "rest", "name", "REPLY"] {
+        assert_eq!(file.variables[name].bash_type, BashType::String);
+    }
+    assert_eq!(
+        file.variables["fields"].bash_type,
+        BashType::Array(Box::new(BashType::String))
+    );
+    assert!(!file.variables.contains_key("IFS"));
+}
+
+#[test]
+fn test_read_keeps_declared_type() {
+    let file = analyze("port=80\nread -r port");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["port"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_ifs_changed_in_function() {
+    let file = analyze(
+        r#"read_fields() {
+    IFS=:
+    read -ra fields
+}"#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "IFS is changed without being restored");
+    assert_eq!(error.code.as_deref(), Some(codes::UNRESTORED_IFS));
+    assert_eq!(error.severity, Severity::Warning);
+    assert_eq!(error.labels[0].range, 20..25);
+}
+
+#[test]
+fn test_ifs_restored() {
+    let file = analyze(
+        r#"saved() {
+    old_ifs="$IFS"
+    IFS=:
+    read -ra fields
+    IFS="$old_ifs"
+}
+local_ifs() {
+    local IFS=,
+    read -ra fields
+}
+subshell() (
+    IFS=,
+    read -ra fields
+)
+unset_ifs() {
+    IFS=,
+    read -ra fields
+    unset IFS
+}
+default() {
+    IFS=,
+    read -ra fields
+    IFS=$' \t\n'
+}"#,
+    );
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_top_level_ifs_is_pedantic() {
+    let source = "IFS=$'\\n'\nfiles=($(ls))";
+    assert!(analyze(source)
+        .errors
+        .iter()
+        .all(|error| error.code.as_deref() != Some(codes::UNRESTORED_IFS)));
+    let file = analyze_with_config(source, pedantic());
+    let errors = file
+        .errors
+        .iter()
+        .filter(|error| error.code.as_deref() == Some(codes::UNRESTORED_IFS))
+        .collect::<Vec<_>>();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].labels[0].range, 0..9);
+}
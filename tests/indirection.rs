@@ -0,0 +1,57 @@
+use bashtyped::{
+    testing::{analyze, analyze_with_config},
+    BashType, Config, Severity,
+};
+
+fn pedantic() -> Config {
+    let mut config = Config::default();
+    config.pedantic = true;
+    config
+}
+
+#[test]
+fn test_indirection_through_literal() {
+    let file = analyze_with_config(
+        r#"port=8080
+name=port
+value="${!name}""#,
+        pedantic(),
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["value"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_indirection_to_unknown_variable() {
+    let file = analyze(
+        r#"name=missing
+value="${!name}""#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Unknown variable");
+}
+
+#[test]
+fn test_indirection_through_runtime_value() {
+    let source = r#"name="$(pick_name)"
+value=${!name}
+other=$value #/ int"#;
+    let file = analyze(source);
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["value"].bash_type, BashType::Unknown);
+
+    let file = analyze_with_config(source, pedantic());
+    let messages = file
+        .errors
+        .iter()
+        .map(|error| (error.severity, error.message.as_str()))
+        .collect::<Vec<_>>();
+    assert!(messages.contains(&(Severity::Warning, "Indirect expansion can't be checked")));
+}
+
+#[test]
+fn test_indirection_listing_names() {
+    let file = analyze_with_config(r#"names="${!BASH_*}""#, pedantic());
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["names"].bash_type, BashType::String);
+}
@@ -0,0 +1,41 @@
+use std::fs;
+
+use bashtyped::{init::Detected, Config, Dialect};
+
+#[test]
+fn test_detect_and_write_config() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-init-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("vendor")).unwrap();
+    fs::create_dir_all(dir.join("bin")).unwrap();
+    fs::write(dir.join("build.sh"), "count=1 #/ int\n").unwrap();
+    fs::write(dir.join("bin").join("run"), "#!/bin/bash\necho hi\n").unwrap();
+    fs::write(dir.join("vendor").join("lib.sh"), "#/ string\nname=x\n").unwrap();
+
+    let detected = Detected::new(&dir).unwrap();
+    assert_eq!(
+        detected,
+        Detected {
+            scripts: 2,
+            annotated: 1,
+            bash_scripts: 2,
+            zsh_scripts: 0,
+            ignore: vec![String::from("vendor/")],
+        }
+    );
+
+    let contents = detected.config_file();
+    assert!(contents.contains("1 of the 2 scripts"));
+    let config = Config::from_toml(&contents).unwrap();
+    assert_eq!(config.dialect, Dialect::Bash);
+    assert_eq!(config.ignore, ["vendor/"]);
+    assert!(!config.pedantic);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_empty_config_is_valid() {
+    let contents = Detected::default().config_file();
+    assert!(contents.contains("ignore = []"));
+    assert!(Config::from_toml(&contents).is_ok());
+}
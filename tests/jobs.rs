@@ -0,0 +1,110 @@
+use bashtyped::{codes, testing::analyze, BashType, Severity};
+
+#[test]
+fn test_job_id_is_int() {
+    let file = analyze(
+        r#"sleep 10 & pid=$!
+server --port 8080 &
+server_pid=$!
+wait "$pid" "$server_pid""#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["pid"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["server_pid"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_wait_expects_int() {
+    let file = analyze("pid=\"$(pwd)/server.pid\"\nwait \"$pid\"");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].code.as_deref(),
+        Some(codes::ARGUMENT_MISMATCH)
+    );
+}
+
+#[test]
+fn test_job_read_before_background_job() {
+    let file = analyze(
+        r#"server --port 8080
+pid=$!
+stop() {
+    # The job belongs to whoever called this
+    kill "$!"
+}"#,
+    );
+    assert_eq!(file.errors.len(), 2);
+    for error in &file.errors {
+        assert_eq!(error.message, "$! read before any background job started");
+        assert_eq!(error.code.as_deref(), Some(codes::NO_BACKGROUND_JOB));
+        assert_eq!(error.severity, Severity::Warning);
+    }
+    assert_eq!(file.errors[0].labels[0].range, 23..25);
+}
+
+#[test]
+fn test_job_started_on_path() {
+    let file = analyze(
+        r#"start() {
+    server &
+}
+start
+first=$!
+if true; then
+    sleep 1 &
+fi
+second=$!
+for host in a b; do
+    ping "$host" &
+    echo "$!"
+done"#,
+    );
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_job_from_sourced_function() {
+    let file = analyze("source ./lib.sh\nstart_server\npid=$!");
+    assert!(file
+        .errors
+        .iter()
+        .all(|error| error.code.as_deref() != Some(codes::NO_BACKGROUND_JOB)));
+}
+
+#[test]
+fn test_wait_p_declares_int() {
+    let file = analyze("sleep 1 &\nsleep 2 &\nwait -n -p finished\nlast=$finished");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["finished"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["last"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_coproc_declares_variables() {
+    let file = analyze(
+        r#"coproc worker { cat; }
+input=${worker[1]}
+pid=$worker_PID
+coproc filter (tr a b)
+coproc sort
+output=${COPROC[0]}
+sorter=$COPROC_PID"#,
+    );
+    assert!(file.errors.is_empty());
+    for name in ["worker", "filter", "COPROC"] {
+        assert_eq!(
+            file.variables[name].bash_type,
+            BashType::Array(Box::new(BashType::Integer))
+        );
+    }
+    for name in ["worker_PID", "filter_PID", "COPROC_PID", "input", "pid"] {
+        assert_eq!(file.variables[name].bash_type, BashType::Integer);
+    }
+}
+
+#[test]
+fn test_coproc_starts_job() {
+    let file = analyze("coproc { sleep 1; }\npid=$!");
+    assert!(file.errors.is_empty());
+    assert!(file.variables.contains_key("COPROC"));
+}
@@ -0,0 +1,180 @@
+use std::{fs, path::Path, process::Command};
+
+use bashtyped::{codes, testing::analyze_with_config, Config, FileInfo, Severity, SourceIndex};
+
+fn limited(memory_limit: usize) -> Config {
+    let mut config = Config::default();
+    config.memory_limit = Some(memory_limit);
+    config
+}
+
+/// A script with more top-level statements than fit in one chunk, with annotations above
+/// statements all through it
+fn generated_script(blocks: usize) -> String {
+    let mut source = String::from("#[params count: int]\ngreet() {\n    echo \"$1\"\n}\n");
+    for block in 0..blocks {
+        source.push_str(&format!(
+            "#/ int\ncount{block}=1\ncount{block}=text\ngreet {block}\n"
+        ));
+    }
+    source
+}
+
+#[test]
+fn test_chunks_match_whole_file() {
+    let source = generated_script(800);
+    let whole = analyze_with_config(&source, Config::default());
+    let chunked = analyze_with_config(&source, limited(64));
+    assert_eq!(whole.errors.len(), 800);
+    assert_eq!(chunked.errors, whole.errors);
+}
+
+#[test]
+fn test_streaming_hands_over_each_chunk() {
+    let source = generated_script(800);
+    let whole = analyze_with_config(&source, Config::default());
+    let mut file = FileInfo::with_config(&source, limited(1));
+    let mut batches = Vec::new();
+    file.parse_code_streaming(|errors| batches.push(errors));
+    assert!(batches.len() > 2);
+    assert!(file.errors.is_empty());
+    assert_eq!(batches.concat(), whole.errors);
+}
+
+#[test]
+fn test_too_large_for_memory_limit() {
+    let source = generated_script(10);
+    let file = analyze_with_config(&source, limited(0));
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.code.as_deref(), Some(codes::TOO_LARGE));
+    assert_eq!(error.severity, Severity::Warning);
+    assert_eq!(error.offset, 0);
+
+    // A function longer than the limit allows stops the analysis where it starts
+    let mut source = String::from("count=1\n#/ int\nname=text\nsetup() {\n");
+    source.push_str(&"    echo \"$count\"\n".repeat(2000));
+    source.push_str("}\nname=1\n");
+    let file = analyze_with_config(&source, limited(1));
+    let codes = file
+        .errors
+        .iter()
+        .map(|error| error.code.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(codes, [Some(codes::TYPE_MISMATCH), Some(codes::TOO_LARGE)]);
+    assert_eq!(file.errors[1].offset, source.find("setup").unwrap());
+}
+
+#[test]
+fn test_oversized_heredoc_is_skipped() {
+    let mut source = String::from("ports=(80 443)\n#/ heredoc(json)\ncat <<EOF\n");
+    source.push_str(&"{\"ports\": ${ports[@]}}\n".repeat(100));
+    source.push_str("EOF\n");
+    assert_eq!(
+        analyze_with_config(&source, Config::default()).errors.len(),
+        100
+    );
+
+    let config = Config::from_toml("max_heredoc_size = 1").unwrap();
+    assert!(analyze_with_config(&source, config.clone())
+        .errors
+        .is_empty());
+    let mut pedantic = config;
+    pedantic.pedantic = true;
+    let file = analyze_with_config(&source, pedantic);
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Heredoc is too large to check");
+    assert_eq!(file.errors[0].code.as_deref(), Some(codes::UNCHECKED));
+}
+
+#[test]
+fn test_memory_limit_in_config() {
+    let config = Config::from_toml("memory_limit = 512\nmax_heredoc_size = 1024").unwrap();
+    assert_eq!(config.memory_limit, Some(512));
+    assert_eq!(config.max_heredoc_size, Some(1024));
+    assert_eq!(Config::default().memory_limit, None);
+}
+
+#[test]
+fn test_source_index_matches_counting() {
+    let source = "name=日本 #/ string\n".repeat(1000);
+    let index = SourceIndex::new(&source);
+    let line_length = "name=日本 #/ string\n".len();
+    for offset in [0, 5, line_length + 8, 4095, 4097, 20000, source.len()] {
+        let offset = (0..=offset)
+            .rev()
+            .find(|&offset| source.is_char_boundary(offset))
+            .unwrap();
+        let before = &source[..offset];
+        assert_eq!(index.char_offset(offset), before.chars().count());
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |i| i + 1)..]
+            .chars()
+            .count()
+            + 1;
+        assert_eq!(index.line_column(offset), (line, column));
+    }
+}
+
+/// Runs the command line program in `dir`, returning what it printed
+fn run(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+        .current_dir(dir)
+        .args(["--no-cache", "--color", "never"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_cli_streams_chunks() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-large-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("generated.sh"), generated_script(5000)).unwrap();
+    fs::write(dir.join("lib.sh"), "retries=3\n").unwrap();
+    fs::write(dir.join("main.sh"), "source lib.sh\nretries=(1 2)\n").unwrap();
+
+    let whole = run(&dir, &["--format", "short", "generated.sh"]);
+    let chunked = run(
+        &dir,
+        &["--format", "short", "--memory-limit", "1", "generated.sh"],
+    );
+    assert_eq!(whole.lines().count(), 5000);
+    assert_eq!(chunked, whole);
+
+    // Files whose contents aren't kept are read again for problems between files
+    let full = run(
+        &dir,
+        &[
+            "--format",
+            "full",
+            "--memory-limit",
+            "1",
+            "lib.sh",
+            "main.sh",
+        ],
+    );
+    assert!(full.contains("main.sh:2:1"));
+    assert!(full.contains("lib.sh:1:1"));
+}
+
+#[test]
+fn test_source_index_utf16_offsets() {
+    let source = "name=😀日本 #/ string\n".repeat(1000);
+    let index = SourceIndex::new(&source);
+    for offset in [0, 5, 9, 4095, 4097, 20000, source.len()] {
+        let offset = (0..=offset)
+            .rev()
+            .find(|&offset| source.is_char_boundary(offset))
+            .unwrap();
+        let utf16 = source[..offset].encode_utf16().count();
+        assert_eq!(index.utf16_offset(offset), utf16);
+        assert_eq!(index.from_utf16(utf16), offset);
+    }
+    // The second half of the emoji's surrogate pair
+    assert_eq!(index.from_utf16(6), 5);
+    assert_eq!(index.from_utf16(usize::MAX), source.len());
+}
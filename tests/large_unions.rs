@@ -0,0 +1,69 @@
+use bashtyped::{
+    codes, config::RuleConfig, testing::analyze_with_config, BashType, Config, Severity,
+};
+
+const LARGE: &str = "#/ int | string | bool | array<int> | array<string> | map<string, int> | \
+                     map<string, string>\nvalue=1";
+
+#[test]
+fn test_large_union() {
+    let file = analyze_with_config(LARGE, Config::default());
+    assert_eq!(file.errors.len(), 1);
+    let hint = &file.errors[0];
+    assert_eq!(hint.code.as_deref(), Some(codes::LARGE_UNION));
+    assert_eq!(hint.severity, Severity::Advice);
+    assert_eq!(hint.message, "The type of value is a union of 7 types");
+    assert_eq!(hint.labels[0].bash_type.as_ref().unwrap().union_size(), 7);
+}
+
+#[test]
+fn test_max_members() {
+    let mut config = Config::default();
+    config.rules.insert(
+        codes::LARGE_UNION,
+        RuleConfig {
+            max_members: Some(2),
+            ..RuleConfig::default()
+        },
+    );
+    let file = analyze_with_config("count=1 #/ int | string | bool", config.clone());
+    assert_eq!(file.errors.len(), 1);
+    // Repeating the same type isn't reported again
+    let file = analyze_with_config("count=1 #/ int | string | bool\ncount=2", config);
+    assert_eq!(file.errors.len(), 1);
+    assert!(
+        analyze_with_config("count=1 #/ int | string | bool", Config::default())
+            .errors
+            .is_empty()
+    );
+
+    let config = Config::from_toml("[rules.large-union]\nmax_members = 2").unwrap();
+    assert_eq!(config.max_union_members(), 2);
+}
+
+#[test]
+fn test_abbreviated() {
+    let bash_type = "int | string | bool | array<int>"
+        .parse::<BashType>()
+        .unwrap();
+    assert_eq!(
+        bash_type.abbreviated(3).to_string(),
+        "int | string | … (2 more)"
+    );
+    assert_eq!(
+        bash_type.abbreviated(4).to_string(),
+        "int | string | bool | array<int>"
+    );
+
+    let mut config = Config::default();
+    config.color = false;
+    let mut report = Vec::new();
+    let file = analyze_with_config(LARGE, config);
+    for found in file.reports() {
+        found
+            .write(ariadne::Source::from(LARGE), &mut report)
+            .unwrap();
+    }
+    let report = String::from_utf8(report).unwrap();
+    assert!(report.contains("Type declared to be int | string | … (5 more)"));
+}
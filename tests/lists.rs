@@ -0,0 +1,53 @@
+use bashtyped::{codes, testing::analyze, BashType};
+
+#[test]
+fn test_fallback_only_on_failure() {
+    let file = analyze("grep -q x file || fallback=1\ncopy=$fallback");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].code.as_deref(), Some(codes::POSSIBLY_UNSET));
+    assert_eq!(
+        file.errors[0].labels[0].text,
+        "fallback is only set when one side of a && or || list runs"
+    );
+    assert_eq!(file.variables["fallback"].bash_type, BashType::Integer);
+
+    // Assigning it again on every path makes it safe to read
+    let file = analyze("cmd || fallback=1\nfallback=2\ncopy=$fallback");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_branches_merge_into_union() {
+    let file = analyze("value=text\ncmd && value=1\necho done");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].code.as_deref(),
+        Some(codes::REDEFINED_VARIABLE)
+    );
+    // Either assignment may be the last one
+    assert_eq!(
+        file.variables["value"].bash_type,
+        BashType::Or(Box::new(BashType::String), Box::new(BashType::Integer))
+    );
+}
+
+#[test]
+fn test_condition_guards_right_side() {
+    let file = analyze("cmd || name=text\n[[ -n $name ]] && a=$name\n[ -z \"$name\" ] || b=$name");
+    assert!(file.errors.is_empty());
+
+    let file = analyze("cmd || name=text\n[[ -v name ]] && a=$name\nb=$name");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].labels[0].range, 45..49);
+}
+
+#[test]
+fn test_regex_narrows_to_int() {
+    let file = analyze(
+        "input=$(cat) #/ int | string\n[[ $input =~ ^[0-9]+$ ]] && count=$input\nother=$input #/ int",
+    );
+    assert_eq!(file.variables["count"].bash_type, BashType::Integer);
+    // The narrowing ends with the list
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].code.as_deref(), Some(codes::TYPE_MISMATCH));
+}
@@ -0,0 +1,37 @@
+use bashtyped::{testing::analyze, BashType, Severity};
+
+#[test]
+fn test_integer_literals() {
+    let file = analyze("negative=-5\nhex=0xff\noctal=0755\nbinary=2#101\nbase64=64#_@");
+    assert!(file.errors.is_empty());
+    for name in ["negative", "hex", "octal", "binary", "base64"] {
+        assert_eq!(file.variables[name].bash_type, BashType::Integer, "{name}");
+    }
+}
+
+#[test]
+fn test_invalid_integer_literals() {
+    let file = analyze("octal=08\nhex=0xfg\nbinary=2#102");
+    assert!(file.errors.is_empty());
+    for name in ["octal", "hex", "binary"] {
+        assert_eq!(file.variables[name].bash_type, BashType::String, "{name}");
+    }
+}
+
+#[test]
+fn test_invalid_octal_in_arithmetic() {
+    let file = analyze("total=$((08 + 1))\n(( count = 0755 + 09 ))");
+    assert_eq!(file.errors.len(), 2);
+    assert!(file.errors.iter().all(
+        |error| error.message == "Invalid octal literal" && error.severity == Severity::Warning
+    ));
+}
+
+#[test]
+fn test_smallest_integer_literal() {
+    let file = analyze("min=-9223372036854775808\ntotal=$((5 / min))\nhex=-0x8000000000000000");
+    assert!(file.errors.is_empty());
+    for name in ["min", "total", "hex"] {
+        assert_eq!(file.variables[name].bash_type, BashType::Integer, "{name}");
+    }
+}
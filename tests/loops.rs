@@ -0,0 +1,54 @@
+use bashtyped::{testing::analyze, BashType};
+
+#[test]
+fn test_select_variable_type() {
+    let file = analyze("select choice in 1 2 3; do\n  echo \"$choice\"\ndone");
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["choice"].bash_type,
+        BashType::Or(Box::new(BashType::Integer), Box::new(BashType::String))
+    );
+}
+
+#[test]
+fn test_select_sets_reply() {
+    let file = analyze("select opt in start stop; do\n  echo \"$REPLY\"\ndone");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["opt"].bash_type, BashType::String);
+    assert_eq!(file.variables["REPLY"].bash_type, BashType::String);
+}
+
+#[test]
+fn test_select_body_is_checked() {
+    let file = analyze("select opt in a b; do\n  count=$opt #/ int\ndone");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Types do not match");
+}
+
+#[test]
+fn test_select_may_be_empty() {
+    let file = analyze("#/ int\nselect size in 1 2 3; do\n  break\ndone");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Types do not match");
+}
+
+#[test]
+fn test_for_variable_type() {
+    let file = analyze("for i in 1 2 3; do\n  total=$i #/ int\ndone");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["i"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_for_array_elements() {
+    let file = analyze("#/ array<int>\nsizes=(1 2)\nfor size in \"${sizes[@]}\"; do\n  :\ndone");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["size"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_for_positional_parameters() {
+    let file = analyze("for arg; do\n  echo \"$arg\"\ndone");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["arg"].bash_type, BashType::String);
+}
@@ -0,0 +1,68 @@
+use bashtyped::{codes, testing::analyze, BashType};
+
+#[test]
+fn test_annotation_after_multiline_array() {
+    let file = analyze(
+        r#"ports=(
+    8080
+    8443
+) #/ array<int>
+declare -a hosts=(
+    "$(hostname)"
+    localhost
+) #/ array<string>"#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["ports"].bash_type,
+        BashType::Array(Box::new(BashType::Integer))
+    );
+    assert_eq!(
+        file.variables["hosts"].bash_type,
+        BashType::Array(Box::new(BashType::String))
+    );
+}
+
+#[test]
+fn test_annotation_after_continued_command_substitution() {
+    let file = analyze("result=$(curl \\\n    -s example.com) #/ string\nport=\\\n8080 #/ int");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["result"].bash_type, BashType::String);
+    assert_eq!(file.variables["port"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_continued_value() {
+    let file = analyze("name=abc\\\ndef #/ string\nmsg=\"a\"\\\n\"b\" #/ string\ncount=1\\\n2\ntotal=$count #/ int");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["name"].bash_type, BashType::String);
+    assert_eq!(file.variables["msg"].bash_type, BashType::String);
+    assert_eq!(file.variables["total"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_continued_value_is_joined() {
+    let source = "port=80\\\nabc #/ int";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.code.as_deref(), Some(codes::TYPE_MISMATCH));
+    let inferred = error
+        .labels
+        .iter()
+        .find(|label| label.bash_type == Some(BashType::String))
+        .unwrap();
+    assert_eq!(inferred.range, 0..source.find(" #").unwrap());
+}
+
+#[test]
+fn test_indented_continuation_is_a_command() {
+    // The indentation separates the words, so `run` is a command with a prefix assignment
+    let file = analyze("mode=fast \\\n    run #/ string");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Annotation on a line without an assignment"
+    );
+    assert!(!file.variables.contains_key("mode"));
+}
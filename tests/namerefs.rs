@@ -0,0 +1,102 @@
+use bashtyped::{testing::analyze, BashType, LabelKind, Method};
+
+#[test]
+fn test_read_through_nameref() {
+    let file = analyze(
+        r#"count=1
+declare -n ref=count
+copy="$ref""#,
+    );
+    assert!(file.errors.is_empty());
+    assert!(!file.variables.contains_key("ref"));
+    assert_eq!(file.variables["copy"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_write_through_nameref() {
+    let file = analyze(
+        r#"local -n ref=target
+ref="text" #/ string
+copy="${target}""#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["target"].bash_type, BashType::String);
+    assert_eq!(file.variables["target"].method, Method::Declared);
+    assert_eq!(file.variables["copy"].bash_type, BashType::String);
+}
+
+#[test]
+fn test_write_through_nameref_conflicts_with_target() {
+    let file = analyze(
+        r#"target=1 #/ int
+declare -n ref=target
+ref="text" #/ string"#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Variable target defined with different type"
+    );
+}
+
+#[test]
+fn test_nameref_to_unknown_variable() {
+    let file = analyze(
+        r#"declare -n ref=missing
+copy="$ref""#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Unknown nameref target");
+    assert_eq!(file.errors[0].labels[0].kind, LabelKind::ParseErr);
+    assert_eq!(
+        file.errors[0].labels[0].text,
+        "ref refers to unknown variable missing"
+    );
+}
+
+#[test]
+fn test_dynamic_nameref() {
+    let file = analyze(
+        r#"declare -n ref="$1"
+copy="$ref""#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["copy"].bash_type, BashType::Any);
+}
+
+#[test]
+fn test_nameref_to_unknown_variable_in_arguments() {
+    let source = r#"declare -n ref=nothere
+echo $ref
+printf '%s\n' "${ref}" "${ref:-fallback}" "${!ref}""#;
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Unknown nameref target");
+    let labels = file.errors[0]
+        .labels
+        .iter()
+        .map(|label| (&source[label.range.clone()], label.text.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        [
+            ("ref", "ref refers to unknown variable nothere"),
+            ("ref", "Also used here")
+        ]
+    );
+    assert_eq!(
+        file.errors[0].labels[0].range.start,
+        source.find("$ref").unwrap() + 1
+    );
+
+    // Other variables read in arguments can come from the environment
+    let file = analyze("target=1\ndeclare -n ref=target\necho \"$ref\" \"$from_environment\"");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_nameref_in_value_reported_once() {
+    let file = analyze("declare -n ref=missing\ncopy=\"$ref\"");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].labels.len(), 1);
+}
@@ -0,0 +1,63 @@
+use bashtyped::{
+    testing::{analyze, analyze_with_config},
+    BashType, Config,
+};
+
+fn narrowing() -> Config {
+    let mut config = Config::default();
+    config.narrow_on_assignment = true;
+    config
+}
+
+fn union() -> BashType {
+    BashType::Or(Box::new(BashType::Integer), Box::new(BashType::String))
+}
+
+#[test]
+fn test_assignment_narrows() {
+    let source = "x=text #/ int | string\nx=5\ncount=$x #/ int";
+    let file = analyze_with_config(source, narrowing());
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["x"].bash_type, union());
+
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Types do not match");
+}
+
+#[test]
+fn test_later_assignment_widens_again() {
+    let file = analyze_with_config(
+        "x=text #/ int | string\nx=5\nx=other\ncount=$x #/ int",
+        narrowing(),
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Types do not match");
+}
+
+#[test]
+fn test_unknown_assignment_does_not_narrow() {
+    let file = analyze_with_config(
+        "x=5 #/ int | string\nx=$(date)\ncopy=$x #/ int",
+        narrowing(),
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Types do not match");
+}
+
+#[test]
+fn test_declared_type_still_enforced() {
+    let file = analyze_with_config("x=5 #/ int\nx=text", narrowing());
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Variable x defined with different type"
+    );
+}
+
+#[test]
+fn test_config_option() {
+    let config = Config::from_toml("narrow_on_assignment = true").unwrap();
+    assert!(config.narrow_on_assignment);
+    assert!(!Config::default().narrow_on_assignment);
+}
@@ -0,0 +1,80 @@
+use bashtyped::{codes, testing::analyze_with_config, BashType, Config, NumericStrings, Severity};
+
+fn numeric_strings(numeric_strings: NumericStrings) -> Config {
+    let mut config = Config::default();
+    config.numeric_strings = numeric_strings;
+    config
+}
+
+const SOURCE: &str = r#"port="8080" #/ int
+count=5
+count="6"
+seconds="30"
+sleep "$seconds"
+ids=(1 2)
+ids[2]="3""#;
+
+#[test]
+fn test_numeric_strings_forbidden() {
+    let file = analyze_with_config(SOURCE, numeric_strings(NumericStrings::Forbid));
+    let codes = file
+        .errors
+        .iter()
+        .map(|error| error.code.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        codes,
+        [
+            Some(codes::TYPE_MISMATCH),
+            Some(codes::REDEFINED_VARIABLE),
+            Some(codes::ARGUMENT_MISMATCH),
+            Some(codes::ELEMENT_MISMATCH),
+        ]
+    );
+}
+
+#[test]
+fn test_numeric_strings_hinted() {
+    let file = analyze_with_config(SOURCE, numeric_strings(NumericStrings::Hint));
+    assert_eq!(file.errors.len(), 4);
+    for error in &file.errors {
+        assert_eq!(error.message, "Quoted number used as an int");
+        assert_eq!(error.severity, Severity::Advice);
+    }
+    assert_eq!(file.errors[0].code.as_deref(), Some(codes::TYPE_MISMATCH));
+    assert_eq!(file.errors[0].labels[0].range, 5..11);
+    assert_eq!(file.variables["port"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["count"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_numeric_strings_allowed() {
+    let file = analyze_with_config(SOURCE, numeric_strings(NumericStrings::Allow));
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["ids"].bash_type,
+        BashType::Array(Box::new(BashType::Integer))
+    );
+}
+
+#[test]
+fn test_other_strings_still_mismatch() {
+    let file = analyze_with_config(
+        "port=\"80a\" #/ int\nname=web\nsleep \"$name\"",
+        numeric_strings(NumericStrings::Allow),
+    );
+    assert_eq!(file.errors.len(), 2);
+    assert_eq!(file.errors[0].code.as_deref(), Some(codes::TYPE_MISMATCH));
+    assert_eq!(
+        file.errors[1].code.as_deref(),
+        Some(codes::ARGUMENT_MISMATCH)
+    );
+}
+
+#[test]
+fn test_numeric_strings_config() {
+    let config = Config::from_toml("numeric_strings = \"hint\"").unwrap();
+    assert_eq!(config.numeric_strings, NumericStrings::Hint);
+    assert_eq!(Config::default().numeric_strings, NumericStrings::Forbid);
+    assert!(Config::from_toml("numeric_strings = \"sometimes\"").is_err());
+}
@@ -0,0 +1,171 @@
+use std::{collections::BTreeMap, fs};
+
+use bashtyped::{
+    output::{checkstyle, html, junit, stats, FileReport, TypeStats},
+    Diagnostic, FileInfo, Severity,
+};
+
+fn analyzed(source: &str) -> Vec<Diagnostic> {
+    let mut file = FileInfo::new(source);
+    file.parse_code();
+    file.errors
+}
+
+#[test]
+fn test_checkstyle() {
+    let source = "a=1\n  b=2 #/ bool";
+    let diagnostics = analyzed(source);
+    let xml = checkstyle(&[
+        FileReport {
+            path: "bad.sh",
+            source_code: source,
+            diagnostics: &diagnostics,
+            declarations: &BTreeMap::new(),
+        },
+        FileReport {
+            path: "good.sh",
+            source_code: "",
+            diagnostics: &[],
+            declarations: &BTreeMap::new(),
+        },
+    ]);
+    assert_eq!(
+        xml,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="bad.sh">
+    <error line="2" column="3" severity="error" message="Types do not match" source="bashtyped.BT0001"/>
+  </file>
+  <file name="good.sh">
+  </file>
+</checkstyle>
+"#
+    );
+}
+
+#[test]
+fn test_junit() {
+    let source = "a=1\nb=2 #/ bool";
+    let diagnostics = analyzed(source);
+    let xml = junit(&[
+        FileReport {
+            path: "bad.sh",
+            source_code: source,
+            diagnostics: &diagnostics,
+            declarations: &BTreeMap::new(),
+        },
+        FileReport {
+            path: "good.sh",
+            source_code: "",
+            diagnostics: &[],
+            declarations: &BTreeMap::new(),
+        },
+    ]);
+    assert_eq!(
+        xml,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="bashtyped" tests="2" failures="1">
+  <testsuite name="bad.sh" tests="1" failures="1">
+    <testcase name="bad.sh:2:1 BT0001" classname="bashtyped">
+      <failure type="error" message="Types do not match">bad.sh:2:1: Types do not match</failure>
+    </testcase>
+  </testsuite>
+  <testsuite name="good.sh" tests="1" failures="0">
+    <testcase name="good.sh" classname="bashtyped"/>
+  </testsuite>
+</testsuites>
+"#
+    );
+}
+
+#[test]
+fn test_xml_is_escaped() {
+    let diagnostics = [Diagnostic::new("Use of \"<&>\"", 0).with_severity(Severity::Warning)];
+    let report = FileReport {
+        path: "it's.sh",
+        source_code: "eval x",
+        diagnostics: &diagnostics,
+        declarations: &BTreeMap::new(),
+    };
+    let xml = checkstyle(std::slice::from_ref(&report));
+    assert!(xml.contains(r#"<file name="it&apos;s.sh">"#));
+    assert!(xml.contains(r#"message="Use of &quot;&lt;&amp;&gt;&quot;" source="bashtyped""#));
+    assert!(junit(&[report]).contains(r#"<failure type="warning""#));
+}
+
+#[test]
+fn test_html_report() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-html-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let source = "name=\"<world>\"\ncount=text #/ int\n";
+    let mut file = FileInfo::new(source);
+    file.parse_code();
+    let report = FileReport {
+        path: "script.sh",
+        source_code: source,
+        diagnostics: &file.errors,
+        declarations: &file.history,
+    };
+    html(&[report], &dir).unwrap();
+
+    let index = fs::read_to_string(dir.join("index.html")).unwrap();
+    assert!(index.contains("<tr><td>error</td><td>1</td></tr>"));
+    assert!(index.contains("<tr><td>BT0001</td><td>1</td><td>Types do not match</td></tr>"));
+    assert!(index.contains(r#"<a href="files/0.html">script.sh</a>"#));
+
+    let page = fs::read_to_string(dir.join("files").join("0.html")).unwrap();
+    assert!(page.contains(
+        r#"<span class="inferred" title="name: string (inferred)">name=&quot;&lt;world&gt;&quot;</span>"#
+    ));
+    assert!(page.contains(r#"class="error" title="BT0001: Types do not match"#));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_type_stats() {
+    let mut file = FileInfo::new("a=1 #/ int\nb=$(cmd)\nc=x\nd=$a\n");
+    file.parse_code();
+    let stats = TypeStats::new(&file.history);
+    assert_eq!(
+        stats,
+        TypeStats {
+            declared: 1,
+            inferred: 2,
+            unknown: 1
+        }
+    );
+    assert_eq!(stats.coverage(), Some(75.0));
+    assert_eq!(TypeStats::default().coverage(), None);
+}
+
+#[test]
+fn test_stats_report() {
+    let source = "a=1 #/ int\nb=$(cmd)\nc=x\nd=$a\ne=text #/ int\n";
+    let mut file = FileInfo::new(source);
+    file.parse_code();
+    let report = stats(&[
+        FileReport {
+            path: "scripts/build.sh",
+            source_code: source,
+            diagnostics: &file.errors,
+            declarations: &file.history,
+        },
+        FileReport {
+            path: "empty.sh",
+            source_code: "",
+            diagnostics: &[],
+            declarations: &BTreeMap::new(),
+        },
+    ]);
+    assert_eq!(
+        report,
+        "File              Declared  Inferred  Unknown  Coverage
+scripts/build.sh         1         2        1     75.0%
+empty.sh                 0         0        0         -
+Total                    1         2        1     75.0%
+
+Diagnostics:
+BT0001        1
+"
+    );
+}
@@ -0,0 +1,107 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use bashtyped::{codes, config::ConfigError, Config, FileInfo, OutputFormat, Severity};
+
+const CONFIG: &str = r#"
+format = "full"
+
+[rules.BT0003]
+severity = "warning"
+
+[profile.ci]
+pedantic = true
+format = "short"
+
+[profile.ci.rules.BT0002]
+allow = ["CI_*"]
+
+[profile.editor.rules.redefined-variable]
+severity = "advice"
+"#;
+
+#[test]
+fn test_no_profile() {
+    let config = Config::from_toml(CONFIG).unwrap();
+    assert!(!config.pedantic);
+    assert_eq!(config.format, Some(OutputFormat::Full));
+    assert!(!config.rules.contains_key(codes::UNKNOWN_VARIABLE));
+}
+
+#[test]
+fn test_ci_profile() {
+    let config = Config::from_toml_with_profile(CONFIG, Some("ci")).unwrap();
+    assert!(config.pedantic);
+    assert_eq!(config.format, Some(OutputFormat::Short));
+    assert_eq!(config.rules[codes::UNKNOWN_VARIABLE].allow, ["CI_*"]);
+    // Rules the profile doesn't mention keep their settings
+    let mut file = FileInfo::with_config("count=1\ncount=text", config);
+    file.parse_code();
+    assert_eq!(file.errors[0].severity, Severity::Warning);
+}
+
+#[test]
+fn test_editor_profile() {
+    let config = Config::from_toml_with_profile(CONFIG, Some("editor")).unwrap();
+    assert!(!config.pedantic);
+    let mut file = FileInfo::with_config("count=1\ncount=text", config);
+    file.parse_code();
+    assert_eq!(file.errors[0].severity, Severity::Advice);
+}
+
+#[test]
+fn test_invalid_profiles() {
+    assert!(matches!(
+        Config::from_toml_with_profile(CONFIG, Some("release")),
+        Err(ConfigError::UnknownProfile(_))
+    ));
+    assert!(matches!(
+        Config::from_toml_with_profile("[profile.ci]\nroot = true", Some("ci")),
+        Err(ConfigError::InvalidProfile(_))
+    ));
+    assert!(Config::from_toml("[profile.ci]\ncolour = \"red\"").is_err());
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("bashtyped-profiles-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_profile_flag() {
+    let dir = temp_dir("flag");
+    fs::write(dir.join("bashtyped.toml"), CONFIG).unwrap();
+    fs::write(dir.join("script.sh"), "count=1\ncount=text\n").unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+            .current_dir(&dir)
+            .args(["--no-cache", "--color", "never"])
+            .args(args)
+            .arg("script.sh")
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    // The short format from the profile prints a single line
+    assert_eq!(run(&["--profile", "ci"]).lines().count(), 1);
+    assert!(run(&[]).lines().count() > 1);
+    // `--format` wins over the profile
+    assert!(
+        run(&["--profile", "ci", "--format", "full"])
+            .lines()
+            .count()
+            > 1
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+        .current_dir(&dir)
+        .args(["--no-cache", "--profile", "release", "script.sh"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("unknown profile release"));
+}
@@ -0,0 +1,84 @@
+use bashtyped::{
+    codes,
+    config::RuleConfig,
+    fix::{apply_fixes, FixKind},
+    testing::analyze,
+    testing::analyze_with_config,
+    Config, Diagnostic, FileInfo, Severity,
+};
+
+fn pedantic() -> Config {
+    let mut config = Config::default();
+    config.pedantic = true;
+    config
+}
+
+fn unquoted<'a>(file: &'a FileInfo) -> Vec<&'a Diagnostic> {
+    file.errors
+        .iter()
+        .filter(|error| error.code.as_deref() == Some(codes::UNQUOTED_EXPANSION))
+        .collect()
+}
+
+#[test]
+fn test_unquoted_string_argument() {
+    let source = "dir=\"$(pwd)/build\"\nrm -r $dir/cache";
+    assert!(analyze(source).errors.is_empty());
+    let file = analyze_with_config(source, pedantic());
+    let errors = unquoted(&file);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Unquoted expansion of dir");
+    assert_eq!(errors[0].severity, Severity::Warning);
+    assert_eq!(errors[0].labels[0].range, 25..29);
+    let fix = &errors[0].fixes[0];
+    assert_eq!(fix.kind, FixKind::Quote);
+    assert_eq!(
+        apply_fixes(source, [fix]).0,
+        "dir=\"$(pwd)/build\"\nrm -r \"$dir\"/cache"
+    );
+}
+
+#[test]
+fn test_integers_and_safe_literals() {
+    let file = analyze_with_config(
+        "count=3\nname=build\nsleep $count\necho ${name}\ntotal=$((count + 1))\nhead -n $total",
+        pedantic(),
+    );
+    assert!(unquoted(&file).is_empty());
+}
+
+#[test]
+fn test_literals_with_spaces() {
+    let file = analyze_with_config(
+        "message=\"two words\"\npattern=\"*.sh\"\necho $message\nls $pattern",
+        pedantic(),
+    );
+    assert_eq!(unquoted(&file).len(), 2);
+}
+
+#[test]
+fn test_test_commands() {
+    let file = analyze_with_config(
+        "name=\"$(pwd)/build\"\n[[ $name == x ]] && echo same\n[ -n $name ] && echo set\n\
+         [ \"$name\" = $name ] && echo equal",
+        pedantic(),
+    );
+    let errors = unquoted(&file);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].labels[0].range, 55..60);
+}
+
+#[test]
+fn test_enabled_by_rule() {
+    let mut config = Config::default();
+    config.rules.insert(
+        codes::UNQUOTED_EXPANSION,
+        RuleConfig {
+            enabled: Some(true),
+            ..RuleConfig::default()
+        },
+    );
+    let mut file = FileInfo::with_config("name=\"$(pwd)/build\"\necho $name", config);
+    file.parse_code();
+    assert_eq!(file.errors.len(), 1);
+}
@@ -0,0 +1,181 @@
+use bashtyped::{codes, fix::apply_fixes, testing::analyze, Severity};
+
+#[test]
+fn test_unset_key() {
+    let file = analyze(
+        r#"declare -A cfg #/ { host: string, port: int }
+cfg[host]=localhost
+host="${cfg[host]}"
+port="${cfg[port]}""#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "Key port of cfg is never set");
+    assert_eq!(error.code.as_deref(), Some(codes::UNSET_KEY));
+    assert_eq!(error.severity, Severity::Warning);
+}
+
+#[test]
+fn test_keys_set_by_compound_assignment() {
+    let file = analyze(
+        r#"declare -A cfg=([host]=localhost) #/ { host: string, port: int, user: string }
+cfg+=([port]=80)
+host="${cfg[host]}"
+port="${cfg[port]}"
+user="${cfg[user]}""#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Key user of cfg is never set");
+}
+
+#[test]
+fn test_key_set_later() {
+    let file = analyze(
+        r#"declare -A cfg #/ { host: string }
+show() {
+    echo "${cfg[host]}"
+}
+cfg[host]=localhost
+show"#,
+    );
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_read_with_default() {
+    let file = analyze(
+        r#"declare -A cfg #/ { host: string, port: int }
+port="${cfg[port]:-80}""#,
+    );
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_runtime_key_sets_any_key() {
+    let file = analyze(
+        r#"declare -A cfg #/ { host: string, user: string }
+key="$(pwd)/x"
+cfg[$key]=value
+echo "${cfg[user]}""#,
+    );
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_sourced_record() {
+    let file = analyze(
+        r#"source ./config.sh
+declare -A cfg #/ { host: string }
+echo "${cfg[host]}""#,
+    );
+    assert!(file
+        .errors
+        .iter()
+        .all(|error| error.code.as_deref() != Some(codes::UNSET_KEY)));
+}
+
+#[test]
+fn test_read_typo() {
+    let source = r#"declare -A cfg=([host]=localhost [port]=80) #/ { host: string, port: int }
+value="${cfg[hosts]}""#;
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "Key hosts is not declared for cfg");
+    assert_eq!(error.code.as_deref(), Some(codes::INVALID_SUBSCRIPT));
+    assert_eq!(error.help.as_deref(), Some("Did you mean host?"));
+    assert_eq!(
+        apply_fixes(source, [&error.fixes[0]]).0,
+        r#"declare -A cfg=([host]=localhost [port]=80) #/ { host: string, port: int }
+value="${cfg[host]}""#
+    );
+}
+
+#[test]
+fn test_write_typo() {
+    let source = r#"declare -A cfg #/ { host: string, port: int }
+cfg[prt]=80"#;
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "Key prt is not declared for cfg");
+    assert_eq!(error.help.as_deref(), Some("Did you mean port?"));
+    assert_eq!(
+        apply_fixes(source, [&error.fixes[0]]).0,
+        "declare -A cfg #/ { host: string, port: int }\ncfg[port]=80"
+    );
+}
+
+#[test]
+fn test_undeclared_key_without_suggestion() {
+    let file = analyze(
+        r#"declare -A cfg=([host]=localhost) #/ { host: string }
+password="${cfg[password]}""#,
+    );
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Key password is not declared for cfg"
+    );
+    assert!(file.errors[0].help.is_none());
+    assert!(file.errors[0].fixes.is_empty());
+}
+
+#[test]
+fn test_read_typo_in_arguments() {
+    let source = r#"declare -A cfg #/ { host: string, port: int }
+cfg[host]=localhost
+echo "${cfg[hostt]}" "${cfg[port]:-80}"
+value="${cfg[hostt]}""#;
+    let file = analyze(source);
+    let messages = file
+        .errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "Key hostt is not declared for cfg",
+            "Key hostt is not declared for cfg"
+        ]
+    );
+    assert_eq!(file.errors[0].help.as_deref(), Some("Did you mean host?"));
+    assert_eq!(
+        apply_fixes(source, [&file.errors[0].fixes[0]]).0,
+        source.replacen("hostt", "host", 1)
+    );
+}
+
+#[test]
+fn test_unset_key_suggests_set_key() {
+    let source = r#"declare -A cfg #/ { user: string, users: string }
+cfg[users]="alice bob"
+echo "${cfg[user]}""#;
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "Key user of cfg is never set");
+    assert_eq!(error.help.as_deref(), Some("Did you mean users?"));
+    assert_eq!(
+        apply_fixes(source, [&error.fixes[0]]).0,
+        source.replace("${cfg[user]}", "${cfg[users]}")
+    );
+}
+
+#[test]
+fn test_swapped_letters_in_key() {
+    let source = r#"declare -A cfg #/ { host: string, port: int }
+cfg[prot]=80
+value="${cfg[hots]}""#;
+    let file = analyze(source);
+    let help = file
+        .errors
+        .iter()
+        .map(|error| error.help.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        help,
+        [Some("Did you mean port?"), Some("Did you mean host?")]
+    );
+}
@@ -0,0 +1,96 @@
+use bashtyped::{codes, testing::analyze, FileInfo};
+
+fn return_mismatches(file: &FileInfo) -> usize {
+    file.errors
+        .iter()
+        .filter(|error| error.code.as_deref() == Some(codes::RETURN_MISMATCH))
+        .count()
+}
+
+#[test]
+fn test_ending_with_boolean_function() {
+    let file = analyze(
+        r#"check_ready() {
+    [[ -f ready ]]
+}
+#[returns bool]
+is_ready() {
+    check_ready
+}"#,
+    );
+    assert_eq!(return_mismatches(&file), 0);
+}
+
+#[test]
+fn test_mutually_recursive_functions() {
+    let file = analyze(
+        r#"#[returns bool]
+is_even() {
+    if [[ $1 == 0 ]]; then
+        return 0
+    fi
+    is_odd "$(($1 - 1))"
+}
+is_odd() {
+    if [[ $1 == 0 ]]; then
+        return 1
+    fi
+    is_even "$(($1 - 1))"
+}"#,
+    );
+    assert_eq!(return_mismatches(&file), 0);
+}
+
+#[test]
+fn test_unannotated_cycle() {
+    let file = analyze(
+        r#"ping() {
+    [[ -n $1 ]] || return 1
+    pong
+}
+pong() {
+    ping
+}
+#[returns bool]
+check() {
+    pong
+}"#,
+    );
+    assert_eq!(return_mismatches(&file), 0);
+}
+
+#[test]
+fn test_recursion_without_status() {
+    let file = analyze(
+        r#"forever() {
+    forever
+}
+#[returns bool]
+check() {
+    forever
+}"#,
+    );
+    assert_eq!(return_mismatches(&file), 1);
+}
+
+#[test]
+fn test_ending_with_integer_status() {
+    let file = analyze(
+        r#"fail() {
+    return 3
+}
+retry() {
+    sleep 1
+    fail
+}
+#[returns bool]
+check() {
+    retry
+}"#,
+    );
+    assert_eq!(return_mismatches(&file), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Function may end with a status that isn't a bool"
+    );
+}
@@ -0,0 +1,74 @@
+use bashtyped::{testing::analyze, BashType, Method, TypeDeclaration};
+
+#[test]
+fn test_reassignment_updates_range() {
+    let file = analyze("a=\"x\"\na=\"y\"");
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["a"],
+        TypeDeclaration {
+            bash_type: BashType::String,
+            method: Method::Inferred,
+            range: 6..11,
+            node_kind: String::from("variable_assignment"),
+            annotation: None,
+            file: None,
+        }
+    );
+    assert_eq!(file.previous_declarations("a").len(), 1);
+    assert_eq!(file.previous_declarations("a")[0].range, 0..5);
+}
+
+#[test]
+fn test_conflicting_declaration_is_recorded() {
+    let file = analyze("a=1\na=\"text\" #/ string");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Variable a defined with different type"
+    );
+    assert_eq!(file.variables["a"].bash_type, BashType::String);
+    assert_eq!(file.variables["a"].method, Method::Declared);
+}
+
+#[test]
+fn test_broadening_declaration() {
+    let file = analyze("a=1\na=text #/ int | string");
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["a"].bash_type,
+        BashType::Or(Box::new(BashType::Integer), Box::new(BashType::String))
+    );
+}
+
+#[test]
+fn test_assignment_keeps_declared_type() {
+    let file = analyze("a=1 #/ int | string\na=2\na=text");
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["a"].bash_type,
+        BashType::Or(Box::new(BashType::Integer), Box::new(BashType::String))
+    );
+    assert_eq!(file.variables["a"].range, 0..19);
+    assert_eq!(file.history["a"].len(), 3);
+    assert_eq!(file.history["a"][2].bash_type, BashType::String);
+}
+
+#[test]
+fn test_assignment_outside_declared_type() {
+    let file = analyze("a=1 #/ int\na=text");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Variable a defined with different type"
+    );
+    assert_eq!(file.variables["a"].bash_type, BashType::String);
+}
+
+#[test]
+fn test_history_is_reset_by_unset() {
+    let file = analyze("a=1\nunset a\na=text");
+    assert!(file.errors.is_empty());
+    assert!(file.previous_declarations("a").is_empty());
+    assert!(file.previous_declarations("missing").is_empty());
+}
@@ -0,0 +1,99 @@
+use bashtyped::{codes, testing::analyze, BashType, FileInfo};
+
+fn messages<'a>(file: &'a FileInfo) -> Vec<&'a str> {
+    file.errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect()
+}
+
+#[test]
+fn test_process_substitution_unknown_variable() {
+    let source = "diff <(sort \"$left\") <(sort \"$right\")\nleft=a.txt";
+    let file = analyze(source);
+    assert_eq!(messages(&file), ["Unknown variable", "Unknown variable"]);
+    let range = file.errors[1].labels[0].range.clone();
+    assert_eq!(&source[range], "right");
+}
+
+#[test]
+fn test_process_substitution_known_variables() {
+    let file = analyze("log=out.log\nmake > >(tee \"$log\")");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_nested_process_substitution_reported_once() {
+    let file = analyze("cat <(cat <(echo \"$missing\"))");
+    assert_eq!(messages(&file), ["Unknown variable"]);
+}
+
+#[test]
+fn test_redirect_target_unknown_variable() {
+    let source = "echo done > \"$output\"";
+    let file = analyze(source);
+    assert_eq!(messages(&file), ["Unknown variable"]);
+    let range = file.errors[0].labels[0].range.clone();
+    assert_eq!(&source[range], "output");
+}
+
+#[test]
+fn test_herestring_unknown_variable() {
+    let file = analyze("read -r first <<< \"$line\"");
+    assert_eq!(messages(&file), ["Unknown variable"]);
+}
+
+#[test]
+fn test_redirect_target_array() {
+    let file = analyze("files=(a.txt b.txt)\necho done > ${files[@]}");
+    assert_eq!(messages(&file), ["Redirection target is not a path"]);
+}
+
+#[test]
+fn test_redirect_target_array_element() {
+    let file = analyze("files=(a.txt b.txt)\nfd=2\necho done > ${files[0]} 2>&$fd");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_trap_redirect_reported_once() {
+    let file = analyze("trap 'echo bye > \"$log\"' EXIT");
+    assert_eq!(messages(&file), ["Unknown variable"]);
+}
+
+#[test]
+fn test_descriptor_variables() {
+    let file = analyze(
+        r#"exec {log}>>out.log
+echo started >&"$log"
+copy=$log
+exec {input}<in.txt {output}>out.txt
+exec {log}>&-"#,
+    );
+    assert!(file.errors.is_empty());
+    for name in ["log", "copy", "input", "output"] {
+        assert_eq!(file.variables[name].bash_type, BashType::Integer);
+    }
+}
+
+#[test]
+fn test_closing_unknown_descriptor() {
+    let file = analyze("exec {log}>&-");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].code.as_deref(),
+        Some(codes::UNKNOWN_VARIABLE)
+    );
+}
+
+#[test]
+fn test_string_descriptor_variable() {
+    let file = analyze("name=\"$(pwd)/out\"\nexec {name}>file\nexec {name}>&-");
+    assert_eq!(file.errors.len(), 2);
+    for error in &file.errors {
+        assert_eq!(error.message, "File descriptor variable name is not an int");
+        assert_eq!(error.code.as_deref(), Some(codes::REDIRECT_TARGET));
+    }
+    assert_eq!(file.errors[0].labels[1].range, 23..29);
+    assert_eq!(file.variables["name"].bash_type, BashType::String);
+}
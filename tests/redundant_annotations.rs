@@ -0,0 +1,54 @@
+use bashtyped::{codes, fix::apply_fixes, Config, FileInfo, Severity};
+
+fn pedantic(source: &str) -> FileInfo<'_> {
+    let mut config = Config::default();
+    config.pedantic = true;
+    let mut file = FileInfo::with_config(source, config);
+    file.parse_code();
+    file
+}
+
+/// Applies the fixes of every redundant annotation
+fn fixed(source: &str) -> String {
+    let file = pedantic(source);
+    apply_fixes(
+        source,
+        file.errors
+            .iter()
+            .filter(|error| error.code.as_deref() == Some(codes::REDUNDANT_ANNOTATION))
+            .flat_map(|error| &error.fixes),
+    )
+    .0
+}
+
+#[test]
+fn test_redundant_annotation() {
+    let file = pedantic("retries=3 #/ int");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].code.as_deref(),
+        Some(codes::REDUNDANT_ANNOTATION)
+    );
+    assert_eq!(file.errors[0].severity, Severity::Advice);
+    assert_eq!(file.errors[0].labels[0].range, 13..16);
+
+    assert_eq!(fixed("retries=3 #/ int\necho done"), "retries=3\necho done");
+    assert_eq!(
+        fixed("f() {\n  #/ array<string>\n  local names=(a b)\n}"),
+        "f() {\n  local names=(a b)\n}"
+    );
+}
+
+#[test]
+fn test_needed_annotations() {
+    assert!(pedantic("count=3 #/ int | string\ndeclare -i total=1")
+        .errors
+        .is_empty());
+}
+
+#[test]
+fn test_only_pedantic() {
+    let mut file = FileInfo::new("retries=3 #/ int");
+    file.parse_code();
+    assert!(file.errors.is_empty());
+}
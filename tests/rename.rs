@@ -0,0 +1,156 @@
+use bashtyped::{fix::TextEdit, rename::RenameError, testing::analyze, FileInfo};
+
+/// Renames the variable at the `^` in `source` and applies the edits
+fn renamed(source: &str, new_name: &str) -> Result<String, RenameError> {
+    let offset = source.find('^').unwrap();
+    let source = source.replacen('^', "", 1);
+    let edits = analyze(&source).rename(offset, new_name)?;
+    let mut renamed = source.clone();
+    for TextEdit { range, new_text } in edits.into_iter().rev() {
+        renamed.replace_range(range, &new_text);
+    }
+    Ok(renamed)
+}
+
+#[test]
+fn test_rename_everywhere() {
+    assert_eq!(
+        renamed(
+            "co^unt=1 #/ int\necho \"$count\" ${count:-0} $((count + 1))\nfor count in 1 2; do :; done",
+            "total"
+        )
+        .unwrap(),
+        "total=1 #/ int\necho \"$total\" ${total:-0} $((total + 1))\nfor total in 1 2; do :; done"
+    );
+    assert_eq!(
+        renamed(
+            "items=(a b)\nitems[2]=c\necho \"${#items[@]}\" $item^s",
+            "names"
+        )
+        .unwrap(),
+        "names=(a b)\nnames[2]=c\necho \"${#names[@]}\" $names"
+    );
+}
+
+#[test]
+fn test_rename_in_annotations() {
+    assert_eq!(
+        renamed(
+            "#[set_var( port, int)]\nsetup() { :; }\necho $por^t",
+            "listen_port"
+        )
+        .unwrap(),
+        "#[set_var( listen_port, int)]\nsetup() { :; }\necho $listen_port"
+    );
+    assert_eq!(
+        renamed("#[set_var(po^rt, int)]\nsetup() { :; }", "p").unwrap(),
+        "#[set_var(p, int)]\nsetup() { :; }"
+    );
+}
+
+#[test]
+fn test_rename_errors() {
+    assert_eq!(
+        renamed("a=1\nb^=2", "a"),
+        Err(RenameError::Collision("a".to_owned()))
+    );
+    assert_eq!(
+        renamed("a=1\n#[set_var(b, int)]\nsetup() { :; }\necho $^a", "b"),
+        Err(RenameError::Collision("b".to_owned()))
+    );
+    assert_eq!(
+        renamed("a=1\necho $^a", "2fast"),
+        Err(RenameError::InvalidName("2fast".to_owned()))
+    );
+    assert_eq!(renamed("a=1\nec^ho $a", "b"), Err(RenameError::NoVariable));
+}
+
+#[test]
+fn test_occurrences() {
+    let mut file = FileInfo::new("a=1\necho $a ${a}\nab=2");
+    assert_eq!(file.occurrences("a"), [0..1, 10..11, 14..15]);
+}
+
+#[test]
+fn test_rename_read_names() {
+    assert_eq!(
+        renamed(
+            "while read -r line; do echo \"$li^ne\"; done\nprintf -v line %s x",
+            "row"
+        )
+        .unwrap(),
+        "while read -r row; do echo \"$row\"; done\nprintf -v row %s x"
+    );
+    assert_eq!(
+        renamed("read -r -p 'Name: ' first 'last'\necho $la^st", "surname").unwrap(),
+        "read -r -p 'Name: ' first 'surname'\necho $surname"
+    );
+    assert_eq!(
+        renamed("read -ra words\necho \"${wor^ds[@]}\"", "parts").unwrap(),
+        "read -ra parts\necho \"${parts[@]}\""
+    );
+}
+
+#[test]
+fn test_rename_printf_name() {
+    assert_eq!(
+        renamed("printf -v \"stamp\" '%(%s)T'\necho $st^amp", "now").unwrap(),
+        "printf -v \"now\" '%(%s)T'\necho $now"
+    );
+}
+
+#[test]
+fn test_rename_wait_name() {
+    assert_eq!(
+        renamed("sleep 1 &\nwait -n -p finished\necho $fini^shed", "pid").unwrap(),
+        "sleep 1 &\nwait -n -p pid\necho $pid"
+    );
+}
+
+#[test]
+fn test_rename_mapfile_name() {
+    assert_eq!(
+        renamed("mapfile -t lines < file\necho \"${li^nes[0]}\"", "rows").unwrap(),
+        "mapfile -t rows < file\necho \"${rows[0]}\""
+    );
+}
+
+#[test]
+fn test_rename_nameref_target() {
+    assert_eq!(
+        renamed("count=1\ndeclare -n ref=count\necho $co^unt", "total").unwrap(),
+        "total=1\ndeclare -n ref=total\necho $total"
+    );
+}
+
+#[test]
+fn test_rename_unset_name() {
+    assert_eq!(
+        renamed("items=(a b)\nunset items 'items[1]'\necho $ite^ms", "names").unwrap(),
+        "names=(a b)\nunset names 'names[1]'\necho $names"
+    );
+    assert_eq!(
+        renamed("log=1\nlog() { :; }\nunset -f log\necho $lo^g", "level").unwrap(),
+        "level=1\nlog() { :; }\nunset -f log\necho $level"
+    );
+}
+
+#[test]
+fn test_rename_bare_declarations() {
+    assert_eq!(
+        renamed(
+            "f() {\n  local result\n  result=1\n}\nexport result\necho $res^ult",
+            "out"
+        )
+        .unwrap(),
+        "f() {\n  local out\n  out=1\n}\nexport out\necho $out"
+    );
+}
+
+#[test]
+fn test_rename_unrewritable_name() {
+    assert_eq!(
+        renamed("read -r li'ne'\necho $li^ne", "row"),
+        Err(RenameError::Unrewritable("line".to_owned()))
+    );
+}
@@ -0,0 +1,69 @@
+use bashtyped::{codes, Config, FileInfo, Severity};
+
+fn analyzed<'a>(source: &'a str, config: &str) -> FileInfo<'a> {
+    let mut file = FileInfo::with_config(source, Config::from_toml(config).unwrap());
+    file.parse_code();
+    file
+}
+
+fn codes(file: &FileInfo) -> Vec<String> {
+    file.errors
+        .iter()
+        .filter_map(|error| error.code.clone())
+        .collect()
+}
+
+const REDEFINED: &str = "count=1\ncount=text";
+
+#[test]
+fn test_disable_rule() {
+    assert_eq!(codes(&analyzed(REDEFINED, "")), [codes::REDEFINED_VARIABLE]);
+    assert!(analyzed(REDEFINED, "[rules.BT0003]\nenabled = false")
+        .errors
+        .is_empty());
+    assert!(
+        analyzed(REDEFINED, "[rules.redefined-variable]\nenabled = false")
+            .errors
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_rule_severity() {
+    let file = analyzed(REDEFINED, "[rules.bt0003]\nseverity = \"warning\"");
+    assert_eq!(file.errors[0].severity, Severity::Warning);
+}
+
+#[test]
+fn test_enable_pedantic_rule() {
+    let source = "count=3\nshown=${count^^}";
+    assert!(analyzed(source, "").errors.is_empty());
+    assert_eq!(
+        codes(&analyzed(
+            source,
+            "[rules.string-operation]\nenabled = true"
+        )),
+        [codes::STRING_OPERATION]
+    );
+    let pedantic = "pedantic = true\n[rules.BT0017]\nenabled = false";
+    assert!(analyzed(source, pedantic).errors.is_empty());
+}
+
+#[test]
+fn test_allowed_variables() {
+    let source = "token=$CI_TOKEN\nother=$MISSING";
+    assert_eq!(
+        codes(&analyzed(source, "")),
+        [codes::UNKNOWN_VARIABLE, codes::UNKNOWN_VARIABLE]
+    );
+    let file = analyzed(source, "[rules.BT0002]\nallow = [\"CI_*\"]");
+    assert_eq!(codes(&file), [codes::UNKNOWN_VARIABLE]);
+    assert_eq!(file.errors[0].offset, source.find("other").unwrap());
+}
+
+#[test]
+fn test_invalid_rules() {
+    assert!(Config::from_toml("[rules.BT9999]\nenabled = false").is_err());
+    assert!(Config::from_toml("[rules.BT0001]\nmax = 3").is_err());
+    assert!(Config::from_toml("[rules.BT0001]\nseverity = \"fatal\"").is_err());
+}
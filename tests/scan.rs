@@ -0,0 +1,121 @@
+use std::{fs, path::Path};
+
+use bashtyped::{
+    scan::{find_scripts, is_script, IgnoreRules},
+    Config, FileInfo,
+};
+
+#[test]
+fn test_ignore_patterns() {
+    let mut rules = IgnoreRules::new(&["vendor/", "*.generated.sh"]);
+    rules.add_file(
+        "# third party completions\n\
+         /completions/**/*.bash\n\
+         !completions/**/ours.bash\n\
+         build?.sh\n\
+         [ab]c.sh\n",
+    );
+    let ignored = |path: &str, is_dir| rules.is_ignored(Path::new(path), is_dir);
+    assert!(ignored("vendor", true));
+    assert!(ignored("./lib/vendor", true));
+    assert!(!ignored("vendor", false));
+    assert!(ignored("scripts/api.generated.sh", false));
+    assert!(ignored("completions/git.bash", false));
+    assert!(ignored("completions/extra/docker.bash", false));
+    assert!(!ignored("completions/extra/ours.bash", false));
+    assert!(!ignored("lib/completions/git.bash", false));
+    assert!(ignored("build1.sh", false));
+    assert!(!ignored("build10.sh", false));
+    assert!(ignored("bc.sh", false));
+    assert!(!ignored("cc.sh", false));
+    assert!(!ignored("main.sh", false));
+}
+
+#[test]
+fn test_find_scripts() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-scan-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("vendor")).unwrap();
+    fs::create_dir_all(dir.join("bin")).unwrap();
+    fs::write(dir.join("main.sh"), "echo hi\n").unwrap();
+    fs::write(dir.join("api.generated.sh"), "echo hi\n").unwrap();
+    fs::write(dir.join("notes.txt"), "echo hi\n").unwrap();
+    fs::write(dir.join("vendor").join("lib.sh"), "echo hi\n").unwrap();
+    fs::write(
+        dir.join("bin").join("deploy"),
+        "#!/usr/bin/env bash\necho hi\n",
+    )
+    .unwrap();
+    fs::write(dir.join("bin").join("tool"), "#!/usr/bin/python3\n").unwrap();
+
+    let rules = IgnoreRules::new(&["vendor/", "*.generated.sh"]);
+    let explicit = dir.join("vendor").join("lib.sh");
+    let scripts = find_scripts(&[dir.clone(), explicit.clone()], &rules, false).unwrap();
+    assert_eq!(
+        scripts,
+        [
+            dir.join("bin").join("deploy"),
+            dir.join("main.sh"),
+            explicit
+        ]
+    );
+    assert!(!is_script(&dir.join("bin").join("tool")));
+
+    fs::write(dir.join("image.sh"), b"\x89PNG\r\n\x1a\n\0\0").unwrap();
+    assert!(!is_script(&dir.join("image.sh")));
+    let everything = find_scripts(std::slice::from_ref(&dir), &rules, true).unwrap();
+    assert_eq!(
+        everything,
+        [
+            dir.join("bin").join("deploy"),
+            dir.join("bin").join("tool"),
+            dir.join("main.sh"),
+            dir.join("notes.txt"),
+        ]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_ignore_config() {
+    let config = Config::from_toml(r#"ignore = ["vendor/", "*.generated.sh"]"#).unwrap();
+    assert_eq!(config.ignore, ["vendor/", "*.generated.sh"]);
+}
+
+#[test]
+fn test_shebangs() {
+    let dir = std::env::temp_dir().join(format!("bashtyped-shebang-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (name, shebang, expected) in [
+        ("env", "#!/usr/bin/env bash", true),
+        ("env_flags", "#!/usr/bin/env -S bash -e", true),
+        ("bin", "#!/bin/bash", true),
+        ("posix", "#!/bin/sh", true),
+        ("bats", "#!/usr/bin/env bats", true),
+        ("python", "#!/usr/bin/env python3", false),
+        ("none", "echo hi", false),
+        // The shebang decides over the extension, which only counts without one
+        ("deploy.prod", "#!/usr/bin/env bash", true),
+        ("setup.py", "#!/bin/bash", true),
+        ("tool.sh", "#!/usr/bin/env python3", false),
+        ("lib.sh", "echo hi", true),
+    ] {
+        let path = dir.join(name);
+        fs::write(&path, format!("{shebang}\necho hi\n")).unwrap();
+        assert_eq!(is_script(&path), expected, "{shebang}");
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_file_opt_out() {
+    let mut file = FileInfo::new("#!/bin/bash\n# generated\n# bashtyped: off\na=1 #/ bool\n");
+    file.parse_code();
+    assert!(file.errors.is_empty());
+    assert!(file.variables.is_empty());
+
+    let mut file = FileInfo::new("a=1 #/ bool\n# bashtyped: off\n");
+    file.parse_code();
+    assert_eq!(file.errors.len(), 1);
+}
@@ -0,0 +1,48 @@
+use bashtyped::FileInfo;
+
+const FUNCTIONS: &str =
+    "#[params target: string, retries: int]\n#[returns bool]\ndeploy() { :; }\nbuild() { :; }\n";
+
+/// The signature help at the `^` in `call`, which is written after [`FUNCTIONS`]
+fn help(call: &str) -> Option<(String, Option<usize>)> {
+    let source = format!("{FUNCTIONS}{call}");
+    let offset = source.find('^').unwrap();
+    let source = source.replacen('^', "", 1);
+    let mut file = FileInfo::new(&source);
+    file.parse_code();
+    file.signature_help(offset)
+        .map(|help| (help.label, help.active_parameter))
+}
+
+#[test]
+fn test_signature_label() {
+    let source = format!("{FUNCTIONS}deploy ");
+    let mut file = FileInfo::new(&source);
+    file.parse_code();
+    let help = file.signature_help(source.len()).unwrap();
+    assert_eq!(help.label, "deploy(target: string, retries: int) -> bool");
+    let parameters = help
+        .parameters
+        .iter()
+        .map(|range| &help.label[range.clone()])
+        .collect::<Vec<_>>();
+    assert_eq!(parameters, ["target: string", "retries: int"]);
+}
+
+#[test]
+fn test_active_parameter() {
+    assert_eq!(help("deploy ^").unwrap().1, Some(0));
+    assert_eq!(help("deploy pro^d").unwrap().1, Some(0));
+    assert_eq!(help("deploy prod^").unwrap().1, Some(0));
+    assert_eq!(help("deploy prod ^").unwrap().1, Some(1));
+    assert_eq!(help("deploy prod 3 ^").unwrap().1, None);
+    assert_eq!(help("x=$(deploy prod ^)").unwrap().1, Some(1));
+}
+
+#[test]
+fn test_no_signature() {
+    assert_eq!(help("depl^oy prod"), None);
+    assert_eq!(help("build ^"), None);
+    assert_eq!(help("echo ^"), None);
+    assert_eq!(help("deploy prod\n^"), None);
+}
@@ -0,0 +1,75 @@
+use bashtyped::{FileInfo, Method, SourceReference};
+
+fn sources(source: &str) -> Vec<SourceReference> {
+    let mut file = FileInfo::new(source);
+    file.parse_code();
+    assert!(file.errors.is_empty());
+    file.sources
+}
+
+#[test]
+fn test_static_sources() {
+    assert_eq!(
+        sources(
+            r#"source lib/paths.sh
+. "./colors.sh"
+source 'with space.sh'"#
+        ),
+        vec![
+            SourceReference {
+                path: Some(String::from("lib/paths.sh")),
+                range: 0..19,
+            },
+            SourceReference {
+                path: Some(String::from("./colors.sh")),
+                range: 20..35,
+            },
+            SourceReference {
+                path: Some(String::from("with space.sh")),
+                range: 36..58,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_dynamic_source() {
+    assert_eq!(
+        sources(r#"source "$DIR/paths.sh""#),
+        vec![SourceReference {
+            path: None,
+            range: 0..22,
+        }]
+    );
+}
+
+#[test]
+fn test_shellcheck_source_hint() {
+    assert_eq!(
+        sources(
+            r#"# shellcheck disable=SC1091 source=lib/paths.sh
+source "$DIR/paths.sh"
+# shellcheck source=/dev/null
+source "$HOME/.profile""#
+        ),
+        vec![SourceReference {
+            path: Some(String::from("lib/paths.sh")),
+            range: 48..70,
+        }]
+    );
+}
+
+#[test]
+fn test_shellcheck_directives_are_not_annotations() {
+    let mut file = FileInfo::new(
+        r#"#/ int
+# shellcheck disable=SC2034
+count=1
+# shellcheck disable=SC2154
+name="x""#,
+    );
+    file.parse_code();
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["count"].method, Method::Declared);
+    assert_eq!(file.variables["name"].method, Method::Inferred);
+}
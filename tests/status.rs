@@ -0,0 +1,49 @@
+use bashtyped::{testing::analyze, BashType, Severity};
+
+#[test]
+fn test_status_is_int() {
+    let file = analyze("grep -q foo file\nrc=$?\nbraced=${?}");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["rc"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["braced"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_status_before_any_command() {
+    let file = analyze(
+        r#"rc=$?
+check() {
+    # The caller's status isn't this function's business
+    echo "$?"
+}"#,
+    );
+    assert_eq!(file.errors.len(), 2);
+    assert!(file
+        .errors
+        .iter()
+        .all(|error| error.message == "$? read before any command ran"
+            && error.severity == Severity::Warning));
+}
+
+#[test]
+fn test_status_after_command() {
+    let file = analyze(
+        r#"run() {
+    make || echo "failed with $?"
+    if grep -q foo file; then
+        echo "$?"
+    fi
+    while read -r line; do
+        echo "$?"
+    done < file
+}"#,
+    );
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_status_compared_with_string() {
+    let file = analyze("true\n[[ $? == \"ok\" ]]\n[ \"$?\" -eq 0 ]\n[[ 1 != $? ]]");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Exit status compared with a string");
+}
@@ -0,0 +1,133 @@
+use std::path::Path;
+
+use bashtyped::{
+    symbols::{SymbolKind, Symbols, Workspace},
+    testing::analyze,
+    Method,
+};
+
+const SOURCE: &str =
+    "count=1 #/ int\ncount=2\nname=x\n#[returns bool]\nready() {\n  return 0\n}\nbuild() { :; }\n";
+
+#[test]
+fn test_symbols() {
+    let file = analyze(SOURCE);
+    let symbols = Symbols::new(&file);
+    let names = symbols
+        .variables
+        .iter()
+        .map(|variable| variable.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["count", "name"]);
+
+    let count = &symbols.variables[0];
+    assert_eq!(count.bash_type, "int");
+    assert_eq!(count.method, Method::Declared);
+    assert_eq!(count.declarations.len(), 2);
+    assert_eq!(count.declarations[1].method, Method::Inferred);
+    assert_eq!(count.declarations[1].span.line, 2);
+
+    let functions = symbols
+        .functions
+        .iter()
+        .map(|function| (function.name.as_str(), function.returns.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(functions, [("build", None), ("ready", Some("bool"))]);
+    assert_eq!(symbols.functions[1].span.line, 5);
+}
+
+#[test]
+fn test_symbols_text() {
+    assert_eq!(
+        Symbols::new(&analyze(SOURCE)).to_string(),
+        "Variables:
+  count: int (declared) at 1:1
+    declared int at 1:1
+    inferred int at 2:1
+  name: string (inferred) at 3:1
+    inferred string at 3:1
+Functions:
+  build at 8:1
+  ready at 5:1, returns bool
+"
+    );
+}
+
+#[test]
+fn test_redefined_function() {
+    let file = analyze("greet() { echo hi; }\ngreet() {\n  echo hello\n}");
+    assert_eq!(file.functions.len(), 1);
+    assert_eq!(file.functions["greet"].range.start, 21);
+}
+
+#[test]
+fn test_document_symbols() {
+    let file = analyze(SOURCE);
+    let symbols = Symbols::new(&file)
+        .document_symbols()
+        .into_iter()
+        .map(|symbol| (symbol.name, symbol.kind, symbol.detail))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        symbols,
+        [
+            (
+                "count".to_owned(),
+                SymbolKind::Variable,
+                Some("int".to_owned())
+            ),
+            (
+                "name".to_owned(),
+                SymbolKind::Variable,
+                Some("string".to_owned())
+            ),
+            (
+                "ready".to_owned(),
+                SymbolKind::Function,
+                Some("bool".to_owned())
+            ),
+            ("build".to_owned(), SymbolKind::Function, None),
+        ]
+    );
+}
+
+#[test]
+fn test_workspace_search() {
+    let mut workspace = Workspace::new();
+    workspace.update("deploy.sh", &analyze("DEPLOY_ENV=prod\nenv_name=x\n"));
+    workspace.update("lib/env.sh", &analyze("ENV=dev\nload_env() { :; }\n"));
+
+    assert_eq!(
+        found(&workspace, "deploy_env"),
+        [("deploy.sh".to_owned(), "DEPLOY_ENV".to_owned())]
+    );
+    assert_eq!(
+        found(&workspace, "env"),
+        [
+            ("lib/env.sh".to_owned(), "ENV".to_owned()),
+            ("deploy.sh".to_owned(), "env_name".to_owned()),
+            ("deploy.sh".to_owned(), "DEPLOY_ENV".to_owned()),
+            ("lib/env.sh".to_owned(), "load_env".to_owned()),
+        ]
+    );
+
+    let env = workspace.file_id(Path::new("lib/env.sh"));
+    assert!(env.is_some());
+    assert_eq!(workspace.search("ENV")[0].symbol.span.file, env);
+
+    workspace.update("deploy.sh", &analyze("TARGET=prod\n"));
+    assert!(found(&workspace, "deploy").is_empty());
+    workspace.remove(Path::new("lib/env.sh"));
+    assert!(workspace
+        .document_symbols(Path::new("lib/env.sh"))
+        .is_empty());
+    assert_eq!(workspace.document_symbols(Path::new("deploy.sh")).len(), 1);
+}
+
+fn found(workspace: &Workspace, query: &str) -> Vec<(String, String)> {
+    workspace
+        .search(query)
+        .into_iter()
+        .map(|found| (found.path.display().to_string(), found.symbol.name))
+        .collect()
+}
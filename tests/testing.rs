@@ -0,0 +1,85 @@
+use bashtyped::{
+    assert_diagnostics, assert_types,
+    testing::{analyze, check_diagnostics, check_markers, check_types},
+    BashType,
+};
+
+#[test]
+fn test_assert_types() {
+    assert_types!("count=1\nnames=(a b)\nlabel=$count #/ int | string", {
+        "count" => BashType::Integer,
+        "names" => "array<string>",
+        // Unions match in any order
+        "label" => "string | int",
+    });
+    let file = analyze("count=1");
+    assert_types!(file: &file, { "count" => "int" });
+}
+
+#[test]
+fn test_type_differences_are_described() {
+    let file = analyze("count=1");
+    let problems = check_types(
+        &file,
+        vec![("count", BashType::String), ("missing", BashType::Integer)],
+    )
+    .unwrap_err();
+    assert_eq!(
+        problems,
+        "count is int, expected string\nmissing is not set, expected int\n"
+    );
+}
+
+#[test]
+#[should_panic(expected = "Variables don't have the expected types")]
+fn test_assert_types_panics() {
+    assert_types!("count=1", { "count" => "string" });
+}
+
+#[test]
+fn test_assert_diagnostics() {
+    assert_diagnostics!("a=1 #/ bool\nb=1\nb=text", [BT0001 @ 7..11, BT0003]);
+    assert_diagnostics!("a=1", []);
+}
+
+#[test]
+fn test_diagnostic_differences_are_described() {
+    let file = analyze("a=1 #/ bool");
+    let problems = check_diagnostics(&file, vec![("BT0001", Some(0..3))]).unwrap_err();
+    assert!(problems.starts_with("expected [BT0001 @ 0..3]\n   found [BT0001 @ 7..11]\n"));
+    assert!(problems.contains("Types do not match"));
+}
+
+#[test]
+#[should_panic(expected = "The diagnostics aren't the expected ones")]
+fn test_assert_diagnostics_panics() {
+    assert_diagnostics!("a=1 #/ bool", []);
+}
+
+#[test]
+fn test_markers() {
+    check_markers("a=1 #/ bool\n#      ^^^^ error BT0001: Types do not match\nb=2").unwrap();
+    // A marker may be indented, and the `#` counts as a caret when it is followed by one
+    check_markers(
+        "if true; then\n    b=1\n    #^^ error BT0003: Variable b defined with different type\n    b=text\nfi",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_marker_problems() {
+    let problems =
+        check_markers("a=1 #/ bool\n#   ^^^ error BT0001: Types do not match").unwrap_err();
+    assert_eq!(
+        problems,
+        "missing    line 1, columns 4..7: error BT0001: Types do not match\n\
+         unexpected line 1, columns 7..11: error BT0001: Types do not match\n"
+    );
+    assert_eq!(
+        check_markers("#^ error BT0001: Types do not match").unwrap_err(),
+        "Invalid marker on line 1: there is no line above it"
+    );
+    assert!(check_markers("a=1\n# ^ fatal BT0001: x")
+        .unwrap_err()
+        .contains("the severity should be error, warning or advice"));
+}
@@ -0,0 +1,123 @@
+use bashtyped::{tokens::TokenKind, FileInfo};
+
+/// The text and kind of every annotation token in `source`
+fn tokens(source: &str) -> Vec<(&str, TokenKind)> {
+    FileInfo::new(source)
+        .semantic_tokens()
+        .into_iter()
+        .map(|token| (&source[token.range], token.kind))
+        .collect()
+}
+
+#[test]
+fn test_type_annotation() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("count=1 #/ int | string"),
+        [
+            ("#/", Keyword),
+            ("int", Type),
+            ("|", Operator),
+            ("string", Type)
+        ]
+    );
+    assert_eq!(
+        tokens("#/ map<string, array<int>>\ndeclare -A ages"),
+        [
+            ("#/", Keyword),
+            ("map", Type),
+            ("<", Operator),
+            ("string", Type),
+            (",", Operator),
+            ("array", Type),
+            ("<", Operator),
+            ("int", Type),
+            (">", Operator),
+            (">", Operator),
+        ]
+    );
+}
+
+#[test]
+fn test_record_keys_are_variables() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("#/ { host: string, port: int }\nserver=()"),
+        [
+            ("#/", Keyword),
+            ("{", Operator),
+            ("host", Variable),
+            (":", Operator),
+            ("string", Type),
+            (",", Operator),
+            ("port", Variable),
+            (":", Operator),
+            ("int", Type),
+            ("}", Operator),
+        ]
+    );
+}
+
+#[test]
+fn test_commands() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("#[returns bool]\nis_ready() {\n    return 0\n}"),
+        [
+            ("#[", Keyword),
+            ("returns", Keyword),
+            ("bool", Type),
+            ("]", Keyword)
+        ]
+    );
+    assert_eq!(
+        tokens("#[set_var(count, int)]\nsetup() {\n    count=1\n}"),
+        [
+            ("#[", Keyword),
+            ("set_var", Keyword),
+            ("(", Operator),
+            ("count", Variable),
+            (",", Operator),
+            ("int", Type),
+            (")", Operator),
+            ("]", Keyword),
+        ]
+    );
+}
+
+#[test]
+fn test_params_names_are_variables() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("#[params path: string]\ncheck() { :; }"),
+        [
+            ("#[", Keyword),
+            ("params", Keyword),
+            ("path", Variable),
+            (":", Operator),
+            ("string", Type),
+            ("]", Keyword),
+        ]
+    );
+}
+
+#[test]
+fn test_directives() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("#![bash >= 4.2]\necho hi"),
+        [
+            ("#![", Keyword),
+            ("bash", Keyword),
+            (">=", Operator),
+            ("4.2", Number),
+            ("]", Keyword),
+        ]
+    );
+}
+
+#[test]
+fn test_ordinary_comments_have_no_tokens() {
+    assert!(tokens("# a comment about #/ int\necho '#/ int'").is_empty());
+    assert!(tokens("#!/bin/bash\necho hi").is_empty());
+}
@@ -0,0 +1,66 @@
+use bashtyped::{
+    testing::analyze,
+    trace::{MergeReason, Outcome},
+    BashType, Config, FileInfo,
+};
+
+#[test]
+fn test_explain_assignments() {
+    let file = analyze("#/ int | string\nlabel=5\nlabel=7\nother=1\nlabel=done");
+    let explanation = file.explain("label").unwrap();
+    let steps = &explanation.steps;
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[0].line, 2);
+    assert_eq!(steps[0].source, "label=5");
+    assert_eq!(steps[0].outcome, Outcome::First);
+    assert_eq!(
+        steps[0].rule,
+        "declared by the annotation on the line before"
+    );
+    assert_eq!(steps[1].bash_type, BashType::Integer);
+    assert_eq!(steps[1].rule, "inferred from the assigned value");
+    assert_eq!(steps[1].outcome, Outcome::KeptDeclaration);
+    assert_eq!(steps[1].result.to_string(), "int | string");
+    assert_eq!(steps[2].line, 5);
+    assert_eq!(explanation.final_type, Some(steps[1].result.clone()));
+    assert!(file.explain("missing").is_none());
+}
+
+#[test]
+fn test_explain_narrowing_and_conflicts() {
+    let source = "count=1 #/ int | string\ncount=2\nname=text\nname=3";
+    let mut config = Config::default();
+    config.narrow_on_assignment = true;
+    let mut file = FileInfo::with_config(source, config);
+    file.parse_code();
+    let steps = file.explain("count").unwrap().steps;
+    assert_eq!(
+        steps[0].rule,
+        "declared by the annotation at the end of the line"
+    );
+    assert_eq!(steps[1].outcome, Outcome::Narrowed);
+    assert_eq!(steps[1].result, BashType::Integer);
+
+    let steps = file.explain("name").unwrap().steps;
+    assert_eq!(steps[1].outcome, Outcome::Conflict);
+}
+
+#[test]
+fn test_explain_merges() {
+    let file = analyze("count=1\n[[ -n $1 ]] && count=text\nlast=1\n{ last=2; } &\nwait");
+    let steps = file.explain("count").unwrap().steps;
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[2].line, 2);
+    assert_eq!(steps[2].outcome, Outcome::Merged(MergeReason::ShortCircuit));
+    assert_eq!(steps[2].result.to_string(), "int | string");
+
+    let explanation = file.explain("last").unwrap();
+    assert_eq!(
+        explanation.steps.last().unwrap().outcome,
+        Outcome::Merged(MergeReason::Subshell)
+    );
+    assert_eq!(explanation.final_type, Some(BashType::Integer));
+    let text = explanation.to_string();
+    assert!(text.contains("line 4: { last=2; } &"));
+    assert!(text.ends_with("last is int at the end of the file\n"));
+}
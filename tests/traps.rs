@@ -0,0 +1,66 @@
+use bashtyped::{testing::analyze, Severity};
+
+#[test]
+fn test_handler_uses_known_variables() {
+    let file = analyze("tmpdir=/tmp/work\ntrap 'rm -rf \"$tmpdir\"' EXIT INT");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_handler_unknown_variable() {
+    let source = "trap 'rm -rf \"$tmpdir\"' EXIT\ntmpdir=/tmp/work";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Unknown variable");
+    let range = file.errors[0].labels[0].range.clone();
+    assert_eq!(&source[range], "tmpdir");
+}
+
+#[test]
+fn test_handler_type_errors() {
+    let source = "trap 'count=text #/ int' EXIT";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Types do not match");
+    assert_eq!(
+        &source[file.errors[0].labels[1].range.clone()],
+        "count=text"
+    );
+    // Assignments in the handler happen later, so they don't leak into the script
+    assert!(!file.variables.contains_key("count"));
+}
+
+#[test]
+fn test_double_quoted_handler() {
+    let file = analyze("trap \"rm $file\" ERR");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Unknown variable");
+}
+
+#[test]
+fn test_double_quoted_handler_with_escapes() {
+    let source = "trap \"echo \\$undefined_var\" EXIT";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Unknown variable");
+    // The escape moved the code, so the error points at the whole handler
+    let range = file.errors[0].labels[0].range.clone();
+    assert_eq!(&source[range], "\"echo \\$undefined_var\"");
+
+    let file = analyze("trap \"echo \\\"done\\\"\" EXIT");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_signal_names() {
+    let file = analyze("trap '' INT sigterm SIGHUP 15 RTMIN+1\ntrap - EXIT\ntrap -p\ntrap");
+    assert!(file.errors.is_empty());
+
+    let file = analyze("trap 'echo bye' EXTI");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Unknown signal EXTI");
+
+    let file = analyze("trap 'echo bye' KILL");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].severity, Severity::Warning);
+}
@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+
+use bashtyped::{AnnotationPlacement, AnnotationSpan, BashType, Method, TypeDeclaration};
+
+#[test]
+fn test_many_chained_comments() {
+    let source = format!("{}a=1", "#/ int\n".repeat(100_000));
+    let mut file = bashtyped::FileInfo::new(&source);
+    file.parse_code();
+    // Every annotation but the last one is overridden
+    assert_eq!(file.errors.len(), 99_999);
+    assert_eq!(file.variables["a"].method, Method::Declared);
+}
+
+#[test]
+fn test_deep_nesting() {
+    let depth = 2_000;
+    let source = format!(
+        "{}a=1\n{}",
+        "if true; then\n".repeat(depth),
+        "fi\n".repeat(depth)
+    );
+    let mut file = bashtyped::FileInfo::new(&source);
+    file.parse_code();
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["a"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_command_comment_is_not_an_annotation() {
+    let mut file = bashtyped::FileInfo::new(
+        r#"#[set_var(b, string)]
+a=1"#,
+    );
+    file.parse_code();
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables,
+        BTreeMap::from([
+            (
+                String::from("b"),
+                TypeDeclaration {
+                    bash_type: BashType::String,
+                    method: Method::Declared,
+                    range: 0..21,
+                    node_kind: String::from("comment"),
+                    annotation: Some(AnnotationSpan {
+                        range: 0..21,
+                        type_range: 13..19,
+                        placement: AnnotationPlacement::Command,
+                    }),
+                    file: None,
+                },
+            ),
+            (
+                String::from("a"),
+                TypeDeclaration {
+                    bash_type: BashType::Integer,
+                    method: Method::Inferred,
+                    range: 22..25,
+                    node_kind: String::from("variable_assignment"),
+                    annotation: None,
+                    file: None,
+                },
+            )
+        ])
+    );
+}
+
+#[test]
+fn test_annotation_on_next_line_is_not_inline() {
+    let mut file = bashtyped::FileInfo::new(
+        r#"a="text"
+#/ int
+b=1"#,
+    );
+    file.parse_code();
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["a"].method, Method::Inferred);
+    assert_eq!(file.variables["b"].method, Method::Declared);
+}
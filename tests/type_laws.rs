@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use bashtyped::BashType;
+use proptest::prelude::*;
+
+/// Any type
+fn bash_type() -> impl Strategy<Value = BashType> {
+    types(prop_oneof![static_leaf(), Just(BashType::Unknown)].boxed())
+}
+
+/// A type without `unknown`, which is compatible with every type in both directions, so the order
+/// laws only hold without it
+fn static_type() -> impl Strategy<Value = BashType> {
+    types(static_leaf().boxed())
+}
+
+fn static_leaf() -> impl Strategy<Value = BashType> {
+    prop_oneof![
+        Just(BashType::String),
+        Just(BashType::Integer),
+        Just(BashType::Bool),
+        Just(BashType::Any),
+    ]
+}
+
+fn types(leaf: BoxedStrategy<BashType>) -> impl Strategy<Value = BashType> {
+    leaf.prop_recursive(3, 16, 3, |inner| {
+        prop_oneof![
+            inner
+                .clone()
+                .prop_map(|element| BashType::Array(Box::new(element))),
+            (inner.clone(), inner.clone())
+                .prop_map(|(key, value)| BashType::Map(Box::new(key), Box::new(value))),
+            prop::collection::vec(inner.clone(), 0..3).prop_map(BashType::Tuple),
+            prop::collection::btree_map("[ab]", inner.clone(), 0..3)
+                .prop_map(|fields: BTreeMap<String, BashType>| BashType::Record(fields)),
+            (inner.clone(), inner)
+                .prop_map(|(first, second)| BashType::Or(Box::new(first), Box::new(second))),
+        ]
+    })
+}
+
+/// Whether the types are the same once written in a canonical way
+fn same(a: &BashType, b: &BashType) -> bool {
+    a.normalized() == b.normalized()
+}
+
+proptest! {
+    #[test]
+    fn union_is_commutative(a in static_type(), b in static_type()) {
+        let ab = a.clone().union(b.clone());
+        let ba = b.union(a);
+        prop_assert!(same(&ab, &ba), "{ab} and {ba}");
+    }
+
+    #[test]
+    fn union_is_associative(a in static_type(), b in static_type(), c in static_type()) {
+        let left = a.clone().union(b.clone()).union(c.clone());
+        let right = a.union(b.union(c));
+        prop_assert!(same(&left, &right), "{left} and {right}");
+    }
+
+    #[test]
+    fn union_is_idempotent(a in bash_type()) {
+        let aa = a.clone().union(a.clone());
+        prop_assert!(same(&aa, &a), "{aa} and {a}");
+    }
+
+    #[test]
+    fn union_contains_both_sides(a in bash_type(), b in bash_type()) {
+        let union = a.clone().union(b.clone());
+        prop_assert!(union.can_contain(&a) && union.can_contain(&b), "{union}");
+    }
+
+    #[test]
+    fn can_contain_is_reflexive(a in bash_type()) {
+        prop_assert!(a.can_contain(&a));
+    }
+
+    #[test]
+    fn can_contain_is_transitive(a in static_type(), b in static_type(), c in static_type()) {
+        if a.can_contain(&b) && b.can_contain(&c) {
+            prop_assert!(a.can_contain(&c), "{a} contains {b}, which contains {c}");
+        }
+    }
+
+    #[test]
+    fn types_that_contain_each_other_are_the_same(a in static_type(), b in static_type()) {
+        if a.can_contain(&b) && b.can_contain(&a) {
+            prop_assert!(same(&a, &b), "{a} and {b}");
+        }
+    }
+
+    #[test]
+    fn unknown_is_compatible_with_every_type(a in bash_type()) {
+        prop_assert!(a.can_contain(&BashType::Unknown) && BashType::Unknown.can_contain(&a));
+    }
+
+    #[test]
+    fn normalizing_is_a_fixpoint(a in bash_type()) {
+        let normalized = a.normalized();
+        prop_assert_eq!(normalized.normalized(), normalized.clone());
+        prop_assert!(normalized.can_contain(&a) && a.can_contain(&normalized), "{a} and {normalized}");
+    }
+}
+
+fn parsed_type(text: &str) -> BashType {
+    text.parse().unwrap()
+}
+
+#[test]
+fn test_unions_contain_what_their_members_contain() {
+    let union = parsed_type("array<int> | string");
+    assert!(union.can_contain(&BashType::Tuple(vec![BashType::Integer])));
+    assert!(!union.can_contain(&parsed_type("array<any>")));
+    // Like a single type, a union without `any` can't hold a value of any type
+    assert!(!parsed_type("int | string").can_contain(&BashType::Any));
+    assert!(
+        parsed_type("array<int | string>").can_contain(&parsed_type("array<string> | array<int>"))
+    );
+}
+
+#[test]
+fn test_normalizing_removes_contained_members() {
+    assert_eq!(parsed_type("int | any").normalized(), BashType::Any);
+    assert_eq!(
+        parsed_type("array<int> | string | array<any>")
+            .normalized()
+            .to_string(),
+        "string | array<any>"
+    );
+}
@@ -0,0 +1,139 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use bashtyped::{
+    codes,
+    fix::FixKind,
+    testing::{analyze, analyze_with_config},
+    Config, Severity,
+};
+
+fn checking_path() -> Config {
+    let mut config = Config::default();
+    config.check_path = true;
+    config
+}
+
+#[test]
+fn test_misspelled_function() {
+    let file = analyze("deploy() {\n    echo done\n}\ndepoly\ndepoly");
+    assert_eq!(file.errors.len(), 1);
+    let error = &file.errors[0];
+    assert_eq!(error.message, "Unknown command depoly");
+    assert_eq!(error.code.as_deref(), Some(codes::UNKNOWN_COMMAND));
+    assert_eq!(error.severity, Severity::Warning);
+    assert_eq!(error.help.as_deref(), Some("Did you mean deploy?"));
+    assert_eq!(error.labels[1].text, "Also used here");
+    assert_eq!(error.labels[2].range, 0..26);
+    assert_eq!(error.fixes[0].kind, FixKind::Rename);
+    assert_eq!(error.fixes[0].edits.len(), 2);
+    assert_eq!(error.fixes[0].edits[0].range, 27..33);
+    assert_eq!(error.fixes[0].edits[0].new_text, "deploy");
+}
+
+#[test]
+fn test_swapped_letters_are_one_mistake() {
+    let file = analyze("stop() { :; }\nsotp");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].help.as_deref(), Some("Did you mean stop?"));
+
+    // A swap and another mistake are still close enough in a longer name
+    let file = analyze("deploy() { :; }\ndpeloyy");
+    assert_eq!(file.errors[0].help.as_deref(), Some("Did you mean deploy?"));
+}
+
+#[test]
+fn test_function_defined_after_use() {
+    let file = analyze_with_config(
+        "main() {\n    setup\n}\nsetup() {\n    :\n}\nmain",
+        checking_path(),
+    );
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_builtins_aliases_and_paths() {
+    let file = analyze_with_config(
+        "alias ll='ls -l'\nll\nprintf '%s\\n' a\n./build.sh\n\"$editor\" file",
+        checking_path(),
+    );
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_unknown_command_needs_check_path() {
+    let source = "bashtyped-missing-command --help";
+    assert!(analyze(source).errors.is_empty());
+    let file = analyze_with_config(source, checking_path());
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(
+        file.errors[0].message,
+        "Unknown command bashtyped-missing-command"
+    );
+    assert!(file.errors[0].help.is_none());
+}
+
+#[test]
+fn test_sourced_files_can_define_commands() {
+    let file = analyze_with_config(
+        "source ./lib.sh\nbashtyped-missing-command",
+        checking_path(),
+    );
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_short_names_are_not_typos() {
+    let file = analyze("ab() { :; }\nac");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_check_path_config() {
+    let config = Config::from_toml("check_path = true").unwrap();
+    assert!(config.check_path);
+    assert!(!Config::default().check_path);
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "bashtyped-unknown-commands-{name}-{}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_check_path_flag() {
+    let dir = temp_dir("flag");
+    let bin = dir.join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    fs::write(bin.join("installed-tool"), "").unwrap();
+    fs::write(
+        dir.join("script.sh"),
+        "installed-tool
+missing-tool
+sleep 1
+echo done
+",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_bashtyped"))
+            .current_dir(&dir)
+            .env("PATH", &bin)
+            .args(["--no-cache", "--color", "never", "--format", "short"])
+            .args(args)
+            .arg("script.sh")
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(run(&[]), "");
+    let report = run(&["--check-path"]);
+    // Commands with a known signature still have to be installed
+    assert_eq!(report.lines().count(), 2);
+    assert!(report.contains("Unknown command missing-tool"));
+    assert!(report.contains("Unknown command sleep"));
+}
@@ -0,0 +1,58 @@
+use bashtyped::{testing::analyze, BashType, Severity};
+
+#[test]
+fn test_unset_removes_variable() {
+    let file = analyze("count=1\nunset count");
+    assert!(file.errors.is_empty());
+    assert!(!file.variables.contains_key("count"));
+}
+
+#[test]
+fn test_read_after_unset() {
+    let source = "count=1\nunset -v count\ntotal=$count";
+    let file = analyze(source);
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Variable may be unset");
+    assert_eq!(file.errors[0].severity, Severity::Warning);
+    let range = file.errors[0].labels[0].range.clone();
+    assert_eq!(&source[range], "count");
+}
+
+#[test]
+fn test_redeclare_after_unset() {
+    let file = analyze("value=1\nunset value\nvalue=text");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["value"].bash_type, BashType::String);
+}
+
+#[test]
+fn test_read_after_reassignment() {
+    let file = analyze("value=1\nunset value\nvalue=2\ncopy=$value");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_unset_function() {
+    let file = analyze("name=1\nunset -f name\ncopy=$name");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_unset_through_nameref() {
+    let file = analyze("target=1\ndeclare -n ref=target\nunset ref\ncopy=$target");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Variable may be unset");
+}
+
+#[test]
+fn test_unset_nameref_itself() {
+    let file = analyze("target=1\ndeclare -n ref=target\nunset -n ref\ncopy=$target");
+    assert!(file.errors.is_empty());
+}
+
+#[test]
+fn test_unset_in_redirect() {
+    let file = analyze("log=out.log\nunset log\necho done > \"$log\"");
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].message, "Variable may be unset");
+}
@@ -0,0 +1,98 @@
+use std::ops::Range;
+
+use bashtyped::{codes, BashType, Config, FileInfo};
+
+/// The messages and ranges of the unused annotation warnings
+fn unused(source: &str) -> Vec<(String, Range<usize>)> {
+    let mut file = FileInfo::new(source);
+    file.parse_code();
+    file.errors
+        .into_iter()
+        .filter(|error| error.code.as_deref() == Some(codes::UNUSED_ANNOTATION))
+        .map(|error| (error.message, error.labels[0].range.clone()))
+        .collect()
+}
+
+#[test]
+fn test_annotation_before_command() {
+    assert_eq!(
+        unused("#/ int\necho starting\ncount=1"),
+        [(
+            String::from("Annotation is not followed by an assignment"),
+            0..6
+        )]
+    );
+    assert_eq!(
+        unused("count=1\n#/ int"),
+        [(
+            String::from("Annotation is not followed by an assignment"),
+            8..14
+        )]
+    );
+}
+
+#[test]
+fn test_inline_annotation_on_command() {
+    assert_eq!(
+        unused("echo starting #/ int"),
+        [(
+            String::from("Annotation on a line without an assignment"),
+            14..20
+        )]
+    );
+}
+
+#[test]
+fn test_shadowed_annotations() {
+    assert_eq!(
+        unused("#/ int\n#/ string\nname=text"),
+        [(
+            String::from("Annotation is overridden by a closer one"),
+            0..6
+        )]
+    );
+    let mut file = FileInfo::new("#/ int\ncount=1 #/ int | string");
+    file.parse_code();
+    assert_eq!(file.errors.len(), 1);
+    assert_eq!(file.errors[0].labels[0].range, 0..6);
+    assert_eq!(file.errors[0].labels[1].range, 15..30);
+}
+
+#[test]
+fn test_used_annotations() {
+    assert!(unused(
+        "#/ int\ncount=1\nname=x #/ string\n#/ int\n#[force]\nlocal n=1\n#/ string\nfor item in a b; do\n  \
+         :\ndone\ndeclare -a list #/ array<int>"
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_detached_annotation() {
+    let source = "#/ string\n\ncount=1\n#/ int\n# how many\ntotal=2";
+    assert_eq!(
+        unused(source),
+        [(String::from("Annotation does not attach to anything"), 0..9)]
+    );
+    let mut file = FileInfo::new(source);
+    file.parse_code();
+    assert_eq!(file.variables["count"].bash_type, BashType::Integer);
+    assert!(file.errors[0]
+        .help
+        .as_deref()
+        .unwrap()
+        .contains("detached_annotations"));
+}
+
+#[test]
+fn test_detached_annotations_allowed() {
+    let mut config = Config::default();
+    config.detached_annotations = true;
+    let mut file = FileInfo::with_config("#/ int | string\n\ncount=1", config);
+    file.parse_code();
+    assert!(file.errors.is_empty());
+    assert_eq!(
+        file.variables["count"].bash_type,
+        BashType::Or(Box::new(BashType::Integer), Box::new(BashType::String))
+    );
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "zsh")]
+
+use bashtyped::{BashType, Config, Dialect, FileInfo};
+
+fn zsh_file(source: &str) -> FileInfo<'_> {
+    let config = Config::from_toml(r#"dialect = "zsh""#).unwrap();
+    assert_eq!(config.dialect, Dialect::Zsh);
+    let mut file = FileInfo::with_config(source, config);
+    file.parse_code();
+    file
+}
+
+#[test]
+fn test_integer_builtin() {
+    let file = zsh_file("integer count=3 total\nother=\"$count\"");
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["count"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["total"].bash_type, BashType::Integer);
+    assert_eq!(file.variables["other"].bash_type, BashType::Integer);
+}
+
+#[test]
+fn test_integer_builtin_mismatch() {
+    let file = zsh_file("integer count=lots");
+    assert_eq!(file.errors.len(), 1);
+}
+
+#[test]
+fn test_zsh_constructs_are_tolerated() {
+    let file = zsh_file(
+        r#"#![bash >= 3.0]
+typeset -A options
+() { local inner=1 }
+print -l **/*.sh(.)
+name="x""#,
+    );
+    assert!(file.errors.is_empty());
+    assert_eq!(file.variables["name"].bash_type, BashType::String);
+}